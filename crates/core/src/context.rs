@@ -1,17 +1,21 @@
 use crate::view::keyboard::Layout;
+use std::fs;
 use std::path::Path;
 use std::collections::{BTreeMap, VecDeque};
 use fxhash::FxHashMap;
+use serde::{Serialize, Deserialize};
+use serde_json::Value as JsonValue;
+use anyhow::{Error, bail, format_err};
 use chrono::Local;
 use globset::Glob;
 use walkdir::WalkDir;
 use rand_core::SeedableRng;
 use rand_xoshiro::Xoroshiro128Plus;
-use crate::dictionary::{Dictionary, load_dictionary_from_file};
+use crate::dictionary::{Dictionary, load_dictionary_from_file, load_stardict_from_file};
 use crate::framebuffer::{Framebuffer, Display};
 use crate::view::ViewId;
-use crate::helpers::{load_json, IsHidden};
-use crate::settings::Settings;
+use crate::helpers::{load_json, save_json, IsHidden};
+use crate::settings::{Settings, BundleImportMode};
 use crate::frontlight::Frontlight;
 use crate::lightsensor::LightSensor;
 use crate::battery::Battery;
@@ -24,6 +28,31 @@ use crate::rtc::Rtc;
 const KEYBOARD_LAYOUTS_DIRNAME: &str = "keyboard-layouts";
 const DICTIONARIES_DIRNAME: &str = "dictionaries";
 const INPUT_HISTORY_SIZE: usize = 32;
+pub const SETTINGS_BUNDLE_FILENAME: &str = "Settings-bundle.json";
+
+// The keyboard layouts are stored by file name.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsBundle {
+    settings: JsonValue,
+    #[serde(default)]
+    keyboard_layouts: BTreeMap<String, JsonValue>,
+}
+
+// Objects are merged recursively, every other value of *b* replaces the one in *a*.
+fn merge_json(a: &mut JsonValue, b: JsonValue) {
+    match (a, b) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            for (key, value) in b {
+                match a.get_mut(&key) {
+                    Some(entry) => merge_json(entry, value),
+                    None => { a.insert(key, value); },
+                }
+            }
+        },
+        (a, b) => *a = b,
+    }
+}
 
 
 pub struct Context {
@@ -79,8 +108,22 @@ impl Context {
     }
 
     pub fn load_keyboard_layouts(&mut self) {
+        self.load_keyboard_layouts_from(Path::new(KEYBOARD_LAYOUTS_DIRNAME));
+        self.load_custom_keyboard_layouts();
+    }
+
+    // Can be called again to pick up the layouts added since the last call.
+    pub fn load_custom_keyboard_layouts(&mut self) {
+        if let Some(dir) = self.settings.custom_keyboard_layouts.clone() {
+            if dir.is_dir() {
+                self.load_keyboard_layouts_from(&dir);
+            }
+        }
+    }
+
+    fn load_keyboard_layouts_from(&mut self, dir: &Path) {
         let glob = Glob::new("**/*.json").unwrap().compile_matcher();
-        for entry in WalkDir::new(Path::new(KEYBOARD_LAYOUTS_DIRNAME)).min_depth(1)
+        for entry in WalkDir::new(dir).min_depth(1)
                              .into_iter().filter_entry(|e| !e.is_hidden()) {
             if entry.is_err() {
                 continue;
@@ -91,32 +134,112 @@ impl Context {
                 continue;
             }
             if let Ok(layout) = load_json::<Layout, _>(path)
+                                          .and_then(|layout| layout.validate().map(|_| layout))
                                           .map_err(|e| eprintln!("Can't load {}: {:#?}.", path.display(), e)) {
                 self.keyboard_layouts.insert(layout.name.clone(), layout);
             }
         }
     }
 
+    pub fn export_settings<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let mut keyboard_layouts = BTreeMap::new();
+
+        for entry in fs::read_dir(KEYBOARD_LAYOUTS_DIRNAME)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                keyboard_layouts.insert(name.to_string(), load_json::<JsonValue, _>(&path)?);
+            }
+        }
+
+        let bundle = SettingsBundle {
+            settings: serde_json::to_value(&self.settings)?,
+            keyboard_layouts,
+        };
+
+        save_json(&bundle, path)
+    }
+
+    pub fn import_settings<P: AsRef<Path>>(&mut self, path: P, mode: BundleImportMode) -> Result<(), Error> {
+        let bundle = load_json::<SettingsBundle, _>(path)?;
+
+        let value = match mode {
+            BundleImportMode::Merge => {
+                let mut value = serde_json::to_value(&self.settings)?;
+                merge_json(&mut value, bundle.settings);
+                value
+            },
+            BundleImportMode::Replace => bundle.settings,
+        };
+
+        let mut settings: Settings = serde_json::from_value(value)
+                                                .map_err(|e| format_err!("invalid settings: {}", e))?;
+
+        let mut layout_names = self.keyboard_layouts.keys().cloned().collect::<Vec<String>>();
+        for (name, layout) in &bundle.keyboard_layouts {
+            if name.contains('/') || !name.ends_with(".json") {
+                bail!("invalid keyboard layout file name: {}", name);
+            }
+            let layout = serde_json::from_value::<Layout>(layout.clone())
+                                    .map_err(|e| format_err!("invalid keyboard layout {}: {}", name, e))?;
+            layout_names.push(layout.name);
+        }
+
+        if !layout_names.contains(&settings.keyboard_layout) {
+            bail!("unknown keyboard layout: {}", settings.keyboard_layout);
+        }
+
+        // Keep the current library selected.
+        let current_library = self.settings.libraries[self.settings.selected_library].clone();
+        if let Some(index) = settings.libraries.iter().position(|lib| lib.path == current_library.path) {
+            settings.selected_library = index;
+        } else {
+            settings.libraries.push(current_library);
+            settings.selected_library = settings.libraries.len() - 1;
+        }
+
+        for (name, layout) in bundle.keyboard_layouts {
+            let path = Path::new(KEYBOARD_LAYOUTS_DIRNAME).join(&name);
+            if mode == BundleImportMode::Merge && path.exists() {
+                continue;
+            }
+            save_json(&layout, path)?;
+        }
+
+        self.settings = settings;
+        self.load_keyboard_layouts();
+
+        Ok(())
+    }
+
     pub fn load_dictionaries(&mut self) {
-        let glob = Glob::new("**/*.index").unwrap().compile_matcher();
+        let index_glob = Glob::new("**/*.index").unwrap().compile_matcher();
+        let ifo_glob = Glob::new("**/*.ifo").unwrap().compile_matcher();
         for entry in WalkDir::new(Path::new(DICTIONARIES_DIRNAME)).min_depth(1)
                              .into_iter().filter_entry(|e| !e.is_hidden()) {
             if entry.is_err() {
                 continue;
             }
             let entry = entry.unwrap();
-            if !glob.is_match(entry.path()) {
+            let path = entry.path();
+            let dict = if index_glob.is_match(path) {
+                let index_path = path.to_path_buf();
+                let mut content_path = index_path.clone();
+                content_path.set_extension("dict.dz");
+                if !content_path.exists() {
+                    content_path.set_extension("");
+                }
+                load_dictionary_from_file(&content_path, &index_path)
+            } else if ifo_glob.is_match(path) {
+                load_stardict_from_file(path)
+            } else {
                 continue;
-            }
-            let index_path = entry.path().to_path_buf();
-            let mut content_path = index_path.clone();
-            content_path.set_extension("dict.dz");
-            if !content_path.exists() {
-                content_path.set_extension("");
-            }
-            if let Ok(mut dict) = load_dictionary_from_file(&content_path, &index_path) {
+            };
+            if let Ok(mut dict) = dict {
                 let name = dict.short_name().ok().unwrap_or_else(|| {
-                    index_path.file_stem()
+                    path.file_stem()
                         .map(|s| s.to_string_lossy().into_owned())
                         .unwrap_or_default()
                 });
@@ -155,6 +278,34 @@ impl Context {
             self.frontlight.set_warmth(0.0);
         }
     }
+
+    pub fn set_warm_reading(&mut self, enable: bool) {
+        if self.settings.warm_reading.enabled == enable {
+            return;
+        }
+
+        self.settings.warm_reading.enabled = enable;
+
+        if CURRENT_DEVICE.has_natural_light() {
+            let current = if self.settings.frontlight {
+                self.frontlight.levels().warmth
+            } else {
+                self.settings.frontlight_levels.warmth
+            };
+            let warmth = if enable {
+                self.settings.warm_reading.previous_warmth = Some(current);
+                (current + self.settings.warm_reading.warmth).clamp(0.0, 100.0)
+            } else {
+                self.settings.warm_reading.previous_warmth.take()
+                    .unwrap_or_else(|| (current - self.settings.warm_reading.warmth).clamp(0.0, 100.0))
+            };
+            if self.settings.frontlight {
+                self.frontlight.set_warmth(warmth);
+            } else {
+                self.settings.frontlight_levels.warmth = warmth;
+            }
+        }
+    }
 }
 
 
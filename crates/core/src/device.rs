@@ -284,6 +284,12 @@ impl Device {
                  Model::Sage | Model::Libra2 | Model::LibraColour)
     }
 
+    // Plays a short cue after a page turn, and tells whether it was played:
+    // none of the supported devices has a speaker or a vibration motor.
+    pub fn page_turn_cue(&self) -> bool {
+        false
+    }
+
     pub fn has_power_cover(&self) -> bool {
         matches!(self.model, Model::Sage)
     }
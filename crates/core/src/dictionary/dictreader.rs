@@ -39,8 +39,13 @@ pub static GZ_FHCRC: u8   = 0b0000_0010;
 /// offset and length. Users of a type which implements this trait don't need to care about compression
 /// of the dictionary.
 pub trait DictReader {
+    /// Fetch the raw data from the dictionary at offset and length.
+    fn fetch_data(&mut self, start_offset: u64, length: u64) -> Result<Vec<u8>, DictError>;
+
     /// Fetch the definition from the dictionary at offset and length.
-    fn fetch_definition(&mut self, start_offset: u64, length: u64) -> Result<String, DictError>;
+    fn fetch_definition(&mut self, start_offset: u64, length: u64) -> Result<String, DictError> {
+        Ok(String::from_utf8(self.fetch_data(start_offset, length)?)?)
+    }
 }
 
 /// Raw Dict reader.
@@ -60,8 +65,8 @@ impl<B: Read + Seek> DictReaderRaw<B> {
 }
 
 impl<B: Read + Seek> DictReader for DictReaderRaw<B> {
-    /// Fetch definition from dictionary.
-    fn fetch_definition(&mut self, start_offset: u64, length: u64) -> Result<String, DictError> {
+    /// Fetch data from dictionary.
+    fn fetch_data(&mut self, start_offset: u64, length: u64) -> Result<Vec<u8>, DictError> {
         if length > MAX_BYTES_FOR_BUFFER {
             return Err(DictError::MemoryError);
         }
@@ -78,7 +83,7 @@ impl<B: Read + Seek> DictReader for DictReaderRaw<B> {
             return Err(DictError::IoError(io::Error::new(
                             io::ErrorKind::UnexpectedEof, "seek beyond end of file")));
         }
-        Ok(String::from_utf8(read_data)?)
+        Ok(read_data)
     }
 }
 
@@ -258,8 +263,8 @@ impl<B: Read + Seek> DictReaderDz<B> {
 }
 
 impl<B: Read + Seek> DictReader for DictReaderDz<B> {
-    // Fetch definition from the dictionary.
-    fn fetch_definition(&mut self, start_offset: u64, length: u64) -> Result<String, DictError> {
+    // Fetch data from the dictionary.
+    fn fetch_data(&mut self, start_offset: u64, length: u64) -> Result<Vec<u8>, DictError> {
         if length > MAX_BYTES_FOR_BUFFER {
             return Err(DictError::MemoryError);
         }
@@ -280,7 +285,7 @@ impl<B: Read + Seek> DictReader for DictReaderDz<B> {
             data.push(self.inflate(definition)?);
         };
 
-        // Cut definition, convert to string.
+        // Cut definition.
         let cut_front = start_offset as usize % self.uchunk_length;
         // Join the chunks to one vector, only keeping the content of the definition.
         let data = match data.len() {
@@ -298,6 +303,6 @@ impl<B: Read + Seek> DictReader for DictReaderDz<B> {
                 tmp
             },
         };
-        Ok(String::from_utf8(data)?)
+        Ok(data)
     }
 }
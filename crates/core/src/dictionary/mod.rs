@@ -1,17 +1,20 @@
 //! A dict format (`*.dict`) reader crate.
 //!
-//! This crate can read dictionaries in the dict format, as used by dictd. It supports both
-//! uncompressed and compressed dictionaries.
+//! This crate can read dictionaries in the dict format, as used by dictd, and in the StarDict
+//! format. It supports both uncompressed and compressed dictionaries.
 
 mod dictreader;
 mod errors;
 mod indexing;
+mod stardict;
 
 use std::path::Path;
 
 use self::dictreader::DictReader;
 use self::indexing::IndexReader;
 
+pub use self::stardict::load_stardict_from_file;
+
 /// A dictionary wrapper.
 ///
 /// A dictionary is made up of a `*.dict` or `*.dict.dz` file with the actual content and a
@@ -22,6 +25,7 @@ pub struct Dictionary {
     content: Box<dyn DictReader>,
     index: Box<dyn IndexReader>,
     metadata: Metadata,
+    name: Option<String>,
 }
 
 /// The special metadata entries that we care about.
@@ -76,8 +80,12 @@ impl Dictionary {
     /// Get the short name.
     ///
     /// This returns the short name of a dictionary. This corresponds to the
-    /// value passed to the `-s` option of `dictfmt`.
+    /// value passed to the `-s` option of `dictfmt`, or to the `bookname` entry
+    /// of a StarDict dictionary.
     pub fn short_name(&mut self) -> Result<String, errors::DictError> {
+        if let Some(name) = self.name.as_ref() {
+            return Ok(name.clone());
+        }
         self.metadata("short")
     }
 
@@ -114,7 +122,7 @@ pub fn load_dictionary(content: Box<dyn DictReader>, index: Box<dyn IndexReader>
         "00databasecasesensitive"
     };
     let case_sensitive = !index.find(word, false).is_empty();
-    Dictionary { content, index, metadata: Metadata { all_chars, case_sensitive } }
+    Dictionary { content, index, metadata: Metadata { all_chars, case_sensitive }, name: None }
 }
 
 #[cfg(test)]
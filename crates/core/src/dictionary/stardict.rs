@@ -0,0 +1,308 @@
+//! Read StarDict dictionaries.
+//!
+//! A StarDict dictionary is made up of three files sharing the same stem:
+//!
+//! - `*.ifo`: a text file with the metadata, in the `key=value` format.
+//! - `*.idx` (or `*.idx.gz`): the list of headwords, each headword is a zero-terminated UTF-8
+//!   string followed by the offset and the size of its definition, as big-endian numbers.
+//! - `*.dict` (or `*.dict.dz`): the definitions, each one is a sequence of typed fields.
+//!
+//! The format is documented in the `StarDictFileFormat` file of the StarDict sources.
+
+use std::io;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::io::{BufRead, BufReader, Read};
+
+use byteorder::{BigEndian, ByteOrder};
+use flate2::read::GzDecoder;
+
+use crate::helpers::escape_html;
+
+use super::{Dictionary, Metadata};
+use super::dictreader::{self, DictReader};
+use super::indexing::{Index, Entry};
+use super::errors::DictError;
+
+const IFO_MAGIC: &str = "StarDict's dict ifo file";
+
+/// The metadata entries of the `.ifo` file that we care about.
+#[derive(Debug, Clone, Default)]
+pub struct Info {
+    pub book_name: String,
+    pub word_count: usize,
+    pub idx_offset_bits: u8,
+    pub same_type_sequence: Option<String>,
+}
+
+/// Parse the `.ifo` file of a dictionary.
+pub fn parse_ifo<R: BufRead>(reader: R) -> Result<Info, DictError> {
+    let mut lines = reader.lines();
+    let magic = lines.next().transpose()?.unwrap_or_default();
+
+    if magic.trim_start_matches('\u{FEFF}').trim_end() != IFO_MAGIC {
+        return Err(DictError::InvalidFileFormat("Not a StarDict ifo file".into(), None));
+    }
+
+    let mut info = Info { idx_offset_bits: 32, .. Default::default() };
+
+    for line in lines {
+        let line = line?;
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim_end();
+            match key.trim() {
+                "bookname" => info.book_name = value.to_string(),
+                "wordcount" => info.word_count = value.parse().unwrap_or(0),
+                "idxoffsetbits" => info.idx_offset_bits = value.parse().unwrap_or(32),
+                "sametypesequence" if !value.is_empty() => info.same_type_sequence = Some(value.to_string()),
+                _ => (),
+            }
+        }
+    }
+
+    if info.idx_offset_bits != 32 && info.idx_offset_bits != 64 {
+        return Err(DictError::InvalidFileFormat(format!("Invalid index offset size: {}",
+                                                        info.idx_offset_bits), None));
+    }
+
+    Ok(info)
+}
+
+/// Parse the content of the `.idx` file of a dictionary.
+///
+/// The headwords are lower-cased, and the entries sorted accordingly, for case-insensitive lookups.
+pub fn parse_idx(data: &[u8], idx_offset_bits: u8) -> Result<Vec<Entry>, DictError> {
+    let offset_size = (idx_offset_bits / 8) as usize;
+    let mut entries = Vec::new();
+    let mut data = data;
+
+    while !data.is_empty() {
+        let end = data.iter().position(|&b| b == 0)
+                      .ok_or_else(|| truncated_index_error(entries.len()))?;
+        let word = String::from_utf8_lossy(&data[..end]).into_owned();
+        data = &data[end+1..];
+
+        if data.len() < offset_size + 4 {
+            return Err(truncated_index_error(entries.len()));
+        }
+
+        let offset = if offset_size == 8 {
+            BigEndian::read_u64(&data[..8])
+        } else {
+            BigEndian::read_u32(&data[..4]) as u64
+        };
+        let size = BigEndian::read_u32(&data[offset_size..offset_size+4]) as u64;
+        data = &data[offset_size+4..];
+
+        let headword = word.to_lowercase();
+        let original = if headword != word {
+            Some(word)
+        } else {
+            None
+        };
+
+        entries.push(Entry { headword, offset, size, original });
+    }
+
+    entries.sort_by(|a, b| a.headword.cmp(&b.headword));
+
+    Ok(entries)
+}
+
+fn truncated_index_error(index: usize) -> DictError {
+    DictError::InvalidFileFormat(format!("Truncated index entry: {}", index), None)
+}
+
+// Convert a field to HTML. The binary fields (upper case types) and the resources are ignored.
+fn format_field(kind: u8, data: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(data);
+    let text = text.trim_end_matches('\0').trim();
+
+    if text.is_empty() {
+        return None;
+    }
+
+    match kind {
+        b'm' | b'l' | b'y' | b'k' | b'w' => Some(format!("<pre>{}</pre>", escape_html(text))),
+        b't' => Some(format!("<p>[{}]</p>", escape_html(text))),
+        b'h' | b'g' | b'x' => Some(format!("<div>{}</div>", text)),
+        _ => None,
+    }
+}
+
+/// Convert the data of a definition to HTML.
+///
+/// When the type sequence is given by the `.ifo` file, the fields don't have a type marker, and the
+/// last field doesn't have a terminator or a size.
+pub fn format_definition(data: &[u8], same_type_sequence: Option<&str>) -> String {
+    let mut fields = Vec::new();
+    let mut data = data;
+
+    if let Some(sequence) = same_type_sequence {
+        let kinds = sequence.as_bytes();
+        for (i, &kind) in kinds.iter().enumerate() {
+            if i == kinds.len() - 1 {
+                fields.push((kind, data));
+                break;
+            }
+            match next_field(kind, data) {
+                Some((field, rest)) => {
+                    fields.push((kind, field));
+                    data = rest;
+                },
+                None => break,
+            }
+        }
+    } else {
+        while let Some((&kind, rest)) = data.split_first() {
+            match next_field(kind, rest) {
+                Some((field, rest)) => {
+                    fields.push((kind, field));
+                    data = rest;
+                },
+                None => break,
+            }
+        }
+    }
+
+    fields.into_iter()
+          .filter_map(|(kind, field)| format_field(kind, field))
+          .collect::<Vec<String>>()
+          .join("\n")
+}
+
+// The lower case types are zero-terminated strings, the upper case types are preceded by their size.
+fn next_field(kind: u8, data: &[u8]) -> Option<(&[u8], &[u8])> {
+    if kind.is_ascii_uppercase() {
+        if data.len() < 4 {
+            return None;
+        }
+        let size = BigEndian::read_u32(&data[..4]) as usize;
+        let data = &data[4..];
+        if data.len() < size {
+            return None;
+        }
+        Some((&data[..size], &data[size..]))
+    } else {
+        match data.iter().position(|&b| b == 0) {
+            Some(end) => Some((&data[..end], &data[end+1..])),
+            None => Some((data, &[])),
+        }
+    }
+}
+
+/// A reader that converts the definitions of a StarDict dictionary to HTML.
+pub struct StarDictReader {
+    content: Box<dyn DictReader>,
+    same_type_sequence: Option<String>,
+}
+
+impl DictReader for StarDictReader {
+    fn fetch_data(&mut self, start_offset: u64, length: u64) -> Result<Vec<u8>, DictError> {
+        self.content.fetch_data(start_offset, length)
+    }
+
+    fn fetch_definition(&mut self, start_offset: u64, length: u64) -> Result<String, DictError> {
+        let data = self.content.fetch_data(start_offset, length)?;
+        Ok(format_definition(&data, self.same_type_sequence.as_deref()))
+    }
+}
+
+// Find the first existing path among the given extensions.
+fn sibling_path(path: &Path, extensions: &[&str]) -> Option<PathBuf> {
+    extensions.iter()
+              .map(|ext| path.with_extension(ext))
+              .find(|path| path.exists())
+}
+
+/// Load a StarDict dictionary from the path of its `.ifo` file.
+pub fn load_stardict_from_file<P: AsRef<Path>>(ifo_path: P) -> Result<Dictionary, DictError> {
+    let ifo_path = ifo_path.as_ref();
+    let info = parse_ifo(BufReader::new(File::open(ifo_path)?))?;
+
+    let idx_path = sibling_path(ifo_path, &["idx", "idx.gz"])
+                       .ok_or_else(|| missing_file_error("idx", ifo_path))?;
+    let dict_path = sibling_path(ifo_path, &["dict.dz", "dict"])
+                        .ok_or_else(|| missing_file_error("dict", ifo_path))?;
+
+    let idx_data = if idx_path.extension() == Some(OsStr::new("gz")) {
+        let mut buf = Vec::new();
+        GzDecoder::new(File::open(&idx_path)?).read_to_end(&mut buf)?;
+        buf
+    } else {
+        fs::read(&idx_path)?
+    };
+
+    let entries = parse_idx(&idx_data, info.idx_offset_bits)?;
+    let index: Index<io::Empty> = Index { entries, state: None };
+    let content = StarDictReader {
+        content: dictreader::load_dict(&dict_path)?,
+        same_type_sequence: info.same_type_sequence,
+    };
+
+    let name = Some(info.book_name).filter(|name| !name.is_empty());
+
+    Ok(Dictionary {
+        content: Box::new(content),
+        index: Box::new(index),
+        metadata: Metadata { all_chars: true, case_sensitive: false },
+        name,
+    })
+}
+
+fn missing_file_error(kind: &str, ifo_path: &Path) -> DictError {
+    DictError::InvalidFileFormat(format!(": missing {} file", kind),
+                                 Some(ifo_path.to_string_lossy().into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH_STARDICT_IFO: &str = "src/dictionary/testdata/stardict.ifo";
+
+    #[test]
+    fn test_parse_idx() {
+        let mut data = Vec::new();
+        for (word, offset, size) in [("Bar", 0u32, 4u32), ("apple", 4, 8)] {
+            data.extend_from_slice(word.as_bytes());
+            data.push(0);
+            data.extend_from_slice(&offset.to_be_bytes());
+            data.extend_from_slice(&size.to_be_bytes());
+        }
+        let entries = parse_idx(&data, 32).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].headword, "apple");
+        assert_eq!(entries[1].headword, "bar");
+        assert_eq!(entries[1].original.as_deref(), Some("Bar"));
+        assert_eq!((entries[1].offset, entries[1].size), (0, 4));
+        assert!(parse_idx(&data[..data.len()-1], 32).is_err());
+    }
+
+    #[test]
+    fn test_format_definition() {
+        assert_eq!(format_definition(b"a < b", Some("m")), "<pre>a &lt; b</pre>");
+        assert_eq!(format_definition(b"t\0<b>x</b>", Some("mh")), "<pre>t</pre>\n<div><b>x</b></div>");
+        assert_eq!(format_definition(b"mfoo\0W\0\0\0\x02\xFF\xFEtbar\0", None),
+                   "<pre>foo</pre>\n<p>[bar]</p>");
+    }
+
+    #[test]
+    fn test_load_stardict_from_file() {
+        let mut dict = load_stardict_from_file(PATH_STARDICT_IFO).unwrap();
+        assert_eq!(dict.short_name().unwrap(), "Tiny Dictionary");
+
+        let results = dict.lookup("bar", false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0][0], "Bar");
+        assert!(results[0][1].contains("A place for drinks."));
+
+        let results = dict.lookup("Straße", false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0][1].contains("A street."));
+
+        assert!(dict.lookup("cherry", false).unwrap().is_empty());
+        assert_eq!(dict.lookup("appl", true).unwrap().len(), 1);
+    }
+}
@@ -3,14 +3,18 @@ use std::fs::{self, File};
 use std::path::{Path, PathBuf};
 use std::collections::BTreeSet;
 use fxhash::FxHashMap;
+use regex::Regex;
+use lazy_static::lazy_static;
 use zip::ZipArchive;
 use percent_encoding::percent_decode_str;
 use anyhow::{Error, format_err};
 use crate::framebuffer::Pixmap;
 use crate::helpers::{Normalize, decode_entities};
-use crate::document::{Document, Location, TextLocation, TocEntry, BoundedText, chapter_from_uri};
+use crate::document::{Document, Location, TextLocation, TocEntry, BoundedText, DrmProtected, chapter_from_uri, chapter_progress};
+use crate::document::{chapter, chapter_relative};
 use crate::unit::pt_to_px;
-use crate::geom::{Boundary, CycleDir};
+use crate::geom::{Boundary, CycleDir, Edge};
+use crate::settings::DEFAULT_FONT_SIZE;
 use super::pdf::PdfOpener;
 use super::html::dom::{XmlTree, NodeRef};
 use super::html::engine::{Page, Engine, ResourceFetcher};
@@ -24,8 +28,24 @@ use super::html::xml::XmlParser;
 const VIEWER_STYLESHEET: &str = "css/epub.css";
 const USER_STYLESHEET: &str = "css/epub-user.css";
 
+lazy_static! {
+    static ref PAGE_SIZE: Regex = Regex::new(r"@page[^{]*\{[^}]*size\s*:\s*([\d.]+)px\s+([\d.]+)px").unwrap();
+    static ref MARKUP_TAG: Regex = Regex::new(r"<[^>]*>").unwrap();
+    static ref WHITESPACE: Regex = Regex::new(r"\s+").unwrap();
+}
+
+// The values of the `epub:type` attribute that mark the target of a note reference.
+const NOTE_TYPES: [&str; 4] = ["footnote", "endnote", "rearnote", "note"];
+
+// The resolution at which one CSS pixel maps to one pixel of a fixed-layout page.
+const FIXED_LAYOUT_DPI: u16 = 96;
+
 type UriCache = FxHashMap<String, usize>;
 
+// Font obfuscation is the only legitimate use of encryption in EPUB files.
+const FONT_OBFUSCATION_ALGORITHMS: [&str; 2] = ["http://www.idpf.org/2008/embedding",
+                                                "http://ns.adobe.com/pdf/enc#RC"];
+
 impl ResourceFetcher for ZipArchive<File> {
     fn fetch(&mut self, name: &str) -> Result<Vec<u8>, Error> {
         let mut file = self.by_name(name)?;
@@ -43,22 +63,98 @@ pub struct EpubDocument {
     spine: Vec<Chunk>,
     cache: FxHashMap<usize, Vec<Page>>,
     ignore_document_css: bool,
+    // Each item of the spine is a page of fixed dimensions,
+    // the locations are then the indices of the spine items.
+    fixed_layout: bool,
 }
 
 #[derive(Debug)]
 struct Chunk {
     path: String,
     size: usize,
+    // Part of the main reading order.
+    linear: bool,
+    // The dimensions, in CSS pixels, of the page of a fixed-layout item.
+    // They're read when the document is opened.
+    viewport: Option<(u32, u32)>,
 }
 
 unsafe impl Send for EpubDocument {}
 unsafe impl Sync for EpubDocument {}
 
+fn is_drm_protected(archive: &mut ZipArchive<File>) -> bool {
+    // Adobe ADEPT stores its license in this file.
+    if archive.by_name("META-INF/rights.xml").is_ok() {
+        return true;
+    }
+
+    let text = match archive.by_name("META-INF/encryption.xml") {
+        Ok(mut zf) => {
+            let mut text = String::new();
+            if zf.read_to_string(&mut text).is_err() {
+                return true;
+            }
+            text
+        },
+        Err(_) => return false,
+    };
+
+    let root = XmlParser::new(&text).parse();
+    root.root().descendants()
+        .filter(|n| n.tag_name() == Some("EncryptionMethod"))
+        .any(|n| n.attribute("Algorithm")
+                  .is_none_or(|a| !FONT_OBFUSCATION_ALGORITHMS.contains(&a)))
+}
+
+// Reads the page dimensions of a fixed-layout item from its viewport meta tag,
+// or from the size of the `@page` rule of its inner style sheets.
+fn viewport_size(archive: &mut ZipArchive<File>, path: &str) -> Option<(u32, u32)> {
+    let text = archive.fetch(path).ok()
+                      .and_then(|buf| String::from_utf8(buf).ok())?;
+    let root = XmlParser::new(&text).parse();
+    let head = root.root().find("head")?;
+    head.children()
+        .find(|child| child.tag_name() == Some("meta") && child.attribute("name") == Some("viewport"))
+        .and_then(|meta| meta.attribute("content"))
+        .and_then(parse_viewport)
+        .or_else(|| head.children()
+                        .filter(|child| child.tag_name() == Some("style"))
+                        .find_map(|style| parse_page_size(&style.text())))
+}
+
+// Example: `width=1200, height=1600`.
+fn parse_viewport(content: &str) -> Option<(u32, u32)> {
+    let mut width = None;
+    let mut height = None;
+    for (key, value) in content.split([',', ';']).filter_map(|item| item.split_once('=')) {
+        match key.trim() {
+            "width" => width = value.trim().parse::<f32>().ok(),
+            "height" => height = value.trim().parse::<f32>().ok(),
+            _ => (),
+        }
+    }
+    width.zip(height)
+         .filter(|&(width, height)| width >= 1.0 && height >= 1.0)
+         .map(|(width, height)| (width.round() as u32, height.round() as u32))
+}
+
+// Example: `@page { size: 1200px 1600px; }`.
+fn parse_page_size(css: &str) -> Option<(u32, u32)> {
+    let caps = PAGE_SIZE.captures(css)?;
+    let width = caps[1].parse::<f32>().ok()?;
+    let height = caps[2].parse::<f32>().ok()?;
+    Some((width.round() as u32, height.round() as u32)).filter(|&(width, height)| width > 0 && height > 0)
+}
+
 impl EpubDocument {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<EpubDocument, Error> {
-        let file = File::open(path)?;
+        let file = File::open(path.as_ref())?;
         let mut archive = ZipArchive::new(file)?;
 
+        if is_drm_protected(&mut archive) {
+            return Err(DrmProtected.into());
+        }
+
         let opf_path = {
             let mut zf = archive.by_name("META-INF/container.xml")?;
             let mut text = String::new();
@@ -81,6 +177,7 @@ impl EpubDocument {
 
         let info = XmlParser::new(&text).parse();
         let mut spine = Vec::new();
+        let mut pre_paginated = true;
 
         {
             let manifest = info.root().find("manifest")
@@ -107,7 +204,11 @@ impl EpubDocument {
                 });
 
                 if let Some((size, path)) = vertebra_opt {
-                    spine.push(Chunk { path, size });
+                    let linear = child.attribute("linear") != Some("no");
+                    pre_paginated &= child.attribute("properties").into_iter()
+                                          .flat_map(str::split_whitespace)
+                                          .any(|p| p == "rendition:layout-pre-paginated");
+                    spine.push(Chunk { path, size, linear, viewport: None });
                 }
             }
         }
@@ -116,6 +217,24 @@ impl EpubDocument {
             return Err(format_err!("the spine is empty"));
         }
 
+        // The non-linear items are skipped when turning pages:
+        // they can only be reached through links.
+        if spine.iter().all(|chunk| !chunk.linear) {
+            spine.iter_mut().for_each(|chunk| chunk.linear = true);
+        }
+
+        let fixed_layout = pre_paginated || info.root().find("metadata").is_some_and(|md| {
+            md.children().any(|child| child.tag_name() == Some("meta") &&
+                                      child.attribute("property") == Some("rendition:layout") &&
+                                      child.text().trim() == "pre-paginated")
+        });
+
+        if fixed_layout {
+            for chunk in &mut spine {
+                chunk.viewport = viewport_size(&mut archive, &chunk.path);
+            }
+        }
+
         Ok(EpubDocument {
             archive,
             info,
@@ -124,6 +243,7 @@ impl EpubDocument {
             spine,
             cache: FxHashMap::default(),
             ignore_document_css: false,
+            fixed_layout,
         })
     }
 
@@ -153,6 +273,17 @@ impl EpubDocument {
         None
     }
 
+    // The index of the linear item that precedes the given one.
+    fn previous_linear(&self, index: usize) -> Option<usize> {
+        self.spine[..index].iter().rposition(|chunk| chunk.linear)
+    }
+
+    // The index of the linear item that follows the given one.
+    fn next_linear(&self, index: usize) -> Option<usize> {
+        self.spine.iter().skip(index + 1).position(|chunk| chunk.linear)
+            .map(|delta| index + 1 + delta)
+    }
+
     fn vertebra_coordinates(&self, offset: usize) -> Option<(usize, usize)> {
         self.vertebra_coordinates_with(|_, end_offset| {
             offset < end_offset
@@ -280,7 +411,28 @@ impl EpubDocument {
         let frag_index_opt = uri.find('#');
         let name = &uri[..frag_index_opt.unwrap_or_else(|| uri.len())];
 
-        let (index, start_offset) = self.vertebra_coordinates_from_name(name)?;
+        // A bare fragment, e.g. `#chapter3`: the identifier is searched in every chapter.
+        if name.is_empty() {
+            let paths = self.spine.iter().map(|chunk| chunk.path.clone()).collect::<Vec<String>>();
+            return paths.into_iter().find_map(|path| self.resolve_link(&format!("{}{}", path, uri), cache));
+        }
+
+        let (index, start_offset) = match self.vertebra_coordinates_from_name(name) {
+            Some(coordinates) => coordinates,
+            None => {
+                // The path might be relative to the directory of the package document.
+                let path = self.parent.join(name).normalize().to_string_lossy().into_owned();
+                if path == name || self.vertebra_coordinates_from_name(&path).is_none() {
+                    return None;
+                }
+                return self.resolve_link(&format!("{}{}", path, &uri[name.len()..]), cache);
+            },
+        };
+
+        // The fragment is ignored: the whole item is shown on a single page.
+        if self.fixed_layout {
+            return Some(index);
+        }
 
         if frag_index_opt.is_some() {
             let mut text = String::new();
@@ -301,6 +453,24 @@ impl EpubDocument {
         }
     }
 
+    // Converts a URI relative to the chapter at the given offset into a path within the archive.
+    fn normalize_uri(&self, offset: usize, uri: &str) -> Option<String> {
+        let index = if self.fixed_layout {
+            Some(offset).filter(|&index| index < self.spine.len())?
+        } else {
+            self.vertebra_coordinates(offset)?.0
+        };
+        let path = &self.spine[index].path;
+        if uri.starts_with('#') {
+            Some(format!("{}{}", path, uri))
+        } else {
+            let parent = Path::new(path).parent()
+                              .unwrap_or_else(|| Path::new(""));
+            Some(parent.join(uri).normalize()
+                       .to_string_lossy().into_owned())
+        }
+    }
+
     fn cache_uris(&mut self, node: NodeRef, name: &str, start_offset: usize, cache: &mut UriCache) {
         if let Some(id) = node.attribute("id") {
             let location = start_offset + node.offset();
@@ -311,6 +481,75 @@ impl EpubDocument {
         }
     }
 
+    // The viewport of the given fixed-layout item.
+    fn viewport(&self, index: usize) -> Option<(u32, u32)> {
+        self.spine.get(index).and_then(|chunk| chunk.viewport)
+    }
+
+    // Runs *f* with the engine set up for the page of the given fixed-layout item.
+    fn with_viewport<F, T>(&mut self, index: usize, f: F) -> T where F: FnOnce(&mut EpubDocument) -> T {
+        let (dims, dpi, font_size, margin) = (self.engine.dims, self.engine.dpi,
+                                              self.engine.font_size, self.engine.margin);
+        let (width, height) = self.viewport(index).unwrap_or(dims);
+        self.engine.layout(width, height, DEFAULT_FONT_SIZE, FIXED_LAYOUT_DPI);
+        self.engine.set_margin(&Edge::default());
+        let result = f(self);
+        self.engine.layout(dims.0, dims.1, font_size, dpi);
+        self.engine.set_margin(&margin);
+        result
+    }
+
+    // The page of a fixed-layout item. What doesn't fit within the viewport is dropped.
+    fn fixed_page(&mut self, index: usize) -> Option<&Page> {
+        if index >= self.spine.len() {
+            return None;
+        }
+        if !self.cache.contains_key(&index) {
+            let start_offset = self.offset(index);
+            let display_list = self.with_viewport(index, |doc| doc.build_display_list(index, start_offset));
+            self.cache.insert(index, display_list);
+        }
+        self.cache.get(&index).and_then(|display_list| display_list.first())
+    }
+
+    fn resolve_fixed_location(&mut self, loc: Location) -> Option<usize> {
+        let count = self.spine.len();
+        match loc {
+            Location::Exact(index) => Some(index).filter(|&index| index < count),
+            Location::Previous(index) => {
+                if index >= count {
+                    return None;
+                }
+                self.previous_linear(index)
+            },
+            Location::Next(index) => {
+                if index >= count {
+                    return None;
+                }
+                self.next_linear(index)
+            },
+            Location::LocalUri(index, ref uri) => {
+                let normalized_uri = self.normalize_uri(index, uri)?;
+                self.resolve_link(&normalized_uri, &mut FxHashMap::default())
+            },
+            Location::Uri(ref uri) => {
+                self.resolve_link(uri, &mut FxHashMap::default())
+            },
+        }
+    }
+
+    // Turns the URIs of the table of contents into page indices.
+    fn locate_toc_entries(&mut self, entries: &mut [TocEntry]) {
+        for entry in entries {
+            if let Location::Uri(ref uri) = entry.location {
+                if let Some(index) = self.resolve_link(uri, &mut FxHashMap::default()) {
+                    entry.location = Location::Exact(index);
+                }
+            }
+            self.locate_toc_entries(&mut entry.children);
+        }
+    }
+
     fn build_display_list(&mut self, index: usize, start_offset: usize) -> Vec<Page> {
         let mut text = String::new();
         let mut spine_dir = PathBuf::default();
@@ -330,14 +569,15 @@ impl EpubDocument {
         root.wrap_lost_inlines();
 
         let mut stylesheet = StyleSheet::new();
+        let media = self.engine.media();
 
         if let Ok(text) = fs::read_to_string(VIEWER_STYLESHEET) {
-            let mut css = CssParser::new(&text).parse();
+            let mut css = CssParser::new(&text).with_media(media).parse();
             stylesheet.append(&mut css, true);
         }
 
         if let Ok(text) = fs::read_to_string(USER_STYLESHEET) {
-            let mut css = CssParser::new(&text).parse();
+            let mut css = CssParser::new(&text).with_media(media).parse();
             stylesheet.append(&mut css, true);
         }
 
@@ -345,19 +585,22 @@ impl EpubDocument {
             let mut inner_css = StyleSheet::new();
             if let Some(head) = root.root().find("head") {
                 for child in head.children() {
+                    if child.attribute("media").is_some_and(|query| !media.matches(query)) {
+                        continue;
+                    }
                     if child.tag_name() == Some("link") && child.attribute("rel") == Some("stylesheet") {
                         if let Some(href) = child.attribute("href") {
                             if let Some(name) = spine_dir.join(href).normalize().to_str() {
                                 let mut text = String::new();
                                 if let Ok(mut zf) = self.archive.by_name(name) {
                                     zf.read_to_string(&mut text).ok();
-                                    let mut css = CssParser::new(&text).parse();
+                                    let mut css = CssParser::new(&text).with_media(media).parse();
                                     inner_css.append(&mut css, false);
                                 }
                             }
                         }
                     } else if child.tag_name() == Some("style") && child.attribute("type") == Some("text/css") {
-                        let mut css = CssParser::new(&child.text()).parse();
+                        let mut css = CssParser::new(&child.text()).with_media(media).parse();
                         inner_css.append(&mut css, false);
                     }
                 }
@@ -366,6 +609,10 @@ impl EpubDocument {
             stylesheet.append(&mut inner_css, true);
         }
 
+        if let Some(mut css) = self.engine.text_align_stylesheet() {
+            stylesheet.append(&mut css, false);
+        }
+
         let mut display_list = Vec::new();
 
         if let Some(body) = root.root().find("body") {
@@ -543,7 +790,14 @@ impl EpubDocument {
     }
 
     pub fn cover_image(&self) -> Option<&str> {
-        self.info.root().find("metadata")
+        self.info.root().find("manifest")
+            .and_then(|mf| mf.children().find(|child| {
+                child.attribute("properties").into_iter()
+                     .flat_map(str::split_whitespace)
+                     .any(|p| p == "cover-image")
+            }))
+            .and_then(|entry| entry.attribute("href"))
+            .or_else(|| self.info.root().find("metadata")
             .and_then(|md| md.children().find(|child| {
                 child.tag_name() == Some("meta") &&
                 child.attribute("name") == Some("cover")
@@ -553,7 +807,7 @@ impl EpubDocument {
                 self.info.root().find("manifest")
                     .and_then(|entry| entry.find_by_id(cover_id))
                     .and_then(|entry| entry.attribute("href"))
-            })
+            }))
             .or_else(|| {
                 self.info.root().find("manifest")
                     .and_then(|mf| mf.children().find(|child| {
@@ -565,8 +819,19 @@ impl EpubDocument {
             })
     }
 
+    // The raw bytes of the cover image.
+    pub fn cover(&mut self) -> Option<Vec<u8>> {
+        let path = self.cover_image()
+                       .map(|path| self.parent.join(path)
+                                       .to_string_lossy().into_owned())?;
+        self.archive.fetch(&path).ok()
+    }
+
+    // The description might contain markup.
     pub fn description(&self) -> Option<String> {
         self.metadata("dc:description")
+            .map(|text| WHITESPACE.replace_all(&MARKUP_TAG.replace_all(&text, " "), " ").trim().to_string())
+            .filter(|text| !text.is_empty())
     }
 
     pub fn publisher(&self) -> Option<String> {
@@ -585,12 +850,11 @@ impl EpubDocument {
 impl Document for EpubDocument {
     fn preview_pixmap(&mut self, width: f32, height: f32, samples: usize) -> Option<Pixmap> {
         let opener = PdfOpener::new()?;
-        self.cover_image()
-            .map(|path| self.parent.join(path)
-                            .to_string_lossy().into_owned())
-            .and_then(|path| {
-                self.archive.fetch(&path).ok()
-                    .and_then(|buf| opener.open_memory(&path, &buf))
+        let path = self.cover_image().map(String::from);
+        self.cover()
+            .zip(path)
+            .and_then(|(buf, path)| {
+                opener.open_memory(&path, &buf)
                     .and_then(|mut doc| {
                         doc.dims(0).and_then(|dims| {
                             let scale = (width / dims.0).min(height / dims.1);
@@ -608,11 +872,20 @@ impl Document for EpubDocument {
     }
 
     #[inline]
-    fn dims(&self, _index: usize) -> Option<(f32, f32)> {
+    fn dims(&self, index: usize) -> Option<(f32, f32)> {
+        if self.fixed_layout {
+            return self.spine.get(index).map(|chunk| {
+                let (width, height) = chunk.viewport.unwrap_or(self.engine.dims);
+                (width as f32, height as f32)
+            });
+        }
         Some((self.engine.dims.0 as f32, self.engine.dims.1 as f32))
     }
 
     fn pages_count(&self) -> usize {
+        if self.fixed_layout {
+            return self.spine.len();
+        }
         self.spine.iter().map(|c| c.size).sum()
     }
 
@@ -647,7 +920,7 @@ impl Document for EpubDocument {
 
         let root = XmlParser::new(&text).parse();
 
-        if name.ends_with(".ncx") {
+        let mut toc = if name.ends_with(".ncx") {
             root.root().find("navMap").map(|map| {
                 self.walk_toc_ncx(map, toc_dir, &mut 0, &mut FxHashMap::default())
             })
@@ -658,15 +931,27 @@ impl Document for EpubDocument {
                 .and_then(|map| map.find("ol")).map(|map| {
                 self.walk_toc_nav(map, toc_dir, &mut 0, &mut FxHashMap::default())
             })
+        };
+
+        if self.fixed_layout {
+            if let Some(entries) = toc.as_mut() {
+                self.locate_toc_entries(entries);
+            }
         }
+
+        toc
     }
 
     fn chapter<'a>(&mut self, offset: usize, toc: &'a [TocEntry]) -> Option<(&'a TocEntry, f32)> {
+        if self.fixed_layout {
+            return chapter(offset, self.pages_count(), toc);
+        }
         let next_offset = self.resolve_location(Location::Next(offset))
                               .unwrap_or(usize::MAX);
         let (index, start_offset) = self.vertebra_coordinates(offset)?;
         let path = self.spine[index].path.clone();
-        let mut end_offset = start_offset + self.spine[index].size;
+        let item_end_offset = start_offset + self.spine[index].size;
+        let mut end_offset = item_end_offset;
         let mut chap_before = None;
         let mut chap_after = None;
         let mut offset_before = 0;
@@ -680,27 +965,32 @@ impl Document for EpubDocument {
             for i in (0..index).rev() {
                 let chap = chapter_from_uri(&self.spine[i].path, toc);
                 if chap.is_some() {
-                    end_offset = if let Some(j) = (index+1..self.spine.len()).find(|&j| chapter_from_uri(&self.spine[j].path, toc).is_some()) {
-                        self.offset(j)
-                    } else {
-                        self.size()
-                    };
+                    // The chapter ends at the next entry of the current item, if any.
+                    if end_offset == item_end_offset {
+                        end_offset = if let Some(j) = (index+1..self.spine.len()).find(|&j| chapter_from_uri(&self.spine[j].path, toc).is_some()) {
+                            self.offset(j)
+                        } else {
+                            self.size()
+                        };
+                    }
                     let chap_offset = self.offset(i);
-                    let progress = (offset - chap_offset) as f32 / (end_offset - chap_offset) as f32;
-                    return chap.zip(Some(progress));
+                    return chap.zip(Some(chapter_progress(offset, chap_offset, end_offset)));
                 }
             }
             None
         } else {
             match (chap_after, chap_before) {
                 (Some(..), _) => chap_after.zip(Some(0.0)),
-                (None, Some(..)) => chap_before.zip(Some((offset - offset_before) as f32 / (end_offset - offset_before) as f32)),
+                (None, Some(..)) => chap_before.zip(Some(chapter_progress(offset, offset_before, end_offset))),
                 _ => None,
             }
         }
     }
 
     fn chapter_relative<'a>(&mut self, offset: usize, dir: CycleDir, toc: &'a [TocEntry]) -> Option<&'a TocEntry> {
+        if self.fixed_layout {
+            return chapter_relative(offset, dir, toc);
+        }
         let next_offset = self.resolve_location(Location::Next(offset))
                               .unwrap_or(usize::MAX);
         let chap = self.chapter(offset, toc).map(|(c, _)| c);
@@ -714,6 +1004,10 @@ impl Document for EpubDocument {
     fn resolve_location(&mut self, loc: Location) -> Option<usize> {
         self.engine.load_fonts();
 
+        if self.fixed_layout {
+            return self.resolve_fixed_location(loc);
+        }
+
         match loc {
             Location::Exact(offset) => {
                 let (index, start_offset) = self.vertebra_coordinates(offset)?;
@@ -729,10 +1023,8 @@ impl Document for EpubDocument {
                     self.cache.get(&index)
                         .and_then(|display_list| display_list[page_index-1].first().map(DrawCommand::offset))
                 } else {
-                    if index == 0 {
-                        return None;
-                    }
-                    let (index, start_offset) = (index - 1, start_offset - self.spine[index-1].size);
+                    let index = self.previous_linear(index)?;
+                    let start_offset = self.offset(index);
                     if !self.cache.contains_key(&index) {
                         let display_list = self.build_display_list(index, start_offset);
                         self.cache.insert(index, display_list);
@@ -747,10 +1039,8 @@ impl Document for EpubDocument {
                 if page_index < self.cache.get(&index).map(Vec::len)? - 1 {
                     self.cache.get(&index).and_then(|display_list| display_list[page_index+1].first().map(DrawCommand::offset))
                 } else {
-                    if index == self.spine.len() - 1 {
-                        return None;
-                    }
-                    let (index, start_offset) = (index + 1, start_offset + self.spine[index].size);
+                    let index = self.next_linear(index)?;
+                    let start_offset = self.offset(index);
                     if !self.cache.contains_key(&index) {
                         let display_list = self.build_display_list(index, start_offset);
                         self.cache.insert(index, display_list);
@@ -761,18 +1051,7 @@ impl Document for EpubDocument {
             },
             Location::LocalUri(offset, ref uri) => {
                 let mut cache = FxHashMap::default();
-                let normalized_uri: String = {
-                    let (index, _) = self.vertebra_coordinates(offset)?;
-                    let path = &self.spine[index].path;
-                    if uri.starts_with('#') {
-                        format!("{}{}", path, uri)
-                    } else {
-                        let parent = Path::new(path).parent()
-                                          .unwrap_or_else(|| Path::new(""));
-                        parent.join(uri).normalize()
-                              .to_string_lossy().into_owned()
-                    }
-                };
+                let normalized_uri = self.normalize_uri(offset, uri)?;
                 self.resolve_link(&normalized_uri, &mut cache)
             },
             Location::Uri(ref uri) => {
@@ -787,6 +1066,23 @@ impl Document for EpubDocument {
             return None;
         }
 
+        if self.fixed_layout {
+            let index = self.resolve_location(loc)?;
+            let page = self.fixed_page(index)?;
+            return Some((page.iter().filter_map(|dc| {
+                match dc {
+                    DrawCommand::Text(TextCommand { text, rect, .. }) => Some((text, rect)),
+                    _ => None,
+                }
+            }).enumerate().map(|(i, (text, rect))| {
+                BoundedText {
+                    text: text.clone(),
+                    rect: (*rect).into(),
+                    location: TextLocation::Static(index, i),
+                }
+            }).collect(), index));
+        }
+
         let offset = self.resolve_location(loc)?;
         let (index, start_offset) = self.vertebra_coordinates(offset)?;
         let page_index = self.page_index(offset, index, start_offset)?;
@@ -816,6 +1112,24 @@ impl Document for EpubDocument {
             return None;
         }
 
+        if self.fixed_layout {
+            let index = self.resolve_location(loc)?;
+            let page = self.fixed_page(index)?;
+            return Some((page.iter().filter_map(|dc| {
+                match dc {
+                    DrawCommand::Text(TextCommand { uri, rect, .. }) |
+                    DrawCommand::Image(ImageCommand { uri, rect, .. }) if uri.is_some() => Some((uri, rect)),
+                    _ => None,
+                }
+            }).enumerate().map(|(i, (uri, rect))| {
+                BoundedText {
+                    text: uri.clone().unwrap(),
+                    rect: (*rect).into(),
+                    location: TextLocation::Static(index, i),
+                }
+            }).collect(), index));
+        }
+
         let offset = self.resolve_location(loc)?;
         let (index, start_offset) = self.vertebra_coordinates(offset)?;
         let page_index = self.page_index(offset, index, start_offset)?;
@@ -842,6 +1156,17 @@ impl Document for EpubDocument {
             return None;
         }
 
+        if self.fixed_layout {
+            let index = self.resolve_location(loc)?;
+            let page = self.fixed_page(index)?;
+            return Some((page.iter().filter_map(|dc| {
+                match dc {
+                    DrawCommand::Image(ImageCommand { rect, .. }) => Some((*rect).into()),
+                    _ => None,
+                }
+            }).collect(), index));
+        }
+
         let offset = self.resolve_location(loc)?;
         let (index, start_offset) = self.vertebra_coordinates(offset)?;
         let page_index = self.page_index(offset, index, start_offset)?;
@@ -861,6 +1186,15 @@ impl Document for EpubDocument {
             return None;
         }
 
+        if self.fixed_layout {
+            let index = self.resolve_location(loc)?;
+            let page = self.fixed_page(index)?.clone();
+            let pixmap = self.with_viewport(index, |doc| {
+                doc.engine.render_page(&page, scale, samples, &mut doc.archive)
+            })?;
+            return Some((pixmap, index));
+        }
+
         let offset = self.resolve_location(loc)?;
         let (index, start_offset) = self.vertebra_coordinates(offset)?;
 
@@ -907,6 +1241,21 @@ impl Document for EpubDocument {
         self.cache.clear();
     }
 
+    fn set_force_chapter_breaks(&mut self, force: bool) {
+        self.engine.set_force_chapter_breaks(force);
+        self.cache.clear();
+    }
+
+    fn set_force_text_align(&mut self, force: bool) {
+        self.engine.set_force_text_align(force);
+        self.cache.clear();
+    }
+
+    fn set_margin_bias(&mut self, bias: i32) {
+        self.engine.set_margin_bias(bias);
+        self.cache.clear();
+    }
+
     fn set_ignore_document_css(&mut self, ignore: bool) {
         self.ignore_document_css = ignore;
         self.cache.clear();
@@ -927,11 +1276,63 @@ impl Document for EpubDocument {
             .map(|child| decode_entities(&child.text()).into_owned())
     }
 
+    fn footnote(&mut self, loc: Location) -> Option<String> {
+        let uri = match loc {
+            Location::LocalUri(offset, ref uri) => self.normalize_uri(offset, uri)?,
+            Location::Uri(ref uri) => uri.clone(),
+            _ => return None,
+        };
+        let (name, id) = uri.split_once('#')?;
+        let mut text = String::new();
+        {
+            let mut zf = self.archive.by_name(name).ok()?;
+            zf.read_to_string(&mut text).ok()?;
+        }
+        let root = XmlParser::new(&text).parse();
+        let target = root.root().find_by_id(id)?;
+        let note = Some(target).into_iter().chain(target.ancestor_elements())
+                               .find(|node| node.attribute("epub:type")
+                                                .is_some_and(|kind| kind.split_whitespace()
+                                                                        .any(|k| NOTE_TYPES.contains(&k))))?;
+        let text = decode_entities(&note.text()).split_whitespace()
+                                                 .collect::<Vec<&str>>().join(" ");
+        Some(text).filter(|text| !text.is_empty())
+    }
+
     fn is_reflowable(&self) -> bool {
-        true
+        !self.fixed_layout
     }
 
     fn has_synthetic_page_numbers(&self) -> bool {
-        true
+        !self.fixed_layout
+    }
+
+    fn is_right_to_left(&self) -> bool {
+        self.info.root().find("spine")
+            .and_then(|spine| spine.attribute("page-progression-direction"))
+            == Some("rtl")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_viewport, parse_page_size};
+
+    #[test]
+    fn test_parse_viewport() {
+        assert_eq!(parse_viewport("width=1200, height=1600"), Some((1200, 1600)));
+        assert_eq!(parse_viewport("height = 1600.4; width = 1199.6"), Some((1200, 1600)));
+        assert_eq!(parse_viewport("width=device-width, initial-scale=1"), None);
+        assert_eq!(parse_viewport("width=1200"), None);
+        assert_eq!(parse_viewport("width=0, height=1600"), None);
+    }
+
+    #[test]
+    fn test_parse_page_size() {
+        assert_eq!(parse_page_size("@page { size: 1200px 1600px; }"), Some((1200, 1600)));
+        assert_eq!(parse_page_size("body { margin: 0 }\n@page :first {\n  margin: 0;\n  size: 600.5px 800px;\n}"), Some((601, 800)));
+        assert_eq!(parse_page_size("@page { size: A4; }"), None);
+        assert_eq!(parse_page_size("@page { size: 0px 800px; }"), None);
+        assert_eq!(parse_page_size("div { size: 1200px 1600px; }"), None);
     }
 }
@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use fxhash::FxHashSet;
 use super::style::StyleSheet;
 
@@ -113,10 +114,150 @@ pub struct Rule {
     pub declarations: Vec<Declaration>,
 }
 
+// The characteristics of the output device, against which the media queries are evaluated.
+#[derive(Debug, Copy, Clone)]
+pub struct Media {
+    pub width: u32,
+    pub height: u32,
+    pub dpi: u16,
+    // The number of bits per color component, zero for monochrome devices.
+    pub color: u8,
+    // The number of bits per pixel of monochrome devices, zero for color devices.
+    pub monochrome: u8,
+}
+
+// The size, in CSS pixels, of the *em* unit of the media queries.
+const MEDIA_EM: f32 = 16.0;
+const CSS_DPI: f32 = 96.0;
+
+impl Media {
+    // Evaluates a comma-separated list of media queries.
+    pub fn matches(&self, queries: &str) -> bool {
+        queries.split(',').any(|query| self.matches_query(query))
+    }
+
+    fn matches_query(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        let (negated, query) = if let Some(rest) = query.strip_prefix("not ") {
+            (true, rest.trim_start())
+        } else {
+            (false, query.strip_prefix("only ").map_or(&query[..], str::trim_start))
+        };
+
+        let mut result = true;
+
+        for term in query.split(" and ").map(str::trim) {
+            let matched = if let Some(feature) = term.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+                self.matches_feature(feature)
+            } else {
+                matches!(term, "all" | "screen")
+            };
+            if !matched {
+                result = false;
+                break;
+            }
+        }
+
+        result != negated
+    }
+
+    fn matches_feature(&self, feature: &str) -> bool {
+        let (name, value) = match feature.split_once(':') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (feature.trim(), None),
+        };
+        let (prefix, base) = if let Some(base) = name.strip_prefix("min-") {
+            (Some(Ordering::Greater), base)
+        } else if let Some(base) = name.strip_prefix("max-") {
+            (Some(Ordering::Less), base)
+        } else {
+            (None, name)
+        };
+
+        let actual = match base {
+            "width" | "device-width" => self.width as f32 * CSS_DPI / self.dpi as f32,
+            "height" | "device-height" => self.height as f32 * CSS_DPI / self.dpi as f32,
+            "aspect-ratio" | "device-aspect-ratio" => self.width as f32 / self.height.max(1) as f32,
+            "resolution" => self.dpi as f32,
+            "color" => self.color as f32,
+            "monochrome" => self.monochrome as f32,
+            "color-index" => 0.0,
+            "orientation" => {
+                let orientation = if self.width > self.height { "landscape" } else { "portrait" };
+                return prefix.is_none() && value.is_none_or(|v| v == orientation);
+            },
+            // E-ink screens are slowly updated, and are operated with the fingers.
+            "update" => return prefix.is_none() && value.is_none_or(|v| v == "slow"),
+            "hover" | "any-hover" => return prefix.is_none() && value == Some("none"),
+            "pointer" | "any-pointer" => return prefix.is_none() && value.is_none_or(|v| v == "coarse"),
+            "prefers-color-scheme" => return prefix.is_none() && value == Some("light"),
+            _ => return false,
+        };
+
+        let value = match value {
+            Some(value) => value,
+            None => return prefix.is_none() && actual > 0.0,
+        };
+
+        let expected = match base {
+            "width" | "device-width" | "height" | "device-height" => parse_media_length(value),
+            "aspect-ratio" | "device-aspect-ratio" => parse_ratio(value),
+            "resolution" => parse_resolution(value),
+            _ => value.parse::<f32>().ok(),
+        };
+
+        expected.is_some_and(|expected| match prefix {
+            Some(Ordering::Greater) => actual >= expected,
+            Some(Ordering::Less) => actual <= expected,
+            _ => (actual - expected).abs() < 0.5,
+        })
+    }
+}
+
+fn parse_media_length(value: &str) -> Option<f32> {
+    let index = value.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(value.len());
+    let number = value[..index].trim().parse::<f32>().ok()?;
+    let factor = match &value[index..] {
+        "px" | "" => 1.0,
+        "em" | "rem" => MEDIA_EM,
+        "pt" => CSS_DPI / 72.0,
+        "pc" => CSS_DPI / 6.0,
+        "in" => CSS_DPI,
+        "cm" => CSS_DPI / 2.54,
+        "mm" => CSS_DPI / 25.4,
+        _ => return None,
+    };
+    Some(number * factor)
+}
+
+fn parse_ratio(value: &str) -> Option<f32> {
+    match value.split_once('/') {
+        Some((num, den)) => {
+            let den = den.trim().parse::<f32>().ok().filter(|&d| d > 0.0)?;
+            num.trim().parse::<f32>().ok().map(|num| num / den)
+        },
+        None => value.parse::<f32>().ok(),
+    }
+}
+
+fn parse_resolution(value: &str) -> Option<f32> {
+    let index = value.find(|c: char| c.is_ascii_alphabetic())?;
+    let number = value[..index].trim().parse::<f32>().ok()?;
+    match &value[index..] {
+        "dpi" => Some(number),
+        "dpcm" => Some(number * 2.54),
+        "dppx" | "x" => Some(number * CSS_DPI),
+        _ => None,
+    }
+}
+
 #[derive(Debug)]
 pub struct CssParser<'a> {
     input: &'a str,
     offset: usize,
+    // The rules of the media blocks are only kept when their query matches the media.
+    // Without media, the media blocks are skipped.
+    media: Option<Media>,
 }
 
 impl<'a> CssParser<'a> {
@@ -124,9 +265,15 @@ impl<'a> CssParser<'a> {
         CssParser {
             input,
             offset: 0,
+            media: None,
         }
     }
 
+    pub fn with_media(mut self, media: Media) -> CssParser<'a> {
+        self.media = Some(media);
+        self
+    }
+
     fn eof(&self) -> bool {
         self.offset >= self.input.len()
     }
@@ -210,6 +357,39 @@ impl<'a> CssParser<'a> {
         }
     }
 
+    fn parse_media_rule(&mut self, rules: &mut Vec<Rule>) {
+        let media = match self.media {
+            Some(media) => media,
+            None => return self.skip_at_rule(),
+        };
+
+        self.advance("@media".len());
+        let offset = self.offset;
+        self.advance_while(|&c| c != ';' && c != '{');
+
+        if self.next() != Some('{') || !media.matches(&self.input[offset..self.offset]) {
+            self.skip_at_rule();
+            return;
+        }
+
+        self.advance(1);
+
+        while !self.eof() {
+            self.skip_spaces_and_comments();
+
+            match self.next() {
+                None => break,
+                Some('}') => {
+                    self.advance(1);
+                    break;
+                },
+                Some('@') if self.starts_with("@media") => self.parse_media_rule(rules),
+                Some('@') => self.skip_at_rule(),
+                _ => self.parse_rules(rules),
+            }
+        }
+    }
+
     fn attribute_value(&mut self) -> String {
         match self.next() {
             Some(delim @ '"' | delim @ '\'') => {
@@ -488,6 +668,7 @@ impl<'a> CssParser<'a> {
 
             match self.next() {
                 None => break,
+                Some('@') if self.starts_with("@media") => self.parse_media_rule(&mut rules),
                 Some('@') => self.skip_at_rule(),
                 _ => self.parse_rules(&mut rules),
             }
@@ -514,4 +695,32 @@ mod tests {
         let css = CssParser::new(text).parse();
         println!("{:?}", css);
     }
+
+    const EINK: Media = Media { width: 1072, height: 1448, dpi: 300, color: 0, monochrome: 4 };
+
+    #[test]
+    fn media_queries() {
+        assert!(EINK.matches("all"));
+        assert!(EINK.matches("only screen and (monochrome)"));
+        assert!(!EINK.matches("(color)"));
+        assert!(EINK.matches("not (color)"));
+        assert!(!EINK.matches("print"));
+        assert!(EINK.matches("print, (min-monochrome: 2)"));
+        assert!(EINK.matches("screen and (max-width: 400px) and (orientation: portrait)"));
+        assert!(!EINK.matches("(min-width: 30em)"));
+        assert!(EINK.matches("(min-resolution: 2dppx)"));
+        assert!(EINK.matches("(update: slow)"));
+        assert!(!EINK.matches("amzn-kf8"));
+    }
+
+    #[test]
+    fn media_css() {
+        let text = "a { b: c } @media (color) { .d { e: f } } @media screen and (monochrome) { .g { h: i } @page { j: k } } l { m: n }";
+        let css = CssParser::new(text).parse();
+        assert_eq!(css.rules.len(), 2);
+        let css = CssParser::new(text).with_media(EINK).parse();
+        assert_eq!(css.rules.len(), 3);
+        assert!(css.rules[1].selector.simple_selectors[0].classes.contains("g"));
+        assert_eq!(css.rules[2].declarations[0].name, "m");
+    }
 }
@@ -11,26 +11,29 @@ use crate::helpers::{Normalize, decode_entities};
 use crate::framebuffer::{Framebuffer, Pixmap};
 use crate::font::{FontOpener, FontFamily};
 use crate::document::{Document, Location};
-use crate::document::pdf::PdfOpener;
+use crate::device::CURRENT_DEVICE;
+use crate::document::pdf::{PdfOpener, PdfDocument};
 use crate::unit::{mm_to_px, pt_to_px};
-use crate::geom::{Point, Vec2, Rectangle, Edge};
+use crate::geom::{Point, Vec2, Rectangle, Boundary, Edge, halves};
 use crate::settings::{HYPHEN_PENALTY, STRETCH_TOLERANCE};
 use crate::settings::{DEFAULT_FONT_SIZE, DEFAULT_MARGIN_WIDTH, DEFAULT_TEXT_ALIGN, DEFAULT_LINE_HEIGHT};
 use super::parse::{parse_display, parse_edge, parse_float, parse_text_align, parse_text_indent};
 use super::parse::{parse_width, parse_height, parse_inline_material, parse_font_kind, parse_font_style};
 use super::parse::{parse_font_weight, parse_font_size, parse_font_features, parse_font_variant};
-use super::parse::{parse_letter_spacing, parse_word_spacing};
+use super::parse::{parse_letter_spacing, parse_word_spacing, parse_cell_width, parse_border};
 use super::parse::{parse_line_height, parse_vertical_align, parse_color, parse_list_style_type};
 use super::dom::{NodeRef, NodeData, ElementData, TextData, WRAPPER_TAG_NAME};
 use super::layout::{StyleData, InlineMaterial, TextMaterial, ImageMaterial};
 use super::layout::{GlueMaterial, PenaltyMaterial, ChildArtifact, SiblingStyle, LoopContext};
-use super::layout::{RootData, DrawState, DrawCommand, TextCommand, ImageCommand, FontKind, Fonts};
+use super::layout::{RootData, DrawState, DrawCommand, TextCommand, ImageCommand, BorderCommand, FontKind, Fonts};
 use super::layout::{TextAlign, ParagraphElement, TextElement, ImageElement, Display, Float};
 use super::layout::{WordSpacing, ListStyleType, LineStats};
-use super::layout::{hyph_lang, collapse_margins, DEFAULT_HYPH_LANG, HYPHENATION_PATTERNS};
+use super::layout::{hyph_lang, collapse_margins, baseline_offset, DEFAULT_HYPH_LANG, HYPHENATION_PATTERNS};
 use super::layout::{EM_SPACE_RATIOS, WORD_SPACE_RATIOS, FONT_SPACES};
-use super::style::{StyleSheet, specified_values};
+use super::style::{StyleSheet, PropertyMap, specified_values};
+use super::css::{CssParser, Media};
 use super::xml::XmlExt;
+use super::mathml::{mathml_to_svg, INLINE_SVG_PREFIX};
 
 const DEFAULT_DPI: u16 = 300;
 const DEFAULT_WIDTH: u32 = 1404;
@@ -42,6 +45,17 @@ pub trait ResourceFetcher {
     fn fetch(&mut self, name: &str) -> Result<Vec<u8>, Error>;
 }
 
+// The inline SVG images are carried by their path.
+fn open_image(path: &str, resource_fetcher: &mut dyn ResourceFetcher) -> Option<PdfDocument> {
+    let opener = PdfOpener::new()?;
+    if let Some(svg) = path.strip_prefix(INLINE_SVG_PREFIX) {
+        opener.open_memory("image/svg+xml", svg.as_bytes())
+    } else {
+        let buf = resource_fetcher.fetch(path).ok()?;
+        opener.open_memory(path, &buf)
+    }
+}
+
 // TODO: Add min_font_size.
 pub struct Engine {
     // The fonts used for each CSS font family.
@@ -50,8 +64,14 @@ pub struct Engine {
     hyphen_penalty: i32,
     // The stretching/shrinking allowed for word spaces.
     stretch_tolerance: f32,
+    // Start a new page before each chapter heading.
+    force_chapter_breaks: bool,
+    // Impose the text alignment over the document's style sheets.
+    force_text_align: bool,
     // Page margins in pixels.
     pub margin: Edge,
+    // Shift of the text towards the right, in pixels, see `set_margin_bias`.
+    margin_bias: i32,
     // Font size in points.
     pub font_size: f32,
     // Text alignment.
@@ -73,7 +93,10 @@ impl Engine {
             fonts: None,
             hyphen_penalty: HYPHEN_PENALTY,
             stretch_tolerance: STRETCH_TOLERANCE,
+            force_chapter_breaks: false,
+            force_text_align: false,
             margin,
+            margin_bias: 0,
             font_size: DEFAULT_FONT_SIZE,
             text_align: DEFAULT_TEXT_ALIGN,
             line_height,
@@ -97,6 +120,32 @@ impl Engine {
         self.stretch_tolerance = stretch_tolerance;
     }
 
+    pub fn set_force_chapter_breaks(&mut self, force: bool) {
+        self.force_chapter_breaks = force;
+    }
+
+    pub fn set_force_text_align(&mut self, force: bool) {
+        self.force_text_align = force;
+    }
+
+    // The rule that overrides the text alignment of the paragraphs and of their wrappers.
+    // The headings aren't concerned, so that they keep their own alignment.
+    pub fn text_align_stylesheet(&self) -> Option<StyleSheet> {
+        if !self.force_text_align {
+            return None;
+        }
+
+        let value = match self.text_align {
+            TextAlign::Justify => "justify",
+            TextAlign::Left => "left",
+            TextAlign::Right => "right",
+            TextAlign::Center => "center",
+        };
+
+        let text = format!("body, div, section, p, li, dd, blockquote {{ text-align: {} !important }}", value);
+        Some(CssParser::new(&text).parse())
+    }
+
     pub fn set_margin(&mut self, margin: &Edge) {
         self.margin = *margin;
     }
@@ -112,6 +161,18 @@ impl Engine {
         self.font_size = font_size;
     }
 
+    // The media queries are evaluated against the dimensions of the page.
+    pub fn media(&self) -> Media {
+        let color = CURRENT_DEVICE.color_samples() > 1;
+        Media {
+            width: self.dims.0,
+            height: self.dims.1,
+            dpi: self.dpi,
+            color: if color { 8 } else { 0 },
+            monochrome: if color { 0 } else { 4 },
+        }
+    }
+
     pub fn set_text_align(&mut self, text_align: TextAlign) {
         self.text_align = text_align;
     }
@@ -127,6 +188,23 @@ impl Engine {
 
     pub fn set_margin_width(&mut self, width: i32) {
         self.margin = Edge::uniform(mm_to_px(width as f32, self.dpi).round() as i32);
+        self.apply_margin_bias();
+    }
+
+    // Widens the left margin by *bias* millimeters and narrows the right one by the same amount,
+    // or the opposite if *bias* is negative. The width of the lines doesn't change.
+    pub fn set_margin_bias(&mut self, bias: i32) {
+        let width = (self.margin.left + self.margin.right) / 2;
+        self.margin.left = width;
+        self.margin.right = width;
+        self.margin_bias = mm_to_px(bias as f32, self.dpi).round() as i32;
+        self.apply_margin_bias();
+    }
+
+    fn apply_margin_bias(&mut self) {
+        let bias = self.margin_bias.clamp(-self.margin.left, self.margin.right);
+        self.margin.left += bias;
+        self.margin.right -= bias;
     }
 
     pub fn set_line_height(&mut self, line_height: f32) {
@@ -307,7 +385,8 @@ impl Engine {
 
         style.width = width;
 
-        if props.get("page-break-before").map(String::as_str) == Some("always") {
+        if has_page_break(&props, "page-break-before", "break-before") ||
+           (self.force_chapter_breaks && is_chapter_heading(node)) {
             display_list.push(Vec::new());
             draw_state.position.y = root_data.rect.min.y;
         }
@@ -369,6 +448,7 @@ impl Engine {
                     let mut final_page = (0, position);
                     let page_index = display_list.len() - 1;
                     let mut index = 0;
+                    let mut bordered_cells = Vec::new();
 
                     // TODO: rowspan, vertical-align
                     for child in node.children().filter(|child| child.is_element()) {
@@ -421,10 +501,28 @@ impl Engine {
                             style.margin.bottom = collapse_margins(inner_loop_context.sibling_style.margin.bottom, style.margin.bottom);
                         }
 
+                        if has_border(child, stylesheet) {
+                            bordered_cells.push((root_data.start_offset + child.offset(), cur_x, cur_x + column_width));
+                        }
+
                         index += colspan;
                         cur_x += column_width;
                     }
 
+                    // The borders are only drawn when the row fits on a single page.
+                    if final_page.0 == 1 {
+                        let thickness = pt_to_px(0.75, self.dpi).round().max(1.0) as i32;
+                        let page = &mut display_list[page_index];
+                        for (offset, min_x, max_x) in bordered_cells {
+                            page.push(DrawCommand::Border(BorderCommand {
+                                offset,
+                                rect: rect![min_x, position.y, max_x, final_page.1.y],
+                                thickness,
+                                color: style.color,
+                            }));
+                        }
+                    }
+
                     style.start_x = start_x;
                     style.end_x = end_x;
                     draw_state.position = final_page.1;
@@ -521,7 +619,7 @@ impl Engine {
 
         draw_state.position.y += style.padding.bottom;
 
-        if props.get("page-break-after").map(String::as_str) == Some("always") {
+        if has_page_break(&props, "page-break-after", "break-after") {
             display_list.push(Vec::new());
             draw_state.position.y = root_data.rect.min.y;
         }
@@ -556,9 +654,17 @@ impl Engine {
                                                 }
                                             })
                                             .max().unwrap_or(0);
-                let max_width = artifact.rects.into_iter()
-                                        .filter_map(|v| v.map(|r| r.width() as i32 + horiz_padding))
-                                        .max().unwrap_or(0);
+                let mut max_width = artifact.rects.into_iter()
+                                            .filter_map(|v| v.map(|r| r.width() as i32 + horiz_padding))
+                                            .max().unwrap_or(0);
+                // A specified width replaces the width of the content, unless the content can't fit.
+                let props = specified_values(child, stylesheet);
+                if let Some(width) = props.get("width").map(String::as_str)
+                                          .or_else(|| child.attribute("width"))
+                                          .and_then(|value| parse_cell_width(value, parent_style.font_size, self.font_size,
+                                                                             parent_style.width, self.dpi)) {
+                    max_width = width.max(min_width);
+                }
                 if colspan == 1 {
                     if let Some(cw) = draw_state.min_column_widths.get_mut(index) {
                         *cw = (*cw).max(min_width);
@@ -690,6 +796,29 @@ impl Engine {
                         }
                         return;
                     },
+                    "math" => {
+                        if let Some(image) = mathml_to_svg(node, style.font_size) {
+                            let is_block = attributes.get("display").map(String::as_str) == Some("block");
+                            if is_block {
+                                style.display = Display::Block;
+                                let margin = pt_to_px(0.5 * style.font_size, self.dpi).round() as i32;
+                                style.margin = Edge { top: margin, bottom: margin, .. Default::default() };
+                                style.text_align = TextAlign::Center;
+                                inlines.push(InlineMaterial::LineBreak);
+                            } else {
+                                style.vertical_align = -pt_to_px(image.descent, self.dpi).round() as i32;
+                            }
+                            inlines.push(InlineMaterial::Image(ImageMaterial {
+                                offset: *offset,
+                                path: format!("{}{}", INLINE_SVG_PREFIX, image.svg),
+                                style,
+                            }));
+                            if is_block {
+                                inlines.push(InlineMaterial::LineBreak);
+                            }
+                            return;
+                        }
+                    },
                     "a" => {
                         style.uri = attributes.get("href")
                                               .map(|uri| percent_decode_str(&decode_entities(uri))
@@ -759,19 +888,17 @@ impl Engine {
                     let mut scale = 1.0;
                     let dpi = self.dpi;
 
-                    if let Ok(buf) = resource_fetcher.fetch(path) {
-                        if let Some(doc) = PdfOpener::new().and_then(|opener| opener.open_memory(path, &buf)) {
-                            if let Some((w, h)) = doc.dims(0) {
-                                if width == 0 && height == 0 {
-                                    width = pt_to_px(w, dpi).round() as i32;
-                                    height = pt_to_px(h, dpi).round() as i32;
-                                } else if width != 0 {
-                                    height = (width as f32 * h / w).round() as i32;
-                                } else if height != 0 {
-                                    width = (height as f32 * w / h).round() as i32;
-                                }
-                                scale = width as f32 / w;
+                    if let Some(doc) = open_image(path, resource_fetcher) {
+                        if let Some((w, h)) = doc.dims(0) {
+                            if width == 0 && height == 0 {
+                                width = pt_to_px(w, dpi).round() as i32;
+                                height = pt_to_px(h, dpi).round() as i32;
+                            } else if width != 0 {
+                                height = (width as f32 * h / w).round() as i32;
+                            } else if height != 0 {
+                                width = (height as f32 * w / h).round() as i32;
                             }
+                            scale = width as f32 / w;
                         }
 
                         if width * height > 0 {
@@ -784,6 +911,7 @@ impl Engine {
                                     display: style.display,
                                     margin: style.margin,
                                     float: style.float,
+                                    text_align: style.text_align,
                                     path: path.clone(),
                                     uri: style.uri.clone(),
                             };
@@ -1017,8 +1145,7 @@ impl Engine {
             (font.ascender(), font.descender())
         };
 
-        let ratio = ascender as f32 / (ascender - descender) as f32;
-        let space_top = (style.line_height as f32 * ratio) as i32;
+        let space_top = baseline_offset(style.line_height, ascender, descender);
         let space_bottom = style.line_height - space_top;
 
         position.y += style.margin.top + space_top;
@@ -1305,7 +1432,9 @@ impl Engine {
                                     if element.display == Display::Block {
                                         let mut left_margin = element.margin.left;
                                         let total_width = left_margin + width + element.margin.right;
-                                        if total_width > line_width {
+                                        if element.text_align == TextAlign::Center {
+                                            left_margin = (line_width - width).max(0) / 2;
+                                        } else if total_width > line_width {
                                             let remaining_space = line_width - width;
                                             let ratio = left_margin as f32 / (left_margin + element.margin.right) as f32;
                                             left_margin = (ratio * remaining_space as f32).round() as i32;
@@ -1689,17 +1818,24 @@ impl Engine {
                     font.render(&mut fb, *color, &plan, position);
                 },
                 DrawCommand::Image(ImageCommand { position, path, scale, .. }) => {
-                    if let Ok(buf) = resource_fetcher.fetch(path) {
-                        if let Some((pixmap, _)) = PdfOpener::new().and_then(|opener| {
-                            opener.open_memory(path, &buf)
-                        }).and_then(|mut doc| {
-                            doc.pixmap(Location::Exact(0), scale_factor * *scale, samples)
-                        }) {
-                            let position = Point::from(scale_factor * Vec2::from(*position));
-                            fb.draw_pixmap(&pixmap, position);
-                        }
+                    if let Some((pixmap, _)) = open_image(path, resource_fetcher).and_then(|mut doc| {
+                        doc.pixmap(Location::Exact(0), scale_factor * *scale, samples)
+                    }) {
+                        let position = Point::from(scale_factor * Vec2::from(*position));
+                        fb.draw_pixmap(&pixmap, position);
                     }
                 },
+                DrawCommand::Border(BorderCommand { rect, thickness, color, .. }) => {
+                    let boundary: Boundary = (*rect).into();
+                    let rect = (scale_factor * boundary).to_rect();
+                    let thickness = (scale_factor * *thickness as f32).round().max(1.0) as i32;
+                    let (small_half, big_half) = halves(thickness);
+                    let (min, max) = (rect.min, rect.max);
+                    fb.draw_rectangle(&rect![min.x - small_half, min.y - small_half, max.x + big_half, min.y + big_half], *color);
+                    fb.draw_rectangle(&rect![min.x - small_half, max.y - small_half, max.x + big_half, max.y + big_half], *color);
+                    fb.draw_rectangle(&rect![min.x - small_half, min.y - small_half, min.x + big_half, max.y + big_half], *color);
+                    fb.draw_rectangle(&rect![max.x - small_half, min.y - small_half, max.x + big_half, max.y + big_half], *color);
+                },
                 _ => (),
             }
         }
@@ -1708,6 +1844,32 @@ impl Engine {
     }
 }
 
+// The borders of a table cell come from the `border` attribute of its table,
+// unless a `border` or `border-style` property of the cell is given.
+fn has_border(node: NodeRef, stylesheet: &StyleSheet) -> bool {
+    let props = specified_values(node, stylesheet);
+    if let Some(value) = props.get("border").or_else(|| props.get("border-style")) {
+        return parse_border(value);
+    }
+    node.ancestor_elements()
+        .find(|n| n.tag_name() == Some("table"))
+        .and_then(|table| table.attribute("border"))
+        .is_some_and(|value| value.trim() != "0")
+}
+
+// Checks the legacy and current names of a page break property.
+fn has_page_break(props: &PropertyMap, legacy_name: &str, name: &str) -> bool {
+    matches!(props.get(legacy_name).map(String::as_str), Some("always" | "left" | "right")) ||
+    matches!(props.get(name).map(String::as_str), Some("page" | "always" | "left" | "right" | "recto" | "verso"))
+}
+
+// A heading that isn't preceded by another heading, e.g. a chapter title followed by a subtitle.
+fn is_chapter_heading(node: NodeRef) -> bool {
+    let is_heading = |n: NodeRef| matches!(n.tag_name(), Some("h1" | "h2" | "h3" | "h4" | "h5" | "h6"));
+    matches!(node.tag_name(), Some("h1" | "h2")) &&
+    !node.previous_sibling_element().is_some_and(is_heading)
+}
+
 fn format_list_prefix(kind: ListStyleType, index: usize) -> Option<String> {
     match kind {
         ListStyleType::None => None,
@@ -349,6 +349,8 @@ pub struct ImageElement {
     pub display: Display,
     pub margin: Edge,
     pub float: Option<Float>,
+    // Only block images are aligned: the horizontal margins of centered ones are ignored.
+    pub text_align: TextAlign,
     pub path: String,
     pub uri: Option<String>,
 }
@@ -358,6 +360,7 @@ pub enum DrawCommand {
     Text(TextCommand),
     ExtraText(TextCommand),
     Image(ImageCommand),
+    Border(BorderCommand),
     Marker(usize),
 }
 
@@ -386,12 +389,22 @@ pub struct ImageCommand {
     pub rect: Rectangle,
 }
 
+// The outline of a table cell, centered on the edges of `rect`.
+#[derive(Debug, Clone)]
+pub struct BorderCommand {
+    pub offset: usize,
+    pub rect: Rectangle,
+    pub thickness: i32,
+    pub color: Color,
+}
+
 impl DrawCommand {
     pub fn offset(&self) -> usize {
         match *self {
             DrawCommand::Text(TextCommand { offset, .. }) => offset,
             DrawCommand::ExtraText(TextCommand { offset, .. }) => offset,
             DrawCommand::Image(ImageCommand { offset, .. }) => offset,
+            DrawCommand::Border(BorderCommand { offset, .. }) => offset,
             DrawCommand::Marker(offset) => offset,
         }
     }
@@ -415,6 +428,14 @@ impl DrawCommand {
     }
 }
 
+// Returns the distance between the top of a line box and the baseline.
+// The line box is split in proportion to the font's ascender and descender: the
+// ratio barely varies between fonts, hence the baseline stays put when the font changes.
+pub fn baseline_offset(line_height: i32, ascender: i32, descender: i32) -> i32 {
+    let ratio = ascender as f32 / (ascender - descender).max(1) as f32;
+    ((line_height as f32 * ratio) as i32).clamp(0, line_height)
+}
+
 pub fn collapse_margins(a: i32, b: i32) -> i32 {
     if a >= 0 && b >= 0 {
         a.max(b)
@@ -438,6 +459,22 @@ mod tests {
         assert_eq!(hyph_lang("de-CH-uuu"), Some(Language::GermanSwiss));
         assert_eq!(hyph_lang("y"), None);
     }
+
+    #[test]
+    fn test_baseline_offset() {
+        // The ascenders and descenders, in pixels, of Libertinus Serif (894, -246)
+        // and Noto Sans (1069, -293), for an em of 50 pixels.
+        let libertinus_serif = (45, -13);
+        let noto_sans = (54, -15);
+        for line_height in [50, 60, 75, 100] {
+            let a = baseline_offset(line_height, libertinus_serif.0, libertinus_serif.1);
+            let b = baseline_offset(line_height, noto_sans.0, noto_sans.1);
+            assert!((a - b).abs() <= 1, "{} {} {}", line_height, a, b);
+        }
+        assert_eq!(baseline_offset(60, 45, -13), 46);
+        assert_eq!(baseline_offset(60, 54, -15), 46);
+        assert_eq!(baseline_offset(10, 0, 0), 0);
+    }
 }
 
 pub fn hyph_lang(name: &str) -> Option<Language> {
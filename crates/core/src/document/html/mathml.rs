@@ -0,0 +1,459 @@
+// A minimal MathML layout engine.
+//
+// The formulas are converted into SVG images, which are then handled
+// like any other image by the HTML engine. The metrics are rough
+// estimations: all the lengths are expressed in points.
+
+use super::dom::{NodeRef, NodeData};
+use crate::helpers::escape_html;
+
+pub const INLINE_SVG_PREFIX: &str = "inline-svg:";
+
+const AXIS_HEIGHT: f32 = 0.25;
+const RULE_THICKNESS: f32 = 0.06;
+const SCRIPT_RATIO: f32 = 0.7;
+const MIN_SIZE_RATIO: f32 = 0.5;
+
+#[derive(Debug, Clone)]
+enum Item {
+    Text { x: f32, y: f32, size: f32, italic: bool, text: String },
+    Rule { x: f32, y: f32, width: f32, height: f32 },
+    Line { points: Vec<(f32, f32)>, thickness: f32 },
+}
+
+impl Item {
+    fn translate(&mut self, dx: f32, dy: f32) {
+        match self {
+            Item::Text { x, y, .. } | Item::Rule { x, y, .. } => {
+                *x += dx;
+                *y += dy;
+            },
+            Item::Line { points, .. } => {
+                for (x, y) in points.iter_mut() {
+                    *x += dx;
+                    *y += dy;
+                }
+            },
+        }
+    }
+}
+
+// The origin of a box is on its baseline, at its left edge.
+// The vertical axis points downward.
+#[derive(Debug, Clone, Default)]
+struct MathBox {
+    width: f32,
+    ascent: f32,
+    descent: f32,
+    items: Vec<Item>,
+    fence: bool,
+}
+
+impl MathBox {
+    fn append(&mut self, mut other: MathBox, dx: f32, dy: f32) {
+        for item in &mut other.items {
+            item.translate(dx, dy);
+        }
+        self.items.append(&mut other.items);
+    }
+
+    fn height(&self) -> f32 {
+        self.ascent + self.descent
+    }
+}
+
+pub struct MathImage {
+    pub svg: String,
+    pub descent: f32,
+}
+
+fn char_width(c: char) -> f32 {
+    match c {
+        'i' | 'j' | 'l' | '!' | '|' | '.' | ',' | ':' | ';' | '\'' | '`' => 0.28,
+        'f' | 't' | 'r' | 'I' | '(' | ')' | '[' | ']' | '{' | '}' | '/' => 0.35,
+        'm' | 'w' | 'M' | 'W' => 0.78,
+        'A'..='Z' => 0.68,
+        'a'..='z' | '0'..='9' => 0.5,
+        '+' | '=' | '<' | '>' | '−' | '×' | '÷' | '±' | '∓' | '≤' | '≥' | '≠' | '≈' => 0.56,
+        _ if c.is_alphabetic() => 0.55,
+        _ => 0.6,
+    }
+}
+
+fn is_spaced_operator(text: &str) -> bool {
+    matches!(text, "+" | "−" | "=" | "<" | ">" | "×" | "÷" | "±" | "∓" | "·" | "≤" | "≥" |
+                   "≠" | "≈" | "≡" | "∼" | "→" | "←" | "↔" | "⇒" | "⇐" | "⇔" | "∈" | "∉" |
+                   "⊂" | "⊆" | "⊃" | "⊇" | "∪" | "∩" | "∧" | "∨" | "∘" | "⊕" | "⊗")
+}
+
+fn is_fence(text: &str) -> bool {
+    matches!(text, "(" | ")" | "[" | "]" | "{" | "}" | "|" | "‖" | "⟨" | "⟩" | "⌊" | "⌋" | "⌈" | "⌉")
+}
+
+fn text_box(text: &str, size: f32, italic: bool) -> MathBox {
+    let width = text.chars().map(char_width).sum::<f32>() * size;
+    MathBox {
+        width,
+        ascent: 0.7 * size,
+        descent: 0.2 * size,
+        items: vec![Item::Text { x: 0.0, y: 0.0, size, italic, text: text.to_string() }],
+        fence: false,
+    }
+}
+
+fn parse_em(value: &str, size: f32) -> Option<f32> {
+    value.strip_suffix("em")
+         .and_then(|v| v.trim().parse::<f32>().ok())
+         .map(|v| v * size)
+}
+
+fn element_children<'a>(node: NodeRef<'a>) -> impl Iterator<Item=NodeRef<'a>> {
+    node.children().filter(|child| matches!(child.data(), NodeData::Element(..)))
+}
+
+fn layout_row<'a, I>(nodes: I, size: f32) -> Option<MathBox> where I: Iterator<Item=NodeRef<'a>> {
+    let mut boxes = Vec::new();
+    for node in nodes {
+        boxes.push(layout_node(node, size)?);
+    }
+    Some(concat(boxes, size))
+}
+
+// Fences are stretched to the height of their siblings.
+fn concat(boxes: Vec<MathBox>, size: f32) -> MathBox {
+    let (ascent, descent) = boxes.iter().filter(|b| !b.fence)
+                                 .fold((0.0f32, 0.0f32), |(a, d), b| (a.max(b.ascent), d.max(b.descent)));
+    let mut row = MathBox::default();
+    for mut b in boxes {
+        let mut dy = 0.0;
+        if b.fence && ascent + descent > 1.2 * size {
+            let fence_size = (ascent + descent) / 0.9;
+            if let Some(Item::Text { text, italic, .. }) = b.items.first().cloned() {
+                b = text_box(&text, fence_size, italic);
+                dy = (descent - ascent) / 2.0 + AXIS_HEIGHT * fence_size;
+                b.ascent -= dy;
+                b.descent += dy;
+            }
+        }
+        row.ascent = row.ascent.max(b.ascent);
+        row.descent = row.descent.max(b.descent);
+        let x = row.width;
+        row.width += b.width;
+        row.append(b, x, dy);
+    }
+    row
+}
+
+fn layout_script(base: MathBox, sub: Option<MathBox>, sup: Option<MathBox>, size: f32) -> MathBox {
+    let mut result = MathBox { width: base.width, ascent: base.ascent, descent: base.descent, .. Default::default() };
+    let x = base.width + 0.05 * size;
+    result.append(base, 0.0, 0.0);
+    let mut width = x;
+    if let Some(sup) = sup {
+        let shift = (0.45 * size).max(result.ascent - 0.3 * size);
+        result.ascent = result.ascent.max(shift + sup.ascent);
+        width = width.max(x + sup.width);
+        result.append(sup, x, -shift);
+    }
+    if let Some(sub) = sub {
+        let shift = (0.25 * size).max(result.descent);
+        result.descent = result.descent.max(shift + sub.descent);
+        width = width.max(x + sub.width);
+        result.append(sub, x, shift);
+    }
+    result.width = width;
+    result
+}
+
+fn layout_limits(base: MathBox, under: Option<MathBox>, over: Option<MathBox>, size: f32) -> MathBox {
+    let gap = 0.1 * size;
+    let width = base.width.max(under.as_ref().map_or(0.0, |b| b.width))
+                          .max(over.as_ref().map_or(0.0, |b| b.width));
+    let mut result = MathBox { width, ascent: base.ascent, descent: base.descent, .. Default::default() };
+    result.append(base.clone(), (width - base.width) / 2.0, 0.0);
+    if let Some(over) = over {
+        let dy = base.ascent + gap + over.descent;
+        result.ascent = dy + over.ascent;
+        result.append(over.clone(), (width - over.width) / 2.0, -dy);
+    }
+    if let Some(under) = under {
+        let dy = base.descent + gap + under.ascent;
+        result.descent = dy + under.descent;
+        result.append(under.clone(), (width - under.width) / 2.0, dy);
+    }
+    result
+}
+
+fn layout_fraction(num: MathBox, den: MathBox, size: f32) -> MathBox {
+    let gap = 0.12 * size;
+    let padding = 0.1 * size;
+    let thickness = RULE_THICKNESS * size;
+    let width = num.width.max(den.width) + 2.0 * padding;
+    let axis = -AXIS_HEIGHT * size;
+    let num_y = axis - thickness / 2.0 - gap - num.descent;
+    let den_y = axis + thickness / 2.0 + gap + den.ascent;
+    let mut result = MathBox {
+        width,
+        ascent: -num_y + num.ascent,
+        descent: den_y + den.descent,
+        items: vec![Item::Rule { x: 0.0, y: axis - thickness / 2.0, width, height: thickness }],
+        fence: false,
+    };
+    let (num_width, den_width) = (num.width, den.width);
+    result.append(num, (width - num_width) / 2.0, num_y);
+    result.append(den, (width - den_width) / 2.0, den_y);
+    result
+}
+
+fn layout_radical(content: MathBox, index: Option<MathBox>, size: f32) -> MathBox {
+    let thickness = RULE_THICKNESS * size;
+    let gap = 0.15 * size;
+    let top = -(content.ascent + gap);
+    let bottom = content.descent.max(0.2 * size);
+    let mid = (top + bottom) / 2.0;
+    let offset = index.as_ref().map_or(0.0, |i| (i.width - 0.25 * size).max(0.0));
+    let sign_width = 0.5 * size;
+    let end = offset + sign_width + content.width + 0.1 * size;
+    let mut result = MathBox {
+        width: end,
+        ascent: -top + thickness,
+        descent: bottom,
+        items: vec![Item::Line {
+            points: vec![(offset, mid),
+                         (offset + 0.1 * size, mid - 0.05 * size),
+                         (offset + 0.25 * size, bottom),
+                         (offset + 0.45 * size, top),
+                         (end, top)],
+            thickness,
+        }],
+        fence: false,
+    };
+    if let Some(index) = index {
+        let dy = mid - 0.05 * size - index.descent;
+        result.ascent = result.ascent.max(-dy + index.ascent);
+        result.append(index, 0.0, dy);
+    }
+    result.append(content, offset + sign_width, 0.0);
+    result
+}
+
+fn layout_table(node: NodeRef, size: f32) -> Option<MathBox> {
+    let col_gap = 0.8 * size;
+    let row_gap = 0.3 * size;
+    let mut rows = Vec::new();
+    for row in element_children(node) {
+        let mut cells = Vec::new();
+        for cell in element_children(row) {
+            cells.push(layout_row(element_children(cell), size)?);
+        }
+        rows.push(cells);
+    }
+    let cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![0.0f32; cols];
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.width);
+        }
+    }
+    let width = widths.iter().sum::<f32>() + col_gap * cols.saturating_sub(1) as f32;
+    let mut result = MathBox { width, .. Default::default() };
+    let mut y = 0.0;
+    for row in rows {
+        let ascent = row.iter().fold(0.7 * size, |a, c| a.max(c.ascent));
+        let descent = row.iter().fold(0.2 * size, |d, c| d.max(c.descent));
+        y += ascent;
+        let mut x = 0.0;
+        for (i, cell) in row.into_iter().enumerate() {
+            let dx = x + (widths[i] - cell.width) / 2.0;
+            result.append(cell, dx, y);
+            x += widths[i] + col_gap;
+        }
+        y += descent + row_gap;
+    }
+    let height = (y - row_gap).max(0.0);
+    // Center the table on the axis.
+    let dy = -height / 2.0 - AXIS_HEIGHT * size;
+    for item in &mut result.items {
+        item.translate(0.0, dy);
+    }
+    result.ascent = -dy;
+    result.descent = height + dy;
+    Some(result)
+}
+
+fn layout_node(node: NodeRef, size: f32) -> Option<MathBox> {
+    let name = node.tag_name()?;
+    let script_size = (size * SCRIPT_RATIO).max(MIN_SIZE_RATIO * size);
+    let children = element_children(node).collect::<Vec<NodeRef>>();
+    match name {
+        "math" | "mrow" | "mstyle" | "mpadded" | "merror" | "menclose" | "mtd" => {
+            layout_row(children.into_iter(), size)
+        },
+        "semantics" => children.first().and_then(|child| layout_node(*child, size)),
+        "mphantom" => {
+            let mut b = layout_row(children.into_iter(), size)?;
+            b.items.clear();
+            Some(b)
+        },
+        "mi" => {
+            let text = node.text();
+            let text = text.trim();
+            let italic = node.attribute("mathvariant").map_or(text.chars().count() == 1,
+                                                               |v| v.contains("italic"));
+            Some(text_box(text, size, italic))
+        },
+        "mn" | "mtext" => Some(text_box(node.text().trim(), size, false)),
+        "ms" => Some(text_box(&format!("\"{}\"", node.text().trim()), size, false)),
+        "mo" => {
+            let text = node.text().trim().replace('-', "−");
+            let mut b = text_box(&text, size, false);
+            if is_fence(&text) {
+                b.fence = node.attribute("stretchy") != Some("false");
+            } else if is_spaced_operator(&text) {
+                let space = 0.22 * size;
+                b.items[0].translate(space, 0.0);
+                b.width += 2.0 * space;
+            } else if text == "," || text == ";" {
+                b.width += 0.2 * size;
+            }
+            Some(b)
+        },
+        "mspace" => {
+            let width = node.attribute("width").and_then(|w| parse_em(w, size)).unwrap_or(0.0);
+            Some(MathBox { width, .. Default::default() })
+        },
+        "mfrac" if children.len() == 2 => {
+            let frac_size = (size * 0.85).max(MIN_SIZE_RATIO * size);
+            let num = layout_node(children[0], frac_size)?;
+            let den = layout_node(children[1], frac_size)?;
+            Some(layout_fraction(num, den, size))
+        },
+        "msqrt" => {
+            let content = layout_row(children.into_iter(), size)?;
+            Some(layout_radical(content, None, size))
+        },
+        "mroot" if children.len() == 2 => {
+            let content = layout_node(children[0], size)?;
+            let index = layout_node(children[1], 0.6 * size)?;
+            Some(layout_radical(content, Some(index), size))
+        },
+        "msup" if children.len() == 2 => {
+            let base = layout_node(children[0], size)?;
+            let sup = layout_node(children[1], script_size)?;
+            Some(layout_script(base, None, Some(sup), size))
+        },
+        "msub" if children.len() == 2 => {
+            let base = layout_node(children[0], size)?;
+            let sub = layout_node(children[1], script_size)?;
+            Some(layout_script(base, Some(sub), None, size))
+        },
+        "msubsup" if children.len() == 3 => {
+            let base = layout_node(children[0], size)?;
+            let sub = layout_node(children[1], script_size)?;
+            let sup = layout_node(children[2], script_size)?;
+            Some(layout_script(base, Some(sub), Some(sup), size))
+        },
+        "mover" if children.len() == 2 => {
+            let base = layout_node(children[0], size)?;
+            let over = layout_node(children[1], script_size)?;
+            Some(layout_limits(base, None, Some(over), size))
+        },
+        "munder" if children.len() == 2 => {
+            let base = layout_node(children[0], size)?;
+            let under = layout_node(children[1], script_size)?;
+            Some(layout_limits(base, Some(under), None, size))
+        },
+        "munderover" if children.len() == 3 => {
+            let base = layout_node(children[0], size)?;
+            let under = layout_node(children[1], script_size)?;
+            let over = layout_node(children[2], script_size)?;
+            Some(layout_limits(base, Some(under), Some(over), size))
+        },
+        "mfenced" => {
+            let open = node.attribute("open").unwrap_or("(");
+            let close = node.attribute("close").unwrap_or(")");
+            let separators = node.attribute("separators").unwrap_or(",")
+                                 .chars().filter(|c| !c.is_whitespace())
+                                 .collect::<Vec<char>>();
+            let mut boxes = Vec::new();
+            let mut fence = text_box(open, size, false);
+            fence.fence = true;
+            boxes.push(fence);
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 {
+                    if let Some(c) = separators.get(i - 1).or_else(|| separators.last()) {
+                        let mut sep = text_box(&c.to_string(), size, false);
+                        sep.width += 0.2 * size;
+                        boxes.push(sep);
+                    }
+                }
+                boxes.push(layout_node(*child, size)?);
+            }
+            let mut fence = text_box(close, size, false);
+            fence.fence = true;
+            boxes.push(fence);
+            Some(concat(boxes, size))
+        },
+        "mtable" => layout_table(node, size),
+        _ => None,
+    }
+}
+
+// Returns `None` if the formula contains unsupported elements.
+pub fn mathml_to_svg(node: NodeRef, font_size: f32) -> Option<MathImage> {
+    let b = layout_node(node, font_size)?;
+
+    if b.width <= 0.0 || b.height() <= 0.0 {
+        return None;
+    }
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0:.2}\" height=\"{1:.2}\" \
+                           viewBox=\"0 {2:.2} {0:.2} {1:.2}\">",
+                          b.width, b.height(), -b.ascent);
+
+    for item in &b.items {
+        match item {
+            Item::Text { x, y, size, italic, text } => {
+                svg.push_str(&format!("<text x=\"{:.2}\" y=\"{:.2}\" font-family=\"serif\" font-size=\"{:.2}\"{}>{}</text>",
+                                      x, y, size, if *italic { " font-style=\"italic\"" } else { "" }, escape_html(text)));
+            },
+            Item::Rule { x, y, width, height } => {
+                svg.push_str(&format!("<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"black\"/>",
+                                      x, y, width, height));
+            },
+            Item::Line { points, thickness } => {
+                let points = points.iter().map(|(x, y)| format!("{:.2},{:.2}", x, y))
+                                   .collect::<Vec<String>>().join(" ");
+                svg.push_str(&format!("<polyline points=\"{}\" fill=\"none\" stroke=\"black\" stroke-width=\"{:.2}\"/>",
+                                      points, thickness));
+            },
+        }
+    }
+
+    svg.push_str("</svg>");
+
+    Some(MathImage { svg, descent: b.descent })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::html::xml::XmlParser;
+
+    #[test]
+    fn test_fraction() {
+        let text = "<math><mfrac><mi>a</mi><mn>2</mn></mfrac></math>";
+        let xml = XmlParser::new(text).parse();
+        let image = mathml_to_svg(xml.root().first_child().unwrap(), 10.0).unwrap();
+        assert!(image.svg.contains("<rect"));
+        assert!(image.svg.contains("font-style=\"italic\">a</text>"));
+        assert!(image.descent > 2.0);
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let text = "<math><mrow><mi>x</mi><mlongdiv/></mrow></math>";
+        let xml = XmlParser::new(text).parse();
+        assert!(mathml_to_svg(xml.root().first_child().unwrap(), 10.0).is_none());
+    }
+}
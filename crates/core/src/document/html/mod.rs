@@ -5,6 +5,7 @@ pub mod parse;
 pub mod style;
 pub mod layout;
 pub mod engine;
+pub mod mathml;
 
 use std::io::{Read, Write};
 use std::fs::{self, File};
@@ -94,6 +95,11 @@ impl HtmlDocument {
         }
     }
 
+    // The directory from which the resources of a document created from memory are fetched.
+    pub fn set_parent<P: AsRef<Path>>(&mut self, parent: P) {
+        self.parent = parent.as_ref().to_path_buf();
+    }
+
     pub fn update(&mut self, text: &str) {
         self.size = text.len();
         self.content = XmlParser::new(text).parse();
@@ -161,15 +167,16 @@ impl HtmlDocument {
 
     fn build_pages(&mut self) -> Vec<Page> {
         let mut stylesheet = StyleSheet::new();
+        let media = self.engine.media();
         let spine_dir = PathBuf::default();
 
         if let Ok(text) = fs::read_to_string(&self.viewer_stylesheet) {
-            let mut css = CssParser::new(&text).parse();
+            let mut css = CssParser::new(&text).with_media(media).parse();
             stylesheet.append(&mut css, true);
         }
 
         if let Ok(text) = fs::read_to_string(&self.user_stylesheet) {
-            let mut css = CssParser::new(&text).parse();
+            let mut css = CssParser::new(&text).with_media(media).parse();
             stylesheet.append(&mut css, true);
         }
 
@@ -178,19 +185,22 @@ impl HtmlDocument {
 
             if let Some(head) = self.content.root().find("head") {
                 for child in head.children() {
+                    if child.attribute("media").is_some_and(|query| !media.matches(query)) {
+                        continue;
+                    }
                     if child.tag_name() == Some("link") && child.attribute("rel") == Some("stylesheet") {
                         if let Some(href) = child.attribute("href") {
                             if let Some(name) = spine_dir.join(href).normalize().to_str() {
                                 if let Ok(buf) = self.parent.fetch(name) {
                                     if let Ok(text) = String::from_utf8(buf) {
-                                        let mut css = CssParser::new(&text).parse();
+                                        let mut css = CssParser::new(&text).with_media(media).parse();
                                         inner_css.append(&mut css, false);
                                     }
                                 }
                             }
                         }
                     } else if child.tag_name() == Some("style") && child.attribute("type") == Some("text/css") {
-                        let mut css = CssParser::new(&child.text()).parse();
+                        let mut css = CssParser::new(&child.text()).with_media(media).parse();
                         inner_css.append(&mut css, false);
                     }
                 }
@@ -199,6 +209,10 @@ impl HtmlDocument {
             stylesheet.append(&mut inner_css, true);
         }
 
+        if let Some(mut css) = self.engine.text_align_stylesheet() {
+            stylesheet.append(&mut css, false);
+        }
+
         let mut pages = Vec::new();
 
         let mut rect = self.engine.rect();
@@ -416,6 +430,21 @@ impl Document for HtmlDocument {
         self.pages.clear();
     }
 
+    fn set_force_chapter_breaks(&mut self, force: bool) {
+        self.engine.set_force_chapter_breaks(force);
+        self.pages.clear();
+    }
+
+    fn set_force_text_align(&mut self, force: bool) {
+        self.engine.set_force_text_align(force);
+        self.pages.clear();
+    }
+
+    fn set_margin_bias(&mut self, bias: i32) {
+        self.engine.set_margin_bias(bias);
+        self.pages.clear();
+    }
+
     fn set_ignore_document_css(&mut self, ignore: bool) {
         self.ignore_document_css = ignore;
         self.pages.clear();
@@ -215,6 +215,21 @@ pub fn parse_width(value: &str, em: f32, rem: f32, width: i32, dpi: u16) -> Opti
     }
 }
 
+// The width of a table cell, from its `width` property or attribute: the unitless
+// values of the attribute are in pixels.
+pub fn parse_cell_width(value: &str, em: f32, rem: f32, width: i32, dpi: u16) -> Option<i32> {
+    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        parse_width(&format!("{}px", value), em, rem, width, dpi)
+    } else {
+        parse_width(value, em, rem, width, dpi)
+    }.filter(|w| *w > 0)
+}
+
+// Whether a `border` or `border-style` value draws a border.
+pub fn parse_border(value: &str) -> bool {
+    !value.split_whitespace().any(|v| matches!(v, "none" | "hidden" | "0"))
+}
+
 pub fn parse_height(value: &str, em: f32, rem: f32, width: i32, dpi: u16) -> Option<i32> {
     if value == "auto" {
         Some(0)
@@ -390,4 +405,20 @@ mod tests {
         assert_eq!(d, Some(Color::Rgb(0, 0, 255)));
         assert_eq!(e, Some(Color::Rgb(255, 255, 255)));
     }
+
+    #[test]
+    fn test_parse_cell_width() {
+        assert_eq!(parse_cell_width("100", 12.0, 12.0, 600, 96), Some(100));
+        assert_eq!(parse_cell_width("50%", 12.0, 12.0, 600, 96), Some(300));
+        assert_eq!(parse_cell_width("auto", 12.0, 12.0, 600, 96), None);
+        assert_eq!(parse_cell_width("", 12.0, 12.0, 600, 96), None);
+    }
+
+    #[test]
+    fn test_parse_border() {
+        assert!(parse_border("1px solid black"));
+        assert!(parse_border("thin dotted"));
+        assert!(!parse_border("none"));
+        assert!(!parse_border("0"));
+    }
 }
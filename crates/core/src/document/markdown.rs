@@ -0,0 +1,822 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use anyhow::Error;
+use crate::framebuffer::Pixmap;
+use crate::metadata::TextAlign;
+use crate::geom::{Boundary, CycleDir};
+use crate::helpers::escape_html;
+use super::{Document, Location, TocEntry, BoundedText, chapter, chapter_relative};
+use super::html::HtmlDocument;
+
+// A Markdown document is converted to HTML and laid out by the HTML engine.
+// Its table of contents is built from its headings.
+pub struct MarkdownDocument {
+    text: String,
+    doc: HtmlDocument,
+    toc: Vec<TocEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Heading {
+    level: usize,
+    title: String,
+    // The offset of the heading's element in the HTML text.
+    offset: usize,
+}
+
+impl MarkdownDocument {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<MarkdownDocument, Error> {
+        let text = fs::read_to_string(path.as_ref())?;
+        let title = path.as_ref().file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+        let (html, headings) = markdown_to_html(&text, &title);
+        let mut doc = HtmlDocument::new_from_memory(&html);
+        if let Some(parent) = path.as_ref().parent() {
+            doc.set_parent(parent);
+        }
+        let toc = toc_entries(&headings, &mut 0, 0, &mut 0);
+
+        Ok(MarkdownDocument {
+            text,
+            doc,
+            toc,
+        })
+    }
+}
+
+fn toc_entries(headings: &[Heading], position: &mut usize, level: usize, index: &mut usize) -> Vec<TocEntry> {
+    let mut entries = Vec::new();
+
+    while let Some(heading) = headings.get(*position) {
+        if heading.level <= level {
+            break;
+        }
+        *position += 1;
+        let current_index = *index;
+        *index += 1;
+        let children = toc_entries(headings, position, heading.level, index);
+        entries.push(TocEntry {
+            title: heading.title.clone(),
+            location: Location::Exact(heading.offset),
+            index: current_index,
+            children,
+        });
+    }
+
+    entries
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+// The text of an HTML fragment, without its tags.
+fn plain_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    unescape(&text)
+}
+
+fn indentation(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 }).sum()
+}
+
+// Removes up to *width* columns of indentation.
+fn dedent(line: &str, width: usize) -> &str {
+    let mut columns = 0;
+    for (i, c) in line.char_indices() {
+        if columns >= width || !(c == ' ' || c == '\t') {
+            return &line[i..];
+        }
+        columns += if c == '\t' { 4 } else { 1 };
+    }
+    ""
+}
+
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let title = rest.trim();
+    // The closing sequence must be preceded by a space.
+    let stripped = title.trim_end_matches('#');
+    let title = if stripped.is_empty() || stripped.ends_with([' ', '\t']) {
+        stripped.trim_end()
+    } else {
+        title
+    };
+    Some((level, title))
+}
+
+fn is_rule(line: &str) -> bool {
+    let line = line.trim();
+    ['-', '*', '_'].iter().any(|&m| {
+        line.chars().filter(|&c| c == m).count() >= 3 &&
+        line.chars().all(|c| c == m || c == ' ' || c == '\t')
+    })
+}
+
+fn setext_level(line: &str) -> Option<usize> {
+    let line = line.trim();
+    if !line.is_empty() && line.chars().all(|c| c == '=') {
+        Some(1)
+    } else if !line.is_empty() && line.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+// Returns the fence's character and length.
+fn fence(line: &str) -> Option<(char, usize)> {
+    let c = line.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let count = line.chars().take_while(|&d| d == c).count();
+    if count >= 3 {
+        Some((c, count))
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ListKind {
+    Bullet(char),
+    Ordered(char),
+}
+
+// Returns the kind of the list, its start number and the width of the item's marker.
+fn list_marker(line: &str) -> Option<(ListKind, usize, usize)> {
+    let first = line.chars().next()?;
+    let (kind, start, width) = if matches!(first, '-' | '*' | '+') {
+        (ListKind::Bullet(first), 1, 1)
+    } else {
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 || digits > 9 {
+            return None;
+        }
+        let delim = line[digits..].chars().next().filter(|&c| c == '.' || c == ')')?;
+        (ListKind::Ordered(delim), line[..digits].parse().ok()?, digits + 1)
+    };
+    let rest = &line[width..];
+    if rest.trim().is_empty() {
+        return Some((kind, start, width + 1));
+    }
+    let spaces = rest.chars().take_while(|&c| c == ' ').count();
+    if spaces == 0 {
+        return None;
+    }
+    Some((kind, start, width + if spaces > 4 { 1 } else { spaces }))
+}
+
+fn starts_block(line: &str) -> bool {
+    let line = line.trim_start();
+    atx_heading(line).is_some() || fence(line).is_some() || is_rule(line) ||
+    line.starts_with('>') || list_marker(line).is_some_and(|(_, _, width)| !line[width.min(line.len())..].trim().is_empty())
+}
+
+struct Converter {
+    buf: String,
+    headings: Vec<Heading>,
+    ids: Vec<String>,
+}
+
+impl Converter {
+    fn heading(&mut self, level: usize, text: &str) {
+        let mut inline = String::new();
+        render_inlines(text, &mut inline);
+        let title = plain_text(&inline);
+        let slug = title.to_lowercase().chars()
+                        .filter_map(|c| if c.is_alphanumeric() || c == '-' || c == '_' {
+                            Some(c)
+                        } else if c.is_whitespace() {
+                            Some('-')
+                        } else {
+                            None
+                        }).collect::<String>();
+        let mut id = slug.clone();
+        let mut suffix = 0;
+        while self.ids.contains(&id) {
+            suffix += 1;
+            id = format!("{}-{}", slug, suffix);
+        }
+        self.headings.push(Heading { level, title, offset: self.buf.len() });
+        self.buf.push_str(&format!("<h{} id=\"{}\">{}</h{}>\n", level, escape_html(&id), inline, level));
+        self.ids.push(id);
+    }
+
+    fn code_block(&mut self, lines: &[&str]) {
+        self.buf.push_str("<pre><code>");
+        self.buf.push_str(&escape_html(&lines.join("\n")));
+        self.buf.push_str("</code></pre>\n");
+    }
+
+    // In tight lists, the paragraphs of the items aren't wrapped in *p* elements.
+    fn blocks(&mut self, lines: &[&str], tight: bool) {
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i];
+
+            if line.trim().is_empty() {
+                i += 1;
+                continue;
+            }
+
+            if indentation(line) >= 4 {
+                let start = i;
+                while i < lines.len() && (lines[i].trim().is_empty() || indentation(lines[i]) >= 4) {
+                    i += 1;
+                }
+                let mut end = i;
+                while end > start && lines[end - 1].trim().is_empty() {
+                    end -= 1;
+                }
+                let code = lines[start..end].iter().map(|l| dedent(l, 4)).collect::<Vec<&str>>();
+                self.code_block(&code);
+                continue;
+            }
+
+            let indent = indentation(line);
+            let trimmed = line.trim_start();
+
+            if let Some((c, count)) = fence(trimmed) {
+                i += 1;
+                let start = i;
+                while i < lines.len() && fence(lines[i].trim()).is_none_or(|(d, n)| d != c || n < count ||
+                                                                              lines[i].trim().len() != n) {
+                    i += 1;
+                }
+                let code = lines[start..i].iter().map(|l| dedent(l, indent)).collect::<Vec<&str>>();
+                self.code_block(&code);
+                i += 1;
+                continue;
+            }
+
+            if let Some((level, title)) = atx_heading(trimmed) {
+                self.heading(level, title);
+                i += 1;
+                continue;
+            }
+
+            if is_rule(trimmed) {
+                self.buf.push_str("<hr/>\n");
+                i += 1;
+                continue;
+            }
+
+            if trimmed.starts_with('>') {
+                let mut quote = Vec::new();
+                while i < lines.len() && !lines[i].trim().is_empty() {
+                    let trimmed = lines[i].trim_start();
+                    if let Some(rest) = trimmed.strip_prefix('>') {
+                        quote.push(rest.strip_prefix(' ').unwrap_or(rest));
+                    } else if starts_block(trimmed) {
+                        break;
+                    } else {
+                        quote.push(trimmed);
+                    }
+                    i += 1;
+                }
+                self.buf.push_str("<blockquote>\n");
+                self.blocks(&quote, false);
+                self.buf.push_str("</blockquote>\n");
+                continue;
+            }
+
+            if let Some((kind, start, _)) = list_marker(trimmed) {
+                i = self.list(lines, i, kind, start);
+                continue;
+            }
+
+            let mut paragraph = vec![trimmed];
+            i += 1;
+            let mut level = None;
+            while i < lines.len() && !lines[i].trim().is_empty() {
+                if let Some(l) = setext_level(lines[i]).filter(|_| indentation(lines[i]) < 4) {
+                    level = Some(l);
+                    i += 1;
+                    break;
+                }
+                if starts_block(lines[i]) {
+                    break;
+                }
+                paragraph.push(lines[i].trim_start());
+                i += 1;
+            }
+
+            if let Some(level) = level {
+                self.heading(level, &paragraph.join(" "));
+                continue;
+            }
+
+            // Trailing spaces or backslashes are line breaks.
+            let text = paragraph.iter().enumerate().map(|(j, l)| {
+                if j + 1 < paragraph.len() && l.ends_with("  ") {
+                    format!("{}\\", l.trim_end())
+                } else if j + 1 == paragraph.len() {
+                    l.trim_end().to_string()
+                } else {
+                    l.to_string()
+                }
+            }).collect::<Vec<String>>().join("\n");
+
+            if !tight {
+                self.buf.push_str("<p>");
+            }
+            render_inlines(&text, &mut self.buf);
+            if !tight {
+                self.buf.push_str("</p>");
+            }
+            self.buf.push('\n');
+        }
+    }
+
+    // Returns the index of the first line after the list.
+    fn list(&mut self, lines: &[&str], mut i: usize, kind: ListKind, start: usize) -> usize {
+        let mut items: Vec<Vec<&str>> = Vec::new();
+        let mut tight = true;
+        let base_indent = indentation(lines[i]);
+
+        while i < lines.len() {
+            let indent = indentation(lines[i]);
+            let trimmed = lines[i].trim_start();
+            let width = match list_marker(trimmed) {
+                Some((k, _, width)) if k == kind && indent < base_indent + 2 && !is_rule(trimmed) => width,
+                _ => break,
+            };
+
+            let content_indent = indent + width;
+            let mut item = vec![trimmed.get(width..).unwrap_or("").trim_start()];
+            i += 1;
+
+            while i < lines.len() {
+                let line = lines[i];
+                if line.trim().is_empty() {
+                    let next = lines[i..].iter().find(|l| !l.trim().is_empty());
+                    if next.is_some_and(|l| indentation(l) >= content_indent) {
+                        item.push("");
+                        i += 1;
+                        continue;
+                    }
+                    break;
+                }
+                if indentation(line) >= content_indent {
+                    item.push(dedent(line, content_indent));
+                } else if item.last().is_some_and(|l| !l.is_empty()) && !starts_block(line) {
+                    // Lazy continuation of the item's paragraph.
+                    item.push(line.trim_start());
+                } else {
+                    break;
+                }
+                i += 1;
+            }
+
+            if item.iter().skip(1).any(|l| l.is_empty()) {
+                tight = false;
+            }
+
+            items.push(item);
+
+            if i < lines.len() && lines[i].trim().is_empty() {
+                let next = lines[i..].iter().position(|l| !l.trim().is_empty()).map(|j| i + j);
+                let continues = next.is_some_and(|j| {
+                    let trimmed = lines[j].trim_start();
+                    list_marker(trimmed).is_some_and(|(k, _, _)| k == kind) &&
+                    indentation(lines[j]) < base_indent + 2 && !is_rule(trimmed)
+                });
+                if let Some(j) = next.filter(|_| continues) {
+                    tight = false;
+                    i = j;
+                } else {
+                    break;
+                }
+            }
+        }
+
+        match kind {
+            ListKind::Bullet(_) => self.buf.push_str("<ul>\n"),
+            ListKind::Ordered(_) if start != 1 => self.buf.push_str(&format!("<ol start=\"{}\">\n", start)),
+            ListKind::Ordered(_) => self.buf.push_str("<ol>\n"),
+        }
+
+        for item in &items {
+            self.buf.push_str("<li>");
+            self.blocks(item, tight);
+            self.buf.push_str("</li>\n");
+        }
+
+        match kind {
+            ListKind::Bullet(_) => self.buf.push_str("</ul>\n"),
+            ListKind::Ordered(_) => self.buf.push_str("</ol>\n"),
+        }
+
+        i
+    }
+}
+
+fn find_run(chars: &[char], from: usize, c: char, len: usize) -> Option<usize> {
+    let mut j = from;
+    while j < chars.len() {
+        if chars[j] == '\\' {
+            j += 2;
+            continue;
+        }
+        if chars[j] == c {
+            let count = chars[j..].iter().take_while(|&&d| d == c).count();
+            if count == len {
+                return Some(j);
+            }
+            j += count;
+        } else {
+            j += 1;
+        }
+    }
+    None
+}
+
+fn find_emphasis_end(chars: &[char], from: usize, c: char, len: usize) -> Option<usize> {
+    let mut j = from;
+    while j < chars.len() {
+        match chars[j] {
+            '\\' => j += 2,
+            '`' => {
+                let count = chars[j..].iter().take_while(|&&d| d == '`').count();
+                j = find_run(chars, j + count, '`', count).map_or(j + count, |k| k + count);
+            },
+            d if d == c => {
+                let count = chars[j..].iter().take_while(|&&e| e == c).count();
+                let after = chars.get(j + count);
+                if count == len && !chars[j - 1].is_whitespace() &&
+                   (c == '*' || after.is_none_or(|a| !a.is_alphanumeric())) {
+                    return Some(j);
+                }
+                j += count;
+            },
+            _ => j += 1,
+        }
+    }
+    None
+}
+
+fn find_bracket_end(chars: &[char], from: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut j = from;
+    while j < chars.len() {
+        match chars[j] {
+            '\\' => j += 1,
+            '[' => depth += 1,
+            ']' if depth == 0 => return Some(j),
+            ']' => depth -= 1,
+            _ => (),
+        }
+        j += 1;
+    }
+    None
+}
+
+// Parses the destination of a link, starting after the opening parenthesis.
+// Returns the destination and the index of the closing parenthesis.
+fn link_destination(chars: &[char], from: usize) -> Option<(String, usize)> {
+    let end = from + chars[from..].iter().position(|&c| c == ')')?;
+    let inner = chars[from..end].iter().collect::<String>();
+    let inner = inner.trim();
+    let dest = if let Some(rest) = inner.strip_prefix('<') {
+        rest.split('>').next().unwrap_or_default()
+    } else {
+        inner.split_whitespace().next().unwrap_or_default()
+    };
+    Some((dest.to_string(), end))
+}
+
+fn render_inlines(text: &str, buf: &mut String) {
+    let chars = text.chars().collect::<Vec<char>>();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if chars.get(i + 1) == Some(&'\n') => {
+                buf.push_str("<br/>\n");
+                i += 2;
+            },
+            '\\' if chars.get(i + 1).is_some_and(char::is_ascii_punctuation) => {
+                buf.push_str(&escape_html(&chars[i + 1].to_string()));
+                i += 2;
+            },
+            '`' => {
+                let count = chars[i..].iter().take_while(|&&d| d == '`').count();
+                if let Some(end) = find_run(&chars, i + count, '`', count) {
+                    let code = chars[i + count..end].iter().collect::<String>().replace('\n', " ");
+                    let code = if code.len() > 2 && code.starts_with(' ') && code.ends_with(' ') {
+                        &code[1..code.len() - 1]
+                    } else {
+                        &code[..]
+                    };
+                    buf.push_str("<code>");
+                    buf.push_str(&escape_html(code));
+                    buf.push_str("</code>");
+                    i = end + count;
+                } else {
+                    buf.push_str(&"`".repeat(count));
+                    i += count;
+                }
+            },
+            '!' | '[' => {
+                let open = if c == '!' { i + 1 } else { i };
+                let link = Some(open).filter(|&o| chars.get(o) == Some(&'['))
+                                     .and_then(|o| find_bracket_end(&chars, o + 1).map(|e| (o, e)))
+                                     .filter(|&(_, e)| chars.get(e + 1) == Some(&'('))
+                                     .and_then(|(o, e)| link_destination(&chars, e + 2).map(|(d, end)| (o, e, d, end)));
+                if let Some((open, close, dest, end)) = link {
+                    let label = chars[open + 1..close].iter().collect::<String>();
+                    if c == '!' {
+                        let mut alt = String::new();
+                        render_inlines(&label, &mut alt);
+                        buf.push_str(&format!("<img src=\"{}\" alt=\"{}\"/>",
+                                              escape_html(&dest), escape_html(&plain_text(&alt))));
+                    } else {
+                        buf.push_str(&format!("<a href=\"{}\">", escape_html(&dest)));
+                        render_inlines(&label, buf);
+                        buf.push_str("</a>");
+                    }
+                    i = end + 1;
+                } else {
+                    buf.push(c);
+                    i += 1;
+                }
+            },
+            '<' => {
+                let end = chars[i..].iter().position(|&d| d == '>' || d.is_whitespace()).map(|j| i + j);
+                let autolink = end.filter(|&e| chars[e] == '>')
+                                  .map(|e| chars[i + 1..e].iter().collect::<String>())
+                                  .filter(|url| url.contains(':') || url.contains('@'));
+                if let Some(url) = autolink {
+                    let href = if url.contains(':') { url.clone() } else { format!("mailto:{}", url) };
+                    buf.push_str(&format!("<a href=\"{}\">{}</a>", escape_html(&href), escape_html(&url)));
+                    i += url.chars().count() + 2;
+                } else {
+                    buf.push_str("&lt;");
+                    i += 1;
+                }
+            },
+            '*' | '_' => {
+                let count = chars[i..].iter().take_while(|&&d| d == c).count();
+                let len = count.min(3);
+                let left_flanking = chars.get(i + count).is_some_and(|d| !d.is_whitespace());
+                let intraword = c == '_' && i > 0 && chars[i - 1].is_alphanumeric();
+                let end = Some(i + count).filter(|_| left_flanking && !intraword)
+                                         .and_then(|from| find_emphasis_end(&chars, from, c, len));
+                if let Some(end) = end {
+                    let inner = chars[i + count..end].iter().collect::<String>();
+                    let (open, close) = match len {
+                        1 => ("<em>", "</em>"),
+                        2 => ("<strong>", "</strong>"),
+                        _ => ("<strong><em>", "</em></strong>"),
+                    };
+                    buf.push_str(&c.to_string().repeat(count - len));
+                    buf.push_str(open);
+                    render_inlines(&inner, buf);
+                    buf.push_str(close);
+                    i = end + len;
+                } else {
+                    buf.push_str(&c.to_string().repeat(count));
+                    i += count;
+                }
+            },
+            '&' => {
+                buf.push_str("&amp;");
+                i += 1;
+            },
+            '>' => {
+                buf.push_str("&gt;");
+                i += 1;
+            },
+            '"' => {
+                buf.push_str("&quot;");
+                i += 1;
+            },
+            _ => {
+                buf.push(c);
+                i += 1;
+            },
+        }
+    }
+}
+
+// Returns the HTML text and the headings of the given Markdown text.
+// The first level one heading, if any, is the document's title.
+fn markdown_to_html(text: &str, title: &str) -> (String, Vec<Heading>) {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let lines = text.lines().collect::<Vec<&str>>();
+    let mut converter = Converter {
+        buf: String::new(),
+        headings: Vec::new(),
+        ids: Vec::new(),
+    };
+
+    converter.blocks(&lines, false);
+
+    let title = converter.headings.iter()
+                         .find(|h| h.level == 1)
+                         .map_or(title, |h| h.title.as_str());
+    let head = format!("<html>\n<head>\n<title>{}</title>\n</head>\n<body>\n", escape_html(title));
+    let mut headings = converter.headings;
+    for heading in &mut headings {
+        heading.offset += head.len();
+    }
+
+    (format!("{}{}</body>\n</html>", head, converter.buf), headings)
+}
+
+impl Document for MarkdownDocument {
+    #[inline]
+    fn dims(&self, index: usize) -> Option<(f32, f32)> {
+        self.doc.dims(index)
+    }
+
+    fn pages_count(&self) -> usize {
+        self.doc.pages_count()
+    }
+
+    fn toc(&mut self) -> Option<Vec<TocEntry>> {
+        if self.toc.is_empty() {
+            None
+        } else {
+            Some(self.toc.clone())
+        }
+    }
+
+    fn chapter<'a>(&mut self, offset: usize, toc: &'a [TocEntry]) -> Option<(&'a TocEntry, f32)> {
+        chapter(offset, self.pages_count(), toc)
+    }
+
+    fn chapter_relative<'a>(&mut self, offset: usize, dir: CycleDir, toc: &'a [TocEntry]) -> Option<&'a TocEntry> {
+        chapter_relative(offset, dir, toc)
+    }
+
+    fn resolve_location(&mut self, loc: Location) -> Option<usize> {
+        self.doc.resolve_location(loc)
+    }
+
+    fn words(&mut self, loc: Location) -> Option<(Vec<BoundedText>, usize)> {
+        self.doc.words(loc)
+    }
+
+    fn lines(&mut self, loc: Location) -> Option<(Vec<BoundedText>, usize)> {
+        self.doc.lines(loc)
+    }
+
+    fn images(&mut self, loc: Location) -> Option<(Vec<Boundary>, usize)> {
+        self.doc.images(loc)
+    }
+
+    fn links(&mut self, loc: Location) -> Option<(Vec<BoundedText>, usize)> {
+        self.doc.links(loc)
+    }
+
+    fn pixmap(&mut self, loc: Location, scale: f32, samples: usize) -> Option<(Pixmap, usize)> {
+        self.doc.pixmap(loc, scale, samples)
+    }
+
+    fn layout(&mut self, width: u32, height: u32, font_size: f32, dpi: u16) {
+        self.doc.layout(width, height, font_size, dpi);
+    }
+
+    fn set_text_align(&mut self, text_align: TextAlign) {
+        self.doc.set_text_align(text_align);
+    }
+
+    fn set_font_family(&mut self, family_name: &str, search_path: &str) {
+        self.doc.set_font_family(family_name, search_path);
+    }
+
+    fn set_margin_width(&mut self, width: i32) {
+        self.doc.set_margin_width(width);
+    }
+
+    fn set_line_height(&mut self, line_height: f32) {
+        self.doc.set_line_height(line_height);
+    }
+
+    fn set_hyphen_penalty(&mut self, hyphen_penalty: i32) {
+        self.doc.set_hyphen_penalty(hyphen_penalty);
+    }
+
+    fn set_stretch_tolerance(&mut self, stretch_tolerance: f32) {
+        self.doc.set_stretch_tolerance(stretch_tolerance);
+    }
+
+    fn set_force_chapter_breaks(&mut self, force: bool) {
+        self.doc.set_force_chapter_breaks(force);
+    }
+
+    fn set_force_text_align(&mut self, force: bool) {
+        self.doc.set_force_text_align(force);
+    }
+
+    fn set_margin_bias(&mut self, bias: i32) {
+        self.doc.set_margin_bias(bias);
+    }
+
+    fn set_ignore_document_css(&mut self, ignore: bool) {
+        self.doc.set_ignore_document_css(ignore);
+    }
+
+    fn title(&self) -> Option<String> {
+        self.doc.title()
+    }
+
+    fn author(&self) -> Option<String> {
+        self.doc.author()
+    }
+
+    fn metadata(&self, key: &str) -> Option<String> {
+        self.doc.metadata(key)
+    }
+
+    fn save(&self, path: &str) -> Result<(), Error> {
+        let mut file = File::create(path)?;
+        file.write_all(self.text.as_bytes())
+            .map_err(Into::into)
+    }
+
+    fn is_reflowable(&self) -> bool {
+        true
+    }
+
+    fn has_synthetic_page_numbers(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(text: &str) -> String {
+        let (html, _) = markdown_to_html(text, "t");
+        let start = html.find("<body>\n").unwrap() + 7;
+        let end = html.find("</body>").unwrap();
+        html[start..end].to_string()
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(body("# Title #\n\nSome *text*."),
+                   "<h1 id=\"title\">Title</h1>\n<p>Some <em>text</em>.</p>\n");
+        assert_eq!(body("A\n===\nB\n---"),
+                   "<h1 id=\"a\">A</h1>\n<h2 id=\"b\">B</h2>\n");
+        assert_eq!(body("```rust\nlet a = b < c;\n```"),
+                   "<pre><code>let a = b &lt; c;</code></pre>\n");
+        assert_eq!(body("    x\n\n    y"),
+                   "<pre><code>x\n\ny</code></pre>\n");
+        assert_eq!(body("> a\nb"),
+                   "<blockquote>\n<p>a\nb</p>\n</blockquote>\n");
+        assert_eq!(body("- a\n- b\n  - c\n\n***"),
+                   "<ul>\n<li>a\n</li>\n<li>b\n<ul>\n<li>c\n</li>\n</ul>\n</li>\n</ul>\n<hr/>\n");
+        assert_eq!(body("3. a\n\n4. b"),
+                   "<ol start=\"3\">\n<li><p>a</p>\n</li>\n<li><p>b</p>\n</li>\n</ol>\n");
+    }
+
+    #[test]
+    fn test_inlines() {
+        let mut buf = String::new();
+        render_inlines("**a** `*b*` [c](d.md \"e\") ![f](g.png) snake_case_name \\*h\\*", &mut buf);
+        assert_eq!(buf, "<strong>a</strong> <code>*b*</code> <a href=\"d.md\">c</a> \
+                         <img src=\"g.png\" alt=\"f\"/> snake_case_name *h*");
+        buf.clear();
+        render_inlines("a  b\\\nc <https://e.org> 1 < 2 & 3", &mut buf);
+        assert_eq!(buf, "a  b<br/>\nc <a href=\"https://e.org\">https://e.org</a> 1 &lt; 2 &amp; 3");
+    }
+
+    #[test]
+    fn test_toc() {
+        let (html, headings) = markdown_to_html("# A\n## B\n### C\n## D\n# E", "t");
+        assert!(html.contains("<title>A</title>"));
+        assert!(headings.iter().all(|h| html[h.offset..].starts_with(&format!("<h{}", h.level))));
+        let toc = toc_entries(&headings, &mut 0, 0, &mut 0);
+        assert_eq!(toc.len(), 2);
+        assert_eq!(toc[0].children.len(), 2);
+        assert_eq!(toc[0].children[0].children[0].title, "C");
+        assert_eq!(toc[1].index, 4);
+    }
+}
@@ -2,6 +2,8 @@ pub mod djvu;
 pub mod pdf;
 pub mod epub;
 pub mod html;
+pub mod text;
+pub mod markdown;
 
 mod djvulibre_sys;
 mod mupdf_sys;
@@ -11,7 +13,7 @@ use std::process::Command;
 use std::path::Path;
 use std::fs::{self, File};
 use std::ffi::OsStr;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, BTreeMap};
 use std::os::unix::fs::FileExt;
 use anyhow::{Error, format_err};
 use regex::Regex;
@@ -22,18 +24,26 @@ use fxhash::FxHashMap;
 use unicode_normalization::UnicodeNormalization;
 use unicode_normalization::char::{is_combining_mark};
 use serde::{Serialize, Deserialize};
+use thiserror::Error;
 use self::djvu::DjvuOpener;
 use self::pdf::PdfOpener;
 use self::epub::EpubDocument;
 use self::html::HtmlDocument;
+use self::text::{TextEncoding, open_text};
+use self::markdown::MarkdownDocument;
 use crate::geom::{Boundary, CycleDir};
-use crate::metadata::{TextAlign, Annotation};
+use crate::metadata::{Info, TextAlign, Annotation};
 use crate::framebuffer::Pixmap;
 use crate::settings::INTERNAL_CARD_ROOT;
 use crate::device::CURRENT_DEVICE;
+use crate::helpers::escape_html;
 
 pub const BYTES_PER_PAGE: f64 = 2048.0;
 
+#[derive(Error, Debug)]
+#[error("This book is DRM-protected and can't be opened.")]
+pub struct DrmProtected;
+
 #[derive(Debug, Clone)]
 pub enum Location {
     Exact(usize),
@@ -121,6 +131,24 @@ pub trait Document: Send+Sync {
         false
     }
 
+    fn is_right_to_left(&self) -> bool {
+        false
+    }
+
+    // Returns the text of the footnote, or endnote, targeted by the given link.
+    fn footnote(&mut self, _loc: Location) -> Option<String> {
+        None
+    }
+
+    fn set_force_chapter_breaks(&mut self, _force: bool) {
+    }
+
+    fn set_force_text_align(&mut self, _force: bool) {
+    }
+
+    fn set_margin_bias(&mut self, _bias: i32) {
+    }
+
     fn save(&self, _path: &str) -> Result<(), Error> {
         Err(format_err!("this document can't be saved"))
     }
@@ -173,6 +201,12 @@ pub fn file_kind<P: AsRef<Path>>(path: P) -> Option<String> {
                               .map(String::from))
 }
 
+// Tells whether the documents of the given kind are laid out by the reader, hence whether
+// their pages depend on the reader's settings. Fixed-layout EPUBs can't be told apart by their kind.
+pub fn is_reflowable_kind(kind: &str) -> bool {
+    matches!(kind, "epub" | "html" | "htm" | "md" | "markdown" | "txt" | "mobi" | "fb2")
+}
+
 pub fn guess_kind<P: AsRef<Path>>(path: P) -> Result<&'static str, Error> {
     let file = File::open(path.as_ref())?;
     let mut magic = [0; 4];
@@ -220,36 +254,53 @@ pub fn asciify(name: &str) -> String {
         .replace('’', "'")
 }
 
-pub fn open<P: AsRef<Path>>(path: P) -> Option<Box<dyn Document>> {
-    file_kind(path.as_ref()).and_then(|k| {
-        match k.as_ref() {
-            "epub" => {
-                EpubDocument::new(&path)
-                             .map_err(|e| eprintln!("{}: {:#}.", path.as_ref().display(), e))
-                             .map(|d| Box::new(d) as Box<dyn Document>).ok()
-            },
-            "html" | "htm" => {
-                HtmlDocument::new(&path)
-                             .map_err(|e| eprintln!("{}: {:#}.", path.as_ref().display(), e))
-                             .map(|d| Box::new(d) as Box<dyn Document>).ok()
-            },
-            "djvu" | "djv" => {
-                DjvuOpener::new().and_then(|o| {
-                    o.open(path)
-                     .map(|d| Box::new(d) as Box<dyn Document>)
-                })
-            },
-            _ => {
-                PdfOpener::new().and_then(|mut o| {
-                    if matches!(k.as_ref(), "mobi" | "fb2" | "xps" | "txt") {
-                        o.load_user_stylesheet();
-                    }
-                    o.open(path)
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Box<dyn Document>, Error> {
+    open_with_encoding(path, None)
+}
+
+// The encoding is only relevant to plain text files, it's detected when missing.
+pub fn open_with_encoding<P: AsRef<Path>>(path: P, encoding: Option<TextEncoding>) -> Result<Box<dyn Document>, Error> {
+    let kind = file_kind(path.as_ref())
+                        .ok_or_else(|| format_err!("unknown file kind"))?;
+    match kind.as_ref() {
+        "epub" => {
+            EpubDocument::new(&path)
+                         .map_err(|e| if e.is::<DrmProtected>() { e } else { format_err!("invalid epub file: {:#}", e) })
+                         .map(|d| Box::new(d) as Box<dyn Document>)
+        },
+        "html" | "htm" => {
+            HtmlDocument::new(&path)
+                         .map_err(|e| format_err!("invalid {} file: {:#}", kind, e))
+                         .map(|d| Box::new(d) as Box<dyn Document>)
+        },
+        "md" | "markdown" => {
+            MarkdownDocument::new(&path)
+                             .map_err(|e| format_err!("invalid {} file: {:#}", kind, e))
+                             .map(|d| Box::new(d) as Box<dyn Document>)
+        },
+        "txt" => {
+            open_text(&path, encoding)
+                     .map_err(|e| format_err!("invalid {} file: {:#}", kind, e))
                      .map(|d| Box::new(d) as Box<dyn Document>)
-                })
-            },
-        }
-    })
+        },
+        "djvu" | "djv" => {
+            let opener = DjvuOpener::new()
+                                    .ok_or_else(|| format_err!("the DjVu backend is unavailable"))?;
+            opener.open(path)
+                  .map(|d| Box::new(d) as Box<dyn Document>)
+                  .ok_or_else(|| format_err!("corrupt {} file", kind))
+        },
+        _ => {
+            let mut opener = PdfOpener::new()
+                                       .ok_or_else(|| format_err!("the MuPDF backend is unavailable"))?;
+            if matches!(kind.as_ref(), "mobi" | "fb2" | "xps") {
+                opener.load_user_stylesheet();
+            }
+            opener.open(path)
+                  .map(|d| Box::new(d) as Box<dyn Document>)
+                  .ok_or_else(|| format_err!("corrupt or unsupported {} file", kind))
+        },
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -294,7 +345,7 @@ pub fn toc_as_html_aux(toc: &[TocEntry], chap_index: usize, depth: usize, buf: &
             Location::Uri(ref uri) => buf.push_str(&format!("<li><a href=\"@{}\">", uri)),
             _ => buf.push_str("<li><a href=\"#\">"),
         }
-        let title = entry.title.replace('<', "&lt;").replace('>', "&gt;");
+        let title = escape_html(&entry.title);
         if entry.index == chap_index {
             buf.push_str(&format!("<strong>{}</strong>", title));
         } else {
@@ -309,22 +360,72 @@ pub fn toc_as_html_aux(toc: &[TocEntry], chap_index: usize, depth: usize, buf: &
     buf.push_str("</ul>\n");
 }
 
-pub fn annotations_as_html(annotations: &[Annotation], active_range: Option<(TextLocation, TextLocation)>) -> String {
+// The maximum number of bytes, besides the last word of the first highlight,
+// between two dynamic highlights that are considered contiguous.
+const MAX_HIGHLIGHTS_GAP: usize = 16;
+
+// The URI of the link that shows the grouped highlights individually.
+pub const EXPAND_HIGHLIGHTS_URI: &str = "#expand-highlights";
+
+// Tells whether *second* is a highlight that starts right after the highlight *first*.
+fn contiguous_highlights(first: &Annotation, second: &Annotation) -> bool {
+    if !first.note.is_empty() || !second.note.is_empty() {
+        return false;
+    }
+    match (first.selection[1], second.selection[0]) {
+        (TextLocation::Static(p1, w1), TextLocation::Static(p2, w2)) => {
+            (p1 == p2 && w2 == w1 + 1) || (p2 == p1 + 1 && w2 == 0)
+        },
+        (TextLocation::Dynamic(o1), TextLocation::Dynamic(o2)) => {
+            let last_word_len = first.text.split_whitespace().last().map_or(0, str::len);
+            o2 > o1 && o2 - o1 <= last_word_len + MAX_HIGHLIGHTS_GAP
+        },
+        _ => false,
+    }
+}
+
+// Splits the sorted annotations into runs of contiguous highlights.
+// The annotations with a note are always alone in their run.
+pub fn group_highlights(annotations: &[Annotation]) -> Vec<&[Annotation]> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for index in 1..=annotations.len() {
+        if index == annotations.len() || !contiguous_highlights(&annotations[index-1], &annotations[index]) {
+            groups.push(&annotations[start..index]);
+            start = index;
+        }
+    }
+    groups
+}
+
+// When *group* is true, the contiguous highlights are shown as a single entry.
+pub fn annotations_as_html(annotations: &[Annotation], active_range: Option<(TextLocation, TextLocation)>, group: bool) -> String {
     let mut buf = "<html>\n\t<head>\n\t\t<title>Annotations</title>\n\t\t\
                    <link rel=\"stylesheet\" type=\"text/css\" href=\"css/annotations.css\"/>\n\t\
                    </head>\n\t<body>\n".to_string();
     buf.push_str("\t\t<ul>\n");
-    for annot in annotations {
-        let mut note = annot.note.replace('<', "&lt;").replace('>', "&gt;");
-        let mut text = annot.text.replace('<', "&lt;").replace('>', "&gt;");
+    let groups = if group {
+        group_highlights(annotations)
+    } else {
+        annotations.chunks(1).collect()
+    };
+    for annots in groups {
+        let annot = &annots[0];
+        let mut note = escape_html(&annot.note);
+        let mut text = escape_html(&annots.iter().map(|annot| annot.text.as_str())
+                                          .collect::<Vec<&str>>().join(" "));
         let start = annot.selection[0];
-        if active_range.map_or(false, |(first, last)| start >= first && start <= last) {
+        if annots.iter().any(|annot| active_range.is_some_and(|(first, last)| annot.selection[0] >= first &&
+                                                                                annot.selection[0] <= last)) {
             if !note.is_empty() {
                 note = format!("<b>{}</b>", note);
             }
             text = format!("<b>{}</b>", text);
         }
-        if note.is_empty() {
+        if annots.len() > 1 {
+            buf.push_str(&format!("\t\t<li><a href=\"@{}\">{}</a> <a href=\"{}\"><i>({} highlights)</i></a></li>\n",
+                                  start.location(), text, EXPAND_HIGHLIGHTS_URI, annots.len()));
+        } else if note.is_empty() {
             buf.push_str(&format!("\t\t<li><a href=\"@{}\">{}</a></li>\n", start.location(), text));
         } else {
             buf.push_str(&format!("\t\t<li><a href=\"@{}\"><i>{}</i> — {}</a></li>\n", start.location(), note, text));
@@ -335,7 +436,128 @@ pub fn annotations_as_html(annotations: &[Annotation], active_range: Option<(Tex
     buf
 }
 
-pub fn bookmarks_as_html(bookmarks: &BTreeSet<usize>, index: usize, synthetic: bool) -> String {
+// Quotes a CSV field when it contains the separator, a double quote or a line break.
+fn csv_field(text: &str, separator: char) -> String {
+    if text.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// Each annotation with a note is turned into a line with two fields: the text and the note.
+pub fn annotations_as_csv(annotations: &[Annotation], separator: char, note_first: bool) -> String {
+    let mut buf = String::new();
+    for annot in annotations.iter().filter(|annot| !annot.note.is_empty()) {
+        let text = csv_field(&annot.text, separator);
+        let note = csv_field(&annot.note, separator);
+        let (front, back) = if note_first { (note, text) } else { (text, note) };
+        buf.push_str(&front);
+        buf.push(separator);
+        buf.push_str(&back);
+        buf.push('\n');
+    }
+    buf
+}
+
+// The annotated text is quoted and followed by the note, under the book's title.
+pub fn annotations_as_markdown(annotations: &[Annotation], title: &str) -> String {
+    let mut buf = format!("# {}\n", title);
+    for annot in annotations {
+        buf.push('\n');
+        for line in annot.text.lines() {
+            buf.push_str("> ");
+            buf.push_str(line);
+            buf.push('\n');
+        }
+        if !annot.note.is_empty() {
+            buf.push('\n');
+            buf.push_str(&annot.note);
+            buf.push('\n');
+        }
+    }
+    buf
+}
+
+// Adds the imported annotations: when a selection is already annotated, the most recently
+// modified annotation is kept. Returns the number of annotations added or replaced.
+pub fn merge_annotations(annotations: &mut Vec<Annotation>, imported: Vec<Annotation>) -> usize {
+    let mut count = 0;
+    for annot in imported {
+        if let Some(existing) = annotations.iter_mut().find(|a| a.selection == annot.selection) {
+            if annot.modified > existing.modified {
+                *existing = annot;
+                count += 1;
+            }
+        } else {
+            annotations.push(annot);
+            count += 1;
+        }
+    }
+    count
+}
+
+// Fills the placeholders of a quote template: {text}, {author}, {title} and {page}.
+pub fn format_quote(template: &str, text: &str, author: &str, title: &str, page: &str) -> String {
+    template.replace("{text}", text)
+            .replace("{author}", author)
+            .replace("{title}", title)
+            .replace("{page}", page)
+}
+
+pub fn notes_as_html(matches: &[(String, &Path, Vec<&Annotation>)]) -> String {
+    let mut buf = "<html>\n\t<head>\n\t\t<title>Notes</title>\n\t\t\
+                   <link rel=\"stylesheet\" type=\"text/css\" href=\"css/annotations.css\"/>\n\t\
+                   </head>\n\t<body>\n".to_string();
+    for (title, path, annotations) in matches {
+        let title = escape_html(title);
+        let path = escape_html(&path.to_string_lossy());
+        buf.push_str(&format!("\t\t<h2>{}</h2>\n", title));
+        buf.push_str("\t\t<ul>\n");
+        for annot in annotations {
+            let note = escape_html(&annot.note);
+            let text = escape_html(&annot.text);
+            let start = annot.selection[0];
+            if note.is_empty() {
+                buf.push_str(&format!("\t\t\t<li><a href=\"@{}@{}\">{}</a></li>\n", start.location(), path, text));
+            } else {
+                buf.push_str(&format!("\t\t\t<li><a href=\"@{}@{}\"><i>{}</i> — {}</a></li>\n", start.location(), path, note, text));
+            }
+        }
+        buf.push_str("\t\t</ul>\n");
+    }
+    buf.push_str("\t</body>\n</html>");
+    buf
+}
+
+pub fn description_as_html(info: &Info) -> String {
+    let title = escape_html(&info.title());
+    let mut buf = format!("<html>\n\t<head>\n\t\t<title>{}</title>\n\t\t\
+                           <link rel=\"stylesheet\" type=\"text/css\" href=\"css/html.css\"/>\n\t\
+                           </head>\n\t<body>\n", title);
+    buf.push_str(&format!("\t\t<h2>{}</h2>\n", title));
+    if !info.author.is_empty() {
+        buf.push_str(&format!("\t\t<p><i>{}</i></p>\n", escape_html(&info.author)));
+    }
+    if let Some(reading_time) = info.reading_time {
+        buf.push_str(&format!("\t\t<p>Reading time: {} min.</p>\n", reading_time));
+    }
+    buf.push_str(&format!("\t\t<p>{}, {}, added on {}.<br/>{}</p>\n",
+                          info.file.kind.to_uppercase(), info.file.size.human_size(),
+                          info.added.format("%Y-%m-%d"),
+                          escape_html(&info.file.path.display().to_string())));
+    if !info.description.is_empty() {
+        buf.push_str(&format!("\t\t<p>{}</p>\n", escape_html(&info.description)));
+    }
+    if !info.url.is_empty() {
+        let url = escape_html(&info.url);
+        buf.push_str(&format!("\t\t<p><a href=\"{0}\">{0}</a></p>\n", url));
+    }
+    buf.push_str("\t</body>\n</html>");
+    buf
+}
+
+pub fn bookmarks_as_html(bookmarks: &BTreeSet<usize>, labels: &BTreeMap<usize, String>, index: usize, synthetic: bool) -> String {
     let mut buf = "<html>\n\t<head>\n\t\t<title>Bookmarks</title>\n\t\t\
                    <link rel=\"stylesheet\" type=\"text/css\" href=\"css/bookmarks.css\"/>\n\t\
                    </head>\n\t<body>\n".to_string();
@@ -349,6 +571,9 @@ pub fn bookmarks_as_html(bookmarks: &BTreeSet<usize>, index: usize, synthetic: b
         if *bkm == index {
             text = format!("<b>{}</b>", text);
         }
+        if let Some(label) = labels.get(bkm) {
+            text = format!("{} <i>(closed on {})</i>", text, label);
+        }
         buf.push_str(&format!("\t\t<li><a href=\"@{}\">{}</a></li>\n", bkm, text));
     }
     buf.push_str("\t\t</ul>\n");
@@ -362,7 +587,14 @@ fn chapter(index: usize, pages_count: usize, toc: &[TocEntry]) -> Option<(&TocEn
     let mut chap_index = 0;
     let mut end_index = pages_count;
     chapter_aux(toc, index, &mut chap, &mut chap_index, &mut end_index);
-    chap.zip(Some((index - chap_index) as f32 / (end_index - chap_index) as f32))
+    chap.zip(Some(chapter_progress(index, chap_index, end_index)))
+}
+
+// Returns the progress of *position* within the chapter that spans *start..end*.
+// The result stays within [0, 1] even if *position* is past the presumed end.
+pub fn chapter_progress(position: usize, start: usize, end: usize) -> f32 {
+    let end = end.max(position + 1);
+    (position.saturating_sub(start) as f32 / (end - start.min(position)) as f32).clamp(0.0, 1.0)
 }
 
 fn chapter_aux<'a>(toc: &'a [TocEntry], index: usize, chap: &mut Option<&'a TocEntry>,
@@ -585,3 +817,137 @@ pub fn sys_info_as_html() -> String {
     buf.push_str("\t\t</table>\n\t</body>\n</html>");
     buf
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{chapter, chapter_progress, group_highlights, annotations_as_markdown, annotations_as_html, merge_annotations};
+    use super::{TocEntry, Location, TextLocation};
+    use crate::metadata::Annotation;
+    use chrono::NaiveDateTime;
+
+    fn entry(title: &str, index: usize, location: usize, children: Vec<TocEntry>) -> TocEntry {
+        TocEntry {
+            title: title.to_string(),
+            location: Location::Exact(location),
+            index,
+            children,
+        }
+    }
+
+    #[test]
+    fn test_nested_chapter_progress() {
+        let toc = vec![
+            entry("Part One", 0, 0, vec![
+                entry("Chapter 1", 1, 2, Vec::new()),
+                entry("Chapter 2", 2, 6, Vec::new()),
+            ]),
+            entry("Part Two", 3, 10, vec![
+                entry("Chapter 3", 4, 10, Vec::new()),
+            ]),
+        ];
+
+        let (chap, progress) = chapter(1, 20, &toc).unwrap();
+        assert_eq!(chap.title, "Part One");
+        assert_eq!(progress, 0.5);
+
+        let (chap, progress) = chapter(7, 20, &toc).unwrap();
+        assert_eq!(chap.title, "Chapter 2");
+        assert_eq!(progress, 0.25);
+
+        let (chap, progress) = chapter(15, 20, &toc).unwrap();
+        assert_eq!(chap.title, "Part Two");
+        assert_eq!(progress, 0.5);
+
+        // The saved location can be past the end of a document whose page count changed.
+        let (chap, progress) = chapter(24, 20, &toc).unwrap();
+        assert_eq!(chap.title, "Part Two");
+        assert!(progress <= 1.0);
+
+        let progresses = (10..24).map(|index| chapter(index, 20, &toc).unwrap().1)
+                                 .collect::<Vec<f32>>();
+        assert!(progresses.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_chapter_progress_bounds() {
+        assert_eq!(chapter_progress(5, 5, 5), 0.0);
+        assert_eq!(chapter_progress(3, 5, 10), 0.0);
+        assert_eq!(chapter_progress(10, 0, 10), 10.0 / 11.0);
+        assert_eq!(chapter_progress(4, 0, 8), 0.5);
+    }
+
+    fn highlight(text: &str, start: TextLocation, end: TextLocation, note: &str) -> Annotation {
+        Annotation {
+            note: note.to_string(),
+            text: text.to_string(),
+            selection: [start, end],
+            .. Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group_static_highlights() {
+        let annotations = vec![
+            highlight("a b", TextLocation::Static(0, 0), TextLocation::Static(0, 1), ""),
+            highlight("c", TextLocation::Static(0, 2), TextLocation::Static(0, 2), ""),
+            highlight("e", TextLocation::Static(0, 4), TextLocation::Static(0, 4), ""),
+            highlight("f", TextLocation::Static(0, 5), TextLocation::Static(0, 5), "note"),
+        ];
+        let sizes = group_highlights(&annotations).iter().map(|g| g.len()).collect::<Vec<usize>>();
+        assert_eq!(sizes, vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn test_group_dynamic_highlights() {
+        let annotations = vec![
+            highlight("One sentence.", TextLocation::Dynamic(100), TextLocation::Dynamic(104), ""),
+            highlight("Another.", TextLocation::Dynamic(114), TextLocation::Dynamic(114), ""),
+            highlight("Far away.", TextLocation::Dynamic(400), TextLocation::Dynamic(404), ""),
+        ];
+        let sizes = group_highlights(&annotations).iter().map(|g| g.len()).collect::<Vec<usize>>();
+        assert_eq!(sizes, vec![2, 1]);
+        assert!(group_highlights(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_annotations_as_markdown() {
+        let annotations = vec![
+            highlight("First line.\nSecond line.", TextLocation::Dynamic(100), TextLocation::Dynamic(104), ""),
+            highlight("Another.", TextLocation::Dynamic(114), TextLocation::Dynamic(114), "A note."),
+        ];
+        assert_eq!(annotations_as_markdown(&annotations, "Title"),
+                   "# Title\n\n> First line.\n> Second line.\n\n> Another.\n\nA note.\n");
+    }
+
+    #[test]
+    fn test_annotations_as_html_escaping() {
+        let annotations = vec![
+            highlight("Fish &amp; <chips>", TextLocation::Dynamic(100), TextLocation::Dynamic(104), "R&D"),
+        ];
+        let html = annotations_as_html(&annotations, None, false);
+        assert!(html.contains("Fish &amp;amp; &lt;chips&gt;"));
+        assert!(html.contains("R&amp;D"));
+    }
+
+    fn modified_on(mut annot: Annotation, date: &str) -> Annotation {
+        annot.modified = NaiveDateTime::parse_from_str(&format!("{} 00:00:00", date), "%Y-%m-%d %H:%M:%S").unwrap();
+        annot
+    }
+
+    #[test]
+    fn test_merge_annotations() {
+        let mut annotations = vec![
+            modified_on(highlight("One.", TextLocation::Dynamic(10), TextLocation::Dynamic(14), "Old."), "2024-01-02"),
+            modified_on(highlight("Two.", TextLocation::Dynamic(20), TextLocation::Dynamic(24), "Kept."), "2024-01-02"),
+        ];
+        let imported = vec![
+            modified_on(highlight("One.", TextLocation::Dynamic(10), TextLocation::Dynamic(14), "New."), "2024-01-03"),
+            modified_on(highlight("Two.", TextLocation::Dynamic(20), TextLocation::Dynamic(24), "Stale."), "2024-01-01"),
+            modified_on(highlight("Three.", TextLocation::Dynamic(30), TextLocation::Dynamic(36), ""), "2024-01-01"),
+        ];
+        assert_eq!(merge_annotations(&mut annotations, imported), 2);
+        let notes = annotations.iter().map(|annot| annot.note.as_str()).collect::<Vec<&str>>();
+        assert_eq!(notes, vec!["New.", "Kept.", ""]);
+        assert_eq!(annotations[2].text, "Three.");
+    }
+}
@@ -0,0 +1,210 @@
+use std::fs;
+use std::path::Path;
+use anyhow::Error;
+use serde::{Serialize, Deserialize};
+use crate::helpers::escape_html;
+use super::html::HtmlDocument;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+    Windows1251,
+}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 5] = [TextEncoding::Utf8, TextEncoding::Utf16Le,
+                                        TextEncoding::Utf16Be, TextEncoding::Windows1252,
+                                        TextEncoding::Windows1251];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+            TextEncoding::Windows1252 => "Windows-1252",
+            TextEncoding::Windows1251 => "Windows-1251",
+        }
+    }
+}
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+// The characters of the range 0x80..=0x9F, the remaining characters match Latin-1.
+const WINDOWS_1252: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+// The characters of the range 0x80..=0xBF, the range 0xC0..=0xFF maps to U+0410..=U+044F.
+const WINDOWS_1251: [char; 64] = [
+    '\u{0402}', '\u{0403}', '\u{201A}', '\u{0453}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{20AC}', '\u{2030}', '\u{0409}', '\u{2039}', '\u{040A}', '\u{040C}', '\u{040B}', '\u{040F}',
+    '\u{0452}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{FFFD}', '\u{2122}', '\u{0459}', '\u{203A}', '\u{045A}', '\u{045C}', '\u{045B}', '\u{045F}',
+    '\u{00A0}', '\u{040E}', '\u{045E}', '\u{0408}', '\u{00A4}', '\u{0490}', '\u{00A6}', '\u{00A7}',
+    '\u{0401}', '\u{00A9}', '\u{0404}', '\u{00AB}', '\u{00AC}', '\u{00AD}', '\u{00AE}', '\u{0407}',
+    '\u{00B0}', '\u{00B1}', '\u{0406}', '\u{0456}', '\u{0491}', '\u{00B5}', '\u{00B6}', '\u{00B7}',
+    '\u{0451}', '\u{2116}', '\u{0454}', '\u{00BB}', '\u{0458}', '\u{0405}', '\u{0455}', '\u{0457}',
+];
+
+pub fn detect_encoding(bytes: &[u8]) -> TextEncoding {
+    if bytes.starts_with(UTF8_BOM) {
+        return TextEncoding::Utf8;
+    }
+
+    if bytes.starts_with(UTF16LE_BOM) {
+        return TextEncoding::Utf16Le;
+    }
+
+    if bytes.starts_with(UTF16BE_BOM) {
+        return TextEncoding::Utf16Be;
+    }
+
+    // Text encoded in UTF-16 without a BOM has a zero byte in most of its code units.
+    let sample = &bytes[..bytes.len().min(4096)];
+    let units_count = sample.len() / 2;
+    if units_count > 0 {
+        let even_zeros = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+        let odd_zeros = sample.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+        if 3 * odd_zeros > units_count && odd_zeros > 2 * even_zeros {
+            return TextEncoding::Utf16Le;
+        }
+        if 3 * even_zeros > units_count && even_zeros > 2 * odd_zeros {
+            return TextEncoding::Utf16Be;
+        }
+    }
+
+    if std::str::from_utf8(bytes).is_ok() {
+        return TextEncoding::Utf8;
+    }
+
+    // In Cyrillic texts, most letters are encoded above 0xBF, while
+    // accented letters are the exception in Western European texts.
+    let high_letters = bytes.iter().filter(|&&b| b >= 0xC0).count();
+    let ascii_letters = bytes.iter().filter(|b| b.is_ascii_alphabetic()).count();
+
+    if high_letters > ascii_letters / 2 {
+        TextEncoding::Windows1251
+    } else {
+        TextEncoding::Windows1252
+    }
+}
+
+pub fn decode(bytes: &[u8], encoding: TextEncoding) -> String {
+    match encoding {
+        TextEncoding::Utf8 => {
+            let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+            String::from_utf8_lossy(bytes).into_owned()
+        },
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+            let little_endian = encoding == TextEncoding::Utf16Le;
+            let bom = if little_endian { UTF16LE_BOM } else { UTF16BE_BOM };
+            let bytes = bytes.strip_prefix(bom).unwrap_or(bytes);
+            let units = bytes.chunks_exact(2).map(|c| {
+                if little_endian {
+                    u16::from_le_bytes([c[0], c[1]])
+                } else {
+                    u16::from_be_bytes([c[0], c[1]])
+                }
+            });
+            char::decode_utf16(units)
+                 .map(|r| r.unwrap_or(char::REPLACEMENT_CHARACTER))
+                 .collect()
+        },
+        TextEncoding::Windows1252 => {
+            bytes.iter().map(|&b| match b {
+                0x80..=0x9F => WINDOWS_1252[(b - 0x80) as usize],
+                _ => b as char,
+            }).collect()
+        },
+        TextEncoding::Windows1251 => {
+            bytes.iter().map(|&b| match b {
+                0x00..=0x7F => b as char,
+                0x80..=0xBF => WINDOWS_1251[(b - 0x80) as usize],
+                _ => char::from_u32(0x0410 + (b - 0xC0) as u32).unwrap(),
+            }).collect()
+        },
+    }
+}
+
+// When the text has blank lines, they separate the paragraphs,
+// otherwise each line is a paragraph.
+pub fn text_to_html(text: &str, title: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let has_blank_lines = text.lines().any(|line| line.trim().is_empty());
+    let mut buf = format!("<html>\n\t<head>\n\t\t<title>{}</title>\n\t</head>\n\t<body>\n",
+                          escape_html(title));
+
+    let mut paragraphs = Vec::new();
+    let mut paragraph = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if !line.is_empty() {
+            paragraph.push(line);
+        }
+        if (line.is_empty() || !has_blank_lines) && !paragraph.is_empty() {
+            paragraphs.push(paragraph.join(" "));
+            paragraph.clear();
+        }
+    }
+
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph.join(" "));
+    }
+
+    for paragraph in &paragraphs {
+        buf.push_str("\t\t<p>");
+        buf.push_str(&escape_html(paragraph));
+        buf.push_str("</p>\n");
+    }
+
+    buf.push_str("\t</body>\n</html>");
+    buf
+}
+
+pub fn open_text<P: AsRef<Path>>(path: P, encoding: Option<TextEncoding>) -> Result<HtmlDocument, Error> {
+    let bytes = fs::read(path.as_ref())?;
+    let encoding = encoding.unwrap_or_else(|| detect_encoding(&bytes));
+    let text = decode(&bytes, encoding);
+    let title = path.as_ref().file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+    Ok(HtmlDocument::new_from_memory(&text_to_html(&text, &title)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBFabc"), TextEncoding::Utf8);
+        assert_eq!(detect_encoding(b"\xFF\xFEa\x00"), TextEncoding::Utf16Le);
+        assert_eq!(detect_encoding(b"\x00a\x00b\x00c"), TextEncoding::Utf16Be);
+        assert_eq!(detect_encoding("caf\u{e9}".as_bytes()), TextEncoding::Utf8);
+        assert_eq!(detect_encoding(b"caf\xE9 cr\xE8me"), TextEncoding::Windows1252);
+        assert_eq!(detect_encoding(b"\xCF\xF0\xE8\xE2\xE5\xF2 \xEC\xE8\xF0"), TextEncoding::Windows1251);
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(b"caf\xE9 \x93a\x94", TextEncoding::Windows1252), "café “a”");
+        assert_eq!(decode(b"\xCF\xF0\xE8\xE2\xE5\xF2 \xB8", TextEncoding::Windows1251), "Привет ё");
+        assert_eq!(decode(b"\xFE\xFF\x00a\x04\x10", TextEncoding::Utf16Be), "aА");
+    }
+
+    #[test]
+    fn test_text_to_html() {
+        assert_eq!(text_to_html("a\nb", "t"),
+                   "<html>\n\t<head>\n\t\t<title>t</title>\n\t</head>\n\t<body>\n\t\t<p>a</p>\n\t\t<p>b</p>\n\t</body>\n</html>");
+        assert!(text_to_html("a\nb\n\nc & d", "t").contains("<p>a b</p>\n\t\t<p>c &amp; d</p>"));
+    }
+}
@@ -56,6 +56,12 @@ pub const KBD_LABEL: Style = Style {
     size: FONT_SIZES[0],
 };
 
+pub const PAGE_INDICATOR_STYLE: Style = Style {
+    family: Family::SansSerif,
+    variant: Variant::REGULAR,
+    size: FONT_SIZES[0],
+};
+
 pub const DISPLAY_STYLE: Style = Style {
     family: Family::Display,
     variant: Variant::REGULAR,
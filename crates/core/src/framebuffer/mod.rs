@@ -164,6 +164,34 @@ pub trait Framebuffer {
         }
     }
 
+    // Maps the white and black of the pixmap to the given paper and ink colors.
+    fn draw_framed_pixmap_tinted(&mut self, pixmap: &Pixmap, rect: &Rectangle, pt: Point, exponent: f32, gray: f32, paper: u8) {
+        let contrast = (exponent - 1.0).abs() >= f32::EPSILON;
+        let rem_gray = 255.0 - gray;
+        let inv_exponent = 1.0 / exponent;
+        let ink = (255 - paper) as f32;
+        let scale = (paper as f32 - ink) / 255.0;
+        for y in rect.min.y..rect.max.y {
+            for x in rect.min.x..rect.max.x {
+                let px = x - rect.min.x + pt.x;
+                let py = y - rect.min.y + pt.y;
+                let raw_color = pixmap.get_pixel(x as u32, y as u32);
+                let color = raw_color.apply(|comp| {
+                    let mut c = comp as f32;
+                    if contrast {
+                        if c < gray {
+                            c = gray * (c / gray).powf(exponent);
+                        } else if c > gray {
+                            c = gray + rem_gray * ((c - gray) / rem_gray).powf(inv_exponent);
+                        }
+                    }
+                    (ink + scale * c) as u8
+                });
+                self.set_pixel(px as u32, py as u32, color);
+            }
+        }
+    }
+
     fn draw_framed_pixmap_halftone(&mut self, pixmap: &Pixmap, rect: &Rectangle, pt: Point) {
         for y in rect.min.y..rect.max.y {
             for x in rect.min.x..rect.max.x {
@@ -5,9 +5,10 @@ use std::borrow::Cow;
 use std::time::SystemTime;
 use std::num::ParseIntError;
 use std::fs::{self, File, Metadata};
-use std::io::{self, BufReader, BufWriter};
+use std::io::{self, Read, Seek, SeekFrom, BufReader, BufWriter};
 use std::path::{Path, PathBuf, Component};
-use fxhash::FxHashMap;
+use std::hash::Hasher;
+use fxhash::{FxHashMap, FxHasher64};
 use std::ops::{Deref, DerefMut};
 use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use serde::de::{self, Visitor};
@@ -66,6 +67,14 @@ pub fn decode_entities(text: &str) -> Cow<str> {
     Cow::Owned(buf)
 }
 
+// Escapes the characters that have a special meaning in HTML text and attribute values.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 pub fn load_json<T, P: AsRef<Path>>(path: P) -> Result<T, Error> where for<'a> T: Deserialize<'a> {
     let file = File::open(path.as_ref())
                     .with_context(|| format!("can't open file {}", path.as_ref().display()))?;
@@ -112,6 +121,31 @@ impl Fingerprint for Metadata {
     }
 }
 
+// The size of the samples, taken at both ends of a file, used to compute its content hash.
+const CONTENT_SAMPLE_SIZE: u64 = 64 * 1024;
+
+// Hashes the size and the beginning and the end of the given file.
+// Unlike the fingerprint, it doesn't change when the file is copied.
+pub fn content_hash<P: AsRef<Path>>(path: P) -> io::Result<Fp> {
+    let mut file = File::open(path.as_ref())?;
+    let size = file.metadata()?.len();
+    let mut hasher = FxHasher64::default();
+    let mut buf = Vec::with_capacity(CONTENT_SAMPLE_SIZE as usize);
+
+    hasher.write_u64(size);
+    (&mut file).take(CONTENT_SAMPLE_SIZE).read_to_end(&mut buf)?;
+    hasher.write(&buf);
+
+    if size > 2 * CONTENT_SAMPLE_SIZE {
+        buf.clear();
+        file.seek(SeekFrom::End(-(CONTENT_SAMPLE_SIZE as i64)))?;
+        file.read_to_end(&mut buf)?;
+        hasher.write(&buf);
+    }
+
+    Ok(Fp(hasher.finish()))
+}
+
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 pub struct Fp(u64);
 
@@ -231,6 +265,26 @@ pub mod datetime_format {
     }
 }
 
+pub mod optional_datetime_format {
+    use chrono::NaiveDateTime;
+    use serde::{self, Deserialize, Serializer, Deserializer};
+    use super::datetime_format::FORMAT;
+
+    pub fn serialize<S>(date: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        match date {
+            Some(date) => serializer.serialize_str(&format!("{}", date.format(FORMAT))),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<NaiveDateTime>, D::Error> where D: Deserializer<'de> {
+        Option::<String>::deserialize(deserializer)?
+              .map(|s| NaiveDateTime::parse_from_str(&s, FORMAT)
+                                     .map_err(serde::de::Error::custom))
+              .transpose()
+    }
+}
+
 pub trait IsHidden {
     fn is_hidden(&self) -> bool;
 }
@@ -256,4 +310,11 @@ mod tests {
         assert_eq!(decode_entities("a &#38; b"), "a & b");
         assert_eq!(decode_entities("a &lt; b &gt; c"), "a < b > c");
     }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("a & b"), "a &amp; b");
+        assert_eq!(escape_html("<a href=\"x\">"), "&lt;a href=&quot;x&quot;&gt;");
+        assert_eq!(escape_html("&lt;"), "&amp;lt;");
+    }
 }
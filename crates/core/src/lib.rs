@@ -30,3 +30,4 @@ pub use rand_xoshiro;
 pub use serde;
 pub use serde_json;
 pub use png;
+pub use regex;
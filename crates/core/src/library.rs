@@ -5,6 +5,7 @@ use std::path::{PathBuf, Path};
 use std::collections::BTreeSet;
 use std::io::{Error as IoError, ErrorKind};
 use walkdir::WalkDir;
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use indexmap::IndexMap;
 use fxhash::{FxHashMap, FxHashSet, FxBuildHasher};
 use chrono::{Local, DateTime};
@@ -13,9 +14,10 @@ use crate::metadata::{Info, ReaderInfo, FileInfo, BookQuery, SimpleStatus, SortM
 use crate::metadata::{sort, sorter, extract_metadata_from_document};
 use crate::settings::{LibraryMode, ImportSettings};
 use crate::document::file_kind;
-use crate::helpers::{Fingerprint, Fp, save_json, load_json, IsHidden};
+use crate::helpers::{Fingerprint, Fp, save_json, load_json, content_hash, IsHidden};
 
 pub const METADATA_FILENAME: &str = ".metadata.json";
+pub const MISSING_FILENAME: &str = ".missing.json";
 pub const FAT32_EPOCH_FILENAME: &str = ".fat32-epoch";
 pub const READING_STATES_DIRNAME: &str = ".reading-states";
 pub const THUMBNAIL_PREVIEWS_DIRNAME: &str = ".thumbnail-previews";
@@ -24,6 +26,8 @@ pub struct Library {
     pub home: PathBuf,
     pub mode: LibraryMode,
     pub db: IndexMap<Fp, Info, FxBuildHasher>,
+    // The entries whose files vanished, kept until they're found at another path.
+    pub missing: IndexMap<Fp, Info, FxBuildHasher>,
     pub paths: FxHashMap<PathBuf, Fp>,
     pub reading_states: FxHashMap<Fp, ReaderInfo>,
     pub modified_reading_states: FxHashSet<Fp>,
@@ -59,6 +63,12 @@ impl Library {
             db = IndexMap::with_capacity_and_hasher(0, FxBuildHasher::default());
         }
 
+        let mut missing = if mode == LibraryMode::Database {
+            load_missing(home.as_ref())
+        } else {
+            IndexMap::with_capacity_and_hasher(0, FxBuildHasher::default())
+        };
+
         let mut reading_states = FxHashMap::default();
 
         let path = home.as_ref().join(READING_STATES_DIRNAME);
@@ -75,7 +85,7 @@ impl Library {
                                   .and_then(|v| Fp::from_str(v).ok()) {
                 if let Ok(reader_info) = load_json(path).map_err(|e| eprintln!("Can't load reading state: {:#}.", e)) {
                     if mode == LibraryMode::Database {
-                        if let Some(info) = db.get_mut(&fp) {
+                        if let Some(info) = db.get_mut(&fp).or_else(|| missing.get_mut(&fp)) {
                             info.reader = Some(reader_info);
                         } else {
                             eprintln!("Unknown fingerprint: {}.", fp);
@@ -112,6 +122,7 @@ impl Library {
             home: home.as_ref().to_path_buf(),
             mode,
             db,
+            missing,
             paths,
             reading_states,
             modified_reading_states: FxHashSet::default(),
@@ -145,6 +156,11 @@ impl Library {
                         if skip_files {
                             continue;
                         }
+                        // Hidden books are only listed when requested.
+                        if info.hidden && !self.show_hidden &&
+                           query.is_none_or(|q| q.hidden.is_none()) {
+                            continue;
+                        }
                         if query.map_or(true, |q| q.is_match(info)) {
                             files.push(info.clone());
                         }
@@ -194,6 +210,7 @@ impl Library {
                             path: relat.to_path_buf(),
                             kind,
                             size,
+                            hash: None,
                         };
                         let secs = (*fp >> 32) as i64;
                         let nsecs = ((*fp & ((1<<32) - 1)) % 1_000_000_000) as u32;
@@ -221,8 +238,12 @@ impl Library {
             return;
         }
 
+        let filter = ImportFilter::new(settings);
+        let home = self.home.clone();
+
         for entry in WalkDir::new(&self.home).min_depth(1).into_iter()
-                             .filter_entry(|e| !e.is_hidden()) {
+                             .filter_entry(|e| !e.is_hidden() &&
+                                               !filter.excludes(e.path().strip_prefix(&home).unwrap_or(e.path()))) {
             if entry.is_err() {
                 continue;
             }
@@ -235,11 +256,19 @@ impl Library {
             let path = entry.path();
             let relat = path.strip_prefix(&self.home)
                             .unwrap_or(path);
+
+            if !filter.includes(relat) {
+                continue;
+            }
             let md = entry.metadata().unwrap();
             let fp = md.fingerprint(self.fat32_epoch).unwrap();
 
             // The fp is know: update the path if it changed.
             if self.db.contains_key(&fp) {
+                if self.db[&fp].file.hash.is_none() {
+                    self.db[&fp].file.hash = content_hash(path).ok();
+                    self.has_db_changed = true;
+                }
                 if relat != self.db[&fp].file.path {
                     println!("Update path for {}: {} → {}.",
                              fp, self.db[&fp].file.path.display(), relat.display());
@@ -257,6 +286,7 @@ impl Library {
                 }
                 self.db.insert(fp, info);
                 self.db[&fp].file.size = md.len();
+                self.db[&fp].file.hash = content_hash(path).ok();
                 self.paths.insert(relat.to_path_buf(), fp);
                 let rp1 = self.reading_state_path(fp2);
                 let rp2 = self.reading_state_path(fp);
@@ -301,18 +331,44 @@ impl Library {
                         self.paths.insert(relat.to_path_buf(), fp);
                         self.db[&fp].file.path = relat.to_path_buf();
                     }
-                // We found a new file: add it to the db.
                 } else {
                     let kind = file_kind(&path).unwrap_or_default();
                     if !settings.allowed_kinds.contains(&kind) {
                         continue;
                     }
-                    println!("Add new entry: {}, {}.", fp, relat.display());
                     let size = md.len();
+                    let hash = content_hash(path).ok();
+                    let mfp = hash.and_then(|hash| {
+                        self.missing.iter()
+                            .find(|(_, info)| info.file.size == size && info.file.hash == Some(hash))
+                            .map(|(mfp, _)| *mfp)
+                    });
+
+                    // We found a file that went missing: restore its entry.
+                    if let Some(mfp) = mfp {
+                        println!("Found missing entry: {}, {} → {}.",
+                                 mfp, self.missing[&mfp].file.path.display(), relat.display());
+                        let mut info = self.missing.swap_remove(&mfp).unwrap();
+                        info.file.path = relat.to_path_buf();
+                        self.db.insert(fp, info);
+                        self.paths.insert(relat.to_path_buf(), fp);
+                        let rp1 = self.reading_state_path(mfp);
+                        let rp2 = self.reading_state_path(fp);
+                        fs::rename(rp1, rp2).ok();
+                        let tp1 = self.thumbnail_preview_path(mfp);
+                        let tp2 = self.thumbnail_preview_path(fp);
+                        fs::rename(tp1, tp2).ok();
+                        self.has_db_changed = true;
+                        continue;
+                    }
+
+                    // We found a new file: add it to the db.
+                    println!("Add new entry: {}, {}.", fp, relat.display());
                     let file = FileInfo {
                         path: relat.to_path_buf(),
                         kind,
                         size,
+                        hash,
                     };
                     let mut info = Info {
                         file,
@@ -331,13 +387,23 @@ impl Library {
 
         let home = &self.home;
         let len = self.db.len();
+        let missing = &mut self.missing;
 
+        // The entries of the vanished files are set aside: the files
+        // might have been moved or renamed outside of the application.
+        // The excluded entries are set aside too, so that their reading
+        // states survive a change of the import patterns.
         self.db.retain(|fp, info| {
             let path = home.join(&info.file.path);
-            if path.exists() {
+            if info.file.path.ancestors().any(|p| filter.excludes(p)) || !filter.includes(&info.file.path) {
+                println!("Excluded entry: {}, {}.", fp, info.file.path.display());
+                missing.insert(*fp, info.clone());
+                false
+            } else if path.exists() {
                 true
             } else {
-                println!("Remove entry: {}, {}.", fp, info.file.path.display());
+                println!("Missing entry: {}, {}.", fp, info.file.path.display());
+                missing.insert(*fp, info.clone());
                 false
             }
         });
@@ -346,7 +412,7 @@ impl Library {
             self.has_db_changed = true;
             let db = &self.db;
             self.paths.retain(|_, fp| db.contains_key(fp));
-            self.modified_reading_states.retain(|fp| db.contains_key(fp));
+            self.modified_reading_states.retain(|fp| db.contains_key(fp) || missing.contains_key(fp));
 
             let reading_states_dir = home.join(READING_STATES_DIRNAME);
             let thumbnail_previews_dir = home.join(THUMBNAIL_PREVIEWS_DIRNAME);
@@ -356,10 +422,12 @@ impl Library {
                     continue;
                 }
                 let entry = entry.unwrap();
+                // The page previews are named after the fingerprint followed by the page.
                 if let Some(fp) = entry.path().file_stem()
                                        .and_then(|v| v.to_str())
+                                       .and_then(|v| v.split('-').next())
                                        .and_then(|v| Fp::from_str(v).ok()) {
-                    if !self.db.contains_key(&fp) {
+                    if !self.db.contains_key(&fp) && !self.missing.contains_key(&fp) {
                         fs::remove_file(entry.path()).ok();
                     }
                 }
@@ -435,6 +503,7 @@ impl Library {
         }
 
         let tpp = self.thumbnail_preview_path(fp);
+        remove_page_previews(&tpp, None);
         if tpp.exists() {
             fs::remove_file(tpp)?;
         }
@@ -531,7 +600,7 @@ impl Library {
         Ok(())
     }
 
-    pub fn move_to<P: AsRef<Path>>(&mut self, path: P, other: &mut Library) -> Result<(), Error> {
+    pub fn move_to<P: AsRef<Path>>(&mut self, path: P, other: &mut Library) -> Result<PathBuf, Error> {
         let src = self.home.join(path.as_ref());
 
         if !src.exists() {
@@ -559,6 +628,8 @@ impl Library {
 
         fs::rename(&src, &dest)?;
 
+        let dest_path = dest.strip_prefix(&other.home)?.to_path_buf();
+
         let rsp_src = self.reading_state_path(fp);
         if rsp_src.exists() {
             let rsp_dest = other.reading_state_path(fp);
@@ -586,11 +657,10 @@ impl Library {
                                    })
                            );
             if let Some(mut info) = info {
-                let dest_path = dest.strip_prefix(&other.home)?;
-                info.file.path = dest_path.to_path_buf();
+                info.file.path = dest_path.clone();
                 other.db.insert(fp, info);
                 self.paths.remove(path.as_ref());
-                other.paths.insert(dest_path.to_path_buf(), fp);
+                other.paths.insert(dest_path.clone(), fp);
                 self.has_db_changed = true;
                 other.has_db_changed = true;
             }
@@ -607,7 +677,7 @@ impl Library {
             other.modified_reading_states.insert(fp);
         }
 
-        Ok(())
+        Ok(dest_path)
     }
 
     pub fn clean_up(&mut self) {
@@ -685,6 +755,17 @@ impl Library {
         self.has_db_changed = true;
     }
 
+    pub fn edit_info<P: AsRef<Path>, F>(&mut self, path: P, f: F) where F: FnOnce(&mut Info) {
+        if self.mode == LibraryMode::Filesystem {
+            return;
+        }
+
+        if let Some(info) = self.paths.get(path.as_ref()).and_then(|fp| self.db.get_mut(fp)) {
+            f(info);
+            self.has_db_changed = true;
+        }
+    }
+
     pub fn sync_reader_info<P: AsRef<Path>>(&mut self, path: P, reader: &ReaderInfo) {
         let fp = self.paths.get(path.as_ref()).cloned().unwrap_or_else(|| {
             self.home.join(path.as_ref())
@@ -736,6 +817,9 @@ impl Library {
                     if let Some(info) = self.db.get_mut(&fp) {
                         let reader_info = info.reader
                                               .get_or_insert_with(ReaderInfo::default);
+                        if status == SimpleStatus::Finished && !reader_info.finished {
+                            reader_info.finished_at = Some(Local::now().naive_local());
+                        }
                         reader_info.finished = status == SimpleStatus::Finished;
                         self.modified_reading_states.insert(fp);
                     }
@@ -751,6 +835,9 @@ impl Library {
                 SimpleStatus::Reading | SimpleStatus::Finished => {
                     let reader_info = self.reading_states.entry(fp)
                                           .or_insert_with(ReaderInfo::default);
+                    if status == SimpleStatus::Finished && !reader_info.finished {
+                        reader_info.finished_at = Some(Local::now().naive_local());
+                    }
                     reader_info.finished = status == SimpleStatus::Finished;
                     self.modified_reading_states.insert(fp);
                 },
@@ -772,6 +859,8 @@ impl Library {
                     self.has_db_changed = false;
                 },
             }
+
+            self.missing = load_missing(&self.home);
         }
 
         let path = self.home.join(READING_STATES_DIRNAME);
@@ -788,7 +877,7 @@ impl Library {
                                   .and_then(|v| Fp::from_str(v).ok()) {
                 if let Ok(reader_info) = load_json(path).map_err(|e| eprintln!("Can't load reading state: {:#}.", e)) {
                     if self.mode == LibraryMode::Database {
-                        if let Some(info) = self.db.get_mut(&fp) {
+                        if let Some(info) = self.db.get_mut(&fp).or_else(|| self.missing.get_mut(&fp)) {
                             info.reader = Some(reader_info);
                         } else {
                             eprintln!("Unknown fingerprint: {}.", fp);
@@ -823,6 +912,8 @@ impl Library {
         if self.has_db_changed {
             save_json(&self.db, self.home.join(METADATA_FILENAME))
                      .map_err(|e| eprintln!("Can't save database: {:#}.", e)).ok();
+            save_json(&self.missing, self.home.join(MISSING_FILENAME))
+                     .map_err(|e| eprintln!("Can't save missing entries: {:#}.", e)).ok();
             self.has_db_changed = false;
         }
     }
@@ -841,9 +932,83 @@ impl Library {
             .join(format!("{}.json", fp))
     }
 
+    // The thumbnail preview of the given page of a document.
+    pub fn page_preview<P: AsRef<Path>>(&self, path: P, page: usize) -> PathBuf {
+        let tpp = self.thumbnail_preview(path);
+        let stem = tpp.file_stem().and_then(|v| v.to_str()).unwrap_or_default();
+        tpp.with_file_name(format!("{}-{}.png", stem, page))
+    }
+
     fn thumbnail_preview_path(&self, fp: Fp) -> PathBuf {
         self.home
             .join(THUMBNAIL_PREVIEWS_DIRNAME)
             .join(format!("{}.png", fp))
     }
 }
+
+// Removes the page previews of the document of the given thumbnail preview, except *keep*.
+pub fn remove_page_previews(thumbnail_preview: &Path, keep: Option<&Path>) {
+    let prefix = thumbnail_preview.file_stem()
+                                  .and_then(|v| v.to_str())
+                                  .and_then(|v| v.split('-').next())
+                                  .map(|v| format!("{}-", v));
+    let (Some(prefix), Some(dir)) = (prefix, thumbnail_preview.parent()) else {
+        return;
+    };
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if Some(path.as_path()) != keep && entry.file_name().to_string_lossy().starts_with(&prefix) {
+                fs::remove_file(path).ok();
+            }
+        }
+    }
+}
+
+// The include and exclude patterns of the import settings.
+struct ImportFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl ImportFilter {
+    fn new(settings: &ImportSettings) -> ImportFilter {
+        let include = if settings.include_patterns.is_empty() {
+            None
+        } else {
+            Some(build_glob_set(&settings.include_patterns))
+        };
+        ImportFilter {
+            include,
+            exclude: build_glob_set(&settings.exclude_patterns),
+        }
+    }
+
+    fn excludes(&self, path: &Path) -> bool {
+        self.exclude.is_match(path)
+    }
+
+    fn includes(&self, path: &Path) -> bool {
+        self.include.as_ref().is_none_or(|include| include.is_match(path))
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match Glob::new(pattern) {
+            Ok(glob) => { builder.add(glob); },
+            Err(e) => eprintln!("Invalid pattern {}: {:#}.", pattern, e),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+fn load_missing(home: &Path) -> IndexMap<Fp, Info, FxBuildHasher> {
+    let path = home.join(MISSING_FILENAME);
+    if !path.exists() {
+        return IndexMap::with_capacity_and_hasher(0, FxBuildHasher::default());
+    }
+    load_json(&path).map_err(|e| eprintln!("Can't load missing entries: {:#}.", e))
+                    .unwrap_or_else(|_| IndexMap::with_capacity_and_hasher(0, FxBuildHasher::default()))
+}
@@ -4,6 +4,7 @@ use std::ffi::OsStr;
 use std::collections::{BTreeSet, BTreeMap};
 use std::path::{Path, PathBuf};
 use std::cmp::Ordering;
+use std::ops::RangeInclusive;
 use regex::Regex;
 use chrono::{NaiveDateTime, Local};
 use fxhash::FxHashMap;
@@ -15,12 +16,15 @@ use crate::document::{Document, SimpleTocEntry, TextLocation};
 use crate::document::asciify;
 use crate::document::epub::EpubDocument;
 use crate::document::html::HtmlDocument;
+use crate::document::markdown::MarkdownDocument;
+use crate::document::text::TextEncoding;
 use crate::document::pdf::PdfOpener;
 use crate::document::djvu::DjvuOpener;
-use crate::helpers::datetime_format;
+use crate::helpers::{Fp, datetime_format, optional_datetime_format};
 
 pub const DEFAULT_CONTRAST_EXPONENT: f32 = 1.0;
 pub const DEFAULT_CONTRAST_GRAY: f32 = 224.0;
+pub const MAX_RATING: u8 = 5;
 
 pub type Metadata = Vec<Info>;
 
@@ -49,8 +53,20 @@ pub struct Info {
     pub number: String,
     #[serde(skip_serializing_if = "String::is_empty")]
     pub identifier: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    // Estimated reading time, in minutes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reading_time: Option<u32>,
     #[serde(skip_serializing_if = "BTreeSet::is_empty")]
     pub categories: BTreeSet<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    // From 1 to 5.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
     pub file: FileInfo,
     #[serde(skip_serializing)]
     pub reader: Option<ReaderInfo>,
@@ -60,27 +76,22 @@ pub struct Info {
     pub toc: Option<Vec<SimpleTocEntry>>,
     #[serde(with = "datetime_format")]
     pub added: NaiveDateTime,
+    // When the book was moved to the trash.
+    #[serde(default, with = "optional_datetime_format", skip_serializing_if = "Option::is_none")]
+    pub removed: Option<NaiveDateTime>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct FileInfo {
     pub path: PathBuf,
     pub kind: String,
     pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<Fp>,
 }
 
-impl Default for FileInfo {
-    fn default() -> Self {
-        FileInfo {
-            path: PathBuf::default(),
-            kind: String::default(),
-            size: u64::default(),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 pub struct Annotation {
     #[serde(skip_serializing_if = "String::is_empty")]
@@ -199,13 +210,27 @@ pub struct ReaderInfo {
     pub current_page: usize,
     pub pages_count: usize,
     pub finished: bool,
+    // When the book was last finished.
+    #[serde(default, with = "optional_datetime_format", skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<NaiveDateTime>,
     pub dithered: bool,
+    // Overrides the global color inversion while the book is open.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inverted: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub zoom_mode: Option<ZoomMode>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scroll_mode: Option<ScrollMode>,
+    // Relative abscissa of the boundary between the two columns of each page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_split: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page_offset: Option<Point>,
+    // Position of the center of the view port, relative to the dimensions of the page's frame.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zoom_anchor: Option<(f32, f32)>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rotation: Option<i8>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -214,6 +239,12 @@ pub struct ReaderInfo {
     pub margin_width: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub screen_margin_width: Option<i32>,
+    // Widens the left (positive) or the right (negative) margin by this many millimeters,
+    // and narrows the other one by the same amount.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub margin_bias: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<TextEncoding>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub font_family: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -222,14 +253,26 @@ pub struct ReaderInfo {
     pub text_align: Option<TextAlign>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line_height: Option<f32>,
+    // Gray level of the page background, the text uses the complementary level.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paper_tint: Option<u8>,
+    // Overrides the page progression direction of the document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub right_to_left: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub contrast_exponent: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub contrast_gray: Option<f32>,
+    // Derive the contrast of each page from its gray levels, instead of using the values above.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_contrast: Option<bool>,
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub page_names: BTreeMap<usize, String>,
     #[serde(skip_serializing_if = "BTreeSet::is_empty")]
     pub bookmarks: BTreeSet<usize>,
+    // The bookmarks added when the book was closed, with the closing time.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub auto_bookmarks: BTreeMap<usize, String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub annotations: Vec<Annotation>,
 }
@@ -273,22 +316,33 @@ impl Default for ReaderInfo {
             current_page: 0,
             pages_count: 1,
             finished: false,
+            finished_at: None,
             dithered: false,
+            inverted: None,
             zoom_mode: None,
             scroll_mode: None,
+            column_split: None,
+            column: None,
             page_offset: None,
+            zoom_anchor: None,
             rotation: None,
             cropping_margins: None,
             margin_width: None,
             screen_margin_width: None,
+            margin_bias: None,
+            encoding: None,
             font_family: None,
             font_size: None,
             text_align: None,
             line_height: None,
+            paper_tint: None,
+            right_to_left: None,
             contrast_exponent: None,
             contrast_gray: None,
+            auto_contrast: None,
             page_names: BTreeMap::new(),
             bookmarks: BTreeSet::new(),
+            auto_bookmarks: BTreeMap::new(),
             annotations: Vec::new(),
         }
     }
@@ -308,9 +362,15 @@ impl Default for Info {
             volume: String::default(),
             number: String::default(),
             identifier: String::default(),
+            description: String::default(),
+            url: String::default(),
+            reading_time: None,
             categories: BTreeSet::new(),
+            hidden: false,
+            rating: None,
             file: FileInfo::default(),
             added: Local::now().naive_local(),
+            removed: None,
             reader: None,
             reader_info: None,
             toc: None,
@@ -455,6 +515,23 @@ pub fn make_query(text: &str) -> Option<Regex> {
           .ok()
 }
 
+// Parses conditions such as `>=4`, `<3` or `5`.
+fn parse_rating_condition(text: &str) -> Option<RangeInclusive<u8>> {
+    let (operator, value) = match text.find(|c: char| c.is_ascii_digit()) {
+        Some(index) => text.split_at(index),
+        None => return None,
+    };
+    let value = value.parse::<u8>().ok().filter(|v| *v <= MAX_RATING)?;
+    match operator {
+        "" | "=" => Some(value..=value),
+        ">=" => Some(value..=MAX_RATING),
+        ">" => Some(value.saturating_add(1)..=MAX_RATING),
+        "<=" => Some(0..=value),
+        "<" => value.checked_sub(1).map(|v| 0..=v),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct BookQuery {
     pub free: Option<Regex>,
@@ -468,11 +545,18 @@ pub struct BookQuery {
     pub edition: Option<Regex>,
     pub volume: Option<Regex>,
     pub number: Option<Regex>,
+    // Matches the title or the author.
+    pub title_or_author: Option<Regex>,
     pub reading: Option<bool>,
     pub new: Option<bool>,
     pub finished: Option<bool>,
     pub annotations: Option<bool>,
     pub bookmarks: Option<bool>,
+    pub hidden: Option<bool>,
+    // The unrated books have a rating of 0.
+    pub rating: Option<RangeInclusive<u8>>,
+    // The file kind, in lowercase.
+    pub kind: Option<String>,
     pub opened_after: Option<(bool, NaiveDateTime)>,
     pub added_after: Option<(bool, NaiveDateTime)>,
 }
@@ -482,6 +566,15 @@ impl BookQuery {
         let mut buf = Vec::new();
         let mut query = BookQuery::default();
         for word in text.rsplit(' ') {
+            // The invalid conditions are treated as free text.
+            if let Some(range) = word.strip_prefix("rating:").and_then(parse_rating_condition) {
+                query.rating = Some(range);
+                continue;
+            }
+            if let Some(kind) = word.strip_prefix("kind:") {
+                query.kind = Some(kind.to_lowercase()).filter(|kind| !kind.is_empty());
+                continue;
+            }
             let mut chars = word.chars().peekable();
             match chars.next() {
                 Some('\'') => {
@@ -506,6 +599,7 @@ impl BookQuery {
                         Some('F') => query.finished = Some(!invert),
                         Some('A') => query.annotations = Some(!invert),
                         Some('B') => query.bookmarks = Some(!invert),
+                        Some('H') => query.hidden = Some(!invert),
                         Some('O') => {
                             buf.reverse();
                             query.opened_after = NaiveDateTime::parse_from_str(&buf.join(" "),
@@ -545,6 +639,9 @@ impl BookQuery {
            query.finished.is_none() &&
            query.annotations.is_none() &&
            query.bookmarks.is_none() &&
+           query.hidden.is_none() &&
+           query.rating.is_none() &&
+           query.kind.is_none() &&
            query.opened_after.is_none() &&
            query.added_after.is_none() {
             None
@@ -571,16 +668,33 @@ impl BookQuery {
         self.edition.as_ref().map(|re| re.is_match(&info.edition)) != Some(false) &&
         self.volume.as_ref().map(|re| re.is_match(&info.volume)) != Some(false) &&
         self.number.as_ref().map(|re| re.is_match(&info.number)) != Some(false) &&
+        self.title_or_author.as_ref().map(|re| re.is_match(&info.title) ||
+                                               re.is_match(&info.author)) != Some(false) &&
         self.reading.as_ref().map(|eq| info.simple_status().eq(&SimpleStatus::Reading) == *eq) != Some(false) &&
         self.new.as_ref().map(|eq| info.simple_status().eq(&SimpleStatus::New) == *eq) != Some(false) &&
         self.finished.as_ref().map(|eq| info.simple_status().eq(&SimpleStatus::Finished) == *eq) != Some(false) &&
         self.annotations.as_ref().map(|eq| info.reader.as_ref().map_or(false, |r| !r.annotations.is_empty()) == *eq) != Some(false) &&
         self.bookmarks.as_ref().map(|eq| info.reader.as_ref().map_or(false, |r| !r.bookmarks.is_empty()) == *eq) != Some(false) &&
+        self.hidden.as_ref().map(|eq| info.hidden == *eq) != Some(false) &&
+        self.rating.as_ref().map(|range| range.contains(&info.rating.unwrap_or(0))) != Some(false) &&
+        self.kind.as_ref().map(|kind| info.file.kind.eq_ignore_ascii_case(kind)) != Some(false) &&
         self.opened_after.as_ref().map(|(eq, opened)| info.reader.as_ref().map_or(false, |r| r.opened.gt(opened)) == *eq) != Some(false) &&
         self.added_after.as_ref().map(|(eq, added)| info.added.gt(added) == *eq) != Some(false)
     }
 
 
+    // Builds a query that matches the titles and the authors containing the given text.
+    // Returns `None` for empty texts, and for texts using the query syntax.
+    pub fn substring(text: &str) -> Option<BookQuery> {
+        let text = text.trim();
+        if text.is_empty() || text.split(' ').any(|word| word.starts_with('\'') || word.starts_with("kind:") ||
+                                                         word.strip_prefix("rating:").and_then(parse_rating_condition).is_some()) {
+            return None;
+        }
+        Regex::new(&format!("(?i){}", regex::escape(text))).ok()
+              .map(|re| BookQuery { title_or_author: Some(re), .. Default::default() })
+    }
+
     #[inline]
     pub fn is_simple_match(&self, text: &str) -> bool {
         self.free.as_ref().map_or(true, |q| q.is_match(text))
@@ -594,6 +708,7 @@ pub enum SortMethod {
     Added,
     Status,
     Progress,
+    Rating,
     Title,
     Year,
     Author,
@@ -625,6 +740,7 @@ impl SortMethod {
             SortMethod::Added => "Date Added",
             SortMethod::Status => "Status",
             SortMethod::Progress => "Progress",
+            SortMethod::Rating => "Rating",
             SortMethod::Author => "Author",
             SortMethod::Title => "Title",
             SortMethod::Year => "Year",
@@ -659,6 +775,7 @@ pub fn sorter(sort_method: SortMethod) -> fn(&Info, &Info) -> Ordering {
         SortMethod::Added => sort_added,
         SortMethod::Status => sort_status,
         SortMethod::Progress => sort_progress,
+        SortMethod::Rating => sort_rating,
         SortMethod::Author => sort_author,
         SortMethod::Title => sort_title,
         SortMethod::Year => sort_year,
@@ -732,6 +849,10 @@ pub fn sort_kind(i1: &Info, i2: &Info) -> Ordering {
     i1.file.kind.cmp(&i2.file.kind)
 }
 
+pub fn sort_rating(i1: &Info, i2: &Info) -> Ordering {
+    i1.rating.cmp(&i2.rating)
+}
+
 pub fn sort_year(i1: &Info, i2: &Info) -> Ordering {
     i1.year.cmp(&i2.year)
 }
@@ -778,6 +899,7 @@ pub fn extract_metadata_from_document(prefix: &Path, info: &mut Info) {
                         info.number = index;
                     }
                     info.language = doc.language().unwrap_or_default();
+                    info.description = doc.description().unwrap_or_default();
                     info.categories.append(&mut doc.categories());
                 },
                 Err(e) => eprintln!("Can't open {}: {:#}.", info.file.path.display(), e),
@@ -789,10 +911,17 @@ pub fn extract_metadata_from_document(prefix: &Path, info: &mut Info) {
                     info.title = doc.title().unwrap_or_default();
                     info.author = doc.author().unwrap_or_default();
                     info.language = doc.language().unwrap_or_default();
+                    info.description = doc.description().unwrap_or_default();
                 },
                 Err(e) => eprintln!("Can't open {}: {:#}.", info.file.path.display(), e),
             }
         },
+        "md" | "markdown" => {
+            match MarkdownDocument::new(&path) {
+                Ok(doc) => info.title = doc.title().unwrap_or_default(),
+                Err(e) => eprintln!("Can't open {}: {:#}.", info.file.path.display(), e),
+            }
+        },
         "pdf" => {
             match PdfOpener::new().and_then(|o| o.open(path)) {
                 Some(doc) => {
@@ -933,3 +1062,50 @@ pub fn file_name_from_info(info: &Info) -> String {
         .replace('!', "")
         .replace(':', "")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rating_condition() {
+        assert_eq!(parse_rating_condition("4"), Some(4..=4));
+        assert_eq!(parse_rating_condition("=4"), Some(4..=4));
+        assert_eq!(parse_rating_condition(">=4"), Some(4..=MAX_RATING));
+        assert_eq!(parse_rating_condition(">4"), Some(5..=MAX_RATING));
+        assert_eq!(parse_rating_condition("<=3"), Some(0..=3));
+        assert_eq!(parse_rating_condition("<3"), Some(0..=2));
+        assert_eq!(parse_rating_condition("<0"), None);
+        assert_eq!(parse_rating_condition("abc"), None);
+        assert_eq!(parse_rating_condition(">>4"), None);
+        assert_eq!(parse_rating_condition("9"), None);
+        assert_eq!(parse_rating_condition(""), None);
+    }
+
+    #[test]
+    fn test_invalid_rating_is_free_text() {
+        let query = BookQuery::new("rating:abc").unwrap();
+        assert!(query.rating.is_none());
+        assert!(query.free.as_ref().is_some_and(|re| re.is_match("rating:abc")));
+        let info = Info { title: "Dune".to_string(), .. Default::default() };
+        assert!(!query.is_match(&info));
+
+        let query = BookQuery::new("dune rating:>=4").unwrap();
+        assert_eq!(query.rating, Some(4..=MAX_RATING));
+        assert!(query.free.as_ref().is_some_and(|re| re.is_match("Dune") && !re.is_match("rating")));
+    }
+
+    #[test]
+    fn test_substring() {
+        assert!(BookQuery::substring("  ").is_none());
+        assert!(BookQuery::substring("'t dune").is_none());
+        assert!(BookQuery::substring("kind:epub").is_none());
+        assert!(BookQuery::substring("rating:>=4").is_none());
+        let query = BookQuery::substring("rating:abc").unwrap();
+        assert!(query.title_or_author.as_ref().is_some_and(|re| re.is_match("Rating:ABC")));
+        let query = BookQuery::substring("a.b (c)").unwrap();
+        let re = query.title_or_author.unwrap();
+        assert!(re.is_match("The A.B (C) Book"));
+        assert!(!re.is_match("aXb (c)"));
+    }
+}
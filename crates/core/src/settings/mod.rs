@@ -6,8 +6,9 @@ use std::fmt::{self, Debug};
 use std::path::PathBuf;
 use std::collections::{BTreeMap, HashMap};
 use fxhash::FxHashSet;
+use chrono::NaiveDateTime;
 use serde::{Serialize, Deserialize};
-use crate::metadata::{SortMethod, TextAlign};
+use crate::metadata::{SortMethod, TextAlign, ZoomMode};
 use crate::frontlight::LightLevels;
 use crate::color::{Color, BLACK};
 use crate::device::CURRENT_DEVICE;
@@ -112,6 +113,8 @@ pub struct Settings {
     pub date_format: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_urls_queue: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_keyboard_layouts: Option<PathBuf>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub libraries: Vec<LibrarySettings>,
     pub intermissions: Intermissions,
@@ -125,6 +128,7 @@ pub struct Settings {
     pub calculator: CalculatorSettings,
     pub battery: BatterySettings,
     pub frontlight_levels: LightLevels,
+    pub warm_reading: WarmReadingSettings,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -134,6 +138,12 @@ pub enum LibraryMode {
     Filesystem,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BundleImportMode {
+    Merge,
+    Replace,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct LibrarySettings {
@@ -141,9 +151,16 @@ pub struct LibrarySettings {
     pub path: PathBuf,
     pub mode: LibraryMode,
     pub sort_method: SortMethod,
+    // Show the books as a list of lines, or as a grid of covers.
+    pub layout: ShelfLayout,
     pub first_column: FirstColumn,
     pub second_column: SecondColumn,
     pub thumbnail_previews: bool,
+    // Preview the current page of the books in progress instead of their cover.
+    pub current_page_previews: bool,
+    // The end of the last successful run of the library's hooks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_sync: Option<NaiveDateTime>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub hooks: Vec<Hook>,
 }
@@ -156,9 +173,12 @@ impl Default for LibrarySettings {
                       .unwrap_or_else(|| PathBuf::from("/")),
             mode: LibraryMode::Database,
             sort_method: SortMethod::Opened,
+            layout: ShelfLayout::List,
             first_column: FirstColumn::TitleAndAuthor,
             second_column: SecondColumn::Progress,
             thumbnail_previews: true,
+            current_page_previews: false,
+            last_sync: None,
             hooks: Vec::new(),
         }
     }
@@ -172,6 +192,11 @@ pub struct ImportSettings {
     pub sync_metadata: bool,
     pub metadata_kinds: FxHashSet<String>,
     pub allowed_kinds: FxHashSet<String>,
+    // Glob patterns, matched against the paths relative to the library's directory.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include_patterns: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_patterns: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -260,6 +285,13 @@ pub struct Columns {
     second: SecondColumn,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShelfLayout {
+    List,
+    Grid,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum FirstColumn {
@@ -274,6 +306,18 @@ pub enum SecondColumn {
     Year,
 }
 
+// The reader events that can be sent to a hook.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    Share,
+    ShareQuote,
+    ReadAloud,
+    Translate,
+    PageTurned,
+    FollowLink,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct Hook {
@@ -282,6 +326,9 @@ pub struct Hook {
     pub sort_method: Option<SortMethod>,
     pub first_column: Option<FirstColumn>,
     pub second_column: Option<SecondColumn>,
+    // The reader events sent to the hook, besides the ones it asked for.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<HookEvent>,
 }
 
 impl Default for Hook {
@@ -292,6 +339,7 @@ impl Default for Hook {
             sort_method: None,
             first_column: None,
             second_column: None,
+            events: Vec::new(),
         }
     }
 }
@@ -303,6 +351,53 @@ pub struct HomeSettings {
     pub navigation_bar: bool,
     pub max_levels: usize,
     pub max_trash_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_trash_age: Option<u32>,
+    pub recently_added: HomeSectionSettings,
+    pub recently_finished: HomeSectionSettings,
+    pub max_section_items: usize,
+    // Filter the books by title and author while typing in the search bar.
+    pub search_as_you_type: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HomeSectionSettings {
+    pub enabled: bool,
+    pub collapsed: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HomeSection {
+    RecentlyAdded,
+    RecentlyFinished,
+}
+
+impl HomeSection {
+    pub const ALL: [HomeSection; 2] = [HomeSection::RecentlyAdded, HomeSection::RecentlyFinished];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HomeSection::RecentlyAdded => "Recently Added",
+            HomeSection::RecentlyFinished => "Recently Finished",
+        }
+    }
+}
+
+impl HomeSettings {
+    pub fn section(&self, section: HomeSection) -> &HomeSectionSettings {
+        match section {
+            HomeSection::RecentlyAdded => &self.recently_added,
+            HomeSection::RecentlyFinished => &self.recently_finished,
+        }
+    }
+
+    pub fn section_mut(&mut self, section: HomeSection) -> &mut HomeSectionSettings {
+        match section {
+            HomeSection::RecentlyAdded => &mut self.recently_added,
+            HomeSection::RecentlyFinished => &mut self.recently_finished,
+        }
+    }
 }
 
 
@@ -326,15 +421,29 @@ pub struct RefreshRatePair {
 #[serde(default, rename_all = "kebab-case")]
 pub struct ReaderSettings {
     pub finished: FinishedAction,
+    // Stop at the end of each chapter: the next page turn leads to the following chapter.
+    pub pause_at_chapter_end: bool,
     pub south_east_corner: SouthEastCornerAction,
     pub bottom_right_gesture: BottomRightGestureAction,
+    pub north_strip: NorthStripAction,
     pub south_strip: SouthStripAction,
     pub west_strip: WestStripAction,
     pub east_strip: EastStripAction,
+    pub home_button: HomeButtonAction,
+    pub cross_gesture: CrossGestureAction,
+    pub hold_button: HoldButtonAction,
+    // The number of pages turned by holding a page turn button.
+    pub hold_button_pages: usize,
+    // Open the table of contents when the chapter label of the bottom bar is tapped.
+    pub chapter_label_toc: bool,
     pub strip_width: f32,
     pub corner_width: f32,
+    // Distance, in millimeters, around the visible bars within which the taps are ignored.
+    pub bar_tap_buffer: f32,
     pub font_path: String,
     pub font_family: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub favorite_font_families: Vec<String>,
     pub font_size: f32,
     pub min_font_size: f32,
     pub max_font_size: f32,
@@ -343,11 +452,116 @@ pub struct ReaderSettings {
     pub min_margin_width: i32,
     pub max_margin_width: i32,
     pub line_height: f32,
+    // Bounds of the scaling factor of the custom zoom mode.
+    pub min_zoom: f32,
+    pub max_zoom: f32,
     pub continuous_fit_to_width: bool,
+    pub scroll_bar: bool,
+    // Darken the edge of the screen when the scrolling stops at either end of the document.
+    pub scroll_edge_cue: bool,
     pub ignore_document_css: bool,
+    pub force_chapter_breaks: bool,
+    // Override the text alignment of the document's style sheets.
+    pub force_text_align: bool,
+    // Turn the URLs and the page references of the text documents into links.
+    pub linkify_text: bool,
+    pub reduced_motion: bool,
+    pub selection_granularity: SelectionGranularity,
+    // Maximum distance, in pixels at 300 DPI, between a touch point and a selectable word.
+    pub selection_hit_radius: f32,
+    pub annotation_style: AnnotationStyle,
+    // Holding an annotation that has a note opens the note's editor instead of the annotation menu.
+    pub edit_note_on_hold: bool,
+    // Show the contiguous highlights as a single entry of the list of annotations.
+    pub group_highlights: bool,
+    // The format of the quotes copied to the clippings or shared, see `format_quote`.
+    pub quote_template: String,
+    pub pixmap_cache_size: usize,
+    // Fully refresh the screen after a contrast change, instead of following the refresh rate.
+    pub contrast_full_refresh: bool,
+    // The initial state of the *Auto Contrast* entry of the book menu.
+    pub auto_contrast: bool,
+    // Draw a band across the page to guide the eyes along the lines.
+    pub reading_ruler: bool,
+    // The position of the reading ruler, as a fraction of the page's height.
+    pub reading_ruler_position: f32,
+    // Bookmark the current page when the book is closed.
+    pub auto_bookmark: bool,
+    // The number of automatic bookmarks kept per book.
+    pub max_auto_bookmarks: usize,
+    // Save the annotations of the books whose annotations changed when they're closed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations_export: Option<AnnotationsFormat>,
+    // Skip the blank pages of fixed-layout documents when turning pages.
+    pub skip_blank_pages: bool,
+    // The maximum share of the pixels of a blank page that differ from the background.
+    pub blank_page_threshold: f32,
+    // The entries of the selection menu, in order.
+    pub selection_menu: Vec<SelectionMenuEntry>,
+    // The minimum time, in milliseconds, between two taps that turn pages.
+    pub min_tap_interval: u64,
+    // Notify the hooks of each page turn, so that they can play a cue.
+    pub page_turn_cue: bool,
+    pub search_scope: SearchScope,
+    pub immersive: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_indicator: Option<PageIndicatorCorner>,
     pub dithered_kinds: FxHashSet<String>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub zoom_modes: HashMap<String, ZoomMode>,
     pub paragraph_breaker: ParagraphBreakerSettings,
     pub refresh_rate: RefreshRateSettings,
+    pub top_bar: TopBarSettings,
+    pub page_separator: PageSeparatorSettings,
+    pub flashcards: FlashcardsSettings,
+    pub vertical_swipe: VerticalSwipeSettings,
+}
+
+// The action of the vertical swipes, for each zoom mode.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct VerticalSwipeSettings {
+    pub fit_to_page: VerticalSwipeAction,
+    pub fit_to_width: VerticalSwipeAction,
+    pub custom: VerticalSwipeAction,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VerticalSwipeAction {
+    Scroll,
+    Chapter,
+}
+
+// The separation between consecutive pages in fit-to-width mode.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PageSeparatorSettings {
+    // Height of the gap, in millimeters.
+    pub gap: f32,
+    // Draw a line in the middle of the gap.
+    pub line: bool,
+}
+
+// The format of the flashcards exported from the annotations.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct FlashcardsSettings {
+    // The field separator.
+    pub separator: char,
+    // Put the note on the front of the card, and the annotated text on the back.
+    pub note_first: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct TopBarSettings {
+    pub title: bool,
+    pub author: bool,
+    pub clock: bool,
+    pub battery: bool,
+    pub battery_percentage: bool,
+    pub page: bool,
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
@@ -357,6 +571,20 @@ pub struct ParagraphBreakerSettings {
     pub stretch_tolerance: f32,
 }
 
+// Warms up the frontlight and softens the contrast of the pages.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct WarmReadingSettings {
+    pub enabled: bool,
+    // Added to the frontlight's warmth, from 0 to 100.
+    pub warmth: f32,
+    // Gray level of the paper, the ink is the complementary level.
+    pub paper: u8,
+    // Warmth of the frontlight before the mode was enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_warmth: Option<f32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct BatterySettings {
@@ -364,6 +592,48 @@ pub struct BatterySettings {
     pub power_off: f32,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SelectionMenuEntry {
+    Highlight,
+    AddNote,
+    Define,
+    Translate,
+    Search,
+    CopyToClippings,
+    Share,
+    Speak,
+    GoTo,
+    AdjustSelection,
+    Separator,
+}
+
+pub const DEFAULT_SELECTION_MENU: [SelectionMenuEntry; 11] = [
+    SelectionMenuEntry::Highlight,
+    SelectionMenuEntry::AddNote,
+    SelectionMenuEntry::Separator,
+    SelectionMenuEntry::Define,
+    SelectionMenuEntry::Translate,
+    SelectionMenuEntry::Search,
+    SelectionMenuEntry::CopyToClippings,
+    SelectionMenuEntry::Share,
+    SelectionMenuEntry::GoTo,
+    SelectionMenuEntry::Separator,
+    SelectionMenuEntry::AdjustSelection,
+];
+
+// The part of the book covered by the searches.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SearchScope {
+    // The whole book, starting at the current page.
+    CurrentPage,
+    // The whole book, from its start (or its end, for backward searches).
+    Book,
+    // The current chapter, starting at the current page.
+    Chapter,
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum FinishedAction {
@@ -385,6 +655,14 @@ pub enum BottomRightGestureAction {
     ToggleInverted,
 }
 
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NorthStripAction {
+    ToggleBars,
+    // Only the top bar: the title, the clock and the battery.
+    ToggleTopBar,
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum SouthStripAction {
@@ -400,6 +678,69 @@ pub enum EastStripAction {
     None,
 }
 
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HomeButtonAction {
+    Quit,
+    ToggleBars,
+    Bookmark,
+    None,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrossGestureAction {
+    Quit,
+    // Bookmark the current page before quitting.
+    BookmarkAndQuit,
+    ToggleBars,
+    None,
+}
+
+// The format of the annotations exported when a book is closed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnnotationsFormat {
+    // The format of the annotations' import file.
+    Json,
+    Markdown,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HoldButtonAction {
+    // Go to the previous or next chapter.
+    Chapter,
+    // Turn `hold-button-pages` pages backward or forward.
+    Pages,
+    GoToPage,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SelectionGranularity {
+    Word,
+    Sentence,
+    Paragraph,
+}
+
+// How the highlights and the annotations are drawn over the text.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnnotationStyle {
+    Shade,
+    Underline,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PageIndicatorCorner {
+    NorthWest,
+    NorthEast,
+    SouthWest,
+    SouthEast,
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum WestStripAction {
@@ -424,6 +765,11 @@ impl Default for HomeSettings {
             navigation_bar: true,
             max_levels: 3,
             max_trash_size: 32 * (1 << 20),
+            max_trash_age: None,
+            recently_added: HomeSectionSettings::default(),
+            recently_finished: HomeSectionSettings::default(),
+            max_section_items: 3,
+            search_as_you_type: true,
         }
     }
 }
@@ -441,15 +787,24 @@ impl Default for ReaderSettings {
     fn default() -> Self {
         ReaderSettings {
             finished: FinishedAction::Close,
+            pause_at_chapter_end: false,
             south_east_corner: SouthEastCornerAction::GoToPage,
             bottom_right_gesture: BottomRightGestureAction::ToggleDithered,
+            north_strip: NorthStripAction::ToggleBars,
             south_strip: SouthStripAction::ToggleBars,
             west_strip: WestStripAction::PreviousPage,
             east_strip: EastStripAction::NextPage,
+            home_button: HomeButtonAction::Quit,
+            cross_gesture: CrossGestureAction::Quit,
+            hold_button: HoldButtonAction::Chapter,
+            hold_button_pages: 10,
+            chapter_label_toc: true,
             strip_width: 0.6,
             corner_width: 0.4,
+            bar_tap_buffer: 1.5,
             font_path: DEFAULT_FONT_PATH.to_string(),
             font_family: DEFAULT_FONT_FAMILY.to_string(),
+            favorite_font_families: Vec::new(),
             font_size: DEFAULT_FONT_SIZE,
             min_font_size: DEFAULT_FONT_SIZE / 2.0,
             max_font_size: 3.0 * DEFAULT_FONT_SIZE / 2.0,
@@ -458,11 +813,98 @@ impl Default for ReaderSettings {
             min_margin_width: DEFAULT_MARGIN_WIDTH.saturating_sub(8),
             max_margin_width: DEFAULT_MARGIN_WIDTH.saturating_add(2),
             line_height: DEFAULT_LINE_HEIGHT,
+            min_zoom: 0.25,
+            max_zoom: 8.0,
             continuous_fit_to_width: true,
+            scroll_bar: false,
+            scroll_edge_cue: false,
             ignore_document_css: false,
+            force_chapter_breaks: false,
+            force_text_align: false,
+            linkify_text: true,
+            reduced_motion: false,
+            selection_granularity: SelectionGranularity::Word,
+            selection_hit_radius: 24.0,
+            annotation_style: AnnotationStyle::Shade,
+            edit_note_on_hold: false,
+            group_highlights: false,
+            quote_template: "“{text}” — {author}, {title} (p. {page})".to_string(),
+            pixmap_cache_size: 3,
+            contrast_full_refresh: true,
+            auto_contrast: false,
+            reading_ruler: false,
+            reading_ruler_position: 0.33,
+            auto_bookmark: false,
+            max_auto_bookmarks: 3,
+            annotations_export: None,
+            skip_blank_pages: false,
+            blank_page_threshold: 0.005,
+            selection_menu: DEFAULT_SELECTION_MENU.to_vec(),
+            min_tap_interval: 150,
+            page_turn_cue: false,
+            search_scope: SearchScope::CurrentPage,
+            immersive: false,
+            page_indicator: None,
             dithered_kinds: ["cbz", "png", "jpg", "jpeg"].iter().map(|k| k.to_string()).collect(),
+            zoom_modes: HashMap::new(),
             paragraph_breaker: ParagraphBreakerSettings::default(),
             refresh_rate: RefreshRateSettings::default(),
+            top_bar: TopBarSettings::default(),
+            page_separator: PageSeparatorSettings::default(),
+            flashcards: FlashcardsSettings::default(),
+            vertical_swipe: VerticalSwipeSettings::default(),
+        }
+    }
+}
+
+impl Default for TopBarSettings {
+    fn default() -> Self {
+        TopBarSettings {
+            title: true,
+            author: false,
+            clock: true,
+            battery: true,
+            battery_percentage: false,
+            page: false,
+        }
+    }
+}
+
+impl Default for PageSeparatorSettings {
+    fn default() -> Self {
+        PageSeparatorSettings {
+            gap: 0.0,
+            line: false,
+        }
+    }
+}
+
+impl Default for VerticalSwipeSettings {
+    fn default() -> Self {
+        VerticalSwipeSettings {
+            fit_to_page: VerticalSwipeAction::Scroll,
+            fit_to_width: VerticalSwipeAction::Scroll,
+            custom: VerticalSwipeAction::Scroll,
+        }
+    }
+}
+
+impl Default for FlashcardsSettings {
+    fn default() -> Self {
+        FlashcardsSettings {
+            separator: ',',
+            note_first: false,
+        }
+    }
+}
+
+impl Default for WarmReadingSettings {
+    fn default() -> Self {
+        WarmReadingSettings {
+            enabled: false,
+            warmth: 20.0,
+            paper: 0xF4,
+            previous_warmth: None,
         }
     }
 }
@@ -476,6 +918,8 @@ impl Default for ImportSettings {
             metadata_kinds: ["epub", "pdf", "djvu"].iter().map(|k| k.to_string()).collect(),
             allowed_kinds: ["pdf", "djvu", "epub", "fb2", "txt",
                             "xps", "oxps", "mobi", "cbz"].iter().map(|k| k.to_string()).collect(),
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
         }
     }
 }
@@ -504,6 +948,7 @@ impl Default for Settings {
                             sort_method: Some(SortMethod::Added),
                             first_column: Some(FirstColumn::TitleAndAuthor),
                             second_column: Some(SecondColumn::Progress),
+                            events: Vec::new(),
                         }
                     ],
                     .. Default::default()
@@ -526,6 +971,7 @@ impl Default for Settings {
             ],
             external_urls_queue: Some(PathBuf::from("bin/article_fetcher/urls.txt")),
             keyboard_layout: "English".to_string(),
+            custom_keyboard_layouts: None,
             frontlight: true,
             wifi: false,
             inverted: false,
@@ -550,6 +996,7 @@ impl Default for Settings {
             calculator: CalculatorSettings::default(),
             battery: BatterySettings::default(),
             frontlight_levels: LightLevels::default(),
+            warm_reading: WarmReadingSettings::default(),
             frontlight_presets: Vec::new(),
         }
     }
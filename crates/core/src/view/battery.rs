@@ -8,7 +8,7 @@ use super::icon::ICONS_PIXMAPS;
 use crate::gesture::GestureEvent;
 use crate::battery::Status;
 use crate::unit::scale_by_dpi;
-use crate::font::Fonts;
+use crate::font::{Fonts, font_from_style, NORMAL_STYLE};
 use crate::context::Context;
 
 const BUMP_HEIGHT: f32 = 5.0 * THICKNESS_LARGE;
@@ -22,6 +22,7 @@ pub struct Battery {
     children: Vec<Box<dyn View>>,
     status: Status,
     capacity: f32,
+    percentage: bool,
 }
 
 impl Battery {
@@ -32,9 +33,20 @@ impl Battery {
             children: Vec::new(),
             capacity,
             status,
+            percentage: false,
         }
     }
 
+    pub fn percentage(mut self, percentage: bool) -> Battery {
+        self.percentage = percentage;
+        self
+    }
+
+    pub fn percentage_width(fonts: &mut Fonts) -> i32 {
+        let font = font_from_style(fonts, &NORMAL_STYLE, CURRENT_DEVICE.dpi);
+        font.plan("100%", None, None).width + font.em() as i32 / 2
+    }
+
     pub fn update(&mut self, rq: &mut RenderQueue, context: &mut Context) {
         self.capacity = context.battery.capacity().map_or(self.capacity, |v| v[0]);
         self.status = context.battery.status().map_or(self.status, |v| v[0]);
@@ -57,7 +69,7 @@ impl View for Battery {
         }
     }
 
-    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, _fonts: &mut Fonts) {
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, fonts: &mut Fonts) {
         let dpi = CURRENT_DEVICE.dpi;
 
         let border_radius = scale_by_dpi(BORDER_RADIUS_SMALL, dpi) as i32;
@@ -71,14 +83,29 @@ impl View for Battery {
 
         let edge_width = scale_by_dpi(THICKNESS_MEDIUM, dpi) as i32;
 
-        let dx = (self.rect.width() as i32 - (batt_width + bump_width - border_thickness)) / 2;
-        let dy = (self.rect.height() as i32 - batt_height) / 2;
+        let icon_rect = if self.percentage {
+            rect![self.rect.max.x - self.rect.height() as i32, self.rect.min.y,
+                  self.rect.max.x, self.rect.max.y]
+        } else {
+            self.rect
+        };
 
-        let mut pt = self.rect.min + pt!(dx, dy);
+        let dx = (icon_rect.width() as i32 - (batt_width + bump_width - border_thickness)) / 2;
+        let dy = (icon_rect.height() as i32 - batt_height) / 2;
+
+        let mut pt = icon_rect.min + pt!(dx, dy);
         let batt_rect = rect![pt, pt + pt!(batt_width, batt_height)];
 
         fb.draw_rectangle(&self.rect, WHITE);
 
+        if self.percentage {
+            let font = font_from_style(fonts, &NORMAL_STYLE, dpi);
+            let plan = font.plan(format!("{:.0}%", self.capacity.clamp(0.0, 100.0)), None, None);
+            let text_dy = (self.rect.height() as i32 - font.x_heights.0 as i32) / 2;
+            let text_pt = pt!(icon_rect.min.x - plan.width, self.rect.max.y - text_dy);
+            font.render(fb, BLACK, &plan, text_pt);
+        }
+
         let max_fill_width = batt_width - 2 * border_thickness;
         let fill_width = (self.capacity.clamp(0.0, 100.0) / 100.0 * max_fill_width as f32) as i32;
         let fill_height = batt_height - 2 * border_thickness;
@@ -102,7 +129,7 @@ impl View for Battery {
                                                             color: BLACK },
                                               &WHITE);
 
-        pt = self.rect.min + pt!(dx, dy) + pt!(border_thickness);
+        pt = icon_rect.min + pt!(dx, dy) + pt!(border_thickness);
 
         if self.status.is_wired() {
             let name = if self.status == Status::Charging { "plug" } else { "check_mark-small" };
@@ -37,6 +37,13 @@ impl Button {
         self.disabled = value;
         self
     }
+
+    pub fn update(&mut self, text: String, rq: &mut RenderQueue) {
+        if self.text != text {
+            self.text = text;
+            rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+        }
+    }
 }
 
 impl View for Button {
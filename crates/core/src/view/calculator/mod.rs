@@ -17,6 +17,7 @@ use self::input_bar::InputBar;
 use self::bottom_bar::BottomBar;
 use self::code_area::CodeArea;
 use crate::view::top_bar::TopBar;
+use crate::settings::TopBarSettings;
 use crate::view::keyboard::Keyboard;
 use crate::view::menu::{Menu, MenuKind};
 use crate::view::common::{locate_by_id};
@@ -127,6 +128,7 @@ impl Calculator {
                                         rect.max.x, rect.min.y + side - small_thickness],
                                   Event::Back,
                                   "Calculator".to_string(),
+                                  &TopBarSettings::default(),
                                   context);
         children.push(Box::new(top_bar) as Box<dyn View>);
 
@@ -1,13 +1,13 @@
 use std::sync::mpsc;
 use chrono::Local;
 use crate::device::CURRENT_DEVICE;
-use crate::settings::{ButtonScheme, RotationLock};
+use crate::settings::{ButtonScheme, RotationLock, BundleImportMode};
 use crate::framebuffer::UpdateMode;
 use crate::geom::{Point, Rectangle};
 use super::{View, RenderQueue, RenderData, ViewId, AppCmd, EntryId, EntryKind};
 use super::menu::{Menu, MenuKind};
 use super::notification::Notification;
-use crate::context::Context;
+use crate::context::{Context, SETTINGS_BUNDLE_FILENAME};
 
 pub fn shift(view: &mut dyn View, delta: Point) {
     *view.rect_mut() += delta;
@@ -109,6 +109,16 @@ pub fn toggle_main_menu(view: &mut dyn View, rect: Rectangle, enable: Option<boo
                                EntryKind::SubMenu("Applications".to_string(), apps),
                                EntryKind::Separator];
 
+        let mut bundle = vec![EntryKind::Command("Export".to_string(),
+                                                 EntryId::ExportSettings)];
+        if context.library.home.join(SETTINGS_BUNDLE_FILENAME).exists() {
+            bundle.push(EntryKind::Command("Import and Merge".to_string(),
+                                           EntryId::ImportSettings(BundleImportMode::Merge)));
+            bundle.push(EntryKind::Command("Import and Replace".to_string(),
+                                           EntryId::ImportSettings(BundleImportMode::Replace)));
+        }
+        entries.insert(entries.len() - 1, EntryKind::SubMenu("Settings".to_string(), bundle));
+
         entries.push(EntryKind::Command("Reboot".to_string(), EntryId::Reboot));
         entries.push(EntryKind::Command("Quit".to_string(), EntryId::Quit));
 
@@ -232,6 +242,7 @@ pub fn toggle_keyboard_layout_menu(view: &mut dyn View, rect: Rectangle, enable:
         if let Some(false) = enable {
             return;
         }
+        context.load_custom_keyboard_layouts();
         let entries = context.keyboard_layouts.keys()
                              .map(|s| EntryKind::Command(s.to_string(),
                                                          EntryId::SetKeyboardLayout(s.to_string())))
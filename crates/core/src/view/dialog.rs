@@ -26,6 +26,10 @@ pub struct Dialog {
 
 impl Dialog {
     pub fn new(view_id: ViewId, event: Option<Event>, text: String, context: &mut Context) -> Dialog {
+        Dialog::with_validate_label(view_id, event, text, LABEL_VALIDATE, context)
+    }
+
+    pub fn with_validate_label(view_id: ViewId, event: Option<Event>, text: String, validate_label: &str, context: &mut Context) -> Dialog {
         let id = ID_FEEDER.next();
         let mut children = Vec::new();
         let dpi = CURRENT_DEVICE.dpi;
@@ -60,7 +64,7 @@ impl Dialog {
         children.push(Box::new(label) as Box<dyn View>);
 
         let plan_cancel = event.as_ref().map(|_| font.plan(LABEL_CANCEL, Some(max_button_width), None));
-        let plan_validate = font.plan(LABEL_VALIDATE, Some(max_button_width), None);
+        let plan_validate = font.plan(validate_label, Some(max_button_width), None);
 
         let button_width = plan_validate.width.max(plan_cancel.map_or(0, |p| p.width)) as i32 + padding;
 
@@ -77,7 +81,7 @@ impl Dialog {
                                   rect.max.y - button_height - padding,
                                   rect.max.x - padding,
                                   rect.max.y - padding];
-        let button_validate = Button::new(rect_validate, Event::Validate, LABEL_VALIDATE.to_string()); 
+        let button_validate = Button::new(rect_validate, Event::Validate, validate_label.to_string()); 
         children.push(Box::new(button_validate) as Box<dyn View>);
 
         Dialog {
@@ -24,6 +24,7 @@ use crate::view::keyboard::Keyboard;
 use crate::view::menu::{Menu, MenuKind};
 use crate::view::search_bar::SearchBar;
 use crate::view::top_bar::TopBar;
+use crate::settings::TopBarSettings;
 use self::bottom_bar::BottomBar;
 
 const VIEWER_STYLESHEET: &str = "css/dictionary.css";
@@ -100,6 +101,7 @@ impl Dictionary {
                                         rect.max.x, rect.min.y + small_height - small_thickness],
                                   Event::Back,
                                   "Dictionary".to_string(),
+                                  &TopBarSettings::default(),
                                   context);
         children.push(Box::new(top_bar) as Box<dyn View>);
 
@@ -19,6 +19,12 @@ use crate::context::Context;
 
 const LABEL_SAVE: &str = "Save";
 const LABEL_GUESS: &str = "Guess";
+const LABEL_WARM_ON: &str = "Warm: On";
+const LABEL_WARM_OFF: &str = "Warm: Off";
+
+fn warm_label(enabled: bool) -> String {
+    if enabled { LABEL_WARM_ON } else { LABEL_WARM_OFF }.to_string()
+}
 
 pub struct FrontlightWindow {
     id: Id,
@@ -139,12 +145,20 @@ impl FrontlightWindow {
 
         let max_label_width = {
             let font = font_from_style(fonts, &NORMAL_STYLE, dpi);
-            [LABEL_SAVE, LABEL_GUESS].iter().map(|t| font.plan(t, None, None).width)
-                                                         .max().unwrap() as i32
+            [LABEL_SAVE, LABEL_GUESS, LABEL_WARM_ON, LABEL_WARM_OFF].iter().map(|t| font.plan(t, None, None).width)
+                                                                           .max().unwrap()
         };
 
         let button_height = 4 * x_height;
 
+        let button_warm = Button::new(rect![rect.center().x - padding - max_label_width / 2,
+                                            button_y + small_height - button_height,
+                                            rect.center().x + padding + max_label_width / 2,
+                                            button_y + small_height],
+                                      Event::Select(EntryId::ToggleWarmReading),
+                                      warm_label(context.settings.warm_reading.enabled));
+        children.push(Box::new(button_warm) as Box<dyn View>);
+
         let button_save = Button::new(rect![rect.min.x + 3 * padding,
                                             button_y + small_height - button_height,
                                             rect.min.x + 5 * padding + max_label_width,
@@ -291,6 +305,25 @@ impl View for FrontlightWindow {
                 }
                 true
             },
+            Event::Select(EntryId::ToggleWarmReading) => {
+                let enable = !context.settings.warm_reading.enabled;
+                context.set_warm_reading(enable);
+                if CURRENT_DEVICE.has_natural_light() {
+                    let warmth = context.frontlight.levels().warmth;
+                    if let Some(slider_warmth) = self.child_mut(5).downcast_mut::<Slider>() {
+                        slider_warmth.update(warmth, rq);
+                    }
+                }
+                let index = if context.settings.frontlight_presets.is_empty() {
+                    self.len() - 3
+                } else {
+                    self.len() - 4
+                };
+                if let Some(button_warm) = self.child_mut(index).downcast_mut::<Button>() {
+                    button_warm.update(warm_label(enable), rq);
+                }
+                false
+            },
             Event::LoadPreset(index) => {
                 let frontlight_levels = context.settings.frontlight_presets[index].frontlight_levels;
                 self.set_frontlight_levels(frontlight_levels, rq, context);
@@ -399,12 +432,19 @@ impl View for FrontlightWindow {
 
         let max_label_width = {
             let font = font_from_style(&mut context.fonts, &NORMAL_STYLE, dpi);
-            [LABEL_SAVE, LABEL_GUESS].iter().map(|t| font.plan(t, None, None).width)
-                                                         .max().unwrap() as i32
+            [LABEL_SAVE, LABEL_GUESS, LABEL_WARM_ON, LABEL_WARM_OFF].iter().map(|t| font.plan(t, None, None).width)
+                                                                           .max().unwrap()
         };
 
         let button_height = 4 * x_height;
 
+        self.children[index].resize(rect![rect.center().x - padding - max_label_width / 2,
+                                          button_y + small_height - button_height,
+                                          rect.center().x + padding + max_label_width / 2,
+                                          button_y + small_height],
+                                    hub, rq, context);
+        index += 1;
+
         self.children[index].resize(rect![rect.min.x + 3 * padding,
                                           button_y + small_height - button_height,
                                           rect.min.x + 5 * padding + max_label_width,
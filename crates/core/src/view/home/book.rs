@@ -1,58 +1,178 @@
 use std::path::PathBuf;
 use crate::device::CURRENT_DEVICE;
-use crate::framebuffer::{Framebuffer, UpdateMode};
+use crate::framebuffer::{Framebuffer, UpdateMode, Pixmap};
 use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData, THICKNESS_SMALL};
 use crate::font::{MD_TITLE, MD_AUTHOR, MD_YEAR, MD_KIND, MD_SIZE};
-use crate::color::{BLACK, WHITE, READING_PROGRESS};
-use crate::color::{TEXT_NORMAL, TEXT_INVERTED_HARD};
+use crate::color::{Color, BLACK, WHITE, READING_PROGRESS};
+use crate::color::{TEXT_NORMAL, TEXT_BUMP_LARGE, TEXT_INVERTED_HARD};
 use crate::gesture::GestureEvent;
 use crate::metadata::{Info, Status};
-use crate::settings::{FirstColumn, SecondColumn};
+use crate::settings::{ShelfLayout, FirstColumn, SecondColumn};
 use crate::unit::scale_by_dpi;
 use crate::document::{HumanSize, Location, Document};
 use crate::document::pdf::PdfOpener;
 use crate::font::{Fonts, font_from_style};
-use crate::geom::{Rectangle, CornerSpec, BorderSpec, halves};
+use crate::geom::{Point, Rectangle, CornerSpec, BorderSpec, halves};
 use crate::context::Context;
 
 const PROGRESS_HEIGHT: f32 = 13.0;
 
+// How the books of a shelf are displayed.
+#[derive(Debug, Copy, Clone)]
+pub struct BookDisplay {
+    pub layout: ShelfLayout,
+    pub first_column: FirstColumn,
+    pub second_column: SecondColumn,
+}
+
 pub struct Book {
     id: Id,
     rect: Rectangle,
     children: Vec<Box<dyn View>>,
     info: Info,
     index: usize,
-    first_column: FirstColumn,
-    second_column: SecondColumn,
+    display: BookDisplay,
     preview_path: Option<PathBuf>,
+    selected: Option<bool>,
     active: bool,
 }
 
 impl Book {
-    pub fn new(rect: Rectangle, info: Info, index: usize,
-               first_column: FirstColumn, second_column: SecondColumn, preview_path: Option<PathBuf>) -> Book {
+    pub fn new(rect: Rectangle, info: Info, index: usize, display: BookDisplay,
+               preview_path: Option<PathBuf>, selected: Option<bool>) -> Book {
         Book {
             id: ID_FEEDER.next(),
             rect,
             children: Vec::new(),
             info,
             index,
-            first_column,
-            second_column,
+            display,
             preview_path,
+            selected,
             active: false,
         }
     }
+
+    fn title_and_author(&self) -> (String, &str) {
+        if self.display.first_column == FirstColumn::TitleAndAuthor {
+            (self.info.title(), self.info.author.as_str())
+        } else {
+            let filename = self.info.file.path.file_stem()
+                               .map(|v| v.to_string_lossy().into_owned())
+                               .unwrap_or_default();
+            (filename, "")
+        }
+    }
+
+    // Loads the preview, scaled to fit in the given dimensions.
+    fn preview_pixmap(&self, tw: i32, th: i32) -> Option<Pixmap> {
+        let preview_path = self.preview_path.as_ref().filter(|path| path.exists())?;
+        PdfOpener::new().and_then(|opener| {
+            opener.open(preview_path)
+        }).and_then(|mut doc| {
+            doc.dims(0).and_then(|dims| {
+                let scale = (tw as f32 / dims.0).min(th as f32 / dims.1);
+                doc.pixmap(Location::Exact(0), scale, CURRENT_DEVICE.color_samples())
+            })
+        }).map(|(pixmap, _)| pixmap)
+    }
+
+    // Draws the reading progress of the book, centered on *center*, as a bar of the given width,
+    // or, for the new and finished books, as an empty or full disk.
+    fn draw_progress(&self, fb: &mut dyn Framebuffer, center: Point, progress_width: i32) {
+        let dpi = CURRENT_DEVICE.dpi;
+        let progress_height = scale_by_dpi(PROGRESS_HEIGHT, dpi) as i32;
+        let thickness = scale_by_dpi(THICKNESS_SMALL, dpi) as u16;
+        let (small_radius, big_radius) = halves(progress_height);
+        match self.info.status() {
+            Status::New | Status::Finished => {
+                let color = if self.info.reader.is_none() { WHITE } else { BLACK };
+                fb.draw_rounded_rectangle_with_border(&rect![center - pt!(small_radius, small_radius),
+                                                             center + pt!(big_radius, big_radius)],
+                                                      &CornerSpec::Uniform(small_radius),
+                                                      &BorderSpec { thickness, color: BLACK },
+                                                      &color);
+            },
+            Status::Reading(progress) => {
+                let (small_progress_width, big_progress_width) = halves(progress_width);
+                let x_offset = center.x - progress_width / 2 +
+                               (progress_width as f32 * progress.min(1.0)) as i32;
+                fb.draw_rounded_rectangle_with_border(&rect![center - pt!(small_progress_width, small_radius),
+                                                             center + pt!(big_progress_width, big_radius)],
+                                                      &CornerSpec::Uniform(small_radius),
+                                                      &BorderSpec { thickness, color: BLACK },
+                                                      &|x, _| if x < x_offset { READING_PROGRESS } else { WHITE });
+            }
+        }
+    }
+
+    // Draws the book as a cell of the grid: the cover, with the title and the progress below it.
+    fn render_cell(&self, fb: &mut dyn Framebuffer, scheme: [Color; 3], fonts: &mut Fonts) {
+        let dpi = CURRENT_DEVICE.dpi;
+        let font = font_from_style(fonts, &MD_TITLE, dpi);
+        let x_height = font.x_heights.0 as i32;
+        let padding = font.em() as i32;
+        let progress_height = scale_by_dpi(PROGRESS_HEIGHT, dpi) as i32;
+        let label_height = 4 * x_height + progress_height;
+        let tw = self.rect.width() as i32 - 2 * padding;
+        let th = self.rect.height() as i32 - label_height - padding;
+
+        // Cover
+        let cover_rect = if let Some(pixmap) = self.preview_pixmap(tw, th) {
+            let dx = (tw - pixmap.width as i32) / 2;
+            let dy = th - pixmap.height as i32;
+            let pt = pt!(self.rect.min.x + padding + dx,
+                         self.rect.min.y + padding + dy);
+            fb.draw_pixmap(&pixmap, pt);
+            let rect = pixmap.rect() + pt;
+            if fb.inverted() {
+                fb.invert_region(&rect);
+            }
+            rect
+        } else {
+            let cw = (3 * th / 4).min(tw);
+            let rect = rect![self.rect.min.x + padding + (tw - cw) / 2, self.rect.min.y + padding,
+                             self.rect.min.x + padding + (tw - cw) / 2 + cw, self.rect.min.y + padding + th];
+            let thickness = scale_by_dpi(THICKNESS_SMALL, dpi) as u16;
+            fb.draw_rectangle_outline(&rect, &BorderSpec { thickness, color: scheme[1] });
+            let kind = self.info.file.kind.to_uppercase();
+            let font = font_from_style(fonts, &MD_KIND, dpi);
+            let plan = font.plan(&kind, Some(rect.width() as i32), None);
+            let pt = pt!(rect.min.x + (rect.width() as i32 - plan.width) / 2,
+                         rect.min.y + (rect.height() as i32 + font.x_heights.1 as i32) / 2);
+            font.render(fb, scheme[1], &plan, pt);
+            rect
+        };
+
+        // Title
+        let (title, _) = self.title_and_author();
+        let font = font_from_style(fonts, &MD_TITLE, dpi);
+        let plan = font.plan(&title, Some(self.rect.width() as i32 - padding), None);
+        let pt = pt!(self.rect.min.x + (self.rect.width() as i32 - plan.width) / 2,
+                     self.rect.min.y + padding + th + 2 * x_height);
+        font.render(fb, scheme[1], &plan, pt);
+
+        // Progress
+        let center = pt!(self.rect.min.x + self.rect.width() as i32 / 2,
+                         self.rect.max.y - x_height - progress_height / 2);
+        self.draw_progress(fb, center, cover_rect.width() as i32 * 2 / 3);
+    }
 }
 
 impl View for Book {
     fn handle_event(&mut self, evt: &Event, hub: &Hub, bus: &mut Bus, rq: &mut RenderQueue, _context: &mut Context) -> bool {
         match *evt {
+            Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) && self.selected.is_some() => {
+                let selected = !self.selected.unwrap_or(false);
+                self.selected = Some(selected);
+                rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+                bus.push_back(Event::SelectBook(self.info.file.path.clone(), selected));
+                true
+            },
             Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) => {
                 self.active = true;
                 rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
-                hub.send(Event::Open(Box::new(self.info.clone()))).ok();
+                hub.send(Event::Open(Box::new(self.info.clone()), None)).ok();
                 true
             },
             Event::Gesture(GestureEvent::HoldFingerShort(center, ..)) if self.rect.includes(center) => {
@@ -87,20 +207,20 @@ impl View for Book {
 
         let scheme = if self.active {
             TEXT_INVERTED_HARD
+        } else if self.selected == Some(true) {
+            TEXT_BUMP_LARGE
         } else {
             TEXT_NORMAL
         };
 
         fb.draw_rectangle(&self.rect, scheme[0]);
 
-        let (title, author) = if self.first_column == FirstColumn::TitleAndAuthor {
-            (self.info.title(), self.info.author.as_str())
-        } else {
-            let filename = self.info.file.path.file_stem()
-                               .map(|v| v.to_string_lossy().into_owned())
-                               .unwrap_or_default();
-            (filename, "")
-        };
+        if self.display.layout == ShelfLayout::Grid {
+            self.render_cell(fb, scheme, fonts);
+            return;
+        }
+
+        let (title, author) = self.title_and_author();
 
         let year = &self.info.year;
         let file_info = &self.info.file;
@@ -119,28 +239,19 @@ impl View for Book {
         let mut start_x = self.rect.min.x + padding;
 
         // Preview
-        if let Some(preview_path) = self.preview_path.as_ref() {
+        if self.preview_path.is_some() {
             let th = self.rect.height() as i32 - x_height;
             let tw = 3 * th / 4;
 
-            if preview_path.exists() {
-                if let Some((pixmap, _)) = PdfOpener::new().and_then(|opener| {
-                    opener.open(preview_path)
-                }).and_then(|mut doc| {
-                    doc.dims(0).and_then(|dims| {
-                        let scale = (tw as f32 / dims.0).min(th as f32 / dims.1);
-                        doc.pixmap(Location::Exact(0), scale, CURRENT_DEVICE.color_samples())
-                    })
-                }) {
-                    let dx = (tw - pixmap.width as i32) / 2;
-                    let dy = (th - pixmap.height as i32) / 2;
-                    let pt = pt!(self.rect.min.x + padding + dx,
-                                 self.rect.min.y + x_height / 2 + dy);
-                    fb.draw_pixmap(&pixmap, pt);
-                    if fb.inverted() {
-                        let rect = pixmap.rect() + pt;
-                        fb.invert_region(&rect);
-                    }
+            if let Some(pixmap) = self.preview_pixmap(tw, th) {
+                let dx = (tw - pixmap.width as i32) / 2;
+                let dy = (th - pixmap.height as i32) / 2;
+                let pt = pt!(self.rect.min.x + padding + dx,
+                             self.rect.min.y + x_height / 2 + dy);
+                fb.draw_pixmap(&pixmap, pt);
+                if fb.inverted() {
+                    let rect = pixmap.rect() + pt;
+                    fb.invert_region(&rect);
                 }
             }
 
@@ -196,7 +307,7 @@ impl View for Book {
         }
 
         // Year or Progress
-        match self.second_column {
+        match self.display.second_column {
             SecondColumn::Year => {
                 let font = font_from_style(fonts, &MD_YEAR, dpi);
                 let plan = font.plan(year, None, None);
@@ -207,32 +318,9 @@ impl View for Book {
                 font.render(fb, scheme[1], &plan, pt);
             },
             SecondColumn::Progress => {
-                let progress_height = scale_by_dpi(PROGRESS_HEIGHT, dpi) as i32;
-                let thickness = scale_by_dpi(THICKNESS_SMALL, dpi) as u16;
-                let (small_radius, big_radius) = halves(progress_height);
                 let center = pt!(self.rect.min.x + first_width + second_width / 2,
                                  self.rect.min.y + self.rect.height() as i32 / 2);
-                match self.info.status() {
-                    Status::New | Status::Finished => {
-                        let color = if self.info.reader.is_none() { WHITE } else { BLACK };
-                        fb.draw_rounded_rectangle_with_border(&rect![center - pt!(small_radius, small_radius),
-                                                                     center + pt!(big_radius, big_radius)],
-                                                              &CornerSpec::Uniform(small_radius),
-                                                              &BorderSpec { thickness, color: BLACK },
-                                                              &color);
-                    },
-                    Status::Reading(progress) => {
-                        let progress_width = 2 * (second_width - padding) / 3;
-                        let (small_progress_width, big_progress_width) = halves(progress_width);
-                        let x_offset = center.x - progress_width / 2 +
-                                       (progress_width as f32 * progress.min(1.0)) as i32;
-                        fb.draw_rounded_rectangle_with_border(&rect![center - pt!(small_progress_width, small_radius),
-                                                                     center + pt!(big_progress_width, big_radius)],
-                                                              &CornerSpec::Uniform(small_radius),
-                                                              &BorderSpec { thickness, color: BLACK },
-                                                              &|x, _| if x < x_offset { READING_PROGRESS } else { WHITE });
-                    }
-                }
+                self.draw_progress(fb, center, 2 * (second_width - padding) / 3);
             },
         }
 
@@ -79,6 +79,11 @@ impl BottomBar {
         library_label.update(name, count, filter, rq);
     }
 
+    pub fn update_sync_status(&mut self, status: Option<String>, rq: &mut RenderQueue) {
+        let library_label = self.children[1].as_mut().downcast_mut::<LibraryLabel>().unwrap();
+        library_label.set_status(status, rq);
+    }
+
     pub fn update_page_label(&mut self, current_page: usize, pages_count: usize, rq: &mut RenderQueue) {
         let page_label = self.children[2].as_mut().downcast_mut::<PageLabel>().unwrap();
         page_label.update(current_page, pages_count, rq);
@@ -0,0 +1,145 @@
+use crate::device::CURRENT_DEVICE;
+use crate::framebuffer::{Framebuffer, UpdateMode};
+use crate::geom::{Rectangle, CornerSpec, BorderSpec, halves};
+use crate::font::{Fonts, font_from_style, NORMAL_STYLE};
+use crate::color::{BLACK, WHITE, TEXT_NORMAL, PROGRESS_FULL, PROGRESS_EMPTY};
+use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData, ViewId};
+use crate::view::{SMALL_BAR_HEIGHT, THICKNESS_LARGE, THICKNESS_SMALL, BORDER_RADIUS_MEDIUM};
+use crate::gesture::GestureEvent;
+use crate::input::DeviceEvent;
+use crate::unit::scale_by_dpi;
+use crate::context::Context;
+
+const PROGRESS_HEIGHT: f32 = 13.0;
+
+pub struct FetcherProgress {
+    id: Id,
+    rect: Rectangle,
+    children: Vec<Box<dyn View>>,
+    view_id: ViewId,
+    current: usize,
+    total: usize,
+}
+
+impl FetcherProgress {
+    pub fn new(fetcher_id: u32, current: usize, total: usize, context: &mut Context) -> FetcherProgress {
+        let id = ID_FEEDER.next();
+        let rect = FetcherProgress::frame(context);
+
+        FetcherProgress {
+            id,
+            rect,
+            children: Vec::new(),
+            view_id: ViewId::FetcherProgress(fetcher_id),
+            current,
+            total,
+        }
+    }
+
+    fn frame(context: &mut Context) -> Rectangle {
+        let dpi = CURRENT_DEVICE.dpi;
+        let (width, height) = context.display.dims;
+        let small_height = scale_by_dpi(SMALL_BAR_HEIGHT, dpi) as i32;
+
+        let font = font_from_style(&mut context.fonts, &NORMAL_STYLE, dpi);
+        let x_height = font.x_heights.0 as i32;
+        let padding = font.em() as i32;
+
+        let dialog_width = width as i32 - 2 * padding;
+        let dialog_height = 7 * x_height;
+        let dx = padding;
+        let dy = height as i32 - small_height - padding - dialog_height;
+
+        rect![dx, dy,
+              dx + dialog_width, dy + dialog_height]
+    }
+
+    pub fn update(&mut self, current: usize, total: usize, rq: &mut RenderQueue) {
+        if self.current != current || self.total != total {
+            self.current = current;
+            self.total = total;
+            rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+        }
+    }
+}
+
+impl View for FetcherProgress {
+    fn handle_event(&mut self, evt: &Event, _hub: &Hub, _bus: &mut Bus, _rq: &mut RenderQueue, _context: &mut Context) -> bool {
+        match *evt {
+            Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) => true,
+            Event::Gesture(GestureEvent::Swipe { start, .. }) if self.rect.includes(start) => true,
+            Event::Device(DeviceEvent::Finger { position, .. }) if self.rect.includes(position) => true,
+            _ => false,
+        }
+    }
+
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, fonts: &mut Fonts) {
+        let dpi = CURRENT_DEVICE.dpi;
+
+        let border_radius = scale_by_dpi(BORDER_RADIUS_MEDIUM, dpi) as i32;
+        let border_thickness = scale_by_dpi(THICKNESS_LARGE, dpi) as u16;
+        let progress_thickness = scale_by_dpi(THICKNESS_SMALL, dpi) as u16;
+        let progress_height = scale_by_dpi(PROGRESS_HEIGHT, dpi) as i32;
+
+        fb.draw_rounded_rectangle_with_border(&self.rect,
+                                              &CornerSpec::Uniform(border_radius),
+                                              &BorderSpec { thickness: border_thickness,
+                                                            color: BLACK },
+                                              &WHITE);
+
+        let font = font_from_style(fonts, &NORMAL_STYLE, dpi);
+        let padding = font.em() as i32;
+        let x_height = font.x_heights.0 as i32;
+        let plan = font.plan(format!("{}/{}", self.current, self.total), None, None);
+
+        let dy = (self.rect.height() as i32 - x_height) / 2;
+        let pt = pt!(self.rect.max.x - padding - plan.width, self.rect.max.y - dy);
+        font.render(fb, TEXT_NORMAL[1], &plan, pt);
+
+        let (small_padding, big_padding) = halves(self.rect.height() as i32 - progress_height);
+        let bar_rect = rect![self.rect.min.x + padding, self.rect.min.y + small_padding,
+                             self.rect.max.x - 2 * padding - plan.width, self.rect.max.y - big_padding];
+        let progress = if self.total > 0 {
+            (self.current as f32 / self.total as f32).min(1.0)
+        } else {
+            0.0
+        };
+        let x_offset = bar_rect.min.x + (bar_rect.width() as f32 * progress) as i32;
+        let (small_radius, _) = halves(progress_height);
+
+        fb.draw_rounded_rectangle_with_border(&bar_rect,
+                                              &CornerSpec::Uniform(small_radius),
+                                              &BorderSpec { thickness: progress_thickness,
+                                                            color: BLACK },
+                                              &|x, _| if x < x_offset { PROGRESS_FULL }
+                                                      else { PROGRESS_EMPTY });
+    }
+
+    fn resize(&mut self, _rect: Rectangle, _hub: &Hub, _rq: &mut RenderQueue, context: &mut Context) {
+        self.rect = FetcherProgress::frame(context);
+    }
+
+    fn rect(&self) -> &Rectangle {
+        &self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut Rectangle {
+        &mut self.rect
+    }
+
+    fn children(&self) -> &Vec<Box<dyn View>> {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Box<dyn View>> {
+        &mut self.children
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn view_id(&self) -> Option<ViewId> {
+        Some(self.view_id)
+    }
+}
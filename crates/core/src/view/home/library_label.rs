@@ -14,6 +14,8 @@ pub struct LibraryLabel {
     name: String,
     count: usize,
     filter: bool,
+    // The state of the synchronization performed by the hooks.
+    status: Option<String>,
 }
 
 impl LibraryLabel {
@@ -25,6 +27,14 @@ impl LibraryLabel {
             name: name.to_string(),
             count,
             filter,
+            status: None,
+        }
+    }
+
+    pub fn set_status(&mut self, status: Option<String>, rq: &mut RenderQueue) {
+        if self.status != status {
+            self.status = status;
+            rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
         }
     }
 
@@ -62,10 +72,15 @@ impl LibraryLabel {
             }
         };
 
-        if self.count == 0 {
+        let text = if self.count == 0 {
             format!("{} (No {})", self.name, subject)
         } else {
             format!("{} ({} {})", self.name, self.count, subject)
+        };
+
+        match self.status {
+            Some(ref status) => format!("{} · {}", text, status),
+            None => text,
         }
     }
 }
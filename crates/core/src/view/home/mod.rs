@@ -4,27 +4,33 @@ mod navigation_bar;
 mod directories_bar;
 mod directory;
 mod shelf;
+mod section;
 mod book;
 mod bottom_bar;
+mod fetcher_progress;
 
 use std::fs;
 use std::mem;
 use std::thread;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::collections::BTreeSet;
 use std::process::{Command, Child, Stdio};
 use std::io::{BufRead, BufReader};
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
+use chrono::{Local, Duration, NaiveDateTime};
 use rand_core::RngCore;
 use serde_json::{json, Value as JsonValue};
 use anyhow::{Error, format_err};
 use crate::library::Library;
 use crate::framebuffer::{Framebuffer, UpdateMode};
-use crate::metadata::{Info, Metadata, SortMethod, BookQuery, SimpleStatus, sort};
+use crate::metadata::{Info, Metadata, Annotation, SortMethod, BookQuery, SimpleStatus, sort, make_query};
+use crate::metadata::MAX_RATING;
+use crate::document::{notes_as_html, description_as_html};
 use crate::view::{View, Event, Hub, Bus, RenderQueue, RenderData};
 use crate::view::{Id, ID_FEEDER, ViewId, EntryId, EntryKind};
 use crate::view::{SMALL_BAR_HEIGHT, BIG_BAR_HEIGHT, THICKNESS_MEDIUM};
-use crate::settings::{Hook, LibraryMode, FirstColumn, SecondColumn};
+use crate::settings::{Hook, HookEvent, LibraryMode, ShelfLayout, FirstColumn, SecondColumn, TopBarSettings, HomeSection};
 use crate::view::common::{toggle_main_menu, toggle_battery_menu, toggle_clock_menu};
 use crate::view::common::{locate, rlocate, locate_by_id};
 use crate::view::filler::Filler;
@@ -37,8 +43,9 @@ use crate::view::notification::Notification;
 use super::top_bar::TopBar;
 use self::address_bar::AddressBar;
 use self::navigation_bar::NavigationBar;
-use self::shelf::Shelf;
+use self::shelf::{Shelf, ShelfSection};
 use self::bottom_bar::BottomBar;
+use self::fetcher_progress::FetcherProgress;
 use crate::gesture::GestureEvent;
 use crate::geom::{Rectangle, Dir, DiagDir, CycleDir, halves};
 use crate::input::{DeviceEvent, ButtonCode, ButtonStatus};
@@ -50,6 +57,9 @@ use crate::context::Context;
 
 pub const TRASH_DIRNAME: &str = ".trash";
 
+// The delay after the last keystroke before the books are filtered, while typing in the search bar.
+const FILTER_DELAY: std::time::Duration = std::time::Duration::from_millis(300);
+
 #[derive(Debug)]
 pub struct Home {
     id: Id,
@@ -65,7 +75,38 @@ pub struct Home {
     visible_books: Metadata,
     current_directory: PathBuf,
     target_document: Option<PathBuf>,
+    selection: Option<FxHashSet<PathBuf>>,
+    batch_field: Option<BatchField>,
+    // The book edited from the book menu, instead of the selected ones.
+    batch_book: Option<PathBuf>,
+    batch_undo: Vec<PriorValues>,
     background_fetchers: FxHashMap<u32, Fetcher>,
+    // Identifies the last filter scheduled while typing in the search bar.
+    filter_id: u32,
+    sync_status: Option<SyncStatus>,
+}
+
+// The state of the synchronization performed by the hooks.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SyncStatus {
+    Syncing,
+    Synced,
+    Error(String),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum BatchField {
+    Author,
+    Series,
+    Category,
+}
+
+#[derive(Debug)]
+struct PriorValues {
+    path: PathBuf,
+    author: String,
+    series: String,
+    categories: BTreeSet<String>,
 }
 
 #[derive(Debug)]
@@ -76,6 +117,7 @@ struct Fetcher {
     sort_method: Option<SortMethod>,
     first_column: Option<FirstColumn>,
     second_column: Option<SecondColumn>,
+    events: Vec<HookEvent>,
 }
 
 impl Home {
@@ -107,6 +149,7 @@ impl Home {
                                         rect.max.x, rect.min.y + small_height - small_thickness],
                                   Event::Toggle(ViewId::SearchBar),
                                   sort_method.title(),
+                                  &TopBarSettings::default(),
                                   context);
         children.push(Box::new(top_bar) as Box<dyn View>);
 
@@ -158,17 +201,20 @@ impl Home {
 
         let mut shelf = Shelf::new(rect![rect.min.x, y_start,
                                          rect.max.x, rect.max.y - small_height - small_thickness],
+                                   library_settings.layout,
                                    library_settings.first_column,
                                    library_settings.second_column,
-                                   library_settings.thumbnail_previews);
+                                   library_settings.thumbnail_previews,
+                                   library_settings.current_page_previews);
 
+        shelf.set_sections(home_sections(&visible_books, &current_directory, None, context));
 
         let max_lines = shelf.max_lines;
         let pages_count = (visible_books.len() as f32 / max_lines as f32).ceil() as usize;
         let index_lower = current_page * max_lines;
         let index_upper = (index_lower + max_lines).min(visible_books.len());
 
-        shelf.update(&visible_books[index_lower..index_upper], hub, &mut RenderQueue::new(), context);
+        shelf.update(&visible_books[index_lower..index_upper], None, hub, &mut RenderQueue::new(), context);
 
         children.push(Box::new(shelf) as Box<dyn View>);
 
@@ -202,7 +248,13 @@ impl Home {
             visible_books,
             current_directory,
             target_document: None,
+            selection: None,
+            batch_field: None,
+            batch_book: None,
+            batch_undo: Vec::new(),
             background_fetchers: FxHashMap::default(),
+            filter_id: 0,
+            sync_status: None,
         })
     }
 
@@ -328,6 +380,7 @@ impl Home {
                                               self.query.as_ref(),
                                               false);
         self.visible_books = files;
+        self.update_sections(context);
 
         let max_lines = {
             let shelf = self.child(self.shelf_index).downcast_ref::<Shelf>().unwrap();
@@ -348,6 +401,15 @@ impl Home {
         }
     }
 
+    // The number of books per page depends on the layout.
+    fn update_layout(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let selected_library = context.settings.selected_library;
+        self.children[self.shelf_index].as_mut().downcast_mut::<Shelf>().unwrap()
+           .set_layout(context.settings.libraries[selected_library].layout);
+        self.update_shelf(true, hub, rq, context);
+        self.update_bottom_bar(rq, context);
+    }
+
     fn update_first_column(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
         let selected_library = context.settings.selected_library;
         self.children[self.shelf_index].as_mut().downcast_mut::<Shelf>().unwrap()
@@ -366,21 +428,33 @@ impl Home {
         let selected_library = context.settings.selected_library;
         self.children[self.shelf_index].as_mut().downcast_mut::<Shelf>().unwrap()
            .set_thumbnail_previews(context.settings.libraries[selected_library].thumbnail_previews);
+        self.children[self.shelf_index].as_mut().downcast_mut::<Shelf>().unwrap()
+           .set_current_page_previews(context.settings.libraries[selected_library].current_page_previews);
         self.update_shelf(false, hub, rq, context);
     }
 
+    fn update_sections(&mut self, context: &Context) {
+        let sections = home_sections(&self.visible_books, &self.current_directory,
+                                     self.query.as_ref(), context);
+        self.children[self.shelf_index].as_mut().downcast_mut::<Shelf>().unwrap()
+            .set_sections(sections);
+    }
+
     fn update_shelf(&mut self, was_resized: bool, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
-        let dpi = CURRENT_DEVICE.dpi;
-        let big_height = scale_by_dpi(BIG_BAR_HEIGHT, dpi) as i32;
-        let thickness = scale_by_dpi(THICKNESS_MEDIUM, dpi) as i32;
+        let prev_max_lines = self.child(self.shelf_index).downcast_ref::<Shelf>().unwrap().max_lines;
+
+        if was_resized {
+            self.update_sections(context);
+        }
+
         let shelf = self.children[self.shelf_index].as_mut().downcast_mut::<Shelf>().unwrap();
-        let max_lines = ((shelf.rect.height() as i32 + thickness) / big_height) as usize;
+        let max_lines = shelf.compute_max_lines();
 
         if was_resized {
             let page_position = if self.visible_books.is_empty() {
                 0.0
             } else {
-                self.current_page as f32 * (shelf.max_lines as f32 /
+                self.current_page as f32 * (prev_max_lines as f32 /
                                             self.visible_books.len() as f32)
             };
 
@@ -398,7 +472,7 @@ impl Home {
         let index_lower = self.current_page * max_lines;
         let index_upper = (index_lower + max_lines).min(self.visible_books.len());
 
-        shelf.update(&self.visible_books[index_lower..index_upper], hub, rq, context);
+        shelf.update(&self.visible_books[index_lower..index_upper], self.selection.as_ref(), hub, rq, context);
     }
 
     fn update_top_bar(&mut self, search_visible: bool, rq: &mut RenderQueue) {
@@ -410,7 +484,36 @@ impl Home {
         }
     }
 
+    fn sync_label(&self, context: &Context) -> Option<String> {
+        match self.sync_status {
+            Some(SyncStatus::Syncing) => Some("Syncing…".to_string()),
+            Some(SyncStatus::Error(..)) => Some("Sync error".to_string()),
+            _ => {
+                let last_sync = context.settings.libraries[context.settings.selected_library].last_sync?;
+                let minutes = (Local::now().naive_local() - last_sync).num_minutes();
+                Some(match minutes {
+                    m if m < 1 => "Synced just now".to_string(),
+                    m if m < 60 => format!("Synced {}m ago", m),
+                    m if m < 24 * 60 => format!("Synced {}h ago", m / 60),
+                    m => format!("Synced {}d ago", m / (24 * 60)),
+                })
+            },
+        }
+    }
+
+    // Runs the hooks of the library that aren't already running.
+    fn sync(&mut self, hub: &Hub, context: &Context) {
+        let selected_library = context.settings.selected_library;
+        for hook in &context.settings.libraries[selected_library].hooks {
+            let full_path = context.library.home.join(&hook.path);
+            if !self.background_fetchers.values().any(|fetcher| fetcher.full_path == full_path) {
+                self.insert_fetcher(hook, hub, context);
+            }
+        }
+    }
+
     fn update_bottom_bar(&mut self, rq: &mut RenderQueue, context: &Context) {
+        let sync_label = self.sync_label(context);
         if let Some(index) = rlocate::<BottomBar>(self) {
             let bottom_bar = self.children[index].as_mut().downcast_mut::<BottomBar>().unwrap();
             let filter = self.query.is_some() ||
@@ -418,6 +521,7 @@ impl Home {
             let selected_library = context.settings.selected_library;
             let library_settings = &context.settings.libraries[selected_library];
             bottom_bar.update_library_label(&library_settings.name, self.visible_books.len(), filter, rq);
+            bottom_bar.update_sync_status(sync_label, rq);
             bottom_bar.update_page_label(self.current_page, self.pages_count, rq);
             bottom_bar.update_icons(self.current_page, self.pages_count, rq);
         }
@@ -796,6 +900,62 @@ impl Home {
         }
     }
 
+    fn toggle_batch_edit(&mut self, enable: Option<bool>, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(index) = locate_by_id(self, ViewId::BatchEdit) {
+            if let Some(true) = enable {
+                return;
+            }
+            self.batch_field = None;
+            self.batch_book = None;
+            rq.add(RenderData::expose(*self.child(index).rect(), UpdateMode::Gui));
+            self.children.remove(index);
+            if let Some(ViewId::BatchEditInput) = self.focus {
+                self.toggle_keyboard(false, true, Some(ViewId::BatchEditInput), hub, rq, context);
+            }
+        } else {
+            if let Some(false) = enable {
+                return;
+            }
+            let label = match self.batch_field {
+                Some(BatchField::Author) => "Set author",
+                Some(BatchField::Series) => "Set series",
+                Some(BatchField::Category) => "Add category",
+                None => return,
+            };
+            let batch_edit = NamedInput::new(label.to_string(),
+                                             ViewId::BatchEdit,
+                                             ViewId::BatchEditInput,
+                                             21, context);
+            rq.add(RenderData::new(batch_edit.id(), *batch_edit.rect(), UpdateMode::Gui));
+            hub.send(Event::Focus(Some(ViewId::BatchEditInput))).ok();
+            self.children.push(Box::new(batch_edit) as Box<dyn View>);
+        }
+    }
+
+    fn toggle_search_notes(&mut self, enable: Option<bool>, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(index) = locate_by_id(self, ViewId::SearchNotes) {
+            if let Some(true) = enable {
+                return;
+            }
+            rq.add(RenderData::expose(*self.child(index).rect(), UpdateMode::Gui));
+            self.children.remove(index);
+            if let Some(ViewId::SearchNotesInput) = self.focus {
+                self.toggle_keyboard(false, true, Some(ViewId::SearchNotesInput), hub, rq, context);
+            }
+        } else {
+            if let Some(false) = enable {
+                return;
+            }
+            let search_notes = NamedInput::new("Search notes".to_string(),
+                                               ViewId::SearchNotes,
+                                               ViewId::SearchNotesInput,
+                                               21, context);
+            rq.add(RenderData::new(search_notes.id(), *search_notes.rect(), UpdateMode::Gui));
+            hub.send(Event::Focus(Some(ViewId::SearchNotesInput))).ok();
+            self.children.push(Box::new(search_notes) as Box<dyn View>);
+        }
+    }
+
     fn toggle_go_to_page(&mut self, enable: Option<bool>, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
         if let Some(index) = locate_by_id(self, ViewId::GoToPage) {
             if let Some(true) = enable {
@@ -846,6 +1006,9 @@ impl Home {
                                EntryKind::RadioButton("Progress".to_string(),
                                                       EntryId::Sort(SortMethod::Progress),
                                                       self.sort_method == SortMethod::Progress),
+                               EntryKind::RadioButton("Rating".to_string(),
+                                                      EntryId::Sort(SortMethod::Rating),
+                                                      self.sort_method == SortMethod::Rating),
                                EntryKind::RadioButton("Author".to_string(),
                                                       EntryId::Sort(SortMethod::Author),
                                                       self.sort_method == SortMethod::Author),
@@ -897,11 +1060,36 @@ impl Home {
                 return;
             }
 
+            if let Some(selection) = self.selection.as_ref() {
+                let mut entries = Vec::new();
+
+                if !selection.is_empty() {
+                    entries.push(EntryKind::Command("Set Author".to_string(), EntryId::BatchSetAuthor));
+                    entries.push(EntryKind::Command("Set Series".to_string(), EntryId::BatchSetSeries));
+                    entries.push(EntryKind::Command("Add Category".to_string(), EntryId::BatchAddCategory));
+                    entries.push(EntryKind::Separator);
+                }
+
+                if !self.batch_undo.is_empty() {
+                    entries.push(EntryKind::Command("Undo".to_string(), EntryId::UndoBatchEdit));
+                }
+
+                entries.push(EntryKind::Command("Select Page".to_string(), EntryId::SelectPageBooks));
+                entries.push(EntryKind::Command("Stop Selecting".to_string(), EntryId::StopSelection));
+
+                let book_menu = Menu::new(rect, ViewId::BookMenu, MenuKind::Contextual, entries, context);
+                rq.add(RenderData::new(book_menu.id(), *book_menu.rect(), UpdateMode::Gui));
+                self.children.push(Box::new(book_menu) as Box<dyn View>);
+                return;
+            }
+
             let book_index = self.book_index(index);
             let info = &self.visible_books[book_index];
             let path = &info.file.path;
 
-            let mut entries = Vec::new();
+            let mut entries = vec![EntryKind::Command("Open".to_string(),
+                                                      EntryId::OpenBook(path.clone())),
+                                   EntryKind::Separator];
 
             if let Some(parent) = path.parent() {
                 entries.push(EntryKind::Command("Select Parent".to_string(),
@@ -913,9 +1101,9 @@ impl Home {
                                                 EntryId::SearchAuthor(info.author.clone())));
             }
 
-            if !entries.is_empty() {
-                entries.push(EntryKind::Separator);
-            }
+            entries.push(EntryKind::Command("Details".to_string(),
+                                            EntryId::Description(path.clone())));
+            entries.push(EntryKind::Separator);
 
             let submenu: &[SimpleStatus] = match info.simple_status() {
                 SimpleStatus::New => &[SimpleStatus::Reading, SimpleStatus::Finished],
@@ -927,6 +1115,20 @@ impl Home {
                                                                     EntryId::SetStatus(path.clone(), *s)))
                                  .collect();
             entries.push(EntryKind::SubMenu("Mark As".to_string(), submenu));
+
+            if context.library.mode == LibraryMode::Database {
+                let mut ratings = vec![EntryKind::RadioButton("None".to_string(),
+                                                              EntryId::SetRating(path.clone(), None),
+                                                              info.rating.is_none()),
+                                       EntryKind::Separator];
+                ratings.extend((1..=MAX_RATING).map(|r| {
+                    let label = if r == 1 { "1 Star".to_string() } else { format!("{} Stars", r) };
+                    EntryKind::RadioButton(label, EntryId::SetRating(path.clone(), Some(r)),
+                                           info.rating == Some(r))
+                }));
+                entries.push(EntryKind::SubMenu("Rating".to_string(), ratings));
+            }
+
             entries.push(EntryKind::Separator);
 
             let selected_library = context.settings.selected_library;
@@ -947,6 +1149,16 @@ impl Home {
                 entries.push(EntryKind::SubMenu("Move To".to_string(), move_to));
             }
 
+            if context.library.mode == LibraryMode::Database {
+                entries.push(EntryKind::Command("Add Category".to_string(),
+                                                EntryId::AddCategory(path.clone())));
+                entries.push(EntryKind::Command("Select".to_string(),
+                                                EntryId::StartSelection(path.clone())));
+                let label = if info.hidden { "Unhide" } else { "Hide" };
+                entries.push(EntryKind::Command(label.to_string(),
+                                                EntryId::SetHidden(path.clone(), !info.hidden)));
+            }
+
             entries.push(EntryKind::Command("Rename".to_string(),
                                             EntryId::Rename(path.clone())));
             entries.push(EntryKind::Command("Remove".to_string(),
@@ -959,6 +1171,24 @@ impl Home {
         }
     }
 
+    // Applies the given query, and shows it in the search bar.
+    fn search_for(&mut self, text: &str, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let query = BookQuery::new(text);
+        if query.is_some() {
+            self.query = query;
+            self.toggle_search_bar(Some(true), false, hub, rq, context);
+            self.toggle_keyboard(false, false, None, hub, rq, context);
+            if let Some(search_bar) = self.children[self.shelf_index+2].downcast_mut::<SearchBar>() {
+                search_bar.set_text(text, rq, context);
+            }
+            // Render the search bar and its separator.
+            for i in self.shelf_index + 1 ..= self.shelf_index + 2 {
+                rq.add(RenderData::new(self.child(i).id(), *self.child(i).rect(), UpdateMode::Gui));
+            }
+            self.refresh_visibles(true, true, hub, rq, context);
+        }
+    }
+
     fn toggle_library_menu(&mut self, rect: Rectangle, enable: Option<bool>, rq: &mut RenderQueue, context: &mut Context) {
         if let Some(index) = locate_by_id(self, ViewId::LibraryMenu) {
             if let Some(true) = enable {
@@ -980,7 +1210,9 @@ impl Home {
             }).collect();
 
             let database = if library_settings.mode == LibraryMode::Database {
-                vec![EntryKind::Command("Import".to_string(), EntryId::Import),
+                vec![EntryKind::CheckBox("Show Hidden".to_string(), EntryId::ToggleShowHidden, context.library.show_hidden),
+                     EntryKind::Separator,
+                     EntryKind::Command("Import".to_string(), EntryId::Import),
                      EntryKind::Command("Flush".to_string(), EntryId::Flush)]
             } else {
                 Vec::new()
@@ -995,7 +1227,14 @@ impl Home {
                 Vec::new()
             };
 
-            let mut entries = vec![EntryKind::SubMenu("Library".to_string(), libraries)];
+            let mut entries = Vec::new();
+
+            if self.last_unfinished_book(context).is_some() {
+                entries.push(EntryKind::Command("Continue Reading".to_string(), EntryId::ContinueReading));
+                entries.push(EntryKind::Separator);
+            }
+
+            entries.push(EntryKind::SubMenu("Library".to_string(), libraries));
 
             if !database.is_empty() {
                 entries.push(EntryKind::SubMenu("Database".to_string(), database));
@@ -1012,10 +1251,27 @@ impl Home {
 
             if !hooks.is_empty() {
                 entries.push(EntryKind::SubMenu("Toggle Select".to_string(), hooks));
+                entries.push(EntryKind::Command("Sync".to_string(), EntryId::Sync));
+            }
+
+            entries.push(EntryKind::Command("Search Notes".to_string(), EntryId::SearchNotes));
+
+            let kinds = self.visible_books.iter().map(|info| info.file.kind.to_lowercase())
+                             .collect::<BTreeSet<String>>();
+            if kinds.len() > 1 {
+                let kinds = kinds.into_iter().map(|kind| {
+                    EntryKind::Command(kind.to_uppercase(), EntryId::SearchKind(kind))
+                }).collect::<Vec<EntryKind>>();
+                entries.push(EntryKind::SubMenu("Filter by Kind".to_string(), kinds));
             }
 
             entries.push(EntryKind::Separator);
 
+            let layout = library_settings.layout;
+            entries.push(EntryKind::SubMenu("Layout".to_string(),
+                vec![EntryKind::RadioButton("List".to_string(), EntryId::ShelfLayout(ShelfLayout::List), layout == ShelfLayout::List),
+                     EntryKind::RadioButton("Grid".to_string(), EntryId::ShelfLayout(ShelfLayout::Grid), layout == ShelfLayout::Grid)]));
+
             let first_column = library_settings.first_column;
             entries.push(EntryKind::SubMenu("First Column".to_string(),
                 vec![EntryKind::RadioButton("Title and Author".to_string(), EntryId::FirstColumn(FirstColumn::TitleAndAuthor), first_column == FirstColumn::TitleAndAuthor),
@@ -1030,11 +1286,31 @@ impl Home {
                                              EntryId::ThumbnailPreviews,
                                              library_settings.thumbnail_previews));
 
+            if library_settings.thumbnail_previews {
+                entries.push(EntryKind::CheckBox("Current Page Previews".to_string(),
+                                                 EntryId::CurrentPagePreviews,
+                                                 library_settings.current_page_previews));
+            }
+
+            let sections = HomeSection::ALL.iter().map(|&kind| {
+                EntryKind::CheckBox(kind.label().to_string(),
+                                    EntryId::ShowHomeSection(kind),
+                                    context.settings.home.section(kind).enabled)
+            }).collect::<Vec<EntryKind>>();
+            entries.push(EntryKind::SubMenu("Sections".to_string(), sections));
+
             let trash_path = context.library.home.join(TRASH_DIRNAME);
             if let Ok(trash) = Library::new(trash_path, LibraryMode::Database)
                                        .map_err(|e| eprintln!("Can't inspect trash: {:#?}.", e)) {
                 if trash.is_empty() == Some(false) {
+                    let (mut files, _) = trash.list(&trash.home, None, false);
+                    sort_by_removal(&mut files, false);
+                    let restore = files.iter().map(|info| {
+                        EntryKind::Command(info.title(),
+                                           EntryId::RestoreFromTrash(info.file.path.clone()))
+                    }).collect::<Vec<EntryKind>>();
                     entries.push(EntryKind::Separator);
+                    entries.push(EntryKind::SubMenu("Restore".to_string(), restore));
                     entries.push(EntryKind::Command("Empty Trash".to_string(),
                                                     EntryId::EmptyTrash));
                 }
@@ -1046,6 +1322,64 @@ impl Home {
         }
     }
 
+    fn annotated_books(&self, context: &Context) -> Vec<Info> {
+        let query = BookQuery { annotations: Some(true), .. Default::default() };
+        let (files, _) = context.library.list(&context.library.home, Some(&query), false);
+        files.into_iter().filter(|info| query.is_match(info)).collect()
+    }
+
+    // Scans the annotations of every book of the library.
+    fn search_notes(&mut self, text: &str, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let msg = if let Some(query) = make_query(text) {
+            let mut books = self.annotated_books(context);
+            sort(&mut books, SortMethod::Title, false);
+            let matches = books.iter().filter_map(|info| {
+                let mut annotations = info.reader.as_ref()?.annotations.iter()
+                                          .filter(|annot| query.is_match(&annot.note) ||
+                                                          query.is_match(&annot.text))
+                                          .collect::<Vec<&Annotation>>();
+                if annotations.is_empty() {
+                    return None;
+                }
+                annotations.sort_by_key(|annot| annot.selection[0]);
+                Some((info.title(), info.file.path.as_path(), annotations))
+            }).collect::<Vec<_>>();
+            if !matches.is_empty() {
+                hub.send(Event::OpenHtml(notes_as_html(&matches), None)).ok();
+                return;
+            }
+            "No matching notes."
+        } else {
+            "Invalid search query."
+        };
+        let notif = Notification::new(msg.to_string(), hub, rq, context);
+        self.children.push(Box::new(notif) as Box<dyn View>);
+    }
+
+    // The most recently opened book that isn't finished.
+    fn last_unfinished_book(&self, context: &Context) -> Option<Info> {
+        let (files, _) = context.library.list(&context.library.home, None, false);
+        files.into_iter()
+             .filter(|info| info.simple_status() == SimpleStatus::Reading)
+             .max_by_key(|info| info.reader.as_ref().map(|r| r.opened))
+    }
+
+    fn continue_reading(&mut self, hub: &Hub, context: &Context) {
+        if let Some(info) = self.last_unfinished_book(context) {
+            hub.send(Event::Open(Box::new(info), None)).ok();
+        }
+    }
+
+    fn open_at(&mut self, path: &Path, location: usize, hub: &Hub, context: &Context) {
+        if let Some(mut info) = self.annotated_books(context).into_iter()
+                                    .find(|info| info.file.path == path) {
+            if let Some(r) = info.reader.as_mut() {
+                r.current_page = location;
+            }
+            hub.send(Event::Open(Box::new(info), None)).ok();
+        }
+    }
+
     fn add_document(&mut self, info: Info, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
         context.library.add_document(info);
         self.sort(false, hub, rq, context);
@@ -1063,6 +1397,104 @@ impl Home {
         self.refresh_visibles(true, false, hub, rq, context);
     }
 
+    fn set_rating(&mut self, path: &Path, rating: Option<u8>, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        context.library.edit_info(path, |info| info.rating = rating);
+
+        if self.sort_method == SortMethod::Rating {
+            self.sort(false, hub, rq, context);
+        }
+
+        self.refresh_visibles(true, false, hub, rq, context);
+    }
+
+    fn set_hidden(&mut self, path: &Path, hidden: bool, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        context.library.edit_info(path, |info| info.hidden = hidden);
+        self.refresh_visibles(true, false, hub, rq, context);
+    }
+
+    fn start_selection(&mut self, path: &Path, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let mut selection = FxHashSet::default();
+        selection.insert(path.to_path_buf());
+        self.selection = Some(selection);
+        self.batch_undo.clear();
+        self.update_shelf(false, hub, rq, context);
+    }
+
+    fn stop_selection(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if self.selection.take().is_some() {
+            self.batch_undo.clear();
+            self.update_shelf(false, hub, rq, context);
+        }
+    }
+
+    fn select_page_books(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let max_lines = self.child(self.shelf_index).downcast_ref::<Shelf>().unwrap().max_lines;
+        let index_lower = self.current_page * max_lines;
+        let index_upper = (index_lower + max_lines).min(self.visible_books.len());
+        if let Some(selection) = self.selection.as_mut() {
+            selection.extend(self.visible_books[index_lower..index_upper].iter()
+                                 .map(|info| info.file.path.clone()));
+        }
+        self.update_shelf(false, hub, rq, context);
+    }
+
+    fn batch_edit(&mut self, field: BatchField, text: &str, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let paths = match (self.batch_book.take(), self.selection.as_ref()) {
+            (Some(path), _) => vec![path],
+            (None, Some(selection)) if !selection.is_empty() => selection.iter().cloned().collect(),
+            _ => return,
+        };
+
+        let value = text.trim();
+        if field == BatchField::Category && value.is_empty() {
+            return;
+        }
+
+        self.batch_undo.clear();
+
+        for path in &paths {
+            let batch_undo = &mut self.batch_undo;
+            context.library.edit_info(path, |info| {
+                batch_undo.push(PriorValues {
+                    path: path.clone(),
+                    author: info.author.clone(),
+                    series: info.series.clone(),
+                    categories: info.categories.clone(),
+                });
+                match field {
+                    BatchField::Author => info.author = value.to_string(),
+                    BatchField::Series => info.series = value.to_string(),
+                    BatchField::Category => { info.categories.insert(value.to_string()); },
+                }
+            });
+        }
+
+        let count = self.batch_undo.len();
+        let message = format!("Updated {} book{}.", count, if count != 1 { "s" } else { "" });
+        let notif = Notification::new(message, hub, rq, context);
+        self.children.push(Box::new(notif) as Box<dyn View>);
+
+        self.sort(false, hub, rq, context);
+        self.refresh_visibles(true, false, hub, rq, context);
+    }
+
+    fn undo_batch_edit(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if self.batch_undo.is_empty() {
+            return;
+        }
+
+        for prior in self.batch_undo.drain(..) {
+            context.library.edit_info(&prior.path, |info| {
+                info.author = prior.author;
+                info.series = prior.series;
+                info.categories = prior.categories;
+            });
+        }
+
+        self.sort(false, hub, rq, context);
+        self.refresh_visibles(true, false, hub, rq, context);
+    }
+
     fn empty_trash(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
         let trash_path = context.library.home.join(TRASH_DIRNAME);
 
@@ -1106,11 +1538,21 @@ impl Home {
                 fs::create_dir(&trash_path)?;
             }
             let mut trash = Library::new(trash_path, LibraryMode::Database)?;
-            context.library.move_to(path, &mut trash)?;
+            let dest_path = context.library.move_to(path, &mut trash)?;
+            trash.edit_info(&dest_path, |info| info.removed = Some(Local::now().naive_local()));
             let (mut files, _) = trash.list(&trash.home, None, false);
+            sort_by_removal(&mut files, true);
+            if let Some(max_age) = context.settings.home.max_trash_age {
+                let limit = Local::now().naive_local() - Duration::days(max_age as i64);
+                let expired = files.iter().rev().take_while(|info| removal_date(info) < limit).count();
+                for info in files.drain(files.len() - expired ..) {
+                    if let Err(e) = trash.remove(&info.file.path) {
+                        eprintln!("Can't erase {}: {:#}", info.file.path.display(), e);
+                    }
+                }
+            }
             let mut size = files.iter().map(|info| info.file.size).sum::<u64>();
             if size > context.settings.home.max_trash_size {
-                sort(&mut files, SortMethod::Added, true);
                 while size > context.settings.home.max_trash_size {
                     let info = files.pop().unwrap();
                     if let Err(e) = trash.remove(&info.file.path) {
@@ -1128,6 +1570,17 @@ impl Home {
         Ok(())
     }
 
+    fn restore(&mut self, path: &Path, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) -> Result<(), Error> {
+        let trash_path = context.library.home.join(TRASH_DIRNAME);
+        let mut trash = Library::new(trash_path, LibraryMode::Database)?;
+        let dest_path = trash.move_to(path, &mut context.library)?;
+        context.library.edit_info(&dest_path, |info| info.removed = None);
+        trash.flush();
+        self.sort(false, hub, rq, context);
+        self.refresh_visibles(true, false, hub, rq, context);
+        Ok(())
+    }
+
     fn copy_to(&mut self, path: &Path, index: usize, context: &mut Context) -> Result<(), Error> {
         let library_settings = &context.settings.libraries[index];
         let mut library = Library::new(&library_settings.path, library_settings.mode)?;
@@ -1206,6 +1659,10 @@ impl Home {
 
         context.library.flush();
 
+        self.selection = None;
+        self.batch_undo.clear();
+        self.sync_status = None;
+
         context.library = library;
         context.settings.selected_library = index;
 
@@ -1223,9 +1680,11 @@ impl Home {
         }
 
         if let Some(shelf) = self.children[self.shelf_index].as_mut().downcast_mut::<Shelf>() {
+            shelf.set_layout(library_settings.layout);
             shelf.set_first_column(library_settings.first_column);
             shelf.set_second_column(library_settings.second_column);
             shelf.set_thumbnail_previews(library_settings.thumbnail_previews);
+            shelf.set_current_page_previews(library_settings.current_page_previews);
         }
 
         let home = context.library.home.clone();
@@ -1252,6 +1711,7 @@ impl Home {
             if fetcher.full_path == path {
                 unsafe { libc::kill(*id as libc::pid_t, libc::SIGTERM) };
                 fetcher.process.wait().ok();
+                hub.send(Event::Close(ViewId::FetcherProgress(*id))).ok();
                 if update {
                     if let Some(sort_method) = fetcher.sort_method {
                         hub.send(Event::Select(EntryId::Sort(sort_method))).ok();
@@ -1281,6 +1741,22 @@ impl Home {
         });
     }
 
+    // Sends a reader event to the running hooks that asked for it.
+    // Returns the number of hooks it was sent to.
+    fn send_hook_event(&mut self, kind: HookEvent, event: &JsonValue) -> usize {
+        let mut count = 0;
+        for fetcher in self.background_fetchers.values_mut() {
+            if !fetcher.events.contains(&kind) {
+                continue;
+            }
+            if let Some(stdin) = fetcher.process.stdin.as_mut() {
+                writeln!(stdin, "{}", event).ok();
+                count += 1;
+            }
+        }
+        count
+    }
+
     fn insert_fetcher(&mut self, hook: &Hook, hub: &Hub, context: &Context) {
         let library_path = &context.library.home;
         let save_path = context.library.home.join(&hook.path);
@@ -1301,7 +1777,8 @@ impl Home {
                 }
                 self.background_fetchers.insert(process.id(),
                                                 Fetcher { path: hook.path.clone(), full_path: save_path, process,
-                                                          sort_method, first_column, second_column });
+                                                          sort_method, first_column, second_column,
+                                                          events: hook.events.clone() });
             },
             Err(e) => eprintln!("Can't spawn child: {:#}.", e),
         }
@@ -1356,6 +1833,64 @@ impl Home {
                                     hub2.send(Event::FetcherRemoveDocument(id, PathBuf::from(path))).ok();
                                 }
                             },
+                            Some("status") => {
+                                let status = match event.get("state").and_then(JsonValue::as_str) {
+                                    Some("syncing") => Some(SyncStatus::Syncing),
+                                    Some("synced") => Some(SyncStatus::Synced),
+                                    Some("error") => {
+                                        let message = event.get("message")
+                                                           .and_then(JsonValue::as_str)
+                                                           .unwrap_or_default();
+                                        Some(SyncStatus::Error(message.to_string()))
+                                    },
+                                    _ => None,
+                                };
+                                if let Some(status) = status {
+                                    hub2.send(Event::FetcherStatus(id, status)).ok();
+                                }
+                            },
+                            Some("progress") => {
+                                let current = event.get("current")
+                                                   .and_then(JsonValue::as_u64);
+                                let total = event.get("total")
+                                                 .and_then(JsonValue::as_u64);
+                                if let (Some(current), Some(total)) = (current, total) {
+                                    hub2.send(Event::FetcherProgress(id, current as usize, total as usize)).ok();
+                                }
+                            },
+                            Some("speechPosition") => {
+                                let start = event.get("start").cloned()
+                                                 .and_then(|v| serde_json::from_value(v).ok());
+                                let end = event.get("end").cloned()
+                                               .and_then(|v| serde_json::from_value(v).ok());
+                                if let (Some(start), Some(end)) = (start, end) {
+                                    hub2.send(Event::SpeechPosition([start, end])).ok();
+                                }
+                            },
+                            Some("speechStopped") => {
+                                hub2.send(Event::SpeechStopped).ok();
+                            },
+                            Some("translation") => {
+                                if let Some(text) = event.get("text").and_then(JsonValue::as_str) {
+                                    hub2.send(Event::Translation(text.to_string())).ok();
+                                }
+                            },
+                            Some("openHtml") => {
+                                if let Some(html) = event.get("html").and_then(JsonValue::as_str) {
+                                    let link_uri = event.get("linkUri")
+                                                        .and_then(JsonValue::as_str)
+                                                        .map(String::from);
+                                    hub2.send(Event::OpenHtml(html.to_string(), link_uri)).ok();
+                                }
+                            },
+                            Some("open") => {
+                                if let Some(path) = event.get("path").and_then(JsonValue::as_str) {
+                                    let anchor = event.get("anchor")
+                                                      .and_then(JsonValue::as_str)
+                                                      .map(String::from);
+                                    hub2.send(Event::FetcherOpen(id, PathBuf::from(path), anchor)).ok();
+                                }
+                            },
                             Some("search") => {
                                 let path = event.get("path")
                                                 .and_then(JsonValue::as_str)
@@ -1438,6 +1973,10 @@ impl View for Home {
                 };
                 true
             },
+            Event::Gesture(GestureEvent::MultiArrow { dir: Dir::East, .. }) => {
+                self.continue_reading(hub, context);
+                true
+            },
             Event::Gesture(GestureEvent::Corner { dir, .. }) => {
                 match dir {
                     DiagDir::NorthWest |
@@ -1520,6 +2059,14 @@ impl View for Home {
                 self.toggle_rename_document(Some(false), hub, rq, context);
                 true
             },
+            Event::Close(ViewId::BatchEdit) => {
+                self.toggle_batch_edit(Some(false), hub, rq, context);
+                true
+            },
+            Event::Close(ViewId::SearchNotes) => {
+                self.toggle_search_notes(Some(false), hub, rq, context);
+                true
+            },
             Event::Select(EntryId::Sort(sort_method)) => {
                 let selected_library = context.settings.selected_library;
                 context.settings.libraries[selected_library].sort_method = sort_method;
@@ -1551,10 +2098,37 @@ impl View for Home {
                 self.add_document(*info.clone(), hub, rq, context);
                 true
             },
+            Event::FetcherStatus(_, ref status) => {
+                if *status == SyncStatus::Synced {
+                    let selected_library = context.settings.selected_library;
+                    context.settings.libraries[selected_library].last_sync = Some(Local::now().naive_local());
+                }
+                if let SyncStatus::Error(ref message) = status {
+                    if !message.is_empty() {
+                        let notif = Notification::new(message.clone(), hub, rq, context);
+                        self.children.push(Box::new(notif) as Box<dyn View>);
+                    }
+                }
+                self.sync_status = Some(status.clone());
+                self.update_bottom_bar(rq, context);
+                true
+            },
+            Event::Select(EntryId::Sync) => {
+                self.sync(hub, context);
+                true
+            },
             Event::Select(EntryId::SetStatus(ref path, status)) => {
                 self.set_status(path, status, hub, rq, context);
                 true
             },
+            Event::Select(EntryId::SetRating(ref path, rating)) => {
+                self.set_rating(path, rating, hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::SetHidden(ref path, hidden)) => {
+                self.set_hidden(path, hidden, hub, rq, context);
+                true
+            },
             Event::Select(EntryId::FirstColumn(first_column)) => {
                 let selected_library = context.settings.selected_library;
                 context.settings.libraries[selected_library].first_column = first_column;
@@ -1567,18 +2141,65 @@ impl View for Home {
                 self.update_second_column(hub, rq, context);
                 true
             },
+            Event::Select(EntryId::ShelfLayout(layout)) => {
+                let selected_library = context.settings.selected_library;
+                context.settings.libraries[selected_library].layout = layout;
+                self.update_layout(hub, rq, context);
+                true
+            },
             Event::Select(EntryId::ThumbnailPreviews) => {
                 let selected_library = context.settings.selected_library;
                 context.settings.libraries[selected_library].thumbnail_previews = !context.settings.libraries[selected_library].thumbnail_previews;
                 self.update_thumbnail_previews(hub, rq, context);
                 true
             },
+            Event::Select(EntryId::CurrentPagePreviews) => {
+                let selected_library = context.settings.selected_library;
+                context.settings.libraries[selected_library].current_page_previews = !context.settings.libraries[selected_library].current_page_previews;
+                self.update_thumbnail_previews(hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::ShowHomeSection(kind)) => {
+                let section = context.settings.home.section_mut(kind);
+                section.enabled = !section.enabled;
+                self.refresh_visibles(true, false, hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::CollapseHomeSection(kind)) => {
+                let section = context.settings.home.section_mut(kind);
+                section.collapsed = !section.collapsed;
+                self.refresh_visibles(true, false, hub, rq, context);
+                true
+            },
             Event::Submit(ViewId::AddressBarInput, ref addr) => {
                 self.toggle_keyboard(false, true, None, hub, rq, context);
                 self.select_directory(Path::new(addr), hub, rq, context);
                 true
             },
+            Event::TextChanged(ViewId::HomeSearchInput, ref text) if context.settings.home.search_as_you_type => {
+                self.filter_id = self.filter_id.wrapping_add(1);
+                let id = self.filter_id;
+                let text = text.clone();
+                let hub2 = hub.clone();
+                thread::spawn(move || {
+                    thread::sleep(FILTER_DELAY);
+                    hub2.send(Event::ApplyFilter(id, text)).ok();
+                });
+                true
+            },
+            Event::ApplyFilter(id, ref text) => {
+                if id == self.filter_id && rlocate::<SearchBar>(self).is_some() {
+                    let query = BookQuery::substring(text);
+                    // The query syntax is only applied on submission.
+                    if query.is_some() || text.trim().is_empty() {
+                        self.query = query;
+                        self.refresh_visibles(true, true, hub, rq, context);
+                    }
+                }
+                true
+            },
             Event::Submit(ViewId::HomeSearchInput, ref text) => {
+                self.filter_id = self.filter_id.wrapping_add(1);
                 self.query = BookQuery::new(text);
                 if self.query.is_some() {
                     self.toggle_keyboard(false, false, None, hub, rq, context);
@@ -1615,6 +2236,28 @@ impl View for Home {
                 }
                 true
             },
+            Event::Submit(ViewId::SearchNotesInput, ref text) => {
+                self.search_notes(text, hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::ContinueReading) => {
+                self.continue_reading(hub, context);
+                true
+            },
+            Event::Select(EntryId::SearchNotes) => {
+                self.toggle_search_notes(Some(true), hub, rq, context);
+                true
+            },
+            Event::OpenAt(ref path, location) => {
+                self.open_at(path, location, hub, context);
+                true
+            },
+            Event::Submit(ViewId::BatchEditInput, ref text) => {
+                if let Some(field) = self.batch_field.take() {
+                    self.batch_edit(field, text, hub, rq, context);
+                }
+                true
+            },
             Event::NavigationBarResized(_) => {
                 self.adjust_shelf_top_edge();
                 self.update_shelf(true, hub, rq, context);
@@ -1628,6 +2271,71 @@ impl View for Home {
                 self.empty_trash(hub, rq, context);
                 true
             },
+            Event::Select(EntryId::StartSelection(ref path)) => {
+                self.start_selection(path, hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::StopSelection) => {
+                self.stop_selection(hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::SelectPageBooks) => {
+                self.select_page_books(hub, rq, context);
+                true
+            },
+            Event::SelectBook(ref path, selected) => {
+                if let Some(selection) = self.selection.as_mut() {
+                    if selected {
+                        selection.insert(path.clone());
+                    } else {
+                        selection.remove(path);
+                    }
+                }
+                true
+            },
+            Event::Select(EntryId::BatchSetAuthor) => {
+                self.batch_field = Some(BatchField::Author);
+                self.toggle_batch_edit(Some(true), hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::BatchSetSeries) => {
+                self.batch_field = Some(BatchField::Series);
+                self.toggle_batch_edit(Some(true), hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::BatchAddCategory) => {
+                self.batch_field = Some(BatchField::Category);
+                self.toggle_batch_edit(Some(true), hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::AddCategory(ref path)) => {
+                self.batch_field = Some(BatchField::Category);
+                self.toggle_batch_edit(Some(true), hub, rq, context);
+                self.batch_book = Some(path.clone());
+                true
+            },
+            Event::Select(EntryId::OpenBook(ref path)) => {
+                if let Some(info) = self.visible_books.iter().find(|info| info.file.path == *path) {
+                    hub.send(Event::Open(Box::new(info.clone()), None)).ok();
+                }
+                true
+            },
+            Event::Select(EntryId::UndoBatchEdit) => {
+                self.undo_batch_edit(hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::Description(ref path)) => {
+                if let Some(info) = self.visible_books.iter().find(|info| info.file.path == *path) {
+                    hub.send(Event::OpenHtml(description_as_html(info), None)).ok();
+                }
+                true
+            },
+            Event::Select(EntryId::RestoreFromTrash(ref path)) => {
+                self.restore(path, hub, rq, context)
+                    .map_err(|e| eprintln!("Can't restore document: {:#}.", e))
+                    .ok();
+                true
+            },
             Event::Select(EntryId::Rename(ref path)) => {
                 self.target_document = Some(path.clone());
                 self.toggle_rename_document(Some(true), hub, rq, context);
@@ -1667,21 +2375,11 @@ impl View for Home {
                 true
             },
             Event::Select(EntryId::SearchAuthor(ref author)) => {
-                let text = format!("'a {}", author);
-                let query = BookQuery::new(&text);
-                if query.is_some() {
-                    self.query = query;
-                    self.toggle_search_bar(Some(true), false, hub, rq, context);
-                    self.toggle_keyboard(false, false, None, hub, rq, context);
-                    if let Some(search_bar) = self.children[self.shelf_index+2].downcast_mut::<SearchBar>() {
-                        search_bar.set_text(&text, rq, context);
-                    }
-                    // Render the search bar and its separator.
-                    for i in self.shelf_index + 1 ..= self.shelf_index + 2 {
-                        rq.add(RenderData::new(self.child(i).id(), *self.child(i).rect(), UpdateMode::Gui));
-                    }
-                    self.refresh_visibles(true, true, hub, rq, context);
-                }
+                self.search_for(&format!("'a {}", author), hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::SearchKind(ref kind)) => {
+                self.search_for(&format!("kind:{}", kind), hub, rq, context);
                 true
             },
             Event::GoTo(location) => {
@@ -1716,6 +2414,47 @@ impl View for Home {
                 }
                 true
             },
+            Event::PageShared(ref path) => {
+                self.send_hook_event(HookEvent::Share, &json!({"type": "share", "path": path}));
+                true
+            },
+            Event::QuoteShared(ref text) => {
+                self.send_hook_event(HookEvent::ShareQuote, &json!({"type": "shareQuote", "text": text}));
+                true
+            },
+            Event::ReadAloud(ref words) => {
+                let words = words.iter()
+                                 .map(|word| json!({"text": word.text, "location": word.location}))
+                                 .collect::<Vec<JsonValue>>();
+                self.send_hook_event(HookEvent::ReadAloud, &json!({"type": "readAloud", "words": words}));
+                true
+            },
+            Event::Translate(ref text, ref language) => {
+                let event = json!({"type": "translate", "text": text, "language": language});
+                if self.send_hook_event(HookEvent::Translate, &event) == 0 {
+                    hub.send(Event::Notify("No running hook can translate.".to_string())).ok();
+                }
+                true
+            },
+            Event::PageTurned(location) => {
+                self.send_hook_event(HookEvent::PageTurned, &json!({"type": "pageTurned", "location": location}));
+                true
+            },
+            Event::HookLink(ref uri) => {
+                self.send_hook_event(HookEvent::FollowLink, &json!({"type": "followLink", "uri": uri}));
+                true
+            },
+            Event::StopReadAloud => {
+                self.send_hook_event(HookEvent::ReadAloud, &json!({"type": "stopReadAloud"}));
+                true
+            },
+            Event::FetcherOpen(_, ref path, ref anchor) => {
+                let (files, _) = context.library.list(&context.library.home, None, false);
+                if let Some(info) = files.into_iter().find(|info| info.file.path == *path) {
+                    hub.send(Event::Open(Box::new(info), anchor.clone())).ok();
+                }
+                true
+            },
             Event::FetcherSearch { id, ref path, ref query, ref sort_by } => {
                 let path = path.as_ref().unwrap_or(&context.library.home);
                 let query = query.as_ref().and_then(|text| BookQuery::new(text));
@@ -1735,16 +2474,41 @@ impl View for Home {
                 }
                 true
             },
+            Event::FetcherProgress(id, current, total) => {
+                if let Some(index) = locate_by_id(self, ViewId::FetcherProgress(id)) {
+                    if current >= total {
+                        rq.add(RenderData::expose(*self.child(index).rect(), UpdateMode::Gui));
+                        self.children.remove(index);
+                    } else if let Some(progress) = self.child_mut(index).downcast_mut::<FetcherProgress>() {
+                        progress.update(current, total, rq);
+                    }
+                } else if current < total && self.background_fetchers.contains_key(&id) {
+                    let progress = FetcherProgress::new(id, current, total, context);
+                    rq.add(RenderData::new(progress.id(), *progress.rect(), UpdateMode::Gui));
+                    self.children.push(Box::new(progress) as Box<dyn View>);
+                }
+                true
+            },
             Event::CheckFetcher(id) => {
+                if let Some(index) = locate_by_id(self, ViewId::FetcherProgress(id)) {
+                    rq.add(RenderData::expose(*self.child(index).rect(), UpdateMode::Gui));
+                    self.children.remove(index);
+                }
                 if let Some(fetcher) = self.background_fetchers.get_mut(&id) {
                     if let Ok(exit_status) = fetcher.process.wait() {
                         if !exit_status.success() {
                             let msg = format!("{}: abnormal process termination.", fetcher.path.display());
+                            self.sync_status = Some(SyncStatus::Error(msg.clone()));
                             let notif = Notification::new(msg, hub, rq, context);
                             self.children.push(Box::new(notif) as Box<dyn View>);
+                        } else if self.sync_status == Some(SyncStatus::Syncing) {
+                            let selected_library = context.settings.selected_library;
+                            context.settings.libraries[selected_library].last_sync = Some(Local::now().naive_local());
+                            self.sync_status = Some(SyncStatus::Synced);
                         }
                     }
                 }
+                self.update_bottom_bar(rq, context);
                 true
             },
             Event::ToggleFrontlight => {
@@ -1900,3 +2664,45 @@ impl View for Home {
         self.id
     }
 }
+
+// The books trashed before the removal date was recorded use their addition date.
+fn removal_date(info: &Info) -> NaiveDateTime {
+    info.removed.unwrap_or(info.added)
+}
+
+fn sort_by_removal(files: &mut [Info], reverse_order: bool) {
+    if reverse_order {
+        files.sort_by_key(|b| std::cmp::Reverse(removal_date(b)));
+    } else {
+        files.sort_by_key(removal_date);
+    }
+}
+
+// The sections are only shown at the top of the library, when no search is active.
+fn home_sections(visible_books: &[Info], current_directory: &Path, query: Option<&BookQuery>, context: &Context) -> Vec<ShelfSection> {
+    if query.is_some() || current_directory != context.library.home {
+        return Vec::new();
+    }
+
+    let settings = &context.settings.home;
+
+    HomeSection::ALL.iter().filter_map(|&kind| {
+        let section = settings.section(kind);
+        if !section.enabled {
+            return None;
+        }
+        let mut books: Vec<(NaiveDateTime, &Info)> = visible_books.iter().filter_map(|info| {
+            match kind {
+                HomeSection::RecentlyAdded => Some(info.added),
+                HomeSection::RecentlyFinished => info.reader.as_ref().and_then(|r| r.finished_at),
+            }.map(|date| (date, info))
+        }).collect();
+        books.sort_by_key(|b| std::cmp::Reverse(b.0));
+        Some(ShelfSection {
+            kind,
+            books: books.into_iter().take(settings.max_section_items)
+                        .map(|(_, info)| info.clone()).collect(),
+            collapsed: section.collapsed,
+        })
+    }).collect()
+}
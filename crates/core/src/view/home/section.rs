@@ -0,0 +1,164 @@
+use crate::device::CURRENT_DEVICE;
+use crate::framebuffer::{Framebuffer, UpdateMode};
+use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData, EntryId};
+use crate::view::SMALL_BAR_HEIGHT;
+use crate::font::{Fonts, font_from_style, MD_TITLE, NORMAL_STYLE};
+use crate::color::{TEXT_NORMAL, TEXT_INVERTED_HARD};
+use crate::gesture::GestureEvent;
+use crate::metadata::Info;
+use crate::settings::HomeSection;
+use crate::unit::scale_by_dpi;
+use crate::geom::Rectangle;
+use crate::context::Context;
+
+// A titled list of books shown above the shelf.
+// The first line is the header, tapping it collapses or expands the section.
+pub struct Section {
+    id: Id,
+    rect: Rectangle,
+    children: Vec<Box<dyn View>>,
+    kind: HomeSection,
+    books: Vec<Info>,
+    collapsed: bool,
+    active: Option<usize>,
+}
+
+impl Section {
+    pub fn new(rect: Rectangle, kind: HomeSection, books: Vec<Info>, collapsed: bool) -> Section {
+        Section {
+            id: ID_FEEDER.next(),
+            rect,
+            children: Vec::new(),
+            kind,
+            books,
+            collapsed,
+            active: None,
+        }
+    }
+
+    fn lines_count(books_count: usize, collapsed: bool) -> usize {
+        1 + if collapsed { 0 } else { books_count }
+    }
+
+    pub fn height(books_count: usize, collapsed: bool) -> i32 {
+        let small_height = scale_by_dpi(SMALL_BAR_HEIGHT, CURRENT_DEVICE.dpi) as i32;
+        Section::lines_count(books_count, collapsed) as i32 * small_height
+    }
+
+    fn line_height(&self) -> i32 {
+        let lines_count = Section::lines_count(self.books.len(), self.collapsed) as i32;
+        self.rect.height() as i32 / lines_count
+    }
+
+    fn line_rect(&self, index: usize) -> Rectangle {
+        let line_height = self.line_height();
+        let y_min = self.rect.min.y + index as i32 * line_height;
+        rect![self.rect.min.x, y_min, self.rect.max.x, y_min + line_height]
+    }
+}
+
+impl View for Section {
+    fn handle_event(&mut self, evt: &Event, hub: &Hub, bus: &mut Bus, rq: &mut RenderQueue, _context: &mut Context) -> bool {
+        match *evt {
+            Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) => {
+                let index = ((center.y - self.rect.min.y) / self.line_height().max(1)) as usize;
+                if index == 0 {
+                    bus.push_back(Event::Select(EntryId::CollapseHomeSection(self.kind)));
+                } else if let Some(info) = self.books.get(index - 1).filter(|_| !self.collapsed) {
+                    self.active = Some(index);
+                    rq.add(RenderData::new(self.id, self.line_rect(index), UpdateMode::Gui));
+                    hub.send(Event::Open(Box::new(info.clone()), None)).ok();
+                }
+                true
+            },
+            Event::Gesture(GestureEvent::HoldFingerShort(center, ..)) if self.rect.includes(center) => true,
+            Event::Invalid(ref path) => {
+                if let Some(index) = self.active {
+                    if self.books.get(index - 1).is_some_and(|info| info.file.path == *path) {
+                        self.active = None;
+                        rq.add(RenderData::new(self.id, self.line_rect(index), UpdateMode::Gui));
+                    }
+                }
+                false
+            },
+            _ => false,
+        }
+    }
+
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, fonts: &mut Fonts) {
+        let dpi = CURRENT_DEVICE.dpi;
+
+        fb.draw_rectangle(&self.rect, TEXT_NORMAL[0]);
+
+        // Header
+        let header_rect = self.line_rect(0);
+        let (status_width, padding) = {
+            let font = font_from_style(fonts, &NORMAL_STYLE, dpi);
+            let x_height = font.x_heights.0 as i32;
+            let padding = font.em() as i32;
+            let status = if self.collapsed {
+                format!("{}", self.books.len())
+            } else {
+                "Hide".to_string()
+            };
+            let plan = font.plan(&status, None, None);
+            let dy = (header_rect.height() as i32 - x_height) / 2;
+            let pt = pt!(header_rect.max.x - padding - plan.width, header_rect.max.y - dy);
+            font.render(fb, TEXT_NORMAL[2], &plan, pt);
+            (plan.width, padding)
+        };
+
+        {
+            let font = font_from_style(fonts, &MD_TITLE, dpi);
+            let x_height = font.x_heights.0 as i32;
+            let max_width = header_rect.width() as i32 - 3 * padding - status_width;
+            let plan = font.plan(self.kind.label(), Some(max_width), None);
+            let dy = (header_rect.height() as i32 - x_height) / 2;
+            let pt = pt!(header_rect.min.x + padding, header_rect.max.y - dy);
+            font.render(fb, TEXT_NORMAL[1], &plan, pt);
+        }
+
+        if self.collapsed {
+            return;
+        }
+
+        // Books
+        let font = font_from_style(fonts, &NORMAL_STYLE, dpi);
+        let x_height = font.x_heights.0 as i32;
+        let max_width = self.rect.width() as i32 - 3 * padding;
+
+        for (index, info) in self.books.iter().enumerate() {
+            let line_rect = self.line_rect(index + 1);
+            let scheme = if self.active == Some(index + 1) {
+                TEXT_INVERTED_HARD
+            } else {
+                TEXT_NORMAL
+            };
+            fb.draw_rectangle(&line_rect, scheme[0]);
+            let plan = font.plan(info.label(), Some(max_width), None);
+            let dy = (line_rect.height() as i32 - x_height) / 2;
+            let pt = pt!(line_rect.min.x + 2 * padding, line_rect.max.y - dy);
+            font.render(fb, scheme[1], &plan, pt);
+        }
+    }
+
+    fn rect(&self) -> &Rectangle {
+        &self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut Rectangle {
+        &mut self.rect
+    }
+
+    fn children(&self) -> &Vec<Box<dyn View>> {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Box<dyn View>> {
+        &mut self.children
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+}
@@ -1,17 +1,20 @@
 use std::thread;
 use std::sync::Mutex;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use fxhash::FxHashSet;
 use lazy_static::lazy_static;
-use super::book::Book;
+use super::book::{Book, BookDisplay};
+use super::section::Section;
 use crate::device::CURRENT_DEVICE;
 use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData};
-use crate::view::{BIG_BAR_HEIGHT, THICKNESS_MEDIUM};
+use crate::view::{SMALL_BAR_HEIGHT, BIG_BAR_HEIGHT, THICKNESS_MEDIUM};
 use crate::view::filler::Filler;
-use crate::document::open;
+use crate::document::{open, is_reflowable_kind, Location};
+use crate::library::remove_page_previews;
 use crate::framebuffer::{Framebuffer, UpdateMode};
-use crate::settings::{FirstColumn, SecondColumn};
+use crate::settings::{ShelfLayout, FirstColumn, SecondColumn, HomeSection};
 use crate::geom::{Rectangle, Dir, CycleDir, halves};
-use crate::color::{WHITE, SEPARATOR_NORMAL};
+use crate::color::{WHITE, SEPARATOR_NORMAL, SEPARATOR_STRONG};
 use crate::gesture::GestureEvent;
 use crate::unit::scale_by_dpi;
 use crate::metadata::Info;
@@ -28,79 +31,194 @@ pub struct Shelf {
     pub rect: Rectangle,
     children: Vec<Box<dyn View>>,
     pub max_lines: usize,
-    first_column: FirstColumn,
-    second_column: SecondColumn,
+    display: BookDisplay,
     thumbnail_previews: bool,
+    current_page_previews: bool,
+    sections: Vec<ShelfSection>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShelfSection {
+    pub kind: HomeSection,
+    pub books: Vec<Info>,
+    pub collapsed: bool,
 }
 
 impl Shelf {
-    pub fn new(rect: Rectangle, first_column: FirstColumn, second_column: SecondColumn, thumbnail_previews: bool) -> Shelf {
-        let dpi = CURRENT_DEVICE.dpi;
-        let big_height = scale_by_dpi(BIG_BAR_HEIGHT, dpi) as i32;
-        let thickness = scale_by_dpi(THICKNESS_MEDIUM, dpi) as i32;
-        let max_lines = ((rect.height() as i32 + thickness) / big_height) as usize;
-        Shelf {
+    pub fn new(rect: Rectangle, layout: ShelfLayout, first_column: FirstColumn, second_column: SecondColumn, thumbnail_previews: bool, current_page_previews: bool) -> Shelf {
+        let mut shelf = Shelf {
             id: ID_FEEDER.next(),
             rect,
             children: Vec::new(),
-            max_lines,
-            first_column,
-            second_column,
+            max_lines: 0,
+            display: BookDisplay { layout, first_column, second_column },
             thumbnail_previews,
+            current_page_previews,
+            sections: Vec::new(),
+        };
+        shelf.max_lines = shelf.compute_max_lines();
+        shelf
+    }
+
+    // The height of a line, or of a row of the grid.
+    fn item_height(&self) -> i32 {
+        match self.display.layout {
+            ShelfLayout::List => scale_by_dpi(BIG_BAR_HEIGHT, CURRENT_DEVICE.dpi) as i32,
+            ShelfLayout::Grid => grid_cell_size().1,
+        }
+    }
+
+    // The number of columns and rows of the grid that fits in the given height.
+    fn grid_dims(&self, height: i32) -> (i32, i32) {
+        let (cell_width, cell_height) = grid_cell_size();
+        ((self.rect.width() as i32 / cell_width).max(1),
+         (height / cell_height).max(1))
+    }
+
+    // The sections are shown above the books and reduce the number of lines.
+    pub fn set_sections(&mut self, mut sections: Vec<ShelfSection>) {
+        let max_height = self.rect.height() as i32 - self.item_height();
+
+        // Leave room for at least one book.
+        while !sections.is_empty() && sections_height(&sections) > max_height {
+            if let Some(section) = sections.iter_mut().rev()
+                                           .find(|s| !s.collapsed && !s.books.is_empty()) {
+                section.books.pop();
+            } else {
+                sections.pop();
+            }
+        }
+
+        sections.retain(|s| !s.books.is_empty());
+        self.sections = sections;
+        self.max_lines = self.compute_max_lines();
+    }
+
+    pub fn compute_max_lines(&self) -> usize {
+        let dpi = CURRENT_DEVICE.dpi;
+        let big_height = scale_by_dpi(BIG_BAR_HEIGHT, dpi) as i32;
+        let thickness = scale_by_dpi(THICKNESS_MEDIUM, dpi) as i32;
+        let height = self.rect.height() as i32 - sections_height(&self.sections);
+        match self.display.layout {
+            ShelfLayout::List => ((height + thickness) / big_height) as usize,
+            ShelfLayout::Grid => {
+                let (columns, rows) = self.grid_dims(height);
+                (columns * rows) as usize
+            },
         }
     }
 
+    pub fn set_layout(&mut self, layout: ShelfLayout) {
+        self.display.layout = layout;
+    }
+
     pub fn set_first_column(&mut self, first_column: FirstColumn) {
-        self.first_column = first_column;
+        self.display.first_column = first_column;
     }
 
     pub fn set_second_column(&mut self, second_column: SecondColumn) {
-        self.second_column = second_column;
+        self.display.second_column = second_column;
     }
 
     pub fn set_thumbnail_previews(&mut self, thumbnail_previews: bool) {
         self.thumbnail_previews = thumbnail_previews;
     }
 
-    pub fn update(&mut self, metadata: &[Info], hub: &Hub, rq: &mut RenderQueue, context: &Context) {
+    pub fn set_current_page_previews(&mut self, current_page_previews: bool) {
+        self.current_page_previews = current_page_previews;
+    }
+
+    pub fn update(&mut self, metadata: &[Info], selection: Option<&FxHashSet<PathBuf>>, hub: &Hub, rq: &mut RenderQueue, context: &Context) {
         self.children.clear();
+        let thickness = scale_by_dpi(THICKNESS_MEDIUM, CURRENT_DEVICE.dpi) as i32;
+        let max_lines = self.compute_max_lines();
+        let mut y_pos = self.rect.min.y;
+
+        for section in &self.sections {
+            let height = Section::height(section.books.len(), section.collapsed);
+            let view = Section::new(rect![self.rect.min.x, y_pos,
+                                          self.rect.max.x, y_pos + height],
+                                    section.kind,
+                                    section.books.clone(),
+                                    section.collapsed);
+            self.children.push(Box::new(view) as Box<dyn View>);
+            let separator = Filler::new(rect![self.rect.min.x, y_pos + height,
+                                              self.rect.max.x, y_pos + height + thickness],
+                                        SEPARATOR_STRONG);
+            self.children.push(Box::new(separator) as Box<dyn View>);
+            y_pos += height + thickness;
+        }
+
+        match self.display.layout {
+            ShelfLayout::List => self.update_list(metadata, selection, y_pos, max_lines, hub, context),
+            ShelfLayout::Grid => self.update_grid(metadata, selection, y_pos, hub, context),
+        }
+
+        self.max_lines = max_lines;
+        rq.add(RenderData::new(self.id, self.rect, UpdateMode::Partial));
+    }
+
+    // The path of the preview of the given book. When it doesn't exist, the preview
+    // is created in the background, and an empty path is returned in the meantime.
+    // The previews are created at the size of the grid's covers, the largest one,
+    // and scaled down in the list, hence both layouts can share them.
+    fn preview_path(&self, info: &Info, hub: &Hub, context: &Context) -> PathBuf {
+        let (tw, th) = preview_size();
+        // The pages of the reflowable documents depend on the reader's layout.
+        let current_page = info.reader.as_ref()
+                               .filter(|r| self.current_page_previews && !r.finished && r.current_page > 0 &&
+                                           !is_reflowable_kind(&info.file.kind))
+                               .map(|r| r.current_page);
+        let thumb_path = if let Some(page) = current_page {
+            context.library.page_preview(&info.file.path, page)
+        } else {
+            context.library.thumbnail_preview(&info.file.path)
+        };
+        if !thumb_path.exists() {
+            let hub2 = hub.clone();
+            let thumb_path2 = thumb_path.to_string_lossy().into_owned();
+            let path = info.file.path.clone();
+            let full_path = context.library.home.join(&info.file.path);
+            thread::spawn(move || {
+                // This is a hack to circumvent a segfault (EXC_BAD_ACCESS)
+                // triggered by loading multiple jp2 pixmaps in parallel.
+                let _guard = EXCLUSIVE_ACCESS.lock().unwrap();
+                open(full_path).ok().and_then(|mut doc| {
+                    if let Some(page) = current_page {
+                        doc.dims(page).and_then(|dims| {
+                            let scale = (tw as f32 / dims.0).min(th as f32 / dims.1);
+                            doc.pixmap(Location::Exact(page), scale, CURRENT_DEVICE.color_samples())
+                        }).map(|(pixmap, _)| pixmap)
+                    } else {
+                        doc.preview_pixmap(tw as f32, th as f32, CURRENT_DEVICE.color_samples())
+                    }
+                }).map(|pixmap| {
+                    if pixmap.save(&thumb_path2).is_ok() {
+                        if current_page.is_some() {
+                            remove_page_previews(Path::new(&thumb_path2), Some(Path::new(&thumb_path2)));
+                        }
+                        hub2.send(Event::RefreshBookPreview(path, Some(PathBuf::from(thumb_path2)))).ok();
+                    }
+                })
+            });
+            PathBuf::default()
+        } else {
+            thumb_path
+        }
+    }
+
+    fn update_list(&mut self, metadata: &[Info], selection: Option<&FxHashSet<PathBuf>>, mut y_pos: i32, max_lines: usize, hub: &Hub, context: &Context) {
         let dpi = CURRENT_DEVICE.dpi;
-        let big_height = scale_by_dpi(BIG_BAR_HEIGHT, dpi) as i32;
         let thickness = scale_by_dpi(THICKNESS_MEDIUM, dpi) as i32;
         let (small_thickness, big_thickness) = halves(thickness);
-        let max_lines = ((self.rect.height() as i32 + thickness) / big_height) as usize;
-        let book_heights = divide(self.rect.height() as i32, max_lines as i32);
-        let mut y_pos = self.rect.min.y;
-        let th = big_height;
-        let tw = 3 * th / 4;
+        let book_heights = divide(self.rect.max.y - y_pos, max_lines as i32);
 
         for (index, info) in metadata.iter().enumerate() {
             let y_min = y_pos + if index > 0 { big_thickness } else { 0 };
             let y_max = y_pos + book_heights[index] - if index < max_lines - 1 { small_thickness } else { 0 };
 
-            let preview_path: Option<PathBuf> = if self.thumbnail_previews {
-                let thumb_path = context.library.thumbnail_preview(&info.file.path);
-                if !thumb_path.exists() {
-                    let hub2 = hub.clone();
-                    let thumb_path2 = thumb_path.to_string_lossy().into_owned();
-                    let path = info.file.path.clone();
-                    let full_path = context.library.home.join(&info.file.path);
-                    thread::spawn(move || {
-                        // This is a hack to circumvent a segfault (EXC_BAD_ACCESS)
-                        // triggered by loading multiple jp2 pixmaps in parallel.
-                        let _guard = EXCLUSIVE_ACCESS.lock().unwrap();
-                        open(full_path).and_then(|mut doc| {
-                            doc.preview_pixmap(tw as f32, th as f32, CURRENT_DEVICE.color_samples())
-                        }).map(|pixmap| {
-                            if pixmap.save(&thumb_path2).is_ok() {
-                                hub2.send(Event::RefreshBookPreview(path, Some(PathBuf::from(thumb_path2)))).ok();
-                            }
-                        })
-                    });
-                    Some(PathBuf::default())
-                } else {
-                    Some(thumb_path)
-                }
+            let preview_path = if self.thumbnail_previews {
+                Some(self.preview_path(info, hub, context))
             } else {
                 None
             };
@@ -109,9 +227,9 @@ impl Shelf {
                                        self.rect.max.x, y_max],
                                  info.clone(),
                                  index,
-                                 self.first_column,
-                                 self.second_column,
-                                 preview_path);
+                                 self.display,
+                                 preview_path,
+                                 selection.map(|s| s.contains(&info.file.path)));
             self.children.push(Box::new(book) as Box<dyn View>);
 
             if index < max_lines - 1 {
@@ -131,12 +249,56 @@ impl Shelf {
                                      WHITE);
             self.children.push(Box::new(filler) as Box<dyn View>);
         }
+    }
 
-        self.max_lines = max_lines;
-        rq.add(RenderData::new(self.id, self.rect, UpdateMode::Partial));
+    // The books are placed from left to right and top to bottom. The covers are always shown.
+    fn update_grid(&mut self, metadata: &[Info], selection: Option<&FxHashSet<PathBuf>>, y_pos: i32, hub: &Hub, context: &Context) {
+        let (columns, rows) = self.grid_dims(self.rect.max.y - y_pos);
+        let widths = divide(self.rect.width() as i32, columns);
+        let heights = divide(self.rect.max.y - y_pos, rows);
+
+        let filler = Filler::new(rect![self.rect.min.x, y_pos,
+                                       self.rect.max.x, self.rect.max.y],
+                                 WHITE);
+        self.children.push(Box::new(filler) as Box<dyn View>);
+
+        for (index, info) in metadata.iter().enumerate() {
+            let (row, column) = (index / columns as usize, index % columns as usize);
+            let x_min = self.rect.min.x + widths[..column].iter().sum::<i32>();
+            let y_min = y_pos + heights[..row].iter().sum::<i32>();
+            let preview_path = Some(self.preview_path(info, hub, context));
+            let book = Book::new(rect![x_min, y_min,
+                                       x_min + widths[column], y_min + heights[row]],
+                                 info.clone(),
+                                 index,
+                                 self.display,
+                                 preview_path,
+                                 selection.map(|s| s.contains(&info.file.path)));
+            self.children.push(Box::new(book) as Box<dyn View>);
+        }
     }
 }
 
+// The size of the previews: the covers of the grid.
+fn preview_size() -> (i32, i32) {
+    let th = 2 * scale_by_dpi(BIG_BAR_HEIGHT, CURRENT_DEVICE.dpi) as i32;
+    (3 * th / 4, th)
+}
+
+// The size of the cells of the grid: a cover and its label.
+fn grid_cell_size() -> (i32, i32) {
+    let small_height = scale_by_dpi(SMALL_BAR_HEIGHT, CURRENT_DEVICE.dpi) as i32;
+    let (cover_width, cover_height) = preview_size();
+    (cover_width + small_height / 2, cover_height + small_height)
+}
+
+fn sections_height(sections: &[ShelfSection]) -> i32 {
+    let thickness = scale_by_dpi(THICKNESS_MEDIUM, CURRENT_DEVICE.dpi) as i32;
+    sections.iter()
+            .map(|s| Section::height(s.books.len(), s.collapsed) + thickness)
+            .sum()
+}
+
 impl View for Shelf {
     fn handle_event(&mut self, evt: &Event, _hub: &Hub, bus: &mut Bus, _rq: &mut RenderQueue, _context: &mut Context) -> bool {
         match *evt {
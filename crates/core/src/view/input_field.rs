@@ -22,7 +22,7 @@ pub struct InputField {
     focused: bool,
 }
 
-fn closest_char_boundary(text: &str, index: usize, dir: LinearDir) -> Option<usize> {
+pub fn closest_char_boundary(text: &str, index: usize, dir: LinearDir) -> Option<usize> {
     match dir {
         LinearDir::Backward => {
             if index == 0 {
@@ -43,7 +43,7 @@ fn char_position(text: &str, index: usize) -> Option<usize> {
     text.char_indices().map(|(i, _)| i).position(|i| i == index)
 }
 
-fn word_boundary(text: &str, index: usize, dir: LinearDir) -> usize {
+pub fn word_boundary(text: &str, index: usize, dir: LinearDir) -> usize {
     match dir {
         LinearDir::Backward => {
             if index == 0 {
@@ -224,6 +224,7 @@ impl View for InputField {
                 false
             },
             Event::Keyboard(kbd_evt) if self.focused => {
+                let length = self.text.len();
                 match kbd_evt {
                     KeyboardEvent::Append(c) => {
                         self.text.insert(self.cursor, c);
@@ -254,6 +255,9 @@ impl View for InputField {
                         context.record_input(&self.text, self.view_id);
                     },
                 };
+                if self.text.len() != length {
+                    bus.push_back(Event::TextChanged(self.view_id, self.text.clone()));
+                }
                 rq.add(RenderData::no_wait(self.id, self.rect, UpdateMode::Gui));
                 true
             },
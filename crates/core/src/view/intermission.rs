@@ -102,7 +102,7 @@ impl View for Intermission {
                 fb.draw_blended_pixmap(&pixmap, pt, scheme[1]);
             },
             Message::Image(ref path) => {
-                if let Some(mut doc) = open(path) {
+                if let Ok(mut doc) = open(path) {
                     if let Some((width, height)) = doc.dims(0) {
                         let w_ratio = self.rect.width() as f32 / width;
                         let h_ratio = self.rect.height() as f32 / height;
@@ -123,7 +123,7 @@ impl View for Intermission {
                 }
             },
             Message::Cover(ref path) => {
-                if let Some(mut doc) = open(path) {
+                if let Ok(mut doc) = open(path) {
                     if let Some(pixmap) = doc.preview_pixmap(self.rect.width() as f32,
                                                              self.rect.height() as f32,
                                                              CURRENT_DEVICE.color_samples()) {
@@ -1,6 +1,7 @@
 use fxhash::FxHashMap;
 use lazy_static::lazy_static;
 use serde::Deserialize;
+use anyhow::{Error, bail};
 use crate::device::CURRENT_DEVICE;
 use crate::framebuffer::{Framebuffer, UpdateMode};
 use crate::gesture::GestureEvent;
@@ -25,6 +26,59 @@ pub struct Layout {
     pub widths: Vec<Vec<f32>>,
 }
 
+impl Layout {
+    // Makes sure that the keyboard can be built from this layout.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.is_empty() {
+            bail!("empty name");
+        }
+
+        if self.keys.is_empty() {
+            bail!("no keys");
+        }
+
+        if self.widths.len() != self.keys.len() {
+            bail!("{} rows of widths for {} rows of keys", self.widths.len(), self.keys.len());
+        }
+
+        for (level, outputs) in self.outputs.iter().enumerate() {
+            if outputs.len() != self.keys.len() {
+                bail!("{} rows of outputs at level {} for {} rows of keys",
+                      outputs.len(), level, self.keys.len());
+            }
+        }
+
+        for (i, row) in self.keys.iter().enumerate() {
+            if self.widths[i].len() != row.len() {
+                bail!("{} widths for {} keys in row {}", self.widths[i].len(), row.len(), i);
+            }
+
+            if self.widths[i].iter().any(|w| !w.is_finite() || *w <= 0.0) {
+                bail!("invalid width in row {}", i);
+            }
+
+            // The outputs are indexed from the last key that isn't a printable character.
+            let mut dj = 0;
+            let mut needed = 0;
+            for (j, kind) in row.iter().enumerate() {
+                match kind {
+                    KeyKind::Output(c) if *c != ' ' => needed = needed.max(j - dj + 1),
+                    _ => dj = j + 1,
+                }
+            }
+
+            for (level, outputs) in self.outputs.iter().enumerate() {
+                if outputs[i].len() < needed {
+                    bail!("{} outputs at level {} for {} keys in row {}",
+                          outputs[i].len(), level, needed, i);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct State {
     shift: u8,
@@ -18,6 +18,7 @@ pub mod button;
 pub mod rounded_button;
 pub mod slider;
 pub mod input_field;
+pub mod text_area;
 pub mod page_label;
 pub mod named_input;
 pub mod labeled_icon;
@@ -54,11 +55,13 @@ use fxhash::FxHashMap;
 use downcast_rs::{Downcast, impl_downcast};
 use crate::font::Fonts;
 use crate::color::Color;
-use crate::document::{Location, TextLocation};
-use crate::settings::{ButtonScheme, FirstColumn, SecondColumn, RotationLock};
+use crate::document::{Location, TextLocation, BoundedText};
+use crate::document::text::TextEncoding;
+use self::home::SyncStatus;
+use crate::settings::{ButtonScheme, ShelfLayout, FirstColumn, SecondColumn, HomeSection, RotationLock, BundleImportMode, SearchScope};
 use crate::metadata::{Info, ZoomMode, ScrollMode, SortMethod, TextAlign, SimpleStatus, PageScheme, Margin};
 use crate::geom::{LinearDir, CycleDir, Rectangle, Boundary};
-use crate::framebuffer::{Framebuffer, UpdateMode};
+use crate::framebuffer::{Framebuffer, UpdateMode, Pixmap};
 use crate::input::{DeviceEvent, FingerStatus};
 use crate::gesture::GestureEvent;
 use self::calculator::LineOrigin;
@@ -295,11 +298,15 @@ pub enum Event {
     Gesture(GestureEvent),
     Keyboard(KeyboardEvent),
     Key(KeyKind),
-    Open(Box<Info>),
+    // The optional string is an anchor within the document, e.g. `#chapter3`.
+    Open(Box<Info>, Option<String>),
     OpenHtml(String, Option<String>),
+    OpenAt(PathBuf, usize),
     LoadPixmap(usize),
     Update(UpdateMode),
     RefreshBookPreview(PathBuf, Option<PathBuf>),
+    // The thumbnail of the given page, rendered in the background for the page grid.
+    PageThumbnail(usize, Pixmap),
     Invalid(PathBuf),
     Notify(String),
     Page(CycleDir),
@@ -322,6 +329,7 @@ pub enum Event {
     ToggleNear(ViewId, Rectangle),
     ToggleInputHistoryMenu(ViewId, Rectangle),
     ToggleBookMenu(Rectangle, usize),
+    SelectBook(PathBuf, bool),
     TogglePresetMenu(Rectangle, usize),
     SubMenu(Rectangle, Vec<EntryKind>),
     ProcessLine(LineOrigin, String),
@@ -334,6 +342,9 @@ pub enum Event {
     SearchResult(usize, Vec<Boundary>),
     FetcherAddDocument(u32, Box<Info>),
     FetcherRemoveDocument(u32, PathBuf),
+    FetcherOpen(u32, PathBuf, Option<String>),
+    FetcherProgress(u32, usize, usize),
+    FetcherStatus(u32, SyncStatus),
     FetcherSearch {
         id: u32,
         path: Option<PathBuf>,
@@ -350,6 +361,21 @@ pub enum Event {
     LoadPreset(usize),
     Scroll(i32),
     Save,
+    PageShared(PathBuf),
+    QuoteShared(String),
+    TextChanged(ViewId, String),
+    ApplyFilter(u32, String),
+    ReadAloud(Vec<BoundedText>),
+    StopReadAloud,
+    SpeechPosition([TextLocation; 2]),
+    SpeechStopped,
+    // The text to translate and the language of the book.
+    Translate(String, String),
+    Translation(String),
+    PageTurned(usize),
+    // A link of a page opened by a hook, whose URI starts with `hook:`.
+    HookLink(String),
+    FollowLink(BoundedText),
     Guess,
     CheckBattery,
     SetWifi(bool),
@@ -404,13 +430,15 @@ pub enum ViewId {
     DirectoryMenu,
     BookMenu,
     LibraryMenu,
-    PageMenu,
+    ResultsMenu,
     PresetMenu,
     MarginCropperMenu,
     SearchMenu,
     SketchMenu,
     RenameDocument,
     RenameDocumentInput,
+    BatchEdit,
+    BatchEditInput,
     GoToPage,
     GoToPageInput,
     GoToResultsPage,
@@ -421,6 +449,10 @@ pub enum ViewId {
     EditNoteInput,
     EditLanguages,
     EditLanguagesInput,
+    SearchNotes,
+    SearchNotesInput,
+    SearchAnnotations,
+    SearchAnnotationsInput,
     HomeSearchInput,
     ReaderSearchInput,
     DictionarySearchInput,
@@ -431,10 +463,18 @@ pub enum ViewId {
     Keyboard,
     AboutDialog,
     ShareDialog,
+    LinkPreview,
     MarginCropper,
+    ImageViewer,
+    ScrollBar(Id),
+    ScrollEdge(Id),
+    Footnote,
+    TapZones,
+    PageGrid,
     TopBottomBars,
     TableOfContents,
     MessageNotif(Id),
+    FetcherProgress(u32),
     SubMenu(u8),
 }
 
@@ -512,11 +552,15 @@ pub enum EntryId {
     Load(PathBuf),
     Flush,
     Save,
+    SharePage,
     Import,
+    Sync,
     CleanUp,
     Sort(SortMethod),
     ReverseOrder,
     EmptyTrash,
+    RestoreFromTrash(PathBuf),
+    OpenBook(PathBuf),
     Rename(PathBuf),
     Remove(PathBuf),
     CopyTo(PathBuf, usize),
@@ -525,23 +569,61 @@ pub enum EntryId {
     SelectDirectory(PathBuf),
     ToggleSelectDirectory(PathBuf),
     SetStatus(PathBuf, SimpleStatus),
+    SetHidden(PathBuf, bool),
+    SetRating(PathBuf, Option<u8>),
+    ToggleWarmReading,
     SearchAuthor(String),
+    SearchKind(String),
+    Description(PathBuf),
+    StartSelection(PathBuf),
+    StopSelection,
+    SelectPageBooks,
+    BatchSetAuthor,
+    BatchSetSeries,
+    BatchAddCategory,
+    AddCategory(PathBuf),
+    UndoBatchEdit,
     RemovePreset(usize),
     FirstColumn(FirstColumn),
     SecondColumn(SecondColumn),
+    ShelfLayout(ShelfLayout),
     ThumbnailPreviews,
+    CurrentPagePreviews,
+    ShowHomeSection(HomeSection),
+    CollapseHomeSection(HomeSection),
     ApplyCroppings(usize, PageScheme),
     RemoveCroppings,
+    TapZones,
+    PageGrid,
     SetZoomMode(ZoomMode),
     SetScrollMode(ScrollMode),
+    SetColumnSplit(Option<i32>),
+    SetEncoding(Option<TextEncoding>),
+    SetPaperTint(Option<u8>),
+    ToggleRightToLeft,
+    ToggleAutoContrast,
+    ToggleReadingRuler,
+    ToggleReadAloud,
+    ExportFlashcards,
+    ExportText,
+    ImportAnnotations,
     SetPageName,
     RemovePageName,
     HighlightSelection,
+    HighlightAllResults,
     AnnotateSelection,
     DefineSelection,
     SearchForSelection,
+    CopySelectionToClippings,
+    ShareSelection,
+    SpeakSelection,
+    TranslateSelection,
     AdjustSelection,
     Annotations,
+    ExpandHighlights,
+    SearchAnnotations,
+    SearchNotes,
+    ContinueReading,
     Bookmarks,
     RemoveAnnotation([TextLocation; 2]),
     EditAnnotationNote([TextLocation; 2]),
@@ -549,11 +631,13 @@ pub enum EntryId {
     GoTo(usize),
     GoToSelectedPageName,
     SearchDirection(LinearDir),
+    SearchScope(SearchScope),
     SetButtonScheme(ButtonScheme),
     SetFontFamily(String),
     SetFontSize(i32),
     SetTextAlign(TextAlign),
     SetMarginWidth(i32),
+    SetMarginBias(i32),
     SetLineHeight(i32),
     SetContrastExponent(i32),
     SetContrastGray(i32),
@@ -575,6 +659,8 @@ pub enum EntryId {
     New,
     Refresh,
     TakeScreenshot,
+    ExportSettings,
+    ImportSettings(BundleImportMode),
     Reboot,
     Quit,
 }
@@ -45,24 +45,28 @@ impl PageLabel {
     }
 
     pub fn text(&self, size: u8) -> String {
-        if self.pages_count == 0 {
-            return "No pages".to_string();
-        }
-        let (current_page, pages_count, precision) = if self.synthetic {
-            (self.current_page as f64 / BYTES_PER_PAGE,
-             self.pages_count as f64 / BYTES_PER_PAGE, 1)
-        } else {
-            (self.current_page as f64 + 1.0,
-             self.pages_count as f64, 0)
-        };
-        let percent = 100.0 * self.current_page as f32 / self.pages_count as f32;
-        match size {
-            0 => format!("Page {1:.0$} of {2:.0$} ({3:.1}%)", precision, current_page, pages_count, percent),
-            1 => format!("P. {1:.0$} of {2:.0$} ({3:.1}%)", precision, current_page, pages_count, percent),
-            2 => format!("{1:.0$}/{2:.0$} ({3:.1}%)", precision, current_page, pages_count, percent),
-            3 => format!("{1:.0$} ({2:.1}%)", precision, current_page, percent),
-            _ => format!("{:.1}%", percent),
-        }
+        page_text(self.current_page, self.pages_count, self.synthetic, size)
+    }
+}
+
+pub fn page_text(current_page: usize, pages_count: usize, synthetic: bool, size: u8) -> String {
+    if pages_count == 0 {
+        return "No pages".to_string();
+    }
+    let percent = 100.0 * current_page as f32 / pages_count as f32;
+    let (current_page, pages_count, precision) = if synthetic {
+        (current_page as f64 / BYTES_PER_PAGE,
+         pages_count as f64 / BYTES_PER_PAGE, 1)
+    } else {
+        (current_page as f64 + 1.0,
+         pages_count as f64, 0)
+    };
+    match size {
+        0 => format!("Page {1:.0$} of {2:.0$} ({3:.1}%)", precision, current_page, pages_count, percent),
+        1 => format!("P. {1:.0$} of {2:.0$} ({3:.1}%)", precision, current_page, pages_count, percent),
+        2 => format!("{1:.0$}/{2:.0$} ({3:.1}%)", precision, current_page, pages_count, percent),
+        3 => format!("{1:.0$} ({2:.1}%)", precision, current_page, percent),
+        _ => format!("{:.1}%", percent),
     }
 }
 
@@ -75,7 +79,7 @@ impl View for PageLabel {
                 true
             },
             Event::Gesture(GestureEvent::HoldFingerShort(center, ..)) if self.rect.includes(center) => {
-                bus.push_back(Event::ToggleNear(ViewId::PageMenu, self.rect));
+                bus.push_back(Event::Toggle(ViewId::GoToPage));
                 true
             },
             _ => false,
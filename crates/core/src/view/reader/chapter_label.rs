@@ -44,9 +44,10 @@ impl ChapterLabel {
 
 
 impl View for ChapterLabel {
-    fn handle_event(&mut self, evt: &Event, _hub: &Hub, bus: &mut Bus, _rq: &mut RenderQueue, _context: &mut Context) -> bool {
+    fn handle_event(&mut self, evt: &Event, _hub: &Hub, bus: &mut Bus, _rq: &mut RenderQueue, context: &mut Context) -> bool {
         match *evt {
-            Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) => {
+            Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) &&
+                                                         context.settings.reader.chapter_label_toc => {
                 bus.push_back(Event::Show(ViewId::TableOfContents));
                 true
             },
@@ -0,0 +1,130 @@
+use crate::device::CURRENT_DEVICE;
+use crate::framebuffer::{Framebuffer, UpdateMode, Pixmap};
+use crate::document::{Document, Location};
+use crate::document::html::HtmlDocument;
+use crate::document::text::text_to_html;
+use crate::geom::{Rectangle, CornerSpec, BorderSpec, Dir};
+use crate::font::{Fonts, font_from_style, NORMAL_STYLE};
+use crate::color::{BLACK, WHITE};
+use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData, ViewId};
+use crate::view::{THICKNESS_LARGE, BORDER_RADIUS_MEDIUM};
+use crate::gesture::GestureEvent;
+use crate::settings::DEFAULT_FONT_FAMILY;
+use crate::unit::scale_by_dpi;
+use crate::context::Context;
+
+// Shows the text of a footnote in a box at the bottom of the page.
+// Swiping inside the box turns the pages of the note, tapping anywhere closes it.
+pub struct Footnote {
+    id: Id,
+    rect: Rectangle,
+    children: Vec<Box<dyn View>>,
+    doc: HtmlDocument,
+    location: usize,
+    pixmap: Pixmap,
+    // The rectangle where the text is drawn.
+    frame: Rectangle,
+}
+
+impl Footnote {
+    pub fn new(rect: Rectangle, text: &str, font_family: &str, font_size: f32, context: &mut Context) -> Footnote {
+        let dpi = CURRENT_DEVICE.dpi;
+        let padding = font_from_style(&mut context.fonts, &NORMAL_STYLE, dpi).em() as i32;
+        let height = rect.height() as i32 / 3;
+        let rect = rect![rect.min.x + padding, rect.max.y - padding - height,
+                         rect.max.x - padding, rect.max.y - padding];
+        let frame = rect![rect.min.x + padding, rect.min.y + padding,
+                          rect.max.x - padding, rect.max.y - padding];
+
+        let mut doc = HtmlDocument::new_from_memory(&text_to_html(text, ""));
+        doc.layout(frame.width(), frame.height(), font_size, dpi);
+        doc.set_margin_width(0);
+        if font_family != DEFAULT_FONT_FAMILY {
+            doc.set_font_family(font_family, &context.settings.reader.font_path);
+        }
+
+        let (pixmap, location) = doc.pixmap(Location::Exact(0), 1.0, CURRENT_DEVICE.color_samples())
+                                    .unwrap_or_else(|| (Pixmap::empty(0, 0, 1), 0));
+
+        Footnote {
+            id: ID_FEEDER.next(),
+            rect,
+            children: Vec::new(),
+            doc,
+            location,
+            pixmap,
+            frame,
+        }
+    }
+
+    fn go_to_neighbor(&mut self, dir: Dir, rq: &mut RenderQueue) {
+        let location = match dir {
+            Dir::West => Location::Next(self.location),
+            Dir::East => Location::Previous(self.location),
+            _ => return,
+        };
+        if let Some((pixmap, location)) = self.doc.pixmap(location, 1.0, CURRENT_DEVICE.color_samples()) {
+            self.pixmap = pixmap;
+            self.location = location;
+            rq.add(RenderData::new(self.id, self.frame, UpdateMode::Gui));
+        }
+    }
+}
+
+impl View for Footnote {
+    fn handle_event(&mut self, evt: &Event, _hub: &Hub, bus: &mut Bus, rq: &mut RenderQueue, _context: &mut Context) -> bool {
+        match *evt {
+            Event::Gesture(GestureEvent::Tap(..)) => {
+                bus.push_back(Event::Close(ViewId::Footnote));
+                true
+            },
+            Event::Gesture(GestureEvent::Swipe { dir, start, .. }) if self.rect.includes(start) => {
+                self.go_to_neighbor(dir, rq);
+                true
+            },
+            Event::Gesture(GestureEvent::HoldFingerShort(center, ..)) if self.rect.includes(center) => true,
+            _ => false,
+        }
+    }
+
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, _fonts: &mut Fonts) {
+        let dpi = CURRENT_DEVICE.dpi;
+        let border_radius = scale_by_dpi(BORDER_RADIUS_MEDIUM, dpi) as i32;
+        let border_thickness = scale_by_dpi(THICKNESS_LARGE, dpi) as u16;
+
+        fb.draw_rounded_rectangle_with_border(&self.rect,
+                                              &CornerSpec::Uniform(border_radius),
+                                              &BorderSpec { thickness: border_thickness,
+                                                            color: BLACK },
+                                              &WHITE);
+
+        let width = (self.pixmap.width as i32).min(self.frame.width() as i32);
+        let height = (self.pixmap.height as i32).min(self.frame.height() as i32);
+        fb.draw_rectangle(&self.frame, WHITE);
+        fb.draw_framed_pixmap(&self.pixmap, &rect![0, 0, width, height], self.frame.min);
+    }
+
+    fn rect(&self) -> &Rectangle {
+        &self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut Rectangle {
+        &mut self.rect
+    }
+
+    fn children(&self) -> &Vec<Box<dyn View>> {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Box<dyn View>> {
+        &mut self.children
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn view_id(&self) -> Option<ViewId> {
+        Some(ViewId::Footnote)
+    }
+}
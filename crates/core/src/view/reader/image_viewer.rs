@@ -0,0 +1,174 @@
+use std::sync::{Arc, Mutex};
+use crate::framebuffer::{Framebuffer, UpdateMode, Pixmap};
+use crate::document::{Document, Location};
+use crate::gesture::GestureEvent;
+use crate::input::{DeviceEvent, ButtonCode, ButtonStatus};
+use crate::font::Fonts;
+use crate::geom::{Rectangle, Boundary, Point};
+use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData, ViewId};
+use crate::color::WHITE;
+use crate::device::CURRENT_DEVICE;
+use crate::context::Context;
+
+// The maximum zoom, relative to the scale at which the image fits the screen.
+const MAX_ZOOM: f32 = 4.0;
+
+// The whole page is rendered, hence the pixmap's area is capped, relative to the screen's area,
+// to avoid exhausting the memory when a small image of a large page is zoomed.
+const MAX_PIXMAP_AREA: f32 = 9.0;
+
+// Shows one image of a page on the whole screen.
+// The image can be zoomed with the pinch and spread gestures, and panned by swiping.
+pub struct ImageViewer {
+    id: Id,
+    rect: Rectangle,
+    children: Vec<Box<dyn View>>,
+    doc: Arc<Mutex<Box<dyn Document>>>,
+    location: usize,
+    boundary: Boundary,
+    fit_scale: f32,
+    max_scale: f32,
+    scale: f32,
+    pixmap: Pixmap,
+    // The image's rectangle within the pixmap.
+    frame: Rectangle,
+    // The top left corner of the visible part of the pixmap.
+    offset: Point,
+}
+
+impl ImageViewer {
+    pub fn new(rect: Rectangle, doc: Arc<Mutex<Box<dyn Document>>>, location: usize, boundary: Boundary) -> ImageViewer {
+        let fit_scale = (rect.width() as f32 / boundary.width())
+                        .min(rect.height() as f32 / boundary.height());
+        let max_zoom_scale = MAX_ZOOM * fit_scale;
+        let max_scale = doc.lock().unwrap().dims(location).map_or(max_zoom_scale, |(width, height)| {
+            let area_ratio = MAX_PIXMAP_AREA * rect.area() as f32 / (width * height).max(1.0);
+            area_ratio.sqrt().min(max_zoom_scale)
+        }).max(fit_scale);
+        let mut image_viewer = ImageViewer {
+            id: ID_FEEDER.next(),
+            rect,
+            children: Vec::new(),
+            doc,
+            location,
+            boundary,
+            fit_scale,
+            max_scale,
+            scale: fit_scale,
+            pixmap: Pixmap::empty(0, 0, 1),
+            frame: Rectangle::default(),
+            offset: pt!(0, 0),
+        };
+        image_viewer.load_pixmap(fit_scale);
+        image_viewer.offset = image_viewer.frame.min;
+        image_viewer
+    }
+
+    fn load_pixmap(&mut self, scale: f32) -> bool {
+        let pixmap = self.doc.lock().unwrap()
+                         .pixmap(Location::Exact(self.location), scale, CURRENT_DEVICE.color_samples());
+        if let Some((pixmap, _)) = pixmap {
+            let bounds = rect![0, 0, pixmap.width as i32, pixmap.height as i32];
+            self.frame = (self.boundary * scale).to_rect().intersection(&bounds).unwrap_or(bounds);
+            self.pixmap = pixmap;
+            self.scale = scale;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Returns the visible part of the pixmap and its position on the screen.
+    fn visible_frame(&self) -> (Rectangle, Point) {
+        let width = (self.frame.width() as i32).min(self.rect.width() as i32);
+        let height = (self.frame.height() as i32).min(self.rect.height() as i32);
+        let x_min = self.offset.x.max(self.frame.min.x).min(self.frame.max.x - width);
+        let y_min = self.offset.y.max(self.frame.min.y).min(self.frame.max.y - height);
+        let position = pt!(self.rect.min.x + (self.rect.width() as i32 - width) / 2,
+                           self.rect.min.y + (self.rect.height() as i32 - height) / 2);
+        (rect![x_min, y_min, x_min + width, y_min + height], position)
+    }
+
+    fn zoom(&mut self, center: Point, factor: f32) -> bool {
+        let scale = (self.scale * factor).max(self.fit_scale).min(self.max_scale);
+        if (scale - self.scale).abs() < f32::EPSILON {
+            return false;
+        }
+
+        let (frame, position) = self.visible_frame();
+        let ratio = scale / self.scale;
+        let anchor = frame.min + center - position;
+
+        if !self.load_pixmap(scale) {
+            return false;
+        }
+
+        self.offset = pt!((ratio * anchor.x as f32) as i32,
+                          (ratio * anchor.y as f32) as i32) - (center - position);
+        self.offset = self.visible_frame().0.min;
+        true
+    }
+}
+
+impl View for ImageViewer {
+    fn handle_event(&mut self, evt: &Event, _hub: &Hub, bus: &mut Bus, rq: &mut RenderQueue, _context: &mut Context) -> bool {
+        match *evt {
+            Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) => {
+                bus.push_back(Event::Close(ViewId::ImageViewer));
+                true
+            },
+            Event::Device(DeviceEvent::Button { code: ButtonCode::Home, status: ButtonStatus::Pressed, .. }) => {
+                bus.push_back(Event::Close(ViewId::ImageViewer));
+                true
+            },
+            Event::Gesture(GestureEvent::Spread { center, factor, .. }) |
+            Event::Gesture(GestureEvent::Pinch { center, factor, .. }) if factor.is_finite() && self.rect.includes(center) => {
+                if self.zoom(center, factor) {
+                    rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+                }
+                true
+            },
+            Event::Gesture(GestureEvent::Swipe { start, end, .. }) if self.rect.includes(start) => {
+                let offset = self.visible_frame().0.min;
+                self.offset = offset + start - end;
+                if self.visible_frame().0.min != offset {
+                    rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+                }
+                self.offset = self.visible_frame().0.min;
+                true
+            },
+            Event::Gesture(GestureEvent::HoldFingerShort(center, ..)) if self.rect.includes(center) => true,
+            _ => false,
+        }
+    }
+
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, _fonts: &mut Fonts) {
+        let (frame, position) = self.visible_frame();
+        fb.draw_rectangle(&self.rect, WHITE);
+        fb.draw_framed_pixmap(&self.pixmap, &frame, position);
+    }
+
+    fn rect(&self) -> &Rectangle {
+        &self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut Rectangle {
+        &mut self.rect
+    }
+
+    fn children(&self) -> &Vec<Box<dyn View>> {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Box<dyn View>> {
+        &mut self.children
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn view_id(&self) -> Option<ViewId> {
+        Some(ViewId::ImageViewer)
+    }
+}
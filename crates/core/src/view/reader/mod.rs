@@ -4,18 +4,30 @@ mod results_bar;
 mod margin_cropper;
 mod chapter_label;
 mod results_label;
-
+mod tap_zones;
+mod image_viewer;
+mod footnote;
+mod scroll_bar;
+mod scroll_edge;
+mod page_grid;
+mod note_editor;
+
+use std::env;
+use std::mem;
 use std::thread;
+use std::time::{Instant, Duration};
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering as AtomicOrdering;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::io::prelude::*;
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::collections::{VecDeque, BTreeMap};
+use anyhow::{Error, format_err};
 use fxhash::{FxHashMap, FxHashSet};
 use chrono::Local;
 use regex::Regex;
+use serde_json::Value as JsonValue;
 use septem::prelude::*;
 use septem::{Roman, Digit};
 use rand_core::RngCore;
@@ -23,13 +35,20 @@ use crate::input::{DeviceEvent, FingerStatus, ButtonCode, ButtonStatus};
 use crate::framebuffer::{Framebuffer, UpdateMode, Pixmap};
 use crate::view::{View, Event, AppCmd, Hub, Bus, RenderQueue, RenderData};
 use crate::view::{ViewId, Id, ID_FEEDER, EntryKind, EntryId, SliderId};
-use crate::view::{SMALL_BAR_HEIGHT, BIG_BAR_HEIGHT, THICKNESS_MEDIUM};
+use crate::view::{SMALL_BAR_HEIGHT, BIG_BAR_HEIGHT, THICKNESS_SMALL, THICKNESS_MEDIUM};
 use crate::unit::{scale_by_dpi, mm_to_px};
 use crate::device::CURRENT_DEVICE;
-use crate::helpers::AsciiExtension;
-use crate::font::Fonts;
+use crate::helpers::{load_json, save_json, datetime_format, AsciiExtension};
+use crate::font::{Fonts, font_from_style, PAGE_INDICATOR_STYLE};
 use crate::font::family_names;
 use self::margin_cropper::{MarginCropper, BUTTON_DIAMETER};
+use self::image_viewer::ImageViewer;
+use self::footnote::Footnote;
+use self::scroll_bar::{ScrollBar, SCROLL_BAR_WIDTH};
+use self::scroll_edge::{ScrollEdge, SCROLL_EDGE_HEIGHT};
+use self::tap_zones::TapZones;
+use self::page_grid::PageGrid;
+use self::note_editor::NoteEditor;
 use super::top_bar::TopBar;
 use self::tool_bar::ToolBar;
 use self::bottom_bar::BottomBar;
@@ -43,20 +62,26 @@ use crate::view::keyboard::Keyboard;
 use crate::view::menu::{Menu, MenuKind};
 use crate::view::menu_entry::MenuEntry;
 use crate::view::notification::Notification;
-use crate::settings::{guess_frontlight, FinishedAction, SouthEastCornerAction, BottomRightGestureAction, SouthStripAction, WestStripAction, EastStripAction};
+use crate::view::dialog::Dialog;
+use crate::view::page_label::page_text;
+use crate::settings::{guess_frontlight, Settings, SelectionGranularity, FinishedAction, SouthEastCornerAction, BottomRightGestureAction, NorthStripAction, SouthStripAction, WestStripAction, EastStripAction, HomeButtonAction, CrossGestureAction, HoldButtonAction, AnnotationsFormat, PageIndicatorCorner, PageSeparatorSettings, AnnotationStyle, VerticalSwipeAction};
+use crate::settings::{SelectionMenuEntry, SearchScope, DEFAULT_SELECTION_MENU};
 use crate::settings::{DEFAULT_FONT_FAMILY, DEFAULT_TEXT_ALIGN, DEFAULT_LINE_HEIGHT, DEFAULT_MARGIN_WIDTH};
 use crate::settings::{HYPHEN_PENALTY, STRETCH_TOLERANCE};
 use crate::frontlight::LightLevels;
 use crate::gesture::GestureEvent;
-use crate::document::{Document, open, Location, TextLocation, BoundedText, Neighbors, BYTES_PER_PAGE};
-use crate::document::{TocEntry, SimpleTocEntry, TocLocation, toc_as_html, annotations_as_html, bookmarks_as_html};
+use crate::document::{Document, open_with_encoding, Location, TextLocation, BoundedText, Neighbors, BYTES_PER_PAGE};
+use crate::document::{TocEntry, SimpleTocEntry, TocLocation, toc_as_html, annotations_as_html, annotations_as_csv, annotations_as_markdown, bookmarks_as_html};
+use crate::document::EXPAND_HIGHLIGHTS_URI;
+use crate::document::{format_quote, merge_annotations};
 use crate::document::html::HtmlDocument;
+use crate::document::text::TextEncoding;
 use crate::metadata::{Info, FileInfo, ReaderInfo, Annotation, TextAlign, ZoomMode, ScrollMode, PageScheme};
 use crate::metadata::{Margin, CroppingMargins, make_query};
 use crate::metadata::{DEFAULT_CONTRAST_EXPONENT, DEFAULT_CONTRAST_GRAY};
 use crate::geom::{Point, Vec2, Rectangle, Boundary, CornerSpec, BorderSpec};
 use crate::geom::{Dir, DiagDir, CycleDir, LinearDir, Axis, Region, halves};
-use crate::color::{BLACK, WHITE};
+use crate::color::{Color, BLACK, WHITE, GRAY08, GRAY12, GRAY13, GRAY14, SEPARATOR_NORMAL};
 use crate::context::Context;
 
 const HISTORY_SIZE: usize = 32;
@@ -64,6 +89,14 @@ const RECT_DIST_JITTER: f32 = 24.0;
 const ANNOTATION_DRIFT: u8 =  0x44;
 const HIGHLIGHT_DRIFT: u8 =  0x22;
 const MEM_SCHEME: &str = "mem:";
+const CLIPPINGS_FILENAME: &str = "clippings.txt";
+// The hidden directories aren't imported.
+const TEXT_EXPORTS_DIRNAME: &str = ".text-exports";
+const TRAILING_PUNCTUATION: [char; 12] = ['.', ',', ';', ':', '!', '?', ')', ']', '"', '\'', '»', '”'];
+const PAGE_INDICATOR_COLOR: Color = GRAY08;
+// The height of the reading ruler, in millimeters.
+const READING_RULER_HEIGHT: f32 = 6.0;
+const READING_RULER_ALPHA: f32 = 0.12;
 
 pub struct Reader {
     id: Id,
@@ -88,11 +121,38 @@ pub struct Reader {
     pages_count: usize,
     view_port: ViewPort,
     contrast: Contrast,
+    // Derive the contrast of each page from its gray levels.
+    auto_contrast: bool,
+    // The position of the reading ruler, as a fraction of the page's height.
+    reading_ruler: Option<f32>,
     synthetic: bool,
     page_turns: usize,
     reflowable: bool,
     ephemeral: bool,
     finished: bool,
+    // The page at which the end of a chapter was announced.
+    chapter_end: Option<usize>,
+    page_indicator: Option<PageIndicatorCorner>,
+    immersive: bool,
+    queued_notifications: Vec<String>,
+    page_separator: PageSeparatorSettings,
+    // Find links in the words of a text document.
+    linkify_text: bool,
+    annotation_style: AnnotationStyle,
+    warm_paper: Option<u8>,
+    // The pages progress from right to left.
+    right_to_left: bool,
+    read_aloud: Option<ReadAloud>,
+    // Whether the text export, if any, is still running.
+    text_export: Option<Arc<AtomicBool>>,
+    // Thumbnails of the pages, kept between two openings of the page grid.
+    thumbnails: FxHashMap<usize, Pixmap>,
+    // Tells, for each location, if the page is blank.
+    blank_pages: FxHashMap<usize, bool>,
+    // The annotations when the book was opened: they're only exported if they changed.
+    initial_annotations: Vec<Annotation>,
+    // The time of the last accepted page turning tap.
+    last_page_turn_tap: Option<Instant>,
 }
 
 #[derive(Debug)]
@@ -100,6 +160,7 @@ struct ViewPort {
     zoom_mode: ZoomMode,
     scroll_mode: ScrollMode,
     page_offset: Point,   // Offset relative to the top left corner of a resource's frame.
+    column: usize,        // Index of the displayed column when the pages are split.
     margin_width: i32,
 }
 
@@ -109,6 +170,7 @@ impl Default for ViewPort {
             zoom_mode: ZoomMode::FitToPage,
             scroll_mode: ScrollMode::Screen,
             page_offset: pt!(0, 0),
+            column: 0,
             margin_width: 0,
         }
     }
@@ -125,7 +187,17 @@ enum State {
 struct Selection {
     start: TextLocation,
     end: TextLocation,
-    anchor: TextLocation,
+    anchor: [TextLocation; 2],
+}
+
+// The state of the reading aloud, done by an external speaker.
+#[derive(Debug, Default)]
+struct ReadAloud {
+    // The words being spoken.
+    range: Option<[TextLocation; 2]>,
+    // The location of the last page sent to the speaker, and its last word.
+    last_location: usize,
+    last_word: Option<TextLocation>,
 }
 
 #[derive(Debug)]
@@ -133,6 +205,8 @@ struct Resource {
     pixmap: Pixmap,
     frame: Rectangle,  // The pixmap's rectangle minus the cropping margins.
     scale: f32,
+    // The contrast derived from the pixmap, when the automatic contrast is enabled.
+    contrast: Option<Contrast>,
 }
 
 #[derive(Debug, Clone)]
@@ -164,7 +238,20 @@ impl Default for Search {
     }
 }
 
-#[derive(Debug)]
+impl Search {
+    // Rank of the first result of the current results page, starting at one.
+    fn current_result(&self) -> usize {
+        if self.highlights.is_empty() {
+            return 0;
+        }
+        1 + self.highlights.values()
+                .take(self.current_page)
+                .map(|groups| groups.len())
+                .sum::<usize>()
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
 struct Contrast {
     exponent: f32,
     gray: f32,
@@ -179,6 +266,56 @@ impl Default for Contrast {
     }
 }
 
+const TAP_REGIONS: [Region; 9] = [Region::Corner(DiagDir::NorthWest), Region::Strip(Dir::North),
+                                  Region::Corner(DiagDir::NorthEast), Region::Strip(Dir::West),
+                                  Region::Center, Region::Strip(Dir::East),
+                                  Region::Corner(DiagDir::SouthWest), Region::Strip(Dir::South),
+                                  Region::Corner(DiagDir::SouthEast)];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum TapAction {
+    PreviousLocation,
+    ToggleBookmark,
+    GoToPage,
+    TableOfContents,
+    Page(CycleDir),
+    // Follows the reading direction.
+    PageTurn(CycleDir),
+    ResultsPage(CycleDir),
+    // The signs of the horizontal and vertical scrolling steps.
+    Scroll(Point),
+    ToggleBars,
+    ToggleTopBar,
+    None,
+}
+
+impl TapAction {
+    fn label(self) -> Option<&'static str> {
+        match self {
+            TapAction::PreviousLocation => Some("Previous Location"),
+            TapAction::ToggleBookmark => Some("Bookmark"),
+            TapAction::GoToPage => Some("Go to Page"),
+            TapAction::TableOfContents => Some("Table of Contents"),
+            TapAction::Page(CycleDir::Previous) |
+            TapAction::PageTurn(CycleDir::Previous) => Some("Previous Page"),
+            TapAction::Page(CycleDir::Next) |
+            TapAction::PageTurn(CycleDir::Next) => Some("Next Page"),
+            TapAction::ResultsPage(CycleDir::Previous) => Some("Previous Results Page"),
+            TapAction::ResultsPage(CycleDir::Next) => Some("Next Results Page"),
+            TapAction::Scroll(..) => Some("Scroll"),
+            TapAction::ToggleBars => Some("Toggle Bars"),
+            TapAction::ToggleTopBar => Some("Toggle Top Bar"),
+            TapAction::None => None,
+        }
+    }
+
+    // The page turning taps that follow each other too closely are ignored.
+    fn turns_page(self) -> bool {
+        matches!(self, TapAction::Page(..) | TapAction::PageTurn(..) |
+                       TapAction::ResultsPage(..) | TapAction::Scroll(..))
+    }
+}
+
 fn scaling_factor(rect: &Rectangle, cropping_margin: &Margin, screen_margin_width: i32, dims: (f32, f32), zoom_mode: ZoomMode) -> f32 {
     if let ZoomMode::Custom(sf) = zoom_mode {
         return sf;
@@ -200,11 +337,124 @@ fn scaling_factor(rect: &Rectangle, cropping_margin: &Margin, screen_margin_widt
     }
 }
 
+// The squared maximum distance between a touch point and a selectable word.
+fn hit_distance(context: &Context) -> u32 {
+    (scale_by_dpi(context.settings.reader.selection_hit_radius, CURRENT_DEVICE.dpi) as i32).pow(2) as u32
+}
+
+// A file name made of the given title and suffix. The characters of the title that
+// aren't allowed in file names, as well as the spaces, are replaced by underscores.
+fn title_filename(title: &str, suffix: &str) -> String {
+    let stem: String = title.to_lowercase().chars().map(|c| {
+        if c.is_whitespace() || c.is_control() || "/\\:*?\"<>|".contains(c) {
+            '_'
+        } else {
+            c
+        }
+    }).collect();
+    format!("{}{}", stem, suffix)
+}
+
+// The file, in the library's directory, to which the annotations are exported, and from
+// which the JSON annotations are imported.
+fn annotations_filename(title: &str, extension: &str) -> String {
+    title_filename(title, &format!("-annotations.{}", extension))
+}
+
+// Reads the annotations of a JSON file that exist in the given document.
+// Returns these annotations and the number of entries of the file.
+fn load_annotations(doc: &mut dyn Document, path: &Path) -> Result<(Vec<Annotation>, usize), Error> {
+    let values = load_json::<Vec<JsonValue>, _>(path)?;
+    let total_count = values.len();
+    let reflowable = doc.is_reflowable();
+    let annotations = values.into_iter()
+                            .filter(|value| value.get("selection").is_some())
+                            .filter_map(|value| serde_json::from_value::<Annotation>(value).ok())
+                            .filter(|annot| {
+                                let [start, end] = annot.selection;
+                                start <= end && annot.selection.iter().all(|tl| {
+                                    matches!(tl, TextLocation::Dynamic(..)) == reflowable &&
+                                    doc.resolve_location(Location::Exact(tl.location())).is_some()
+                                })
+                            })
+                            .collect::<Vec<Annotation>>();
+    Ok((annotations, total_count))
+}
+
+fn clamp_zoom(sf: f32, settings: &Settings) -> f32 {
+    sf.max(settings.reader.min_zoom).min(settings.reader.max_zoom)
+}
+
 fn build_pixmap(rect: &Rectangle, doc: &mut dyn Document, location: usize) -> (Pixmap, usize) {
     let scale = scaling_factor(rect, &Margin::default(), 0, doc.dims(location).unwrap(), ZoomMode::FitToPage);
     doc.pixmap(Location::Exact(location), scale, CURRENT_DEVICE.color_samples()).unwrap()
 }
 
+// The width, in pixels, at which pages are rendered to tell if they're blank.
+const BLANK_PAGE_WIDTH: f32 = 256.0;
+// The maximum difference between the gray level of a blank page's pixel and the background.
+const BLANK_PAGE_TOLERANCE: i32 = 48;
+
+// A page is blank when the share of its pixels that stand out from the most common
+// gray level doesn't exceed *threshold*.
+fn is_blank(pixmap: &Pixmap, threshold: f32) -> bool {
+    let mut histogram = [0usize; 256];
+    for pixel in pixmap.data.chunks(pixmap.samples) {
+        let gray = pixel.iter().map(|&v| v as usize).sum::<usize>() / pixel.len();
+        histogram[gray] += 1;
+    }
+    let total = histogram.iter().sum::<usize>();
+    if total == 0 {
+        return false;
+    }
+    let background = (0..256).max_by_key(|&v| histogram[v]).unwrap_or(255) as i32;
+    let outliers = histogram.iter().enumerate()
+                            .filter(|(v, _)| (*v as i32 - background).abs() > BLANK_PAGE_TOLERANCE)
+                            .map(|(_, count)| count).sum::<usize>();
+    outliers as f32 / total as f32 <= threshold
+}
+
+// The minimum difference between the paper and ink levels of a page whose contrast is adjusted.
+const AUTO_CONTRAST_MIN_SPREAD: f32 = 32.0;
+const AUTO_CONTRAST_MAX_EXPONENT: f32 = 5.0;
+
+// Stretches the gray levels of a scanned page: the ink is darkened and the paper is
+// lightened around the level halfway between them. The ink level is given by the
+// darkest pixels and the paper level by the median one.
+fn auto_contrast(pixmap: &Pixmap) -> Contrast {
+    let mut histogram = [0u32; 256];
+    for pixel in pixmap.data.chunks(pixmap.samples) {
+        let level = pixel.iter().map(|&v| v as usize).sum::<usize>() / pixel.len();
+        histogram[level] += 1;
+    }
+
+    let total = histogram.iter().sum::<u32>();
+    let percentile = |fraction: f32| {
+        let target = (fraction * total as f32) as u32;
+        let mut count = 0;
+        for (level, n) in histogram.iter().enumerate() {
+            count += n;
+            if count > target {
+                return level as f32;
+            }
+        }
+        255.0
+    };
+
+    let ink = percentile(0.01);
+    let paper = percentile(0.5);
+
+    // Blank pages and pictures are left untouched.
+    if total == 0 || paper - ink < AUTO_CONTRAST_MIN_SPREAD {
+        return Contrast::default();
+    }
+
+    Contrast {
+        exponent: (255.0 / (paper - ink)).clamp(1.0, AUTO_CONTRAST_MAX_EXPONENT),
+        gray: (ink + paper) / 2.0,
+    }
+}
+
 fn find_cut(frame: &Rectangle, y_pos: i32, scale: f32, dir: LinearDir, lines: &[BoundedText]) -> Option<i32> {
     let y_pos_u = y_pos as f32 / scale;
     let frame_u = frame.to_boundary() / scale;
@@ -228,6 +478,38 @@ fn find_cut(frame: &Rectangle, y_pos: i32, scale: f32, dir: LinearDir, lines: &[
     })
 }
 
+// Returns the name of the page at the given location, or its number: the pages
+// that follow a page named after a number are numbered from it.
+fn page_label(location: usize, page_names: &BTreeMap<usize, String>, synthetic: bool) -> String {
+    if let Some((&index, name)) = page_names.range(..=location).next_back() {
+        if index == location {
+            return name.clone();
+        }
+        if let Ok(number) = name.parse::<usize>() {
+            return (number + location - index).to_string();
+        }
+    }
+    if synthetic {
+        format!("{:.1}", location as f64 / BYTES_PER_PAGE)
+    } else {
+        (location + 1).to_string()
+    }
+}
+
+// The zoom mode applied to the documents of the given kind that have no zoom mode of their own.
+fn default_zoom_mode(kind: &str, reflowable: bool, settings: &Settings) -> Option<ZoomMode> {
+    settings.reader.zoom_modes.get(kind).cloned()
+            .filter(|zoom_mode| !reflowable || *zoom_mode != ZoomMode::FitToWidth)
+}
+
+fn warm_paper(settings: &Settings) -> Option<u8> {
+    if settings.warm_reading.enabled {
+        Some(settings.warm_reading.paper)
+    } else {
+        None
+    }
+}
+
 fn word_separator(lang: &str) -> &'static str {
     let l = lang.to_ascii_lowercase();
     match l.as_str() {
@@ -238,13 +520,30 @@ fn word_separator(lang: &str) -> &'static str {
     }
 }
 
+// The locations and titles of the entries of the table of contents, in reading order.
+fn chapter_locations(doc: &mut dyn Document, toc: &[TocEntry], locations: &mut Vec<(usize, String)>) {
+    for entry in toc {
+        if let Some(location) = doc.resolve_location(entry.location.clone()) {
+            locations.push((location, entry.title.clone()));
+        }
+        chapter_locations(doc, &entry.children, locations);
+    }
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end_matches(|c: char| "\"'’”»)]".contains(c))
+        .ends_with(|c: char| ".!?…。！？".contains(c))
+}
+
 impl Reader {
-    pub fn new(rect: Rectangle, mut info: Info, hub: &Hub, context: &mut Context) -> Option<Reader> {
+    pub fn new(rect: Rectangle, mut info: Info, anchor: Option<&str>, hub: &Hub, context: &mut Context) -> Result<Reader, Error> {
         let id = ID_FEEDER.next();
         let settings = &context.settings;
         let path = context.library.home.join(&info.file.path);
 
-        open(&path).and_then(|mut doc| {
+        let encoding = info.reader.as_ref().and_then(|r| r.encoding);
+
+        open_with_encoding(&path, encoding).and_then(|mut doc| {
             let (width, height) = context.display.dims;
             let font_size = info.reader.as_ref().and_then(|r| r.font_size)
                                 .unwrap_or(settings.reader.font_size);
@@ -258,6 +557,10 @@ impl Reader {
                 doc.set_margin_width(margin_width);
             }
 
+            if let Some(bias) = info.reader.as_ref().and_then(|r| r.margin_bias) {
+                doc.set_margin_bias(bias);
+            }
+
             let font_family = info.reader.as_ref().and_then(|r| r.font_family.as_ref())
                                   .unwrap_or(&settings.reader.font_family);
 
@@ -295,25 +598,50 @@ impl Reader {
                 doc.set_ignore_document_css(true);
             }
 
-            let first_location = doc.resolve_location(Location::Exact(0))?;
+            if settings.reader.force_chapter_breaks {
+                doc.set_force_chapter_breaks(true);
+            }
+
+            if settings.reader.force_text_align {
+                doc.set_force_text_align(true);
+            }
+
+            // Resolving a location paginates the chapter that contains it: the first
+            // chapter is only paginated when the book isn't opened or resumed elsewhere.
+            let resumed_location = anchor.and_then(|uri| doc.resolve_location(Location::Uri(uri.to_string())))
+                                         .or_else(|| {
+                                             info.reader.as_ref()
+                                                 .filter(|r| !r.finished)
+                                                 .and_then(|r| doc.resolve_location(Location::Exact(r.current_page)))
+                                         });
+            let initial_location = match resumed_location {
+                Some(location) => location,
+                None => doc.resolve_location(Location::Exact(0))
+                           .ok_or_else(|| format_err!("the document is empty"))?,
+            };
 
             let mut view_port = ViewPort::default();
             let mut contrast = Contrast::default();
             let pages_count = doc.pages_count();
             let current_page;
 
+            if let Some(zoom_mode) = default_zoom_mode(&info.file.kind, doc.is_reflowable(), settings) {
+                view_port.zoom_mode = zoom_mode;
+            }
+
             // TODO: use get_or_insert_with?
             if let Some(ref mut r) = info.reader {
                 r.opened = Local::now().naive_local();
 
                 if r.finished {
                     r.finished = false;
-                    r.current_page = first_location;
+                    r.current_page = initial_location;
                     r.page_offset = None;
+                    r.zoom_anchor = None;
+                    r.column = None;
                 }
 
-                current_page = doc.resolve_location(Location::Exact(r.current_page))
-                                  .unwrap_or(first_location);
+                current_page = initial_location;
 
                 if let Some(zoom_mode) = r.zoom_mode {
                     view_port.zoom_mode = zoom_mode;
@@ -333,11 +661,28 @@ impl Reader {
                     view_port.page_offset = page_offset;
                 }
 
+                if let Some(column) = r.column {
+                    view_port.column = column.min(1);
+                }
+
                 if !doc.is_reflowable() {
                     view_port.margin_width = mm_to_px(r.screen_margin_width.unwrap_or(0) as f32,
                                                       CURRENT_DEVICE.dpi) as i32;
                 }
 
+                if let (ZoomMode::Custom(sf), Some((x, y))) = (view_port.zoom_mode, r.zoom_anchor) {
+                    if let Some(dims) = doc.dims(current_page) {
+                        let cropping_margin = r.cropping_margins.as_ref()
+                                               .map(|c| c.margin(current_page))
+                                               .cloned().unwrap_or_default();
+                        let frame_width = (1.0 - cropping_margin.left - cropping_margin.right) * dims.0 * sf;
+                        let frame_height = (1.0 - cropping_margin.top - cropping_margin.bottom) * dims.1 * sf;
+                        let smw = view_port.margin_width;
+                        view_port.page_offset = pt!((x * frame_width) as i32 - rect.width() as i32 / 2 + smw,
+                                                    (y * frame_height) as i32 - rect.height() as i32 / 2 + smw);
+                    }
+                }
+
                 if let Some(exponent) = r.contrast_exponent {
                     contrast.exponent = exponent;
                 }
@@ -346,7 +691,7 @@ impl Reader {
                     contrast.gray = gray;
                 }
             } else {
-                current_page = first_location;
+                current_page = initial_location;
 
                 info.reader = Some(ReaderInfo {
                     current_page,
@@ -355,14 +700,22 @@ impl Reader {
                 });
             }
 
+            let initial_annotations = info.reader.as_ref()
+                                          .map(|r| r.annotations.clone())
+                                          .unwrap_or_default();
             let synthetic = doc.has_synthetic_page_numbers();
             let reflowable = doc.is_reflowable();
+            let auto_contrast = !reflowable && info.reader.as_ref().and_then(|r| r.auto_contrast)
+                                                   .unwrap_or(context.settings.reader.auto_contrast);
+            let right_to_left = info.reader.as_ref().and_then(|r| r.right_to_left)
+                                    .unwrap_or_else(|| doc.is_right_to_left());
+            let linkify_text = context.settings.reader.linkify_text && info.file.kind == "txt";
 
             println!("{}", info.file.path.display());
 
             hub.send(Event::Update(UpdateMode::Partial)).ok();
 
-            Some(Reader {
+            Ok(Reader {
                 id,
                 rect,
                 children: Vec::new(),
@@ -387,9 +740,27 @@ impl Reader {
                 synthetic,
                 page_turns: 0,
                 contrast,
+                auto_contrast,
+                reading_ruler: Some(context.settings.reader.reading_ruler_position)
+                                   .filter(|_| context.settings.reader.reading_ruler),
                 ephemeral: false,
                 reflowable,
                 finished: false,
+                chapter_end: None,
+                page_indicator: context.settings.reader.page_indicator,
+                immersive: context.settings.reader.immersive,
+                queued_notifications: Vec::new(),
+                page_separator: context.settings.reader.page_separator,
+                linkify_text,
+                annotation_style: context.settings.reader.annotation_style,
+                warm_paper: warm_paper(&context.settings),
+                right_to_left,
+                read_aloud: None,
+                text_export: None,
+                thumbnails: FxHashMap::default(),
+                blank_pages: FxHashMap::default(),
+                initial_annotations,
+                last_page_turn_tap: None,
             })
         })
     }
@@ -402,6 +773,7 @@ impl Reader {
                 path: PathBuf::from(MEM_SCHEME),
                 kind: "html".to_string(),
                 size: html.len() as u64,
+                hash: None,
             },
             .. Default::default()
         };
@@ -452,9 +824,26 @@ impl Reader {
             synthetic: true,
             page_turns: 0,
             contrast: Contrast::default(),
+            auto_contrast: false,
+            reading_ruler: None,
             ephemeral: true,
             reflowable: true,
             finished: false,
+            chapter_end: None,
+            page_indicator: None,
+            immersive: false,
+            queued_notifications: Vec::new(),
+            page_separator: PageSeparatorSettings::default(),
+            linkify_text: false,
+            annotation_style: context.settings.reader.annotation_style,
+            warm_paper: warm_paper(&context.settings),
+            right_to_left: false,
+            read_aloud: None,
+            text_export: None,
+            thumbnails: FxHashMap::default(),
+            blank_pages: FxHashMap::default(),
+            initial_annotations: Vec::new(),
+            last_page_turn_tap: None,
         }
     }
 
@@ -470,19 +859,23 @@ impl Reader {
                                   .cloned().unwrap_or_default();
         let dims = doc.dims(location).unwrap_or((3.0, 4.0));
         let screen_margin_width = self.view_port.margin_width;
-        let scale = scaling_factor(&self.rect, &cropping_margin, screen_margin_width, dims, self.view_port.zoom_mode);
+        let mut scale = scaling_factor(&self.rect, &cropping_margin, screen_margin_width, dims, self.view_port.zoom_mode);
+        if let Some(ratio) = self.column_split() {
+            scale /= ratio.max(1.0 - ratio);
+        }
         if let Some((pixmap, _)) = doc.pixmap(Location::Exact(location), scale, CURRENT_DEVICE.color_samples()) {
             let frame = rect![(cropping_margin.left * pixmap.width as f32).ceil() as i32,
                               (cropping_margin.top * pixmap.height as f32).ceil() as i32,
                               ((1.0 - cropping_margin.right) * pixmap.width as f32).floor() as i32,
                               ((1.0 - cropping_margin.bottom) * pixmap.height as f32).floor() as i32];
-            self.cache.insert(location, Resource { pixmap, frame, scale });
+            let contrast = Some(&pixmap).filter(|_| self.auto_contrast).map(auto_contrast);
+            self.cache.insert(location, Resource { pixmap, frame, scale, contrast });
         } else {
             let width = (dims.0 as f32 * scale).max(1.0) as u32;
             let height = (dims.1 as f32 * scale).max(1.0) as u32;
             let pixmap = Pixmap::empty(width, height, CURRENT_DEVICE.color_samples());
             let frame = pixmap.rect();
-            self.cache.insert(location, Resource { pixmap, frame, scale });
+            self.cache.insert(location, Resource { pixmap, frame, scale, contrast: None });
         }
     }
 
@@ -519,9 +912,11 @@ impl Reader {
 
             self.current_page = location;
             self.view_port.page_offset = pt!(0);
+            self.view_port.column = 0;
             self.selection = None;
             self.state = State::Idle;
             self.update(None, hub, rq, context);
+            self.update_top_bar(rq);
             self.update_bottom_bar(rq);
 
             if self.search.is_some() {
@@ -555,6 +950,78 @@ impl Reader {
         }
     }
 
+    // Returns the locations of the start of the current chapter and of the start of the next one.
+    fn chapter_bounds(&self) -> [usize; 2] {
+        let mut doc = self.doc.lock().unwrap();
+        let toc = self.toc().or_else(|| doc.toc()).unwrap_or_default();
+        let start = doc.chapter(self.current_page, &toc)
+                       .and_then(|(chap, _)| doc.resolve_location(chap.location.clone()))
+                       .filter(|&location| location <= self.current_page)
+                       .unwrap_or(0);
+        let end = doc.chapter_relative(self.current_page, CycleDir::Next, &toc)
+                     .and_then(|chap| doc.resolve_location(chap.location.clone()))
+                     .filter(|&location| location > self.current_page)
+                     .unwrap_or(self.pages_count);
+        [start, end]
+    }
+
+    // Replaces the scroll bar, in the screen scroll mode, to show the visible part of the current chapter.
+    fn update_scroll_bar(&mut self, hub: &Hub, context: &Context) {
+        if let Some(index) = locate::<ScrollBar>(self) {
+            self.children.remove(index);
+        }
+
+        if !context.settings.reader.scroll_bar || self.ephemeral ||
+           self.view_port.zoom_mode != ZoomMode::FitToWidth ||
+           self.view_port.scroll_mode != ScrollMode::Screen ||
+           self.column_split().is_some() {
+            return;
+        }
+
+        let (first, last) = match (self.chunks.first(), self.chunks.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return,
+        };
+
+        let position = |chunk: &RenderChunk, y: i32| {
+            let frame = self.cache[&chunk.location].frame;
+            chunk.location as f32 + (y - frame.min.y) as f32 / frame.height().max(1) as f32
+        };
+
+        let range = [position(first, first.frame.min.y), position(last, last.frame.max.y)];
+        let width = scale_by_dpi(SCROLL_BAR_WIDTH, CURRENT_DEVICE.dpi) as i32;
+        let rect = rect![self.rect.max.x - width, self.rect.min.y,
+                         self.rect.max.x, self.rect.max.y];
+        let scroll_bar = ScrollBar::new(rect, self.chapter_bounds(), range, hub);
+        self.children.push(Box::new(scroll_bar) as Box<dyn View>);
+    }
+
+    fn show_scroll_edge(&mut self, dir: LinearDir, hub: &Hub, rq: &mut RenderQueue) {
+        if let Some(index) = locate::<ScrollEdge>(self) {
+            self.children.remove(index);
+        }
+
+        let height = scale_by_dpi(SCROLL_EDGE_HEIGHT, CURRENT_DEVICE.dpi) as i32;
+        let rect = match dir {
+            LinearDir::Backward => rect![self.rect.min.x, self.rect.min.y,
+                                         self.rect.max.x, self.rect.min.y + height],
+            LinearDir::Forward => rect![self.rect.min.x, self.rect.max.y - height,
+                                        self.rect.max.x, self.rect.max.y],
+        };
+        let scroll_edge = ScrollEdge::new(rect, hub);
+        rq.add(RenderData::new(scroll_edge.id(), rect, UpdateMode::Fast));
+        self.children.push(Box::new(scroll_edge) as Box<dyn View>);
+    }
+
+    // Tells whether a chapter starts after the first location and not after the second one.
+    fn chapter_starts_between(&self, start: usize, end: usize) -> bool {
+        let mut doc = self.doc.lock().unwrap();
+        self.toc().or_else(|| doc.toc())
+            .and_then(|toc| doc.chapter_relative(start, CycleDir::Next, &toc)
+                               .and_then(|chap| doc.resolve_location(chap.location.clone())))
+            .is_some_and(|location| location > start && location <= end)
+    }
+
     fn text_location_range(&self) -> Option<[TextLocation; 2]> {
         let mut min_loc = None;
         let mut max_loc = None;
@@ -634,8 +1101,19 @@ impl Reader {
 
         let mut next_top_offset = self.view_port.page_offset.y + delta_y;
         let mut location = self.current_page;
+        // The end of the document where the scrolling was stopped.
+        let mut edge = None;
 
         match self.view_port.scroll_mode {
+            _ if self.column_split().is_some() => {
+                let column_height = self.column_frame(location, self.view_port.column).height() as i32;
+                let available_height = self.rect.height() as i32 - 2 * self.view_port.margin_width;
+                if column_height > available_height {
+                    next_top_offset = next_top_offset.max(0).min(column_height - available_height);
+                } else {
+                    next_top_offset = self.view_port.page_offset.y;
+                }
+            },
             ScrollMode::Screen => {
                 let max_top_offset = self.cache[&location].frame.height().saturating_sub(1) as i32;
 
@@ -650,6 +1128,7 @@ impl Reader {
                         next_top_offset = (frame.height() as i32 + next_top_offset).max(0);
                     } else {
                         next_top_offset = 0;
+                        edge = Some(LinearDir::Backward);
                     }
                 } else if next_top_offset > max_top_offset {
                     let mut doc = self.doc.lock().unwrap();
@@ -663,6 +1142,7 @@ impl Reader {
                         next_top_offset = (next_top_offset - max_top_offset - 1).min(mto);
                     } else {
                         next_top_offset = max_top_offset;
+                        edge = Some(LinearDir::Forward);
                     }
                 }
 
@@ -689,6 +1169,10 @@ impl Reader {
             },
         }
 
+        if let Some(dir) = edge.filter(|_| context.settings.reader.scroll_edge_cue) {
+            self.show_scroll_edge(dir, hub, rq);
+        }
+
         let location_changed = location != self.current_page;
         if !location_changed && next_top_offset == self.view_port.page_offset.y {
             return;
@@ -702,6 +1186,7 @@ impl Reader {
             if let Some(ref mut s) = self.search {
                 s.current_page = s.highlights.range(..=location).count().saturating_sub(1);
             }
+            self.update_top_bar(rq);
             self.update_bottom_bar(rq);
             if self.search.is_some() {
                 self.update_results_bar(rq);
@@ -726,6 +1211,30 @@ impl Reader {
         }
     }
 
+    // Turns *count* pages in the given direction, or as many as possible.
+    fn go_to_relative_page(&mut self, dir: CycleDir, count: usize, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let mut location = self.current_page;
+
+        {
+            let mut doc = self.doc.lock().unwrap();
+            for _ in 0..count {
+                let loc = match dir {
+                    CycleDir::Next => Location::Next(location),
+                    CycleDir::Previous => Location::Previous(location),
+                };
+                if let Some(next_location) = doc.resolve_location(loc) {
+                    location = next_location;
+                } else {
+                    break;
+                }
+            }
+        }
+
+        if location != self.current_page {
+            self.go_to_page(location, true, hub, rq, context);
+        }
+    }
+
     fn go_to_neighbor(&mut self, dir: CycleDir, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
         if self.chunks.is_empty() {
             return;
@@ -733,12 +1242,35 @@ impl Reader {
 
         let current_page = self.current_page;
         let page_offset = self.view_port.page_offset;
+        let column = self.view_port.column;
 
         let loc = {
             let neighloc = match dir { 
                 CycleDir::Previous => {
                     match self.view_port.zoom_mode {
                         ZoomMode::FitToPage => Location::Previous(current_page),
+                        ZoomMode::FitToWidth if self.column_split().is_some() => {
+                            let available_height = self.rect.height() as i32 - 2 * self.view_port.margin_width;
+                            if page_offset.y > 0 {
+                                self.view_port.page_offset.y = (page_offset.y - available_height).max(0);
+                                Location::Exact(current_page)
+                            } else if column > 0 {
+                                let column_height = self.column_frame(current_page, 0).height() as i32;
+                                self.view_port.column = 0;
+                                self.view_port.page_offset.y = (column_height - available_height).max(0);
+                                Location::Exact(current_page)
+                            } else {
+                                let previous_location = self.doc.lock().unwrap()
+                                                            .resolve_location(Location::Previous(current_page));
+                                if let Some(location) = previous_location {
+                                    self.load_pixmap(location);
+                                    let column_height = self.column_frame(location, 1).height() as i32;
+                                    self.view_port.column = 1;
+                                    self.view_port.page_offset.y = (column_height - available_height).max(0);
+                                }
+                                Location::Previous(current_page)
+                            }
+                        },
                         ZoomMode::FitToWidth => match self.view_port.scroll_mode {
                             ScrollMode::Screen => {
                                 let first_chunk = self.chunks.first().cloned().unwrap();
@@ -752,6 +1284,8 @@ impl Reader {
                                     let Resource { mut frame, .. } = self.cache[&location];
                                     if location == first_chunk.location {
                                         frame.max.y = first_chunk.frame.min.y;
+                                    } else {
+                                        height += self.page_gap();
                                     }
                                     height += frame.height() as i32;
                                     if height >= available_height {
@@ -807,6 +1341,23 @@ impl Reader {
                 CycleDir::Next => {
                     match self.view_port.zoom_mode {
                         ZoomMode::FitToPage => Location::Next(current_page),
+                        ZoomMode::FitToWidth if self.column_split().is_some() => {
+                            let available_height = self.rect.height() as i32 - 2 * self.view_port.margin_width;
+                            let column_height = self.column_frame(current_page, column).height() as i32;
+                            let next_top_offset = page_offset.y + available_height;
+                            if next_top_offset < column_height {
+                                self.view_port.page_offset.y = next_top_offset.min(column_height - available_height);
+                                Location::Exact(current_page)
+                            } else if column == 0 {
+                                self.view_port.column = 1;
+                                self.view_port.page_offset.y = 0;
+                                Location::Exact(current_page)
+                            } else {
+                                self.view_port.column = 0;
+                                self.view_port.page_offset.y = 0;
+                                Location::Next(current_page)
+                            }
+                        },
                         ZoomMode::FitToWidth => match self.view_port.scroll_mode {
                             ScrollMode::Screen => {
                                 let &RenderChunk { location, frame, .. } = self.chunks.last().unwrap();
@@ -845,8 +1396,28 @@ impl Reader {
             let mut doc = self.doc.lock().unwrap();
             doc.resolve_location(neighloc)
         };
+        let loc = if context.settings.reader.skip_blank_pages && !self.reflowable &&
+                     self.view_port.zoom_mode == ZoomMode::FitToPage {
+            loc.map(|location| self.skip_blank_pages(location, dir, context))
+        } else {
+            loc
+        };
+        if dir == CycleDir::Next && context.settings.reader.pause_at_chapter_end && !self.ephemeral {
+            if let Some(location) = loc.filter(|&location| location > current_page) {
+                if self.chapter_end != Some(current_page) && self.chapter_starts_between(current_page, location) {
+                    self.chapter_end = Some(current_page);
+                    self.view_port.page_offset = page_offset;
+                    self.view_port.column = column;
+                    self.notify("End of chapter: turn the page again to continue.".to_string(), hub, rq, context);
+                    return;
+                }
+            }
+        }
         match loc {
-            Some(location) if location != current_page || self.view_port.page_offset != page_offset => {
+            Some(location) if location != current_page || self.view_port.page_offset != page_offset ||
+                              self.view_port.column != column => {
+                self.chapter_end = None;
+
                 if let Some(ref mut s) = self.search {
                     s.current_page = s.highlights.range(..=location).count().saturating_sub(1);
                 }
@@ -855,15 +1426,28 @@ impl Reader {
                 self.selection = None;
                 self.state = State::Idle;
                 self.update(None, hub, rq, context);
+                self.update_top_bar(rq);
                 self.update_bottom_bar(rq);
 
                 if self.search.is_some() {
                     self.update_results_bar(rq);
                 }
+
+                // The hooks can play the cue on an accessory when the device can't.
+                if context.settings.reader.page_turn_cue && !self.ephemeral && !CURRENT_DEVICE.page_turn_cue() {
+                    hub.send(Event::PageTurned(location)).ok();
+                }
             },
             _ => {
+                self.view_port.page_offset = page_offset;
+                self.view_port.column = column;
                 match dir {
                     CycleDir::Next => {
+                        if !self.finished {
+                            if let Some(ref mut r) = self.info.reader {
+                                r.finished_at = Some(Local::now().naive_local());
+                            }
+                        }
                         self.finished = true;
                         let action = if self.ephemeral {
                             FinishedAction::Notify
@@ -872,9 +1456,7 @@ impl Reader {
                         };
                         match action {
                             FinishedAction::Notify => {
-                                let notif = Notification::new("No next page.".to_string(),
-                                                              hub, rq, context);
-                                self.children.push(Box::new(notif) as Box<dyn View>);
+                                self.notify("No next page.".to_string(), hub, rq, context);
                             },
                             FinishedAction::Close => {
                                 self.quit(context);
@@ -883,15 +1465,42 @@ impl Reader {
                         }
                     },
                     CycleDir::Previous => {
-                        let notif = Notification::new("No previous page.".to_string(),
-                                                      hub, rq, context);
-                        self.children.push(Box::new(notif) as Box<dyn View>);
+                        self.notify("No previous page.".to_string(), hub, rq, context);
                     },
                 }
             },
         }
     }
 
+    // Moves past the blank pages in the given direction. The first and last pages
+    // are never skipped.
+    fn skip_blank_pages(&mut self, mut location: usize, dir: CycleDir, context: &Context) -> usize {
+        let threshold = context.settings.reader.blank_page_threshold;
+        while location != self.current_page && self.is_blank_page(location, threshold) {
+            let neighloc = match dir {
+                CycleDir::Next => Location::Next(location),
+                CycleDir::Previous => Location::Previous(location),
+            };
+            match self.doc.lock().unwrap().resolve_location(neighloc) {
+                Some(next_location) => location = next_location,
+                None => break,
+            }
+        }
+        location
+    }
+
+    fn is_blank_page(&mut self, location: usize, threshold: f32) -> bool {
+        if let Some(&blank) = self.blank_pages.get(&location) {
+            return blank;
+        }
+        let mut doc = self.doc.lock().unwrap();
+        let blank = doc.dims(location).and_then(|(width, _)| {
+            doc.pixmap(Location::Exact(location), BLANK_PAGE_WIDTH / width, 1)
+        }).is_some_and(|(pixmap, _)| is_blank(&pixmap, threshold));
+        self.blank_pages.insert(location, blank);
+        blank
+    }
+
     fn go_to_results_page(&mut self, index: usize, hub: &Hub, rq: &mut RenderQueue, context: &Context) {
         let mut loc = None;
         if let Some(ref mut s) = self.search {
@@ -903,15 +1512,17 @@ impl Reader {
         if let Some(location) = loc {
             self.current_page = location;
             self.view_port.page_offset = pt!(0, 0);
+            self.view_port.column = 0;
             self.selection = None;
             self.state = State::Idle;
             self.update_results_bar(rq);
+            self.update_top_bar(rq);
             self.update_bottom_bar(rq);
             self.update(None, hub, rq, context);
         }
     }
 
-    fn go_to_results_neighbor(&mut self, dir: CycleDir, hub: &Hub, rq: &mut RenderQueue, context: &Context) {
+    fn go_to_results_neighbor(&mut self, dir: CycleDir, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
         let loc = self.search.as_ref().and_then(|s| {
             match dir {
                 CycleDir::Next => s.highlights.range(self.current_page+1..)
@@ -920,15 +1531,31 @@ impl Reader {
                                                   .next_back().map(|e| *e.0),
             }
         });
+        let wrapped = loc.is_none();
+        let loc = loc.or_else(|| self.search.as_ref().and_then(|s| {
+            match dir {
+                CycleDir::Next => s.highlights.keys().next().cloned(),
+                CycleDir::Previous => s.highlights.keys().next_back().cloned(),
+            }
+        })).filter(|location| *location != self.current_page);
         if let Some(location) = loc {
             if let Some(ref mut s) = self.search {
                 s.current_page = s.highlights.range(..=location).count().saturating_sub(1);
             }
             self.view_port.page_offset = pt!(0, 0);
+            self.view_port.column = 0;
             self.current_page = location;
             self.update_results_bar(rq);
+            self.update_top_bar(rq);
             self.update_bottom_bar(rq);
             self.update(None, hub, rq, context);
+            if wrapped {
+                let message = match dir {
+                    CycleDir::Next => "Wrapped around to the first result.",
+                    CycleDir::Previous => "Wrapped around to the last result.",
+                };
+                self.notify(message.to_string(), hub, rq, context);
+            }
         }
     }
 
@@ -990,13 +1617,13 @@ impl Reader {
         if self.search.is_none() {
             return;
         }
-        let (count, current_page, pages_count) = {
+        let (current_result, count, current_page, pages_count) = {
             let s = self.search.as_ref().unwrap();
-            (s.results_count, s.current_page, s.highlights.len())
+            (s.current_result(), s.results_count, s.current_page, s.highlights.len())
         };
         if let Some(index) = locate::<ResultsBar>(self) {
             let results_bar = self.child_mut(index).downcast_mut::<ResultsBar>().unwrap();
-            results_bar.update_results_label(count, rq);
+            results_bar.update_results_label(current_result, count, rq);
             results_bar.update_page_label(current_page, pages_count, rq);
             results_bar.update_icons(current_page, pages_count, rq);
         }
@@ -1014,6 +1641,67 @@ impl Reader {
         }
     }
 
+    // Turns each search result into an annotation, unless it overlaps an existing one.
+    fn highlight_all_results(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let highlights = match self.search.as_ref() {
+            Some(s) => s.highlights.clone(),
+            None => return,
+        };
+
+        let ws = word_separator(&self.info.language);
+        let mut selections = Vec::new();
+
+        {
+            let mut doc = self.doc.lock().unwrap();
+            for (location, matches) in &highlights {
+                let words = match doc.words(Location::Exact(*location)) {
+                    Some((words, _)) => words,
+                    None => continue,
+                };
+                for rects in matches {
+                    let matched = words.iter()
+                                       .filter(|w| rects.iter().any(|r| r.contains(&w.rect)))
+                                       .collect::<Vec<&BoundedText>>();
+                    if let (Some(first), Some(last)) = (matched.first(), matched.last()) {
+                        let text = matched.iter().map(|w| w.text.as_str())
+                                          .collect::<Vec<&str>>().join(ws);
+                        selections.push(([first.location, last.location], text));
+                    }
+                }
+            }
+        }
+
+        let mut count = 0;
+
+        if let Some(r) = self.info.reader.as_mut() {
+            let modified = Local::now().naive_local();
+            for (selection, text) in selections {
+                if r.annotations.iter().any(|annot| annot.selection[0] <= selection[1] &&
+                                                    selection[0] <= annot.selection[1]) {
+                    continue;
+                }
+                r.annotations.push(Annotation {
+                    selection,
+                    note: String::new(),
+                    text,
+                    modified,
+                });
+                count += 1;
+            }
+        }
+
+        self.update_annotations();
+        rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+
+        let message = if count == 1 {
+            "Highlighted 1 result.".to_string()
+        } else {
+            format!("Highlighted {} results.", count)
+        };
+        let notif = Notification::new(message, hub, rq, context);
+        self.children.push(Box::new(notif) as Box<dyn View>);
+    }
+
     #[inline]
     fn update_annotations(&mut self) {
         self.annotations.clear();
@@ -1063,11 +1751,31 @@ impl Reader {
                 let dy = smw + ((self.rect.height() - frame.height()) as i32 - 2 * smw) / 2;
                 self.chunks.push(RenderChunk { frame, location, position: pt!(dx, dy), scale });
             },
+            ZoomMode::FitToWidth if self.column_split().is_some() => {
+                self.load_pixmap(location);
+                self.load_text(location);
+                let available_width = self.rect.width() as i32 - 2 * smw;
+                let available_height = self.rect.height() as i32 - 2 * smw;
+                let scale = self.cache[&location].scale;
+                let mut frame = self.column_frame(location, self.view_port.column);
+                frame.min.y += self.view_port.page_offset.y;
+                frame.max.y = (frame.min.y + available_height).min(frame.max.y);
+                let position = pt!(smw + (available_width - frame.width() as i32) / 2,
+                                   smw + (available_height - frame.height() as i32) / 2);
+                self.chunks.push(RenderChunk { frame, location, position, scale });
+            },
             ZoomMode::FitToWidth => match self.view_port.scroll_mode {
                 ScrollMode::Screen => {
                     let available_height = self.rect.height() as i32 - 2 * smw;
+                    let gap = self.page_gap();
                     let mut height = 0;
                     while height < available_height {
+                        if !self.chunks.is_empty() {
+                            if height + gap >= available_height {
+                                break;
+                            }
+                            height += gap;
+                        }
                         self.load_pixmap(location);
                         self.load_text(location);
                         let Resource { mut frame, scale, .. } = self.cache[&location];
@@ -1097,7 +1805,8 @@ impl Reader {
                                 }
                             }
                         }
-                        let actual_height: i32 = self.chunks.iter().map(|c| c.frame.height() as i32).sum();
+                        let actual_height: i32 = self.chunks.iter().map(|c| c.frame.height() as i32).sum::<i32>() +
+                                                 gap * (self.chunks.len() as i32 - 1);
                         let dy = (available_height - actual_height) / 2;
                         for chunk in &mut self.chunks {
                             chunk.position.y += dy;
@@ -1129,11 +1838,20 @@ impl Reader {
             },
         }
 
+        let shift = self.margin_shift();
+        if shift != 0 {
+            for chunk in &mut self.chunks {
+                chunk.position.x += shift;
+            }
+        }
+
         rq.add(RenderData::new(self.id, self.rect, update_mode));
         let first_location = self.chunks.first().map(|c| c.location).unwrap();
         let last_location = self.chunks.last().map(|c| c.location).unwrap();
 
-        while self.cache.len() > 3 {
+        let cache_size = context.settings.reader.pixmap_cache_size.max(self.chunks.len());
+
+        while self.cache.len() > cache_size {
             let left_count = self.cache.range(..first_location).count();
             let right_count = self.cache.range(last_location+1..).count();
             let extremum = if left_count >= right_count {
@@ -1146,29 +1864,45 @@ impl Reader {
 
         self.update_annotations();
         self.update_noninverted_regions(context.fb.inverted());
+        self.update_scroll_bar(hub, context);
+
+        // The neighbors on each side of the visible pages that are rendered in advance.
+        let neighbors = context.settings.reader.pixmap_cache_size.saturating_sub(1) / 2;
 
-        if self.view_port.zoom_mode == ZoomMode::FitToPage ||
-           self.view_port.zoom_mode == ZoomMode::FitToWidth {
+        if neighbors > 0 && (self.view_port.zoom_mode == ZoomMode::FitToPage ||
+                             self.view_port.zoom_mode == ZoomMode::FitToWidth) {
             let doc2 = self.doc.clone();
             let hub2 = hub.clone();
             thread::spawn(move || {
-                let mut doc = doc2.lock().unwrap();
-                if let Some(next_location) = doc.resolve_location(Location::Next(last_location)) {
-                    hub2.send(Event::LoadPixmap(next_location)).ok();
+                let mut location = last_location;
+                for _ in 0..neighbors {
+                    let mut doc = doc2.lock().unwrap();
+                    if let Some(next_location) = doc.resolve_location(Location::Next(location)) {
+                        hub2.send(Event::LoadPixmap(next_location)).ok();
+                        location = next_location;
+                    } else {
+                        break;
+                    }
                 }
             });
             let doc3 = self.doc.clone();
             let hub3 = hub.clone();
             thread::spawn(move || {
-                let mut doc = doc3.lock().unwrap();
-                if let Some(previous_location) = doc.resolve_location(Location::Previous(first_location)) {
-                    hub3.send(Event::LoadPixmap(previous_location)).ok();
+                let mut location = first_location;
+                for _ in 0..neighbors {
+                    let mut doc = doc3.lock().unwrap();
+                    if let Some(previous_location) = doc.resolve_location(Location::Previous(location)) {
+                        hub3.send(Event::LoadPixmap(previous_location)).ok();
+                        location = previous_location;
+                    } else {
+                        break;
+                    }
                 }
             });
         }
     }
 
-    fn search(&mut self, text: &str, query: Regex, hub: &Hub, rq: &mut RenderQueue) {
+    fn search(&mut self, text: &str, query: Regex, hub: &Hub, rq: &mut RenderQueue, context: &Context) {
         let s = Search {
             query: text.to_string(),
             .. Default::default()
@@ -1180,10 +1914,25 @@ impl Reader {
         let current_page = self.current_page;
         let search_direction = self.search_direction;
         let ws = word_separator(&self.info.language);
+        let last_page = self.pages_count.saturating_sub(1);
+
+        // The searched locations, the first one, and whether the search wraps around.
+        let (bounds, first_page, wrap) = match context.settings.reader.search_scope {
+            SearchScope::CurrentPage => ([0, usize::MAX], current_page, true),
+            SearchScope::Book => {
+                let first_page = match search_direction {
+                    LinearDir::Forward => 0,
+                    LinearDir::Backward => last_page,
+                };
+                ([0, usize::MAX], first_page, false)
+            },
+            SearchScope::Chapter => (self.chapter_bounds(), current_page, true),
+        };
 
         thread::spawn(move || {
-            let mut loc = Location::Exact(current_page);
+            let mut loc = Location::Exact(first_page);
             let mut started = false;
+            let mut wrapped = false;
 
             loop {
                 if !running.load(AtomicOrdering::Relaxed) {
@@ -1194,8 +1943,14 @@ impl Reader {
                 let mut text = String::new();
                 let mut rects = BTreeMap::new();
 
-                if let Some(location) = doc.resolve_location(loc) {
-                    if location == current_page && started {
+                let location = doc.resolve_location(loc)
+                                  .filter(|&location| location >= bounds[0] && location < bounds[1]);
+
+                if let Some(location) = location {
+                    if started && (location == first_page || (wrapped && match search_direction {
+                        LinearDir::Forward => location > first_page,
+                        LinearDir::Backward => location < first_page,
+                    })) {
                         break;
                     }
                     if let Some((ref words, _)) = doc.words(Location::Exact(location)) {
@@ -1225,11 +1980,14 @@ impl Reader {
                         LinearDir::Forward => Location::Next(location),
                         LinearDir::Backward => Location::Previous(location),
                     };
-                } else {
+                } else if wrap && !wrapped {
                     loc = match search_direction {
-                        LinearDir::Forward => Location::Exact(0),
-                        LinearDir::Backward => Location::Exact(doc.pages_count()-1),
+                        LinearDir::Forward => Location::Exact(bounds[0]),
+                        LinearDir::Backward => Location::Exact(bounds[1].min(doc.pages_count()) - 1),
                     };
+                    wrapped = true;
+                } else {
+                    break;
                 }
 
                 started = true;
@@ -1283,6 +2041,8 @@ impl Reader {
                 return;
             }
 
+            self.hide_lone_top_bar(rq, context);
+
             let dpi = CURRENT_DEVICE.dpi;
             let (small_height, big_height) = (scale_by_dpi(SMALL_BAR_HEIGHT, dpi) as i32,
                                               scale_by_dpi(BIG_BAR_HEIGHT, dpi) as i32);
@@ -1398,7 +2158,7 @@ impl Reader {
 
             if let Some(ref s) = self.search {
                 let results_bar = ResultsBar::new(rect, s.current_page,
-                                                  s.highlights.len(), s.results_count,
+                                                  s.highlights.len(), s.current_result(), s.results_count,
                                                   !s.running.load(AtomicOrdering::Relaxed));
                 self.children.insert(index, Box::new(results_bar) as Box<dyn View>);
                 let separator = Filler::new(sp_rect, BLACK);
@@ -1433,6 +2193,7 @@ impl Reader {
                 return;
             }
 
+            self.hide_lone_top_bar(rq, context);
             self.toggle_tool_bar(false, rq, context);
 
             let dpi = CURRENT_DEVICE.dpi;
@@ -1471,58 +2232,194 @@ impl Reader {
         }
     }
 
-    fn toggle_bars(&mut self, enable: Option<bool>, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
-        if let Some(top_index) = locate::<TopBar>(self) {
-            if let Some(true) = enable {
-                return;
-            }
+    pub fn is_immersive(&self) -> bool {
+        self.immersive
+    }
 
-            if let Some(bottom_index) = locate::<BottomBar>(self) {
-                let mut top_rect = *self.child(top_index).rect();
-                top_rect.absorb(self.child(top_index+1).rect());
-                let mut bottom_rect = *self.child(bottom_index).rect();
-                for i in top_index+2 .. bottom_index {
-                    bottom_rect.absorb(self.child(i).rect());
-                }
+    // Transient notifications are postponed until the immersive mode is left.
+    pub fn notify(&mut self, msg: String, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if self.immersive {
+            self.queued_notifications.push(msg);
+            return;
+        }
 
-                self.children.drain(top_index..=bottom_index);
+        let notif = Notification::new(msg, hub, rq, context);
+        self.children.push(Box::new(notif) as Box<dyn View>);
+    }
 
-                rq.add(RenderData::expose(top_rect, UpdateMode::Gui));
-                rq.add(RenderData::expose(bottom_rect, UpdateMode::Gui));
-                hub.send(Event::Focus(None)).ok();
-            }
+    fn toggle_immersive(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        self.immersive = !self.immersive;
+
+        if !self.ephemeral {
+            context.settings.reader.immersive = self.immersive;
+        }
+
+        if self.immersive {
+            self.toggle_bars(Some(false), hub, rq, context);
         } else {
-            if let Some(false) = enable {
-                return;
+            for msg in mem::take(&mut self.queued_notifications) {
+                let notif = Notification::new(msg, hub, rq, context);
+                self.children.push(Box::new(notif) as Box<dyn View>);
             }
+        }
 
-            let dpi = CURRENT_DEVICE.dpi;
-            let thickness = scale_by_dpi(THICKNESS_MEDIUM, dpi) as i32;
-            let (small_thickness, big_thickness) = halves(thickness);
-            let (small_height, big_height) = (scale_by_dpi(SMALL_BAR_HEIGHT, dpi) as i32,
-                                              scale_by_dpi(BIG_BAR_HEIGHT, dpi) as i32);
+        rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+    }
 
-            let mut doc = self.doc.lock().unwrap();
-            let mut index = 0;
+    fn reading_ruler_rect(&self) -> Option<Rectangle> {
+        let position = self.reading_ruler?;
+        let height = mm_to_px(READING_RULER_HEIGHT, CURRENT_DEVICE.dpi) as i32;
+        let y_center = self.rect.min.y + (position * self.rect.height() as f32) as i32;
+        let y_min = (y_center - height / 2).max(self.rect.min.y).min(self.rect.max.y - height);
+        Some(rect![self.rect.min.x, y_min, self.rect.max.x, y_min + height])
+    }
 
-            let top_bar = TopBar::new(rect![self.rect.min.x,
-                                            self.rect.min.y,
-                                            self.rect.max.x,
-                                            self.rect.min.y + small_height - small_thickness],
-                                      Event::Back,
-                                      self.info.title(),
-                                      context);
+    fn toggle_reading_ruler(&mut self, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(rect) = self.reading_ruler_rect() {
+            self.reading_ruler = None;
+            rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
+        } else {
+            self.reading_ruler = Some(context.settings.reader.reading_ruler_position);
+            if let Some(rect) = self.reading_ruler_rect() {
+                rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
+            }
+        }
 
-            self.children.insert(index, Box::new(top_bar) as Box<dyn View>);
-            index += 1;
+        if !self.ephemeral {
+            context.settings.reader.reading_ruler = self.reading_ruler.is_some();
+        }
+    }
 
-            let separator = Filler::new(rect![self.rect.min.x,
-                                              self.rect.min.y + small_height - small_thickness,
+    fn move_reading_ruler(&mut self, y: i32, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(rect) = self.reading_ruler_rect() {
+            rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
+        }
+
+        let position = ((y - self.rect.min.y) as f32 / self.rect.height() as f32).clamp(0.0, 1.0);
+        self.reading_ruler = Some(position);
+
+        if let Some(rect) = self.reading_ruler_rect() {
+            rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
+        }
+
+        if !self.ephemeral {
+            context.settings.reader.reading_ruler_position = position;
+        }
+    }
+
+    // Whether the point is close to one of the visible bars: the taps that miss a control
+    // of the bars by a few millimeters shouldn't turn the page.
+    fn near_bars(&self, pt: Point, context: &Context) -> bool {
+        let buffer = mm_to_px(context.settings.reader.bar_tap_buffer, CURRENT_DEVICE.dpi) as u32;
+        if buffer == 0 {
+            return false;
+        }
+        // When the top bar is shown alone, it's followed by its separator.
+        if let Some(top_index) = locate::<TopBar>(self) {
+            let last_index = locate::<BottomBar>(self).unwrap_or(top_index + 1);
+            self.children[top_index..=last_index].iter()
+                .any(|child| pt.rdist2(child.rect()) < buffer.pow(2))
+        } else {
+            false
+        }
+    }
+
+    // The bars are drawn over the page: toggling them neither changes the layout
+    // of the document nor the current location, hence the text never moves.
+    fn toggle_bars(&mut self, enable: Option<bool>, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        // When only the top bar is shown, all the bars are shown.
+        let enable = enable.unwrap_or_else(|| locate::<BottomBar>(self).is_none());
+
+        if enable {
+            self.toggle_top_bar(true, rq, context);
+            self.toggle_bottom_bars(true, rq, context);
+        } else {
+            self.toggle_bottom_bars(false, rq, context);
+            if locate::<TopBar>(self).is_some() {
+                self.toggle_top_bar(false, rq, context);
+                hub.send(Event::Focus(None)).ok();
+            }
+        }
+    }
+
+    // The top bar can be shown alone, the other bars are stacked below it.
+    fn toggle_top_bar(&mut self, enable: bool, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(index) = locate::<TopBar>(self) {
+            if enable {
+                return;
+            }
+
+            let mut rect = *self.child(index).rect();
+            rect.absorb(self.child(index+1).rect());
+            self.children.drain(index..=index+1);
+            rq.add(RenderData::expose(rect, UpdateMode::Gui));
+        } else {
+            if !enable {
+                return;
+            }
+
+            let dpi = CURRENT_DEVICE.dpi;
+            let thickness = scale_by_dpi(THICKNESS_MEDIUM, dpi) as i32;
+            let (small_thickness, big_thickness) = halves(thickness);
+            let small_height = scale_by_dpi(SMALL_BAR_HEIGHT, dpi) as i32;
+
+            let top_bar = self.make_top_bar(rect![self.rect.min.x,
+                                                  self.rect.min.y,
+                                                  self.rect.max.x,
+                                                  self.rect.min.y + small_height - small_thickness],
+                                            context);
+
+            self.children.insert(0, Box::new(top_bar) as Box<dyn View>);
+
+            let separator = Filler::new(rect![self.rect.min.x,
+                                              self.rect.min.y + small_height - small_thickness,
                                               self.rect.max.x,
                                               self.rect.min.y + small_height + big_thickness],
                                         BLACK);
-            self.children.insert(index, Box::new(separator) as Box<dyn View>);
-            index += 1;
+            self.children.insert(1, Box::new(separator) as Box<dyn View>);
+
+            for i in 0..2 {
+                rq.add(RenderData::new(self.child(i).id(), *self.child(i).rect(), UpdateMode::Gui));
+            }
+        }
+    }
+
+    // Hides the top bar when it's shown alone, before stacking other bars at the bottom.
+    fn hide_lone_top_bar(&mut self, rq: &mut RenderQueue, context: &mut Context) {
+        if locate::<BottomBar>(self).is_none() {
+            self.toggle_top_bar(false, rq, context);
+        }
+    }
+
+    fn toggle_bottom_bars(&mut self, enable: bool, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(bottom_index) = locate::<BottomBar>(self) {
+            if enable {
+                return;
+            }
+
+            let start_index = locate::<TopBar>(self).map(|index| index+2).unwrap_or(0);
+            let mut bottom_rect = *self.child(bottom_index).rect();
+            for i in start_index .. bottom_index {
+                bottom_rect.absorb(self.child(i).rect());
+            }
+
+            self.children.drain(start_index..=bottom_index);
+
+            rq.add(RenderData::expose(bottom_rect, UpdateMode::Gui));
+        } else {
+            if !enable {
+                return;
+            }
+
+            let dpi = CURRENT_DEVICE.dpi;
+            let thickness = scale_by_dpi(THICKNESS_MEDIUM, dpi) as i32;
+            let (small_thickness, big_thickness) = halves(thickness);
+            let (small_height, big_height) = (scale_by_dpi(SMALL_BAR_HEIGHT, dpi) as i32,
+                                              scale_by_dpi(BIG_BAR_HEIGHT, dpi) as i32);
+
+            let mut doc = self.doc.lock().unwrap();
+            let mut index = locate::<TopBar>(self).map(|index| index+2).unwrap_or(0);
+            let start_index = index;
 
             if let Some(ref s) = self.search {
                 if let Some(sindex) = rlocate::<SearchBar>(self) {
@@ -1540,7 +2437,7 @@ impl Reader {
                                                             self.rect.max.y - 3 * small_height + big_thickness,
                                                             self.rect.max.x,
                                                             self.rect.max.y - 2 * small_height - small_thickness],
-                                                      s.current_page, s.highlights.len(),
+                                                      s.current_page, s.highlights.len(), s.current_result(),
                                                       s.results_count, !s.running.load(AtomicOrdering::Relaxed));
                     self.children.insert(index, Box::new(results_bar) as Box<dyn View>);
                     index += 1;
@@ -1608,7 +2505,7 @@ impl Reader {
                                             self.synthetic);
             self.children.insert(index, Box::new(bottom_bar) as Box<dyn View>);
 
-            for i in 0..=index {
+            for i in start_index..=index {
                 rq.add(RenderData::new(self.child(i).id(), *self.child(i).rect(), UpdateMode::Gui));
             }
         }
@@ -1648,6 +2545,345 @@ impl Reader {
         }
     }
 
+    fn toggle_image_viewer(&mut self, image: Option<(usize, Boundary)>, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(index) = locate::<ImageViewer>(self) {
+            if image.is_some() {
+                return;
+            }
+
+            rq.add(RenderData::expose(*self.child(index).rect(), UpdateMode::Gui));
+            self.children.remove(index);
+        } else if let Some((location, boundary)) = image {
+            self.toggle_bars(Some(false), hub, rq, context);
+
+            let image_viewer = ImageViewer::new(self.rect, Arc::clone(&self.doc), location, boundary);
+            rq.add(RenderData::new(image_viewer.id(), *image_viewer.rect(), UpdateMode::Gui));
+            self.children.push(Box::new(image_viewer) as Box<dyn View>);
+        }
+    }
+
+    fn toggle_footnote(&mut self, text: Option<String>, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(index) = locate::<Footnote>(self) {
+            if text.is_some() {
+                return;
+            }
+
+            rq.add(RenderData::expose(*self.child(index).rect(), UpdateMode::Gui));
+            self.children.remove(index);
+        } else if let Some(text) = text {
+            let font_family = self.info.reader.as_ref().and_then(|r| r.font_family.clone())
+                                  .unwrap_or_else(|| context.settings.reader.font_family.clone());
+            let font_size = self.info.reader.as_ref().and_then(|r| r.font_size)
+                                .unwrap_or(context.settings.reader.font_size);
+            let footnote = Footnote::new(self.rect, &text, &font_family, font_size, context);
+            rq.add(RenderData::new(footnote.id(), *footnote.rect(), UpdateMode::Gui));
+            self.children.push(Box::new(footnote) as Box<dyn View>);
+        }
+    }
+
+    // Saves the annotations that have a note, in a CSV file that can be imported by flashcard applications.
+    fn export_flashcards(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let mut annotations = self.info.reader.as_ref()
+                                  .map(|r| r.annotations.clone())
+                                  .unwrap_or_default();
+        annotations.sort_by(|a, b| a.selection[0].cmp(&b.selection[0]));
+        let count = annotations.iter().filter(|annot| !annot.note.is_empty()).count();
+        let settings = &context.settings.reader.flashcards;
+        let csv = annotations_as_csv(&annotations, settings.separator, settings.note_first);
+        let name = title_filename(&self.info.title, "-flashcards.csv");
+        let msg = match fs::write(context.library.home.join(&name), csv) {
+            Err(e) => format!("Can't export flashcards: {}.", e),
+            Ok(()) => format!("Exported {} flashcards to {}.", count, name),
+        };
+        let notif = Notification::new(msg, hub, rq, context);
+        self.children.push(Box::new(notif) as Box<dyn View>);
+    }
+
+    // Merges the annotations of a JSON file written by external tools. The entries that
+    // aren't annotations, or whose selection doesn't exist in the document, are skipped.
+    fn import_annotations(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let name = annotations_filename(&self.info.title, "json");
+        let result = {
+            let mut doc = self.doc.lock().unwrap();
+            load_annotations(doc.as_mut(), &context.library.home.join(&name))
+        };
+        let msg = match result {
+            Err(e) => format!("Can't import annotations: {:#}.", e),
+            Ok((imported, total_count)) => {
+                let count = self.info.reader.as_mut()
+                                .map_or(0, |r| merge_annotations(&mut r.annotations, imported));
+                if count > 0 {
+                    self.update_annotations();
+                    self.update(None, hub, rq, context);
+                }
+                format!("Imported {} annotation{}, skipped {}.",
+                        count, if count != 1 { "s" } else { "" }, total_count - count)
+            },
+        };
+        let notif = Notification::new(msg, hub, rq, context);
+        self.children.push(Box::new(notif) as Box<dyn View>);
+    }
+
+    // Saves the annotations, in the library's directory, when they changed since the book was opened.
+    fn export_annotations(&self, format: AnnotationsFormat, context: &Context) {
+        let mut annotations = match self.info.reader.as_ref() {
+            Some(r) if r.annotations != self.initial_annotations => r.annotations.clone(),
+            _ => return,
+        };
+        annotations.sort_by(|a, b| a.selection[0].cmp(&b.selection[0]));
+        let result = match format {
+            AnnotationsFormat::Json => {
+                let path = context.library.home.join(annotations_filename(&self.info.title, "json"));
+                save_json(&annotations, path)
+            },
+            AnnotationsFormat::Markdown => {
+                let path = context.library.home.join(annotations_filename(&self.info.title, "md"));
+                fs::write(path, annotations_as_markdown(&annotations, &self.info.title))
+                  .map_err(Into::into)
+            },
+        };
+        if let Err(e) = result {
+            eprintln!("Can't export annotations: {:#}.", e);
+        }
+    }
+
+    // A sensitive digitizer can report a single tap twice: the page turning taps
+    // handled within `min-tap-interval` of the end of the last page turn are ignored.
+    // The page turn itself might be slow, hence the end of it is the reference.
+    fn accept_page_turn_tap(&self, context: &Context) -> bool {
+        let interval = Duration::from_millis(context.settings.reader.min_tap_interval);
+        self.last_page_turn_tap.is_none_or(|last| last.elapsed() >= interval)
+    }
+
+    fn toggle_read_aloud(&mut self, hub: &Hub, rq: &mut RenderQueue) {
+        if let Some(read_aloud) = self.read_aloud.take() {
+            hub.send(Event::StopReadAloud).ok();
+            if let Some(rect) = read_aloud.range.and_then(|range| self.text_rect(range)) {
+                rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
+            }
+        } else {
+            self.read_aloud = Some(ReadAloud::default());
+            self.send_words(Location::Exact(self.current_page), hub);
+        }
+    }
+
+    // Sends the words of the given page to the speaker.
+    fn send_words(&mut self, loc: Location, hub: &Hub) {
+        let words = self.doc.lock().unwrap().words(loc);
+        if let (Some((words, location)), Some(read_aloud)) = (words, self.read_aloud.as_mut()) {
+            read_aloud.last_location = location;
+            read_aloud.last_word = words.last().map(|word| word.location);
+            hub.send(Event::ReadAloud(words)).ok();
+        }
+    }
+
+    // Highlights the words being spoken, turns the page when they aren't visible,
+    // and sends the next page when the speaker reaches the end of the last one.
+    fn update_speech_position(&mut self, range: [TextLocation; 2], hub: &Hub, rq: &mut RenderQueue, context: &Context) {
+        let previous_range = match self.read_aloud.as_mut() {
+            Some(read_aloud) => read_aloud.range.replace(range),
+            None => return,
+        };
+
+        let visible = self.text_location_range()
+                          .is_some_and(|[min, max]| range[0] >= min && range[0] <= max);
+
+        if visible {
+            if let Some(rect) = previous_range.and_then(|range| self.text_rect(range)) {
+                rq.add(RenderData::new(self.id, rect, UpdateMode::Fast));
+            }
+            if let Some(rect) = self.text_rect(range) {
+                rq.add(RenderData::new(self.id, rect, UpdateMode::Fast));
+            }
+        } else {
+            self.go_to_page(range[0].location(), false, hub, rq, context);
+        }
+
+        let (last_location, last_word) = self.read_aloud.as_ref()
+                                             .map(|r| (r.last_location, r.last_word)).unwrap();
+        if last_word.is_none_or(|word| range[1] >= word) {
+            self.send_words(Location::Next(last_location), hub);
+        }
+    }
+
+    // Returns the location and the boundary of the image displayed at the given point.
+    fn image_at(&self, pt: Point) -> Option<(usize, Boundary)> {
+        let mut doc = self.doc.lock().unwrap();
+        for chunk in &self.chunks {
+            let chunk_rect = chunk.frame - chunk.frame.min + chunk.position;
+            if !chunk_rect.includes(pt) {
+                continue;
+            }
+            let scale = self.cache.get(&chunk.location)?.scale;
+            let (images, _) = doc.images(Location::Exact(chunk.location))?;
+            for boundary in images {
+                let rect = (boundary * scale).to_rect() - chunk.frame.min + chunk.position;
+                if rect.includes(pt) {
+                    return Some((chunk.location, boundary));
+                }
+            }
+        }
+        None
+    }
+
+    fn toggle_tap_zones(&mut self, enable: bool, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(index) = locate::<TapZones>(self) {
+            if enable {
+                return;
+            }
+
+            rq.add(RenderData::expose(*self.child(index).rect(), UpdateMode::Gui));
+            self.children.remove(index);
+        } else {
+            if !enable {
+                return;
+            }
+
+            self.toggle_bars(Some(false), hub, rq, context);
+
+            let tap_zones = TapZones::new(self.rect, self.tap_zones_labels(context), context);
+            rq.add(RenderData::new(tap_zones.id(), *tap_zones.rect(), UpdateMode::Gui));
+            self.children.push(Box::new(tap_zones) as Box<dyn View>);
+        }
+    }
+
+    fn toggle_page_grid(&mut self, enable: bool, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(index) = locate::<PageGrid>(self) {
+            if enable {
+                return;
+            }
+
+            if let Some(page_grid) = self.child_mut(index).downcast_mut::<PageGrid>() {
+                self.thumbnails = page_grid.take_cache();
+            }
+            rq.add(RenderData::expose(*self.child(index).rect(), UpdateMode::Gui));
+            self.children.remove(index);
+        } else {
+            if !enable {
+                return;
+            }
+
+            self.toggle_bars(Some(false), hub, rq, context);
+
+            let page_names = self.info.reader.as_ref()
+                                 .map(|r| r.page_names.clone())
+                                 .unwrap_or_default();
+            let page_grid = PageGrid::new(self.rect, Arc::clone(&self.doc),
+                                          self.current_page, mem::take(&mut self.thumbnails),
+                                          page_names, self.right_to_left, hub);
+            rq.add(RenderData::new(page_grid.id(), *page_grid.rect(), UpdateMode::Gui));
+            self.children.push(Box::new(page_grid) as Box<dyn View>);
+        }
+    }
+
+    // The action triggered by a tap in the given region.
+    // The dispatch of the taps and the labels of the tap zones are both derived from it.
+    fn tap_action(&self, region: Region, context: &Context) -> TapAction {
+        if let ZoomMode::Custom(_) = self.view_port.zoom_mode {
+            return match region {
+                Region::Corner(DiagDir::NorthEast) => TapAction::Scroll(pt!(1, -1)),
+                Region::Corner(DiagDir::SouthEast) => TapAction::Scroll(pt!(1, 1)),
+                Region::Corner(DiagDir::SouthWest) => TapAction::Scroll(pt!(-1, 1)),
+                Region::Corner(DiagDir::NorthWest) => TapAction::Scroll(pt!(-1, -1)),
+                Region::Strip(Dir::North) => TapAction::Scroll(pt!(0, -1)),
+                Region::Strip(Dir::East) => TapAction::Scroll(pt!(1, 0)),
+                Region::Strip(Dir::South) => TapAction::Scroll(pt!(0, 1)),
+                Region::Strip(Dir::West) => TapAction::Scroll(pt!(-1, 0)),
+                Region::Center => TapAction::ToggleBars,
+            };
+        }
+
+        let searching = self.search.is_some();
+
+        match region {
+            Region::Corner(DiagDir::NorthWest) => TapAction::PreviousLocation,
+            Region::Corner(DiagDir::NorthEast) => TapAction::ToggleBookmark,
+            Region::Corner(DiagDir::SouthEast) => match context.settings.reader.south_east_corner {
+                _ if searching => TapAction::Page(CycleDir::Next),
+                SouthEastCornerAction::GoToPage => TapAction::GoToPage,
+                SouthEastCornerAction::NextPage => TapAction::Page(CycleDir::Next),
+            },
+            Region::Corner(DiagDir::SouthWest) => {
+                if searching {
+                    TapAction::Page(CycleDir::Previous)
+                } else {
+                    TapAction::TableOfContents
+                }
+            },
+            Region::Strip(Dir::West) => match context.settings.reader.west_strip {
+                _ if searching => TapAction::ResultsPage(CycleDir::Previous),
+                WestStripAction::PreviousPage => TapAction::PageTurn(CycleDir::Previous),
+                WestStripAction::NextPage => TapAction::PageTurn(CycleDir::Next),
+                WestStripAction::None => TapAction::None,
+            },
+            Region::Strip(Dir::East) => match context.settings.reader.east_strip {
+                _ if searching => TapAction::ResultsPage(CycleDir::Next),
+                EastStripAction::PreviousPage => TapAction::PageTurn(CycleDir::Previous),
+                EastStripAction::NextPage => TapAction::PageTurn(CycleDir::Next),
+                EastStripAction::None => TapAction::None,
+            },
+            Region::Strip(Dir::South) => match context.settings.reader.south_strip {
+                SouthStripAction::ToggleBars => TapAction::ToggleBars,
+                SouthStripAction::NextPage => TapAction::Page(CycleDir::Next),
+            },
+            Region::Strip(Dir::North) => match context.settings.reader.north_strip {
+                NorthStripAction::ToggleBars => TapAction::ToggleBars,
+                NorthStripAction::ToggleTopBar => TapAction::ToggleTopBar,
+            },
+            Region::Center => TapAction::ToggleBars,
+        }
+    }
+
+    fn tap_zones_labels(&self, context: &Context) -> Vec<(Region, String)> {
+        TAP_REGIONS.iter().filter_map(|&region| {
+            self.tap_action(region, context).label()
+                .map(|label| (region, label.to_string()))
+        }).collect()
+    }
+
+    fn perform_tap_action(&mut self, action: TapAction, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let turns_page = action.turns_page();
+
+        if turns_page && !self.accept_page_turn_tap(context) {
+            return;
+        }
+
+        match action {
+            TapAction::PreviousLocation => self.go_to_last_page(hub, rq, context),
+            TapAction::ToggleBookmark => self.toggle_bookmark(rq),
+            TapAction::GoToPage => {
+                hub.send(Event::Toggle(ViewId::GoToPage)).ok();
+            },
+            TapAction::TableOfContents => {
+                if self.ephemeral && self.info.file.path == PathBuf::from(MEM_SCHEME) {
+                    self.quit(context);
+                    hub.send(Event::Back).ok();
+                } else {
+                    hub.send(Event::Show(ViewId::TableOfContents)).ok();
+                }
+            },
+            TapAction::Page(dir) => self.go_to_neighbor(dir, hub, rq, context),
+            TapAction::PageTurn(dir) => self.go_to_neighbor(self.page_turn(dir), hub, rq, context),
+            TapAction::ResultsPage(dir) => self.go_to_results_neighbor(self.page_turn(dir), hub, rq, context),
+            TapAction::Scroll(dir) => {
+                let dx = self.rect.width() as i32 - 2 * self.view_port.margin_width;
+                let dy = self.rect.height() as i32 - 2 * self.view_port.margin_width;
+                self.directional_scroll(pt!(dir.x * dx, dir.y * dy), hub, rq, context);
+            },
+            TapAction::ToggleTopBar if locate::<BottomBar>(self).is_none() &&
+                                       !self.children.first().is_some_and(|child| child.is::<Filler>()) => {
+                let enable = locate::<TopBar>(self).is_none();
+                self.toggle_top_bar(enable, rq, context);
+            },
+            TapAction::ToggleBars | TapAction::ToggleTopBar => self.toggle_bars(None, hub, rq, context),
+            TapAction::None => (),
+        }
+
+        if turns_page {
+            self.last_page_turn_tap = Some(Instant::now());
+        }
+    }
+
     fn toggle_edit_note(&mut self, text: Option<String>, enable: Option<bool>, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
         if let Some(index) = locate_by_id(self, ViewId::EditNote) {
             if let Some(true) = enable {
@@ -1665,10 +2901,7 @@ impl Reader {
                 return;
             }
 
-            let mut edit_note = NamedInput::new("Note".to_string(), ViewId::EditNote, ViewId::EditNoteInput, 32, context);
-            if let Some(text) = text.as_ref() {
-                edit_note.set_text(text, &mut RenderQueue::new(), context);
-            }
+            let edit_note = NoteEditor::new(text.as_deref().unwrap_or(""), context);
 
             rq.add(RenderData::new(edit_note.id(), *edit_note.rect(), UpdateMode::Gui));
             hub.send(Event::Focus(Some(ViewId::EditNoteInput))).ok();
@@ -1702,6 +2935,31 @@ impl Reader {
         }
     }
 
+    fn toggle_search_annotations(&mut self, enable: Option<bool>, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(index) = locate_by_id(self, ViewId::SearchAnnotations) {
+            if let Some(true) = enable {
+                return;
+            }
+
+            rq.add(RenderData::expose(*self.child(index).rect(), UpdateMode::Gui));
+            self.children.remove(index);
+
+            if self.focus.map(|focus_id| focus_id == ViewId::SearchAnnotationsInput).unwrap_or(false) {
+                self.toggle_keyboard(false, None, hub, rq, context);
+            }
+        } else {
+            if let Some(false) = enable {
+                return;
+            }
+
+            let search_annotations = NamedInput::new("Search annotations".to_string(), ViewId::SearchAnnotations, ViewId::SearchAnnotationsInput, 21, context);
+            rq.add(RenderData::new(search_annotations.id(), *search_annotations.rect(), UpdateMode::Gui));
+            hub.send(Event::Focus(Some(ViewId::SearchAnnotationsInput))).ok();
+
+            self.children.push(Box::new(search_annotations) as Box<dyn View>);
+        }
+    }
+
     fn toggle_go_to_page(&mut self, enable: Option<bool>, id: ViewId, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
         let (text, input_id) = if id == ViewId::GoToPage {
             ("Go to page", ViewId::GoToPageInput)
@@ -1778,28 +3036,159 @@ impl Reader {
             if let Some(false) = enable {
                 return;
             }
-            let mut entries = vec![
-                EntryKind::Command("Highlight".to_string(), EntryId::HighlightSelection),
-                EntryKind::Command("Add Note".to_string(), EntryId::AnnotateSelection)
-            ];
-
-            entries.push(EntryKind::Separator);
-            entries.push(EntryKind::Command("Define".to_string(), EntryId::DefineSelection));
-            entries.push(EntryKind::Command("Search".to_string(), EntryId::SearchForSelection));
+            let mut entries = self.selection_menu_entries(&context.settings.reader.selection_menu);
 
-            if self.info.reader.as_ref().map_or(false, |r| !r.page_names.is_empty()) {
-                entries.push(EntryKind::Command("Go To".to_string(), EntryId::GoToSelectedPageName));
+            if entries.is_empty() {
+                entries = self.selection_menu_entries(&DEFAULT_SELECTION_MENU);
             }
 
-            entries.push(EntryKind::Separator);
-            entries.push(EntryKind::Command("Adjust Selection".to_string(), EntryId::AdjustSelection));
-
             let selection_menu = Menu::new(rect, ViewId::SelectionMenu, MenuKind::Contextual, entries, context);
             rq.add(RenderData::new(selection_menu.id(), *selection_menu.rect(), UpdateMode::Gui));
             self.children.push(Box::new(selection_menu) as Box<dyn View>);
         }
     }
 
+    // Builds the entries of the selection menu from the given list. The entries that
+    // don't apply to the current document, and the superfluous separators, are left out.
+    fn selection_menu_entries(&self, list: &[SelectionMenuEntry]) -> Vec<EntryKind> {
+        let mut entries = Vec::new();
+
+        for entry in list {
+            let (name, id) = match entry {
+                SelectionMenuEntry::Highlight => ("Highlight", EntryId::HighlightSelection),
+                SelectionMenuEntry::AddNote => ("Add Note", EntryId::AnnotateSelection),
+                SelectionMenuEntry::Define => ("Define", EntryId::DefineSelection),
+                SelectionMenuEntry::Translate => ("Translate", EntryId::TranslateSelection),
+                SelectionMenuEntry::Search => ("Search", EntryId::SearchForSelection),
+                SelectionMenuEntry::CopyToClippings => ("Copy to Clippings", EntryId::CopySelectionToClippings),
+                SelectionMenuEntry::Share => ("Share", EntryId::ShareSelection),
+                SelectionMenuEntry::Speak => ("Speak", EntryId::SpeakSelection),
+                SelectionMenuEntry::GoTo => {
+                    if self.info.reader.as_ref().is_none_or(|r| r.page_names.is_empty()) {
+                        continue;
+                    }
+                    ("Go To", EntryId::GoToSelectedPageName)
+                },
+                SelectionMenuEntry::AdjustSelection => ("Adjust Selection", EntryId::AdjustSelection),
+                SelectionMenuEntry::Separator => {
+                    if matches!(entries.last(), Some(EntryKind::Command(..))) {
+                        entries.push(EntryKind::Separator);
+                    }
+                    continue;
+                },
+            };
+            entries.push(EntryKind::Command(name.to_string(), id));
+        }
+
+        if matches!(entries.last(), Some(EntryKind::Separator)) {
+            entries.pop();
+        }
+
+        entries
+    }
+
+    // Saves the text of the whole book in a plain text file, in the background.
+    // The titles of the chapters are inserted before the text of the pages where they start.
+    // Selecting the entry again while the export is running cancels it.
+    fn export_text(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(running) = self.text_export.take() {
+            if running.load(AtomicOrdering::Relaxed) {
+                running.store(false, AtomicOrdering::Relaxed);
+                return;
+            }
+        }
+
+        let running = Arc::new(AtomicBool::new(true));
+        let running2 = Arc::clone(&running);
+        let hub2 = hub.clone();
+        let doc2 = Arc::clone(&self.doc);
+        let toc = self.toc();
+        let ws = word_separator(&self.info.language);
+        let pages_count = self.pages_count.max(1);
+        let name = title_filename(&self.info.title, ".txt");
+        let dir = context.library.home.join(TEXT_EXPORTS_DIRNAME);
+        let path = dir.join(&name);
+
+        thread::spawn(move || {
+            let mut chapters = Vec::new();
+
+            {
+                let mut doc = doc2.lock().unwrap();
+                if let Some(toc) = toc.or_else(|| doc.toc()) {
+                    chapter_locations(doc.as_mut(), &toc, &mut chapters);
+                }
+                chapters.sort_by_key(|(location, _)| *location);
+            }
+
+            let mut text = String::new();
+            let mut loc = Location::Exact(0);
+            let mut next_chapter = 0;
+            let mut last_progress = 0;
+
+            while running2.load(AtomicOrdering::Relaxed) {
+                let mut doc = doc2.lock().unwrap();
+                let location = match doc.resolve_location(loc) {
+                    Some(location) => location,
+                    None => break,
+                };
+                let next_location = doc.resolve_location(Location::Next(location));
+
+                while next_chapter < chapters.len() &&
+                      chapters[next_chapter].0 < next_location.unwrap_or(usize::MAX) {
+                    let title = chapters[next_chapter].1.trim();
+                    if !title.is_empty() {
+                        text = text.trim_end().to_string();
+                        text.push_str("\n\n");
+                        text.push_str(title);
+                        text.push_str("\n\n");
+                    }
+                    next_chapter += 1;
+                }
+
+                if let Some((words, _)) = doc.words(Location::Exact(location)) {
+                    let mut last_rect: Option<Boundary> = None;
+                    for word in words {
+                        if text.ends_with('\u{00AD}') {
+                            text.pop();
+                        } else if !text.ends_with('-') && !text.ends_with('\n') && !text.is_empty() {
+                            let new_line = last_rect.is_some_and(|rect| word.rect.min.y >= rect.max.y);
+                            text.push_str(if new_line { "\n" } else { ws });
+                        }
+                        last_rect = Some(word.rect);
+                        text += &word.text;
+                    }
+                }
+
+                let progress = 4 * location / pages_count;
+                if progress > last_progress && progress < 4 {
+                    hub2.send(Event::Notify(format!("Exporting text: {}%.", 25 * progress))).ok();
+                    last_progress = progress;
+                }
+
+                match next_location {
+                    Some(next_location) => loc = Location::Exact(next_location),
+                    None => break,
+                }
+            }
+
+            let msg = if running2.load(AtomicOrdering::Relaxed) {
+                match fs::create_dir_all(&dir).and_then(|_| fs::write(&path, format!("{}\n", text.trim()))) {
+                    Err(e) => format!("Can't export text: {}.", e),
+                    Ok(()) => format!("Exported text to {}/{}.", TEXT_EXPORTS_DIRNAME, name),
+                }
+            } else {
+                "Text export cancelled.".to_string()
+            };
+
+            running2.store(false, AtomicOrdering::Relaxed);
+            hub2.send(Event::Notify(msg)).ok();
+        });
+
+        self.text_export = Some(running);
+        let notif = Notification::new("Exporting text.".to_string(), hub, rq, context);
+        self.children.push(Box::new(notif) as Box<dyn View>);
+    }
+
     pub fn toggle_title_menu(&mut self, rect: Rectangle, enable: Option<bool>, rq: &mut RenderQueue, context: &mut Context) {
         if let Some(index) = locate_by_id(self, ViewId::TitleMenu) {
             if let Some(true) = enable {
@@ -1846,18 +3235,102 @@ impl Reader {
                                         EntryId::SetScrollMode(ScrollMode::Page),
                                         scroll_mode == ScrollMode::Page)]));
 
+            if !self.reflowable {
+                let column_split = self.info.reader.as_ref()
+                                       .and_then(|r| r.column_split)
+                                       .map(|ratio| (100.0 * ratio).round() as i32);
+                let mut columns = vec![EntryKind::RadioButton("Single".to_string(),
+                                                              EntryId::SetColumnSplit(None),
+                                                              column_split.is_none())];
+                for percent in (40..=60).step_by(5) {
+                    columns.push(EntryKind::RadioButton(format!("Double ({}%)", percent),
+                                                        EntryId::SetColumnSplit(Some(percent)),
+                                                        column_split == Some(percent)));
+                }
+                entries.push(EntryKind::SubMenu("Columns".to_string(), columns));
+                entries.push(EntryKind::CheckBox("Auto Contrast".to_string(),
+                                                 EntryId::ToggleAutoContrast,
+                                                 self.auto_contrast));
+            }
+
+            if self.info.file.kind == "txt" && !self.ephemeral {
+                let encoding = self.info.reader.as_ref().and_then(|r| r.encoding);
+                let mut encodings = vec![EntryKind::RadioButton("Automatic".to_string(),
+                                                                EntryId::SetEncoding(None),
+                                                                encoding.is_none())];
+                for enc in TextEncoding::ALL {
+                    encodings.push(EntryKind::RadioButton(enc.label().to_string(),
+                                                          EntryId::SetEncoding(Some(enc)),
+                                                          encoding == Some(enc)));
+                }
+                entries.push(EntryKind::SubMenu("Encoding".to_string(), encodings));
+            }
+
             if self.ephemeral {
                 entries.push(EntryKind::Command("Save".to_string(), EntryId::Save));
             }
 
+            if let Some(r) = self.info.reader.as_ref() {
+                let mut tints = vec![EntryKind::RadioButton("None".to_string(),
+                                                            EntryId::SetPaperTint(None),
+                                                            r.paper_tint.is_none())];
+                for (name, color) in [("Light", GRAY14), ("Medium", GRAY13), ("Dark", GRAY12)] {
+                    let level = color.gray();
+                    tints.push(EntryKind::RadioButton(name.to_string(),
+                                                      EntryId::SetPaperTint(Some(level)),
+                                                      r.paper_tint == Some(level)));
+                }
+                entries.push(EntryKind::SubMenu("Paper Tint".to_string(), tints));
+                entries.push(EntryKind::CheckBox("Right to Left".to_string(),
+                                                 EntryId::ToggleRightToLeft,
+                                                 self.right_to_left));
+            }
+
+            entries.push(EntryKind::Command("Share Page".to_string(), EntryId::SharePage));
+            entries.push(EntryKind::CheckBox("Read Aloud".to_string(),
+                                             EntryId::ToggleReadAloud,
+                                             self.read_aloud.is_some()));
+            entries.push(EntryKind::CheckBox("Reading Ruler".to_string(),
+                                             EntryId::ToggleReadingRuler,
+                                             self.reading_ruler.is_some()));
+
             if self.info.reader.as_ref().map_or(false, |r| !r.annotations.is_empty()) {
                 entries.push(EntryKind::Command("Annotations".to_string(), EntryId::Annotations));
+                entries.push(EntryKind::Command("Search Annotations".to_string(), EntryId::SearchAnnotations));
+            }
+
+            if self.info.reader.as_ref().is_some_and(|r| r.annotations.iter().any(|annot| !annot.note.is_empty())) {
+                entries.push(EntryKind::Command("Export Flashcards".to_string(), EntryId::ExportFlashcards));
+            }
+
+            let exporting_text = self.text_export.as_ref()
+                                     .is_some_and(|running| running.load(AtomicOrdering::Relaxed));
+            let label = if exporting_text { "Cancel Text Export" } else { "Export Text" };
+            entries.push(EntryKind::Command(label.to_string(), EntryId::ExportText));
+
+            if context.library.home.join(annotations_filename(&self.info.title, "json")).exists() {
+                entries.push(EntryKind::Command("Import Annotations".to_string(), EntryId::ImportAnnotations));
             }
 
             if self.info.reader.as_ref().map_or(false, |r| !r.bookmarks.is_empty()) {
                 entries.push(EntryKind::Command("Bookmarks".to_string(), EntryId::Bookmarks));
             }
 
+            if let Some(r) = self.info.reader.as_ref() {
+                let mut page_names = vec![EntryKind::Command("Name".to_string(), EntryId::SetPageName)];
+                if r.page_names.contains_key(&self.current_page) {
+                    page_names.push(EntryKind::Command("Remove Name".to_string(), EntryId::RemovePageName));
+                }
+                let names = r.page_names.iter()
+                             .map(|(i, s)| EntryKind::Command(s.to_string(), EntryId::GoTo(*i)))
+                             .collect::<Vec<EntryKind>>();
+                if !names.is_empty() {
+                    page_names.push(EntryKind::Separator);
+                    page_names.push(EntryKind::SubMenu("Go To".to_string(), names));
+                }
+                entries.push(EntryKind::SubMenu("Page Names".to_string(), page_names));
+            }
+
             if !entries.is_empty() {
                 entries.push(EntryKind::Separator);
             }
@@ -1865,11 +3338,22 @@ impl Reader {
             entries.push(EntryKind::CheckBox("Apply Dithering".to_string(),
                                              EntryId::ToggleDithered,
                                              context.fb.dithered()));
+            entries.push(EntryKind::Command("Show Tap Zones".to_string(),
+                                            EntryId::TapZones));
+            if !self.reflowable {
+                entries.push(EntryKind::Command("Page Grid".to_string(),
+                                                EntryId::PageGrid));
+            }
 
             let mut title_menu = Menu::new(rect, ViewId::TitleMenu, MenuKind::DropDown, entries, context);
             title_menu.child_mut(1)
                       .downcast_mut::<MenuEntry>().unwrap()
                       .set_disabled(zoom_mode != ZoomMode::FitToWidth, rq);
+            if !self.reflowable {
+                title_menu.child_mut(2)
+                          .downcast_mut::<MenuEntry>().unwrap()
+                          .set_disabled(zoom_mode != ZoomMode::FitToWidth, rq);
+            }
 
             rq.add(RenderData::new(title_menu.id(), *title_menu.rect(), UpdateMode::Gui));
             self.children.push(Box::new(title_menu) as Box<dyn View>);
@@ -1896,9 +3380,20 @@ impl Reader {
                                      .and_then(|r| r.font_family.clone())
                                      .unwrap_or_else(|| context.settings.reader.font_family.clone());
             families.insert(DEFAULT_FONT_FAMILY.to_string());
-            let entries = families.iter().map(|f| EntryKind::RadioButton(f.clone(),
-                                                                         EntryId::SetFontFamily(f.clone()),
-                                                                         *f == current_family)).collect();
+            let favorites = &context.settings.reader.favorite_font_families;
+            let mut entries: Vec<EntryKind> = favorites.iter()
+                                                       .filter(|f| families.contains(*f))
+                                                       .map(|f| EntryKind::RadioButton(f.clone(),
+                                                                                       EntryId::SetFontFamily(f.clone()),
+                                                                                       *f == current_family))
+                                                       .collect();
+            if !entries.is_empty() {
+                entries.push(EntryKind::Separator);
+            }
+            entries.extend(families.iter().filter(|f| !favorites.contains(*f))
+                                   .map(|f| EntryKind::RadioButton(f.clone(),
+                                                                   EntryId::SetFontFamily(f.clone()),
+                                                                   *f == current_family)));
             let font_family_menu = Menu::new(rect, ViewId::FontFamilyMenu, MenuKind::DropDown, entries, context);
             rq.add(RenderData::new(font_family_menu.id(), *font_family_menu.rect(), UpdateMode::Gui));
             self.children.push(Box::new(font_family_menu) as Box<dyn View>);
@@ -2061,19 +3556,34 @@ impl Reader {
                                    .unwrap_or_else(|| if reflowable { context.settings.reader.margin_width } else { 0 });
             let min_margin_width = context.settings.reader.min_margin_width;
             let max_margin_width = context.settings.reader.max_margin_width;
-            let entries = (min_margin_width..=max_margin_width).map(|mw|
+            let mut entries = (min_margin_width..=max_margin_width).map(|mw|
                 EntryKind::RadioButton(format!("{}", mw),
                                        EntryId::SetMarginWidth(mw),
                                        mw == margin_width)
-            ).collect();
+            ).collect::<Vec<EntryKind>>();
+            if margin_width > 0 {
+                let margin_bias = self.info.reader.as_ref()
+                                      .and_then(|r| r.margin_bias)
+                                      .unwrap_or(0);
+                let bias_entries = (-margin_width..=margin_width).map(|b| {
+                    let label = match b {
+                        0 => "Even".to_string(),
+                        _ if b > 0 => format!("Left +{}", b),
+                        _ => format!("Right +{}", -b),
+                    };
+                    EntryKind::RadioButton(label, EntryId::SetMarginBias(b), b == margin_bias)
+                }).collect();
+                entries.push(EntryKind::Separator);
+                entries.push(EntryKind::SubMenu("Bias".to_string(), bias_entries));
+            }
             let margin_width_menu = Menu::new(rect, ViewId::MarginWidthMenu, MenuKind::DropDown, entries, context);
             rq.add(RenderData::new(margin_width_menu.id(), *margin_width_menu.rect(), UpdateMode::Gui));
             self.children.push(Box::new(margin_width_menu) as Box<dyn View>);
         }
     }
 
-    fn toggle_page_menu(&mut self, rect: Rectangle, enable: Option<bool>, rq: &mut RenderQueue, context: &mut Context) {
-        if let Some(index) = locate_by_id(self, ViewId::PageMenu) {
+    fn toggle_results_menu(&mut self, rect: Rectangle, enable: Option<bool>, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(index) = locate_by_id(self, ViewId::ResultsMenu) {
             if let Some(true) = enable {
                 return;
             }
@@ -2085,26 +3595,11 @@ impl Reader {
                 return;
             }
 
-            let has_name = self.info.reader.as_ref()
-                               .map_or(false, |r| r.page_names.contains_key(&self.current_page));
-
-            let mut entries = vec![EntryKind::Command("Name".to_string(), EntryId::SetPageName)];
-            if has_name {
-                entries.push(EntryKind::Command("Remove Name".to_string(), EntryId::RemovePageName));
-            }
-            let names = self.info.reader.as_ref()
-                            .map(|r| r.page_names.iter()
-                                      .map(|(i, s)| EntryKind::Command(s.to_string(), EntryId::GoTo(*i)))
-                                      .collect::<Vec<EntryKind>>())
-                            .unwrap_or_default();
-            if !names.is_empty() {
-                entries.push(EntryKind::Separator);
-                entries.push(EntryKind::SubMenu("Go To".to_string(), names));
-            }
-
-            let page_menu = Menu::new(rect, ViewId::PageMenu, MenuKind::DropDown, entries, context);
-            rq.add(RenderData::new(page_menu.id(), *page_menu.rect(), UpdateMode::Gui));
-            self.children.push(Box::new(page_menu) as Box<dyn View>);
+            let entries = vec![EntryKind::Command("Highlight All Results".to_string(),
+                                                  EntryId::HighlightAllResults)];
+            let results_menu = Menu::new(rect, ViewId::ResultsMenu, MenuKind::DropDown, entries, context);
+            rq.add(RenderData::new(results_menu.id(), *results_menu.rect(), UpdateMode::Gui));
+            self.children.push(Box::new(results_menu) as Box<dyn View>);
         }
     }
 
@@ -2160,12 +3655,23 @@ impl Reader {
                 return;
             }
 
+            let search_scope = context.settings.reader.search_scope;
             let entries = vec![EntryKind::RadioButton("Forward".to_string(),
                                                       EntryId::SearchDirection(LinearDir::Forward),
                                                       self.search_direction == LinearDir::Forward),
                                EntryKind::RadioButton("Backward".to_string(),
                                                       EntryId::SearchDirection(LinearDir::Backward),
-                                                      self.search_direction == LinearDir::Backward)];
+                                                      self.search_direction == LinearDir::Backward),
+                               EntryKind::Separator,
+                               EntryKind::RadioButton("From Current Page".to_string(),
+                                                      EntryId::SearchScope(SearchScope::CurrentPage),
+                                                      search_scope == SearchScope::CurrentPage),
+                               EntryKind::RadioButton("Whole Book".to_string(),
+                                                      EntryId::SearchScope(SearchScope::Book),
+                                                      search_scope == SearchScope::Book),
+                               EntryKind::RadioButton("Current Chapter".to_string(),
+                                                      EntryId::SearchScope(SearchScope::Chapter),
+                                                      search_scope == SearchScope::Chapter)];
 
             let search_menu = Menu::new(rect, ViewId::SearchMenu, MenuKind::Contextual, entries, context);
             rq.add(RenderData::new(search_menu.id(), *search_menu.rect(), UpdateMode::Gui));
@@ -2204,6 +3710,7 @@ impl Reader {
         self.text.clear();
         self.update(None, hub, rq, context);
         self.update_tool_bar(rq, context);
+        self.update_top_bar(rq);
         self.update_bottom_bar(rq);
     }
 
@@ -2235,6 +3742,7 @@ impl Reader {
         self.text.clear();
         self.update(None, hub, rq, context);
         self.update_tool_bar(rq, context);
+        self.update_top_bar(rq);
         self.update_bottom_bar(rq);
     }
 
@@ -2272,9 +3780,32 @@ impl Reader {
         self.text.clear();
         self.update(None, hub, rq, context);
         self.update_tool_bar(rq, context);
+        self.update_top_bar(rq);
         self.update_bottom_bar(rq);
     }
 
+    fn cycle_font_family(&mut self, dir: CycleDir, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let favorites = &context.settings.reader.favorite_font_families;
+
+        if favorites.is_empty() {
+            return;
+        }
+
+        let current_family = self.info.reader.as_ref()
+                                 .and_then(|r| r.font_family.clone())
+                                 .unwrap_or_else(|| context.settings.reader.font_family.clone());
+        let count = favorites.len();
+        let index = match (favorites.iter().position(|f| *f == current_family), dir) {
+            (Some(index), CycleDir::Next) => (index + 1) % count,
+            (Some(index), CycleDir::Previous) => (index + count - 1) % count,
+            (None, CycleDir::Next) => 0,
+            (None, CycleDir::Previous) => count - 1,
+        };
+
+        let font_family = favorites[index].clone();
+        self.set_font_family(&font_family, hub, rq, context);
+    }
+
     fn set_line_height(&mut self, line_height: f32, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
         if Arc::strong_count(&self.doc) > 1 {
             return;
@@ -2303,6 +3834,7 @@ impl Reader {
         self.text.clear();
         self.update(None, hub, rq, context);
         self.update_tool_bar(rq, context);
+        self.update_top_bar(rq);
         self.update_bottom_bar(rq);
     }
 
@@ -2354,6 +3886,51 @@ impl Reader {
         self.cache.clear();
         self.update(None, hub, rq, context);
         self.update_tool_bar(rq, context);
+        self.update_top_bar(rq);
+        self.update_bottom_bar(rq);
+    }
+
+    // The horizontal shift of the pages of fixed-layout documents that implements the margin bias.
+    // The shift can't exceed the screen margin, so that the pages aren't cut.
+    fn margin_shift(&self) -> i32 {
+        if self.reflowable {
+            return 0;
+        }
+        let bias = self.info.reader.as_ref()
+                       .and_then(|r| r.margin_bias)
+                       .unwrap_or(0);
+        let smw = self.view_port.margin_width;
+        (mm_to_px(bias as f32, CURRENT_DEVICE.dpi) as i32).clamp(-smw, smw)
+    }
+
+    fn set_margin_bias(&mut self, bias: i32, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if Arc::strong_count(&self.doc) > 1 {
+            return;
+        }
+
+        if let Some(ref mut r) = self.info.reader {
+            r.margin_bias = Some(bias).filter(|&b| b != 0);
+        }
+
+        if self.reflowable {
+            let mut doc = self.doc.lock().unwrap();
+            doc.set_margin_bias(bias);
+
+            if self.synthetic {
+                let current_page = self.current_page.min(doc.pages_count() - 1);
+                if let Some(location) =  doc.resolve_location(Location::Exact(current_page)) {
+                    self.current_page = location;
+                }
+            } else {
+                self.pages_count = doc.pages_count();
+                self.current_page = self.current_page.min(self.pages_count - 1);
+            }
+
+            self.text.clear();
+            self.cache.clear();
+        }
+
+        self.update(None, hub, rq, context);
         self.update_bottom_bar(rq);
     }
 
@@ -2377,7 +3954,8 @@ impl Reader {
             r.contrast_exponent = Some(exponent);
         }
         self.contrast.exponent = exponent;
-        self.update(None, hub, rq, context);
+        let update_mode = Some(UpdateMode::Full).filter(|_| context.settings.reader.contrast_full_refresh);
+        self.update(update_mode, hub, rq, context);
         self.update_tool_bar(rq, context);
     }
 
@@ -2386,11 +3964,34 @@ impl Reader {
             r.contrast_gray = Some(gray);
         }
         self.contrast.gray = gray;
-        self.update(None, hub, rq, context);
+        let update_mode = Some(UpdateMode::Full).filter(|_| context.settings.reader.contrast_full_refresh);
+        self.update(update_mode, hub, rq, context);
         self.update_tool_bar(rq, context);
     }
 
-    fn set_zoom_mode(&mut self, zoom_mode: ZoomMode, reset_page_offset: bool, hub: &Hub, rq: &mut RenderQueue, context: &Context) {
+    fn toggle_auto_contrast(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        self.auto_contrast = !self.auto_contrast;
+        if let Some(ref mut r) = self.info.reader {
+            r.auto_contrast = Some(self.auto_contrast);
+        }
+        for resource in self.cache.values_mut() {
+            resource.contrast = Some(&resource.pixmap).filter(|_| self.auto_contrast).map(auto_contrast);
+        }
+        let update_mode = Some(UpdateMode::Full).filter(|_| context.settings.reader.contrast_full_refresh);
+        self.update(update_mode, hub, rq, context);
+    }
+
+    fn set_zoom_mode(&mut self, zoom_mode: ZoomMode, reset_page_offset: bool, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let zoom_mode = if let ZoomMode::Custom(sf) = zoom_mode {
+            let clamped_sf = clamp_zoom(sf, &context.settings);
+            if (clamped_sf - sf).abs() > f32::EPSILON {
+                self.notify(format!("Zoom limited to {:.1}%.", 100.0 * clamped_sf), hub, rq, context);
+            }
+            ZoomMode::Custom(clamped_sf)
+        } else {
+            zoom_mode
+        };
+
         if self.view_port.zoom_mode == zoom_mode {
             return;
         }
@@ -2400,11 +4001,18 @@ impl Reader {
                 .child_mut(1)
                 .downcast_mut::<MenuEntry>().unwrap()
                 .set_disabled(zoom_mode != ZoomMode::FitToWidth, rq);
+            if !self.reflowable {
+                self.child_mut(index)
+                    .child_mut(2)
+                    .downcast_mut::<MenuEntry>().unwrap()
+                    .set_disabled(zoom_mode != ZoomMode::FitToWidth, rq);
+            }
         }
 
         self.view_port.zoom_mode = zoom_mode;
         if reset_page_offset {
             self.view_port.page_offset = pt!(0, 0);
+            self.view_port.column = 0;
         }
         self.cache.clear();
         self.update(None, hub, rq, context);
@@ -2419,6 +4027,91 @@ impl Reader {
         self.update(None, hub, rq, context);
     }
 
+    fn set_column_split(&mut self, percent: Option<i32>, hub: &Hub, rq: &mut RenderQueue, context: &Context) {
+        if let Some(ref mut r) = self.info.reader {
+            r.column_split = percent.map(|p| p as f32 / 100.0);
+        }
+        self.view_port.page_offset = pt!(0, 0);
+        self.view_port.column = 0;
+        self.cache.clear();
+        self.update(None, hub, rq, context);
+    }
+
+    // The document is reopened, since its layout depends on the decoded text.
+    fn set_encoding(&mut self, encoding: Option<TextEncoding>, hub: &Hub, context: &mut Context) {
+        if let Some(ref mut r) = self.info.reader {
+            if r.encoding == encoding {
+                return;
+            }
+            r.encoding = encoding;
+        }
+        self.quit(context);
+        hub.send(Event::Back).ok();
+        hub.send(Event::Open(Box::new(self.info.clone()), None)).ok();
+    }
+
+    // Saves the visible part of the document, without the bars, as a PNG image.
+    fn share_page(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let mut bbox: Option<Rectangle> = None;
+
+        for chunk in &self.chunks {
+            let chunk_rect = chunk.frame - chunk.frame.min + chunk.position;
+            if let Some(ref mut bbox) = bbox {
+                bbox.absorb(&chunk_rect);
+            } else {
+                bbox = Some(chunk_rect);
+            }
+        }
+
+        if let Some(bbox) = bbox {
+            let mut pixmap = Pixmap::new(bbox.width(), bbox.height(), CURRENT_DEVICE.color_samples());
+
+            for chunk in &self.chunks {
+                let resource = &self.cache[&chunk.location];
+                let contrast = resource.contrast.unwrap_or(self.contrast);
+                pixmap.draw_framed_pixmap_contrast(&resource.pixmap, &chunk.frame, chunk.position - bbox.min,
+                                                   contrast.exponent, contrast.gray);
+            }
+
+            let name = title_filename(&self.info.title,
+                                      &format!("-{}.png", Local::now().format("%Y%m%d_%H%M%S")));
+            let msg = match pixmap.save(&name) {
+                Err(e) => format!("{}", e),
+                Ok(()) => {
+                    let path = env::current_dir().map(|dir| dir.join(&name))
+                                  .unwrap_or_else(|_| PathBuf::from(&name));
+                    hub.send(Event::PageShared(path)).ok();
+                    format!("Saved {}.", name)
+                },
+            };
+            let notif = Notification::new(msg, hub, rq, context);
+            self.children.push(Box::new(notif) as Box<dyn View>);
+        }
+    }
+
+    fn page_gap(&self) -> i32 {
+        mm_to_px(self.page_separator.gap, CURRENT_DEVICE.dpi).round() as i32
+    }
+
+    // The relative abscissa of the column boundary, if the pages are currently split.
+    fn column_split(&self) -> Option<f32> {
+        if self.reflowable || self.view_port.zoom_mode != ZoomMode::FitToWidth {
+            return None;
+        }
+        self.info.reader.as_ref().and_then(|r| r.column_split)
+    }
+
+    fn column_frame(&self, location: usize, column: usize) -> Rectangle {
+        let frame = self.cache[&location].frame;
+        let ratio = self.column_split().unwrap_or(1.0);
+        let x_split = frame.min.x + (ratio * frame.width() as f32).round() as i32;
+        if column == 0 {
+            rect![frame.min, pt!(x_split, frame.max.y)]
+        } else {
+            rect![pt!(x_split, frame.min.y), frame.max]
+        }
+    }
+
     fn crop_margins(&mut self, index: usize, margin: &Margin, hub: &Hub, rq: &mut RenderQueue, context: &Context) {
         if self.view_port.zoom_mode != ZoomMode::FitToPage {
             let Resource { pixmap, frame, .. } = self.cache.get(&index).unwrap();
@@ -2492,6 +4185,28 @@ impl Reader {
         toc
     }
 
+    // Returns the location of the first chapter after the current page whose title
+    // contains the given text, or of the first matching chapter if there are none.
+    fn find_chapter_by_title(&self, text: &str) -> Option<usize> {
+        let text = text.to_lowercase();
+        let mut doc = self.doc.lock().unwrap();
+        let toc = self.toc().or_else(|| doc.toc())?;
+        let mut locations = Vec::new();
+        let mut entries: Vec<&TocEntry> = toc.iter().rev().collect();
+
+        while let Some(entry) = entries.pop() {
+            if entry.title.to_lowercase().contains(&text) {
+                if let Some(location) = doc.resolve_location(entry.location.clone()) {
+                    locations.push(location);
+                }
+            }
+            entries.extend(entry.children.iter().rev());
+        }
+
+        locations.iter().find(|&&location| location > self.current_page)
+                 .or_else(|| locations.first()).cloned()
+    }
+
     fn find_page_by_name(&self, name: &str) -> Option<usize> {
         self.info.reader.as_ref().and_then(|r| {
             if let Ok(a) = name.parse::<u32>() {
@@ -2545,10 +4260,327 @@ impl Reader {
         Some(text)
     }
 
+    // Returns the bounds of the sentence or paragraph that contains the word at *location*.
+    // The sentences are delimited by their final punctuation marks, and the paragraphs
+    // by the line breaks that follow a short line or precede a wider interline.
+    fn selection_unit(&self, location: TextLocation, granularity: SelectionGranularity) -> [TextLocation; 2] {
+        if granularity == SelectionGranularity::Word {
+            return [location, location];
+        }
+
+        let words = self.chunks.iter()
+                        .filter_map(|chunk| self.text.get(&chunk.location))
+                        .flat_map(|words| words.iter().map(move |word| (words, word)))
+                        .collect::<Vec<(&Vec<BoundedText>, &BoundedText)>>();
+
+        let index = if let Some(index) = words.iter().position(|(_, word)| word.location == location) {
+            index
+        } else {
+            return [location, location];
+        };
+
+        let is_break = |i: usize| {
+            let (page_words, word) = words[i];
+            if i == words.len() - 1 {
+                return true;
+            }
+            if !ends_sentence(&word.text) {
+                return false;
+            }
+            if granularity == SelectionGranularity::Sentence {
+                return true;
+            }
+            let height = word.rect.max.y - word.rect.min.y;
+            let right = page_words.iter().map(|w| w.rect.max.x).fold(word.rect.max.x, f32::max);
+            let (next_page_words, next_word) = words[i+1];
+            (word.rect.max.x < right - 2.0 * height) ||
+            (std::ptr::eq(page_words, next_page_words) && next_word.rect.min.y - word.rect.max.y > height)
+        };
+
+        let mut start = index;
+        while start > 0 && !is_break(start - 1) {
+            start -= 1;
+        }
+
+        let mut end = index;
+        while !is_break(end) {
+            end += 1;
+        }
+
+        [words[start].1.location, words[end].1.location]
+    }
+
+    fn nearest_link(&self, center: Point) -> Option<BoundedText> {
+        let mut nearest_link = None;
+        let mut dmin = u32::MAX;
+        let dmax = (scale_by_dpi(RECT_DIST_JITTER, CURRENT_DEVICE.dpi) as i32).pow(2) as u32;
+
+        for chunk in &self.chunks {
+            let (mut links, _) = self.doc.lock().ok()
+                                     .and_then(|mut doc| doc.links(Location::Exact(chunk.location)))
+                                     .unwrap_or((Vec::new(), 0));
+            if links.is_empty() && self.linkify_text {
+                links = self.text_links(chunk.location);
+            }
+            for link in links {
+                let rect = (link.rect * chunk.scale).to_rect() - chunk.frame.min + chunk.position;
+                let d = center.rdist2(&rect);
+                if d < dmax && d < dmin {
+                    dmin = d;
+                    nearest_link = Some(link.clone());
+                }
+            }
+        }
+
+        nearest_link
+    }
+
+    // Finds the URLs and the page references (e.g. *see page 42*) among the words of a text document.
+    fn text_links(&self, location: usize) -> Vec<BoundedText> {
+        let words = match self.text.get(&location) {
+            Some(words) => words,
+            None => return Vec::new(),
+        };
+        let mut links = Vec::new();
+
+        for (index, word) in words.iter().enumerate() {
+            let text = word.text.trim_end_matches(|c| TRAILING_PUNCTUATION.contains(&c));
+            if text.starts_with("http://") || text.starts_with("https://") {
+                links.push(BoundedText { text: text.to_string(), rect: word.rect, location: word.location });
+            } else if text.starts_with("www.") && text.len() > 4 {
+                links.push(BoundedText { text: format!("https://{}", text), rect: word.rect, location: word.location });
+            } else if let Ok(number) = text.parse::<usize>() {
+                let previous = match index.checked_sub(1).map(|i| &words[i]) {
+                    Some(previous) if ["page", "p."].contains(&previous.text.to_lowercase().as_str()) => previous,
+                    _ => continue,
+                };
+                let target = self.find_page_by_name(text).unwrap_or_else(|| {
+                    if self.synthetic {
+                        (number as f64 * BYTES_PER_PAGE) as usize
+                    } else {
+                        number.saturating_sub(1)
+                    }
+                });
+                if target < self.pages_count {
+                    let rect = Boundary::new(vec2!(previous.rect.min.x.min(word.rect.min.x),
+                                                   previous.rect.min.y.min(word.rect.min.y)),
+                                             vec2!(previous.rect.max.x.max(word.rect.max.x),
+                                                   previous.rect.max.y.max(word.rect.max.y)));
+                    links.push(BoundedText { text: format!("#location={}", target), rect, location: previous.location });
+                }
+            }
+        }
+
+        links
+    }
+
+    // Returns the location targeted by a link to the current document.
+    fn link_destination(&self, link: &BoundedText) -> Option<usize> {
+        let pdf_page = Regex::new(r"^#page=(\d+).*$").unwrap();
+        let djvu_page = Regex::new(r"^#([+-])?(\d+)$").unwrap();
+        let text_page = Regex::new(r"^#location=(\d+)$").unwrap();
+        if let Some(caps) = text_page.captures(&link.text) {
+            caps[1].parse::<usize>().ok()
+        } else if let Some(caps) = pdf_page.captures(&link.text) {
+            caps[1].parse::<usize>().ok()
+                   .map(|index| index.saturating_sub(1))
+        } else if let Some(caps) = djvu_page.captures(&link.text) {
+            caps[2].parse::<usize>().ok().map(|index| {
+                match caps.get(1).map(|m| m.as_str()) {
+                    Some("-") => self.current_page.saturating_sub(index),
+                    Some("+") => self.current_page + index,
+                    _ => index.saturating_sub(1),
+                }
+            })
+        } else if link.text.starts_with('@') {
+            None
+        } else {
+            let mut doc = self.doc.lock().unwrap();
+            doc.resolve_location(Location::LocalUri(self.current_page, link.text.clone()))
+        }
+    }
+
+    fn preview_link(&mut self, link: BoundedText, rq: &mut RenderQueue, context: &mut Context) {
+        if locate_by_id(self, ViewId::LinkPreview).is_some() {
+            return;
+        }
+
+        let text = if let Some(location) = self.link_destination(&link) {
+            let first_line = self.doc.lock().ok()
+                                 .and_then(|mut doc| doc.lines(Location::Exact(location)))
+                                 .and_then(|(lines, _)| lines.into_iter()
+                                                             .map(|line| line.text.trim().to_string())
+                                                             .find(|text| !text.is_empty()));
+            let page = page_text(location, self.pages_count, self.synthetic, 3);
+            match first_line {
+                Some(line) => format!("{}: {}", page, line),
+                None => page,
+            }
+        } else {
+            link.text.clone()
+        };
+
+        let dialog = Dialog::with_validate_label(ViewId::LinkPreview,
+                                                 Some(Event::FollowLink(link)),
+                                                 text, "Go", context);
+        rq.add(RenderData::new(dialog.id(), *dialog.rect(), UpdateMode::Gui));
+        self.children.push(Box::new(dialog) as Box<dyn View>);
+    }
+
+    fn follow_link(&mut self, link: &BoundedText, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let pdf_page = Regex::new(r"^#page=(\d+).*$").unwrap();
+        let djvu_page = Regex::new(r"^#([+-])?(\d+)$").unwrap();
+        let toc_page = Regex::new(r"^@(.+)$").unwrap();
+        let book_page = Regex::new(r"^@(\d+)@(.+)$").unwrap();
+        let text_page = Regex::new(r"^#location=(\d+)$").unwrap();
+        if let Some(caps) = text_page.captures(&link.text) {
+            if let Ok(location) = caps[1].parse::<usize>() {
+                self.go_to_page(location, true, hub, rq, context);
+            }
+        } else if let Some(caps) = book_page.captures(&link.text) {
+            if let Ok(location) = caps[1].parse::<usize>() {
+                self.quit(context);
+                hub.send(Event::Back).ok();
+                hub.send(Event::OpenAt(PathBuf::from(&caps[2]), location)).ok();
+            }
+        } else if let Some(caps) = toc_page.captures(&link.text) {
+            let loc_opt = if caps[1].chars().all(|c| c.is_digit(10)) {
+                caps[1].parse::<usize>()
+                       .map(Location::Exact)
+                       .ok()
+            } else {
+                Some(Location::Uri(caps[1].to_string()))
+            };
+            if let Some(location) = loc_opt {
+                self.quit(context);
+                hub.send(Event::Back).ok();
+                hub.send(Event::GoToLocation(location)).ok();
+            }
+        } else if let Some(caps) = pdf_page.captures(&link.text) {
+            if let Ok(index) = caps[1].parse::<usize>() {
+                self.go_to_page(index.saturating_sub(1), true, hub, rq, context);
+            }
+        } else if link.text.starts_with("hook:") {
+            hub.send(Event::HookLink(link.text.clone())).ok();
+        } else if link.text == EXPAND_HIGHLIGHTS_URI {
+            self.quit(context);
+            hub.send(Event::Back).ok();
+            hub.send(Event::Select(EntryId::ExpandHighlights)).ok();
+        } else if let Some(caps) = djvu_page.captures(&link.text) {
+            if let Ok(mut index) = caps[2].parse::<usize>() {
+                let prefix = caps.get(1).map(|m| m.as_str());
+                match prefix {
+                    Some("-") => index = self.current_page.saturating_sub(index),
+                    Some("+") => index += self.current_page,
+                    _ => index = index.saturating_sub(1),
+                }
+                self.go_to_page(index, true, hub, rq, context);
+            }
+        } else {
+            let mut doc = self.doc.lock().unwrap();
+            let loc = Location::LocalUri(self.current_page, link.text.clone());
+            if let Some(location) = doc.resolve_location(loc) {
+                hub.send(Event::GoTo(location)).ok();
+            } else {
+                if link.text.starts_with("https:") || link.text.starts_with("http:") {
+                    if let Some(path) = context.settings.external_urls_queue.as_ref() {
+                        if let Ok(mut file) = OpenOptions::new().create(true)
+                                                                .append(true)
+                                                                .open(path) {
+                            if let Err(e) = writeln!(file, "{}", link.text) {
+                                eprintln!("Couldn't write to {}: {:#}.", path.display(), e);
+                            } else {
+                                let message = format!("Queued {}.", link.text);
+                                let notif = Notification::new(message, hub, rq, context);
+                                self.children.push(Box::new(notif) as Box<dyn View>);
+                            }
+                        }
+                    }
+                } else {
+                    eprintln!("Can't resolve URI: {}.", link.text);
+                }
+            }
+        }
+    }
+
     fn selected_text(&self) -> Option<String> {
         self.selection.as_ref().and_then(|sel| self.text_excerpt([sel.start, sel.end]))
     }
 
+    // Returns the name of the page at the given location, or its number.
+    fn page_label(&self, location: usize) -> String {
+        match self.info.reader.as_ref() {
+            Some(r) => page_label(location, &r.page_names, self.synthetic),
+            None => page_label(location, &BTreeMap::new(), self.synthetic),
+        }
+    }
+
+    // Formats the selected text according to the quote template.
+    fn selected_quote(&self, context: &Context) -> Option<String> {
+        let sel = self.selection.as_ref()?;
+        let text = self.text_excerpt([sel.start, sel.end])?;
+        let page = self.page_label(sel.start.location());
+        Some(format_quote(&context.settings.reader.quote_template, &text,
+                          &self.info.author, &self.info.title, &page))
+    }
+
+    // Appends the selected quote to the clippings file of the library.
+    fn copy_to_clippings(&mut self, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        if let Some(quote) = self.selected_quote(context) {
+            let path = context.library.home.join(CLIPPINGS_FILENAME);
+            let result = OpenOptions::new().create(true).append(true).open(&path)
+                                           .and_then(|mut file| writeln!(file, "{}\n", quote));
+            let msg = match result {
+                Err(e) => format!("Can't copy to clippings: {}.", e),
+                Ok(()) => format!("Copied to {}.", CLIPPINGS_FILENAME),
+            };
+            let notif = Notification::new(msg, hub, rq, context);
+            self.children.push(Box::new(notif) as Box<dyn View>);
+        }
+    }
+
+    // Absorbs the highlights without notes that overlap or touch the given selection,
+    // and returns the merged selection. Only the highlights that lie within the
+    // current text are considered, so that the merged text is complete.
+    fn merge_highlights(&mut self, sel: [TextLocation; 2]) -> [TextLocation; 2] {
+        let [mut start, mut end] = sel;
+
+        loop {
+            let index = self.info.reader.as_ref().and_then(|r| {
+                r.annotations.iter().position(|annot| {
+                    let [s, e] = annot.selection;
+                    annot.note.is_empty() &&
+                    self.has_word(s) && self.has_word(e) &&
+                    ((s <= end && start <= e) ||
+                     self.adjacent_words(e, start) ||
+                     self.adjacent_words(end, s))
+                })
+            });
+
+            if let Some(index) = index {
+                if let Some(r) = self.info.reader.as_mut() {
+                    let [s, e] = r.annotations.remove(index).selection;
+                    start = start.min(s);
+                    end = end.max(e);
+                }
+            } else {
+                break;
+            }
+        }
+
+        [start, end]
+    }
+
+    fn has_word(&self, location: TextLocation) -> bool {
+        self.text.values().flatten().any(|word| word.location == location)
+    }
+
+    // Tells if no word lies between the words at *first* and *second*.
+    fn adjacent_words(&self, first: TextLocation, second: TextLocation) -> bool {
+        first < second &&
+        !self.text.values().flatten().any(|word| word.location > first && word.location < second)
+    }
+
     fn text_rect(&self, sel: [TextLocation; 2]) -> Option<Rectangle> {
         let [start, end] = sel;
         let mut result: Option<Rectangle> = None;
@@ -2596,6 +4628,33 @@ impl Reader {
         self.selection.as_ref().and_then(|sel| self.text_rect([sel.start, sel.end]))
     }
 
+    // Opens the list of annotations whose note or text matches *query*, with the contiguous
+    // highlights grouped if *group* is true. Returns false if there's nothing to show.
+    fn show_annotations(&mut self, query: Option<&Regex>, group: bool, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) -> bool {
+        let mut annotations = self.info.reader.as_ref()
+                                  .map(|r| r.annotations.clone())
+                                  .unwrap_or_default();
+        if let Some(query) = query {
+            annotations.retain(|annot| query.is_match(&annot.note) || query.is_match(&annot.text));
+        }
+        if annotations.is_empty() {
+            return false;
+        }
+        self.toggle_bars(Some(false), hub, rq, context);
+        let mut starts = self.annotations.values().flatten()
+                             .map(|annot| annot.selection[0]).collect::<Vec<TextLocation>>();
+        starts.sort();
+        let active_range = starts.first().cloned().zip(starts.last().cloned());
+        annotations.sort_by(|a, b| a.selection[0].cmp(&b.selection[0]));
+        let html = annotations_as_html(&annotations, active_range, group);
+        let link_uri = annotations.iter()
+                                  .filter(|annot| annot.selection[0].location() <= self.current_page)
+                                  .max_by_key(|annot| annot.selection[0])
+                                  .map(|annot| format!("@{}", annot.selection[0].location()));
+        hub.send(Event::OpenHtml(html, link_uri)).ok();
+        true
+    }
+
     fn find_annotation_ref(&mut self, sel: [TextLocation; 2]) -> Option<&Annotation> {
         self.info.reader.as_ref()
             .and_then(|r| r.annotations.iter()
@@ -2608,9 +4667,61 @@ impl Reader {
                            .find(|a| a.selection[0] == sel[0] && a.selection[1] == sel[1]))
     }
 
+    fn make_top_bar(&self, rect: Rectangle, context: &mut Context) -> TopBar {
+        let settings = context.settings.reader.top_bar;
+        let title = match (settings.title, settings.author) {
+            (true, true) => self.info.label(),
+            (true, false) => self.info.title(),
+            (false, true) => self.info.author.clone(),
+            (false, false) => String::new(),
+        };
+        TopBar::new(rect, Event::Back, title, &settings, context)
+              .page_label(self.current_page, self.pages_count, self.synthetic)
+    }
+
+    fn update_top_bar(&mut self, rq: &mut RenderQueue) {
+        if let Some(index) = locate::<TopBar>(self) {
+            let top_bar = self.children[index].as_mut().downcast_mut::<TopBar>().unwrap();
+            top_bar.update_page_label(self.current_page, self.pages_count, rq);
+        }
+    }
+
+    fn vertical_swipe_action(&self, context: &Context) -> VerticalSwipeAction {
+        let settings = &context.settings.reader.vertical_swipe;
+        match self.view_port.zoom_mode {
+            ZoomMode::FitToPage => settings.fit_to_page,
+            ZoomMode::FitToWidth => settings.fit_to_width,
+            ZoomMode::Custom(_) => settings.custom,
+        }
+    }
+
+    // Maps the direction of a horizontal page turn gesture to the reading direction.
+    fn page_turn(&self, dir: CycleDir) -> CycleDir {
+        match (self.right_to_left, dir) {
+            (true, CycleDir::Next) => CycleDir::Previous,
+            (true, CycleDir::Previous) => CycleDir::Next,
+            (false, _) => dir,
+        }
+    }
+
     fn reseed(&mut self, rq: &mut RenderQueue, context: &mut Context) {
+        if !self.ephemeral {
+            self.page_indicator = context.settings.reader.page_indicator;
+            self.page_separator = context.settings.reader.page_separator;
+            self.linkify_text = context.settings.reader.linkify_text && self.info.file.kind == "txt";
+        }
+
+        self.annotation_style = context.settings.reader.annotation_style;
+
+        self.warm_paper = warm_paper(&context.settings);
+
         if let Some(index) = locate::<TopBar>(self) {
-            if let Some(top_bar) = self.child_mut(index).downcast_mut::<TopBar>() {
+            if self.child(index).downcast_ref::<TopBar>()
+                   .is_some_and(|top_bar| *top_bar.settings() != context.settings.reader.top_bar) {
+                let rect = *self.child(index).rect();
+                let top_bar = self.make_top_bar(rect, context);
+                self.children[index] = Box::new(top_bar) as Box<dyn View>;
+            } else if let Some(top_bar) = self.child_mut(index).downcast_mut::<TopBar>() {
                 top_bar.reseed(rq, context);
             }
         }
@@ -2623,24 +4734,69 @@ impl Reader {
             s.running.store(false, AtomicOrdering::Relaxed);
         }
 
+        if let Some(running) = self.text_export.take() {
+            running.store(false, AtomicOrdering::Relaxed);
+        }
+
         if self.ephemeral {
             return;
         }
 
+        let zoom_anchor = self.zoom_anchor();
+
         if let Some(ref mut r) = self.info.reader {
             r.current_page = self.current_page;
             r.pages_count = self.pages_count;
             r.finished = self.finished;
+
+            // Forget the automatic bookmarks that were removed by hand.
+            r.auto_bookmarks.retain(|page, _| r.bookmarks.contains(page));
+
+            if context.settings.reader.auto_bookmark &&
+               (!r.bookmarks.contains(&self.current_page) || r.auto_bookmarks.contains_key(&self.current_page)) {
+                r.bookmarks.insert(self.current_page);
+                r.auto_bookmarks.insert(self.current_page,
+                                        Local::now().naive_local().format(datetime_format::FORMAT).to_string());
+            }
+
+            // The labels are timestamps: the oldest sorts first.
+            while r.auto_bookmarks.len() > context.settings.reader.max_auto_bookmarks {
+                let oldest = r.auto_bookmarks.iter().min_by(|a, b| a.1.cmp(b.1)).map(|(page, _)| *page);
+                if let Some(page) = oldest {
+                    r.auto_bookmarks.remove(&page);
+                    r.bookmarks.remove(&page);
+                }
+            }
             r.dithered = context.fb.dithered();
+            r.inverted = Some(context.fb.inverted())
+                          .filter(|&inverted| inverted != context.settings.inverted);
 
-            if self.view_port.zoom_mode == ZoomMode::FitToPage {
+            let zoom_mode = default_zoom_mode(&self.info.file.kind, self.reflowable, &context.settings)
+                                .unwrap_or(ZoomMode::FitToPage);
+
+            if self.view_port.zoom_mode == zoom_mode {
                 r.zoom_mode = None;
+            } else {
+                r.zoom_mode = Some(self.view_port.zoom_mode);
+            }
+
+            r.zoom_anchor = None;
+
+            if self.view_port.zoom_mode == ZoomMode::FitToPage {
                 r.page_offset = None;
+            } else if let Some(anchor) = zoom_anchor {
+                r.page_offset = None;
+                r.zoom_anchor = Some(anchor);
             } else {
-                r.zoom_mode = Some(self.view_port.zoom_mode);
                 r.page_offset = Some(self.view_port.page_offset);
             }
 
+            if r.column_split.is_some() && self.view_port.column > 0 {
+                r.column = Some(self.view_port.column);
+            } else {
+                r.column = None;
+            }
+
             if self.view_port.zoom_mode == ZoomMode::FitToWidth {
                 r.scroll_mode = Some(self.view_port.scroll_mode);
             } else {
@@ -2663,6 +4819,29 @@ impl Reader {
 
             context.library.sync_reader_info(&self.info.file.path, r);
         }
+
+        if let Some(format) = context.settings.reader.annotations_export {
+            self.export_annotations(format, context);
+        }
+    }
+
+    fn zoom_anchor(&self) -> Option<(f32, f32)> {
+        if !matches!(self.view_port.zoom_mode, ZoomMode::Custom(_)) {
+            return None;
+        }
+
+        let frame = self.cache.get(&self.current_page)?.frame;
+
+        if frame.is_empty() {
+            return None;
+        }
+
+        let smw = self.view_port.margin_width;
+        let center = self.view_port.page_offset + pt!(self.rect.width() as i32 / 2 - smw,
+                                                      self.rect.height() as i32 / 2 - smw);
+
+        Some((center.x as f32 / frame.width() as f32,
+              center.y as f32 / frame.height() as f32))
     }
 
     fn scale_page(&mut self, center: Point, factor: f32, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
@@ -2676,6 +4855,9 @@ impl Reader {
             self.cache[&self.current_page].scale
         };
 
+        let sf = current_factor * factor;
+        let factor = clamp_zoom(sf, &context.settings) / current_factor;
+
         if let Some(chunk) = self.chunks.iter().find(|chunk| {
             let chunk_rect = chunk.frame - chunk.frame.min + chunk.position;
             chunk_rect.includes(center)
@@ -2687,7 +4869,7 @@ impl Reader {
                                          pt!(self.rect.width() as i32 / 2 - smw,
                                              self.rect.height() as i32 / 2 - smw);
 
-            self.set_zoom_mode(ZoomMode::Custom(current_factor * factor), false, hub, rq, context);
+            self.set_zoom_mode(ZoomMode::Custom(sf), false, hub, rq, context);
         }
     }
 }
@@ -2701,12 +4883,24 @@ impl View for Reader {
                 hub.send(Event::Select(EntryId::Rotate(n))).ok();
                 true
             },
+            Event::Gesture(GestureEvent::Swipe { dir: Dir::North | Dir::South, start, end, .. })
+                    if self.reading_ruler_rect().is_some_and(|rect| rect.includes(start)) => {
+                self.move_reading_ruler(end.y, rq, context);
+                true
+            },
+            Event::Gesture(GestureEvent::Swipe { dir: dir @ (Dir::North | Dir::South), start, .. })
+                    if self.rect.includes(start) &&
+                       self.vertical_swipe_action(context) == VerticalSwipeAction::Chapter => {
+                let dir = if dir == Dir::North { CycleDir::Next } else { CycleDir::Previous };
+                self.go_to_chapter(dir, hub, rq, context);
+                true
+            },
             Event::Gesture(GestureEvent::Swipe { dir, start, end }) if self.rect.includes(start) => {
                 match self.view_port.zoom_mode {
                     ZoomMode::FitToPage | ZoomMode::FitToWidth => {
                         match dir {
-                            Dir::West => self.go_to_neighbor(CycleDir::Next, hub, rq, context),
-                            Dir::East => self.go_to_neighbor(CycleDir::Previous, hub, rq, context),
+                            Dir::West => self.go_to_neighbor(self.page_turn(CycleDir::Next), hub, rq, context),
+                            Dir::East => self.go_to_neighbor(self.page_turn(CycleDir::Previous), hub, rq, context),
                             Dir::South | Dir::North => self.vertical_scroll(start.y - end.y, hub, rq, context),
                         };
                     },
@@ -2820,24 +5014,59 @@ impl View for Reader {
                 match dir {
                     DiagDir::NorthWest => self.go_to_annotation(CycleDir::Previous, hub, rq, context),
                     DiagDir::NorthEast => self.go_to_annotation(CycleDir::Next, hub, rq, context),
+                    DiagDir::SouthWest if self.reflowable => self.cycle_font_family(CycleDir::Previous, hub, rq, context),
+                    DiagDir::SouthEast if self.reflowable => self.cycle_font_family(CycleDir::Next, hub, rq, context),
                     _ => (),
                 }
                 true
             },
             Event::Gesture(GestureEvent::Cross(_)) => {
-                self.quit(context);
-                hub.send(Event::Back).ok();
+                match context.settings.reader.cross_gesture {
+                    CrossGestureAction::Quit => {
+                        self.quit(context);
+                        hub.send(Event::Back).ok();
+                    },
+                    CrossGestureAction::BookmarkAndQuit => {
+                        if let Some(ref mut r) = self.info.reader {
+                            r.bookmarks.insert(self.current_page);
+                        }
+                        self.quit(context);
+                        hub.send(Event::Back).ok();
+                    },
+                    CrossGestureAction::ToggleBars => self.toggle_bars(None, hub, rq, context),
+                    CrossGestureAction::None => (),
+                }
                 true
             },
             Event::Gesture(GestureEvent::Diamond(_)) => {
                 self.toggle_bars(None, hub, rq, context);
                 true
             },
+            Event::Gesture(GestureEvent::MultiArrow { dir: Dir::North, .. }) => {
+                self.toggle_immersive(hub, rq, context);
+                true
+            },
+            Event::Gesture(GestureEvent::MultiArrow { dir: Dir::South, .. }) => {
+                self.toggle_reading_ruler(rq, context);
+                true
+            },
             Event::Gesture(GestureEvent::HoldButtonShort(code, ..)) => {
-                match code {
-                    ButtonCode::Backward => self.go_to_chapter(CycleDir::Previous, hub, rq, context),
-                    ButtonCode::Forward => self.go_to_chapter(CycleDir::Next, hub, rq, context),
-                    _ => (),
+                let dir = match code {
+                    ButtonCode::Backward => Some(CycleDir::Previous),
+                    ButtonCode::Forward => Some(CycleDir::Next),
+                    _ => None,
+                };
+                if let Some(dir) = dir {
+                    match context.settings.reader.hold_button {
+                        HoldButtonAction::Chapter => self.go_to_chapter(dir, hub, rq, context),
+                        HoldButtonAction::Pages => {
+                            let count = context.settings.reader.hold_button_pages;
+                            self.go_to_relative_page(dir, count, hub, rq, context);
+                        },
+                        HoldButtonAction::GoToPage => {
+                            hub.send(Event::Toggle(ViewId::GoToPage)).ok();
+                        },
+                    }
                 }
                 self.held_buttons.insert(code);
                 true
@@ -2867,8 +5096,11 @@ impl View for Reader {
             Event::Device(DeviceEvent::Finger { position, status: FingerStatus::Motion, id, .. }) if self.state == State::Selection(id) => {
                 let mut nearest_word = None;
                 let mut dmin = u32::MAX;
-                let dmax = (scale_by_dpi(RECT_DIST_JITTER, CURRENT_DEVICE.dpi) as i32).pow(2) as u32;
+                let dmax = hit_distance(context);
                 let mut rects = Vec::new();
+                // With reduced motion, the selection is only rendered when the finger is lifted.
+                let mut discarded = RenderQueue::new();
+                let rq = if context.settings.reader.reduced_motion { &mut discarded } else { rq };
 
                 for chunk in &self.chunks {
                     for word in &self.text[&chunk.location] {
@@ -2882,12 +5114,13 @@ impl View for Reader {
                     }
                 }
 
+                let unit = nearest_word.map(|word| self.selection_unit(word.location, context.settings.reader.selection_granularity));
                 let selection = self.selection.as_mut().unwrap();
 
-                if let Some(word) = nearest_word {
+                if let Some([unit_start, unit_end]) = unit {
                     let old_start = selection.start;
                     let old_end = selection.end;
-                    let (start, end) = word.location.min_max(selection.anchor);
+                    let (start, end) = (unit_start.min(selection.anchor[0]), unit_end.max(selection.anchor[1]));
 
                     if start == old_start && end == old_end {
                         return true;
@@ -2959,6 +5192,9 @@ impl View for Reader {
             },
             Event::Device(DeviceEvent::Finger { status: FingerStatus::Up, position, id, .. }) if self.state == State::Selection(id) => {
                 self.state = State::Idle;
+                if context.settings.reader.reduced_motion {
+                    rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+                }
                 let radius = scale_by_dpi(24.0, CURRENT_DEVICE.dpi) as i32;
                 self.toggle_selection_menu(Rectangle::from_disk(position, radius), Some(true), rq, context);
                 true
@@ -2966,7 +5202,7 @@ impl View for Reader {
             Event::Gesture(GestureEvent::Tap(center)) if self.state == State::AdjustSelection && self.rect.includes(center) => {
                 let mut found = None;
                 let mut dmin = u32::MAX;
-                let dmax = (scale_by_dpi(RECT_DIST_JITTER, CURRENT_DEVICE.dpi) as i32).pow(2) as u32;
+                let dmax = hit_distance(context);
                 let mut rects = Vec::new();
 
                 for chunk in &self.chunks {
@@ -3079,191 +5315,33 @@ impl View for Reader {
                     return true;
                 }
 
-                let mut nearest_link = None;
-                let mut dmin = u32::MAX;
-                let dmax = (scale_by_dpi(RECT_DIST_JITTER, CURRENT_DEVICE.dpi) as i32).pow(2) as u32;
-
-                for chunk in &self.chunks {
-                    let (links, _) = self.doc.lock().ok()
-                                         .and_then(|mut doc| doc.links(Location::Exact(chunk.location)))
-                                         .unwrap_or((Vec::new(), 0));
-                    for link in links {
-                        let rect = (link.rect * chunk.scale).to_rect() - chunk.frame.min + chunk.position;
-                        let d = center.rdist2(&rect);
-                        if d < dmax && d < dmin {
-                            dmin = d;
-                            nearest_link = Some(link.clone());
-                        }
-                    }
-                }
-
-                if let Some(link) = nearest_link.take() {
-                    let pdf_page = Regex::new(r"^#page=(\d+).*$").unwrap();
-                    let djvu_page = Regex::new(r"^#([+-])?(\d+)$").unwrap();
-                    let toc_page = Regex::new(r"^@(.+)$").unwrap();
-                    if let Some(caps) = toc_page.captures(&link.text) {
-                        let loc_opt = if caps[1].chars().all(|c| c.is_digit(10)) {
-                            caps[1].parse::<usize>()
-                                   .map(Location::Exact)
-                                   .ok()
-                        } else {
-                            Some(Location::Uri(caps[1].to_string()))
-                        };
-                        if let Some(location) = loc_opt {
-                            self.quit(context);
-                            hub.send(Event::Back).ok();
-                            hub.send(Event::GoToLocation(location)).ok();
-                        }
-                    } else if let Some(caps) = pdf_page.captures(&link.text) {
-                        if let Ok(index) = caps[1].parse::<usize>() {
-                            self.go_to_page(index.saturating_sub(1), true, hub, rq, context);
-                        }
-                    } else if let Some(caps) = djvu_page.captures(&link.text) {
-                        if let Ok(mut index) = caps[2].parse::<usize>() {
-                            let prefix = caps.get(1).map(|m| m.as_str());
-                            match prefix {
-                                Some("-") => index = self.current_page.saturating_sub(index),
-                                Some("+") => index += self.current_page,
-                                _ => index = index.saturating_sub(1),
-                            }
-                            self.go_to_page(index, true, hub, rq, context);
-                        }
+                if let Some(link) = self.nearest_link(center) {
+                    let footnote = self.doc.lock().unwrap()
+                                       .footnote(Location::LocalUri(self.current_page, link.text.clone()));
+                    if footnote.is_some() {
+                        self.toggle_footnote(footnote, rq, context);
                     } else {
-                        let mut doc = self.doc.lock().unwrap();
-                        let loc = Location::LocalUri(self.current_page, link.text.clone());
-                        if let Some(location) = doc.resolve_location(loc) {
-                            hub.send(Event::GoTo(location)).ok();
-                        } else {
-                            if link.text.starts_with("https:") || link.text.starts_with("http:") {
-                                if let Some(path) = context.settings.external_urls_queue.as_ref() {
-                                    if let Ok(mut file) = OpenOptions::new().create(true)
-                                                                            .append(true)
-                                                                            .open(path) {
-                                        if let Err(e) = writeln!(file, "{}", link.text) {
-                                            eprintln!("Couldn't write to {}: {:#}.", path.display(), e);
-                                        } else {
-                                            let message = format!("Queued {}.", link.text);
-                                            let notif = Notification::new(message, hub, rq, context);
-                                            self.children.push(Box::new(notif) as Box<dyn View>);
-                                        }
-                                    }
-                                }
-                            } else {
-                                eprintln!("Can't resolve URI: {}.", link.text);
-                            }
-                        }
+                        self.follow_link(&link, hub, rq, context);
                     }
                     return true;
                 }
 
-                if let ZoomMode::Custom(_) = self.view_port.zoom_mode {
-                    let dx = self.rect.width() as i32 - 2 * self.view_port.margin_width;
-                    let dy = self.rect.height() as i32 - 2 * self.view_port.margin_width;
-                    match Region::from_point(center, self.rect,
-                                             context.settings.reader.strip_width,
-                                             context.settings.reader.corner_width) {
-                        Region::Corner(diag_dir) => {
-                            match diag_dir {
-                                DiagDir::NorthEast => self.directional_scroll(pt!(dx, -dy), hub, rq, context),
-                                DiagDir::SouthEast => self.directional_scroll(pt!(dx, dy), hub, rq, context),
-                                DiagDir::SouthWest => self.directional_scroll(pt!(-dx, dy), hub, rq, context),
-                                DiagDir::NorthWest => self.directional_scroll(pt!(-dx, -dy), hub, rq, context),
-                            }
-                        },
-                        Region::Strip(dir) => {
-                            match dir {
-                                Dir::North => self.directional_scroll(pt!(0, -dy), hub, rq, context),
-                                Dir::East => self.directional_scroll(pt!(dx, 0), hub, rq, context),
-                                Dir::South => self.directional_scroll(pt!(0, dy), hub, rq, context),
-                                Dir::West => self.directional_scroll(pt!(-dx, 0), hub, rq, context),
-                            }
-                        },
-                        Region::Center => self.toggle_bars(None, hub, rq, context),
+                if self.reflowable {
+                    if let Some(image) = self.image_at(center) {
+                        self.toggle_image_viewer(Some(image), hub, rq, context);
+                        return true;
                     }
+                }
 
+                if self.near_bars(center, context) {
                     return true;
                 }
 
-                match Region::from_point(center, self.rect,
-                                         context.settings.reader.strip_width,
-                                         context.settings.reader.corner_width) {
-                    Region::Corner(diag_dir) => {
-                        match diag_dir {
-                            DiagDir::NorthWest => self.go_to_last_page(hub, rq, context),
-                            DiagDir::NorthEast => self.toggle_bookmark(rq),
-                            DiagDir::SouthEast => {
-                                if self.search.is_none() {
-                                    match context.settings.reader.south_east_corner {
-                                        SouthEastCornerAction::GoToPage => {
-                                            hub.send(Event::Toggle(ViewId::GoToPage)).ok();
-                                        },
-                                        SouthEastCornerAction::NextPage => {
-                                            self.go_to_neighbor(CycleDir::Next, hub, rq, context);
-                                        },
-                                    }
-                                } else {
-                                    self.go_to_neighbor(CycleDir::Next, hub, rq, context);
-                                }
-                            },
-                            DiagDir::SouthWest => {
-                                if self.search.is_none() {
-                                    if self.ephemeral && self.info.file.path == PathBuf::from(MEM_SCHEME) {
-                                        self.quit(context);
-                                        hub.send(Event::Back).ok();
-                                    } else {
-                                        hub.send(Event::Show(ViewId::TableOfContents)).ok();
-                                    }
-                                } else {
-                                    self.go_to_neighbor(CycleDir::Previous, hub, rq, context);
-                                }
-                            },
-                        }
-                    },
-                    Region::Strip(dir) => {
-                        match dir {
-                            Dir::West => {
-                                if self.search.is_none() {
-                                    match context.settings.reader.west_strip {
-                                        WestStripAction::PreviousPage => {
-                                            self.go_to_neighbor(CycleDir::Previous, hub, rq, context);
-                                        }
-                                        WestStripAction::NextPage => {
-                                            self.go_to_neighbor(CycleDir::Next, hub, rq, context);
-                                        }
-                                        WestStripAction::None => (),
-                                    }
-                                } else {
-                                    self.go_to_results_neighbor(CycleDir::Previous, hub, rq, context);
-                                }
-                            },
-                            Dir::East => {
-                                if self.search.is_none() {
-                                    match context.settings.reader.east_strip {
-                                        EastStripAction::PreviousPage => {
-                                            self.go_to_neighbor(CycleDir::Previous, hub, rq, context);
-                                        }
-                                        EastStripAction::NextPage => {
-                                            self.go_to_neighbor(CycleDir::Next, hub, rq, context);
-                                        }
-                                        EastStripAction::None => (),
-                                    }
-                                } else {
-                                    self.go_to_results_neighbor(CycleDir::Next, hub, rq, context);
-                                }
-                            },
-                            Dir::South => match context.settings.reader.south_strip {
-                                SouthStripAction::ToggleBars => {
-                                    self.toggle_bars(None, hub, rq, context);
-                                }
-                                SouthStripAction::NextPage => {
-                                    self.go_to_neighbor(CycleDir::Next, hub, rq, context);
-                                }
-                            },
-                            Dir::North => self.toggle_bars(None, hub, rq, context),
-                        }
-                    },
-                    Region::Center => self.toggle_bars(None, hub, rq, context),
-                }
+                let region = Region::from_point(center, self.rect,
+                                                context.settings.reader.strip_width,
+                                                context.settings.reader.corner_width);
+                let action = self.tap_action(region, context);
+                self.perform_tap_action(action, hub, rq, context);
 
                 true
             },
@@ -3274,7 +5352,7 @@ impl View for Reader {
 
                 let mut found = None;
                 let mut dmin = u32::MAX;
-                let dmax = (scale_by_dpi(RECT_DIST_JITTER, CURRENT_DEVICE.dpi) as i32).pow(2) as u32;
+                let dmax = hit_distance(context);
 
                 if let Some(rect) = self.selection_rect() {
                     let d = center.rdist2(&rect);
@@ -3286,6 +5364,11 @@ impl View for Reader {
                     return true;
                 }
 
+                if let Some(link) = self.nearest_link(center) {
+                    self.preview_link(link, rq, context);
+                    return true;
+                }
+
                 for chunk in &self.chunks {
                     for word in &self.text[&chunk.location] {
                         let rect = (word.rect * chunk.scale).to_rect() - chunk.frame.min + chunk.position;
@@ -3301,12 +5384,18 @@ impl View for Reader {
                     let anchor = nearest_word.location;
                     if let Some(annot) = self.annotations.values().flatten()
                                              .find(|annot| anchor >= annot.selection[0] && anchor <= annot.selection[1]).cloned() {
-                        let radius = scale_by_dpi(24.0, CURRENT_DEVICE.dpi) as i32;
-                        self.toggle_annotation_menu(&annot, Rectangle::from_disk(center, radius), Some(true), rq, context);
+                        if context.settings.reader.edit_note_on_hold && !annot.note.is_empty() {
+                            hub.send(Event::Select(EntryId::EditAnnotationNote(annot.selection))).ok();
+                        } else {
+                            let radius = scale_by_dpi(24.0, CURRENT_DEVICE.dpi) as i32;
+                            self.toggle_annotation_menu(&annot, Rectangle::from_disk(center, radius), Some(true), rq, context);
+                        }
                     } else {
+                        let anchor = self.selection_unit(anchor, context.settings.reader.selection_granularity);
+                        let rect = self.text_rect(anchor).unwrap_or(rect);
                         self.selection = Some(Selection {
-                            start: anchor,
-                            end: anchor,
+                            start: anchor[0],
+                            end: anchor[1],
                             anchor,
                         });
                         self.state = State::Selection(id);
@@ -3326,6 +5415,10 @@ impl View for Reader {
                 self.state = State::Idle;
                 true
             },
+            Event::FollowLink(ref link) => {
+                self.follow_link(link, hub, rq, context);
+                true
+            },
             Event::Update(mode) => {
                 self.update(Some(mode), hub, rq, context);
                 true
@@ -3335,13 +5428,17 @@ impl View for Reader {
                 true
             },
             Event::Submit(ViewId::GoToPageInput, ref text) => {
-                let re = Regex::new(r#"^([-+'])?(.+)$"#).unwrap();
+                let re = Regex::new(r#"^([-+'@])?(.+)$"#).unwrap();
                 if let Some(caps) = re.captures(text) {
                     let prefix = caps.get(1).map(|m| m.as_str());
                     if prefix == Some("'") {
                         if let Some(location) = self.find_page_by_name(&caps[2]) {
                             self.go_to_page(location, true, hub, rq, context);
                         }
+                    } else if prefix == Some("@") {
+                        if let Some(location) = self.find_chapter_by_title(&caps[2]) {
+                            self.go_to_page(location, true, hub, rq, context);
+                        }
                     } else {
                         if text == "_" {
                             let location = (context.rng.next_u64() % self.pages_count as u64) as usize;
@@ -3422,7 +5519,7 @@ impl View for Reader {
             Event::Submit(ViewId::ReaderSearchInput, ref text) => {
                 match make_query(text) {
                     Some(query) => {
-                        self.search(text, query, hub, rq);
+                        self.search(text, query, hub, rq, context);
                         self.toggle_keyboard(false, None, hub, rq, context);
                         self.toggle_results_bar(true, rq, context);
                     },
@@ -3541,8 +5638,8 @@ impl View for Reader {
                 self.toggle_contrast_gray_menu(rect, None, rq, context);
                 true
             },
-            Event::ToggleNear(ViewId::PageMenu, rect) => {
-                self.toggle_page_menu(rect, None, rq, context);
+            Event::ToggleNear(ViewId::ResultsMenu, rect) => {
+                self.toggle_results_menu(rect, None, rq, context);
                 true
             },
             Event::Close(ViewId::MainMenu) => {
@@ -3613,26 +5710,44 @@ impl View for Reader {
                 true
             },
             Event::Select(EntryId::Annotations) => {
-                self.toggle_bars(Some(false), hub, rq, context);
-                let mut starts = self.annotations.values().flatten()
-                                     .map(|annot| annot.selection[0]).collect::<Vec<TextLocation>>();
-                starts.sort();
-                let active_range = starts.first().cloned().zip(starts.last().cloned());
-                if let Some(mut annotations) = self.info.reader.as_ref().map(|r| &r.annotations).cloned() {
-                    annotations.sort_by(|a, b| a.selection[0].cmp(&b.selection[0]));
-                    let html = annotations_as_html(&annotations, active_range);
-                    let link_uri = annotations.iter()
-                                              .filter(|annot| annot.selection[0].location() <= self.current_page)
-                                              .max_by_key(|annot| annot.selection[0])
-                                              .map(|annot| format!("@{}", annot.selection[0].location()));
-                    hub.send(Event::OpenHtml(html, link_uri)).ok();
+                self.show_annotations(None, context.settings.reader.group_highlights, hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::ExpandHighlights) => {
+                self.show_annotations(None, false, hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::SearchAnnotations) => {
+                self.toggle_search_annotations(Some(true), hub, rq, context);
+                true
+            },
+            Event::Submit(ViewId::SearchAnnotationsInput, ref text) => {
+                match make_query(text) {
+                    Some(query) => {
+                        self.toggle_keyboard(false, None, hub, rq, context);
+                        if !self.show_annotations(Some(&query), context.settings.reader.group_highlights, hub, rq, context) {
+                            let notif = Notification::new("No matching annotations.".to_string(),
+                                                          hub, rq, context);
+                            self.children.push(Box::new(notif) as Box<dyn View>);
+                        }
+                    },
+                    None => {
+                        let notif = Notification::new("Invalid search query.".to_string(),
+                                                      hub, rq, context);
+                        self.children.push(Box::new(notif) as Box<dyn View>);
+                    },
                 }
                 true
             },
+            Event::Close(ViewId::SearchAnnotations) => {
+                self.toggle_keyboard(false, None, hub, rq, context);
+                false
+            },
             Event::Select(EntryId::Bookmarks) => {
                 self.toggle_bars(Some(false), hub, rq, context);
-                if let Some(bookmarks) = self.info.reader.as_ref().map(|r| &r.bookmarks) {
-                    let html = bookmarks_as_html(bookmarks, self.current_page, self.synthetic);
+                if let Some(r) = self.info.reader.as_ref() {
+                    let bookmarks = &r.bookmarks;
+                    let html = bookmarks_as_html(bookmarks, &r.auto_bookmarks, self.current_page, self.synthetic);
                     let link_uri = bookmarks.range(..= self.current_page).next_back()
                                             .map(|index| format!("@{}", index));
                     hub.send(Event::OpenHtml(html, link_uri)).ok();
@@ -3651,6 +5766,30 @@ impl View for Reader {
                 self.toggle_margin_cropper(false, hub, rq, context);
                 true
             },
+            Event::Close(ViewId::ImageViewer) => {
+                self.toggle_image_viewer(None, hub, rq, context);
+                true
+            },
+            Event::Close(ViewId::Footnote) => {
+                self.toggle_footnote(None, rq, context);
+                true
+            },
+            Event::Select(EntryId::TapZones) => {
+                self.toggle_tap_zones(true, hub, rq, context);
+                true
+            },
+            Event::Close(ViewId::TapZones) => {
+                self.toggle_tap_zones(false, hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::PageGrid) => {
+                self.toggle_page_grid(true, hub, rq, context);
+                true
+            },
+            Event::Close(ViewId::PageGrid) => {
+                self.toggle_page_grid(false, hub, rq, context);
+                true
+            },
             Event::SearchResult(location, ref rects) => {
                 if self.search.is_none() {
                     return true;
@@ -3696,18 +5835,23 @@ impl View for Reader {
                 self.toggle_edit_note(None, Some(true), hub, rq, context);
                 true
             },
+            Event::Select(EntryId::HighlightAllResults) => {
+                self.highlight_all_results(hub, rq, context);
+                true
+            },
             Event::Select(EntryId::HighlightSelection) => {
                 if let Some(sel) = self.selection.take() {
-                    let text = self.text_excerpt([sel.start, sel.end]).unwrap();
+                    let selection = self.merge_highlights([sel.start, sel.end]);
+                    let text = self.text_excerpt(selection).unwrap();
                     if let Some(r) = self.info.reader.as_mut() {
                         r.annotations.push(Annotation {
-                            selection: [sel.start, sel.end],
+                            selection,
                             note: String::new(),
                             text,
                             modified: Local::now().naive_local(),
                         });
                     }
-                    if let Some(rect) = self.text_rect([sel.start, sel.end]) {
+                    if let Some(rect) = self.text_rect(selection) {
                         rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
                     }
                     self.update_annotations();
@@ -3729,7 +5873,7 @@ impl View for Reader {
                     let text = text.trim_matches(|c: char| !c.is_alphanumeric());
                     match make_query(text) {
                         Some(query) => {
-                            self.search(text, query, hub, rq);
+                            self.search(text, query, hub, rq, context);
                         },
                         None => {
                             let notif = Notification::new("Invalid search query.".to_string(),
@@ -3744,6 +5888,58 @@ impl View for Reader {
                 self.selection = None;
                 true
             },
+            Event::Select(EntryId::CopySelectionToClippings) => {
+                self.copy_to_clippings(hub, rq, context);
+                if let Some(rect) = self.selection_rect() {
+                    rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
+                }
+                self.selection = None;
+                true
+            },
+            Event::Select(EntryId::ShareSelection) => {
+                if let Some(quote) = self.selected_quote(context) {
+                    hub.send(Event::QuoteShared(quote)).ok();
+                }
+                if let Some(rect) = self.selection_rect() {
+                    rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
+                }
+                self.selection = None;
+                true
+            },
+            Event::Select(EntryId::TranslateSelection) => {
+                if let Some(text) = self.selected_text() {
+                    hub.send(Event::Translate(text, self.info.language.clone())).ok();
+                }
+                if let Some(rect) = self.selection_rect() {
+                    rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
+                }
+                self.selection = None;
+                true
+            },
+            Event::Translation(ref text) => {
+                self.toggle_footnote(None, rq, context);
+                self.toggle_footnote(Some(text.clone()), rq, context);
+                true
+            },
+            Event::Select(EntryId::SpeakSelection) => {
+                if let Some(sel) = self.selection.take() {
+                    // The speech of the selection isn't followed on the page.
+                    if self.read_aloud.take().is_some() {
+                        hub.send(Event::StopReadAloud).ok();
+                    }
+                    let mut words = self.text.values().flatten()
+                                        .filter(|word| word.location >= sel.start && word.location <= sel.end)
+                                        .cloned().collect::<Vec<BoundedText>>();
+                    words.sort_by_key(|word| word.location);
+                    if !words.is_empty() {
+                        hub.send(Event::ReadAloud(words)).ok();
+                    }
+                    if let Some(rect) = self.text_rect([sel.start, sel.end]) {
+                        rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
+                    }
+                }
+                true
+            },
             Event::Select(EntryId::GoToSelectedPageName) => {
                 if let Some(loc) = self.selected_text().and_then(|text| {
                     let end = text.find(|c: char| !c.is_ascii_digit() &&
@@ -3799,6 +5995,78 @@ impl View for Reader {
                 self.set_scroll_mode(scroll_mode, hub, rq, context);
                 true
             },
+            Event::Select(EntryId::ToggleWarmReading) => {
+                self.warm_paper = warm_paper(&context.settings);
+                let update_mode = if context.settings.reader.contrast_full_refresh {
+                    UpdateMode::Full
+                } else {
+                    UpdateMode::Gui
+                };
+                rq.add(RenderData::new(self.id, self.rect, update_mode));
+                true
+            },
+            Event::Select(EntryId::SetPaperTint(paper_tint)) => {
+                if let Some(ref mut r) = self.info.reader {
+                    r.paper_tint = paper_tint;
+                }
+                rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+                true
+            },
+            Event::Select(EntryId::ToggleReadingRuler) => {
+                self.toggle_reading_ruler(rq, context);
+                true
+            },
+            Event::Select(EntryId::ToggleAutoContrast) => {
+                self.toggle_auto_contrast(hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::ToggleRightToLeft) => {
+                self.right_to_left = !self.right_to_left;
+                if let Some(ref mut r) = self.info.reader {
+                    r.right_to_left = Some(self.right_to_left);
+                }
+                true
+            },
+            Event::Select(EntryId::SetEncoding(encoding)) => {
+                self.set_encoding(encoding, hub, context);
+                true
+            },
+            Event::Select(EntryId::SetColumnSplit(percent)) => {
+                self.set_column_split(percent, hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::ToggleReadAloud) => {
+                self.toggle_read_aloud(hub, rq);
+                true
+            },
+            Event::SpeechPosition(range) => {
+                self.update_speech_position(range, hub, rq, context);
+                true
+            },
+            Event::SpeechStopped => {
+                if let Some(read_aloud) = self.read_aloud.take() {
+                    if let Some(rect) = read_aloud.range.and_then(|range| self.text_rect(range)) {
+                        rq.add(RenderData::new(self.id, rect, UpdateMode::Gui));
+                    }
+                }
+                true
+            },
+            Event::Select(EntryId::SharePage) => {
+                self.share_page(hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::ExportFlashcards) => {
+                self.export_flashcards(hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::ExportText) => {
+                self.export_text(hub, rq, context);
+                true
+            },
+            Event::Select(EntryId::ImportAnnotations) => {
+                self.import_annotations(hub, rq, context);
+                true
+            },
             Event::Select(EntryId::Save) => {
                 let name = format!("{}-{}.{}", self.info.title.to_lowercase().replace(' ', "_"),
                                    Local::now().format("%Y%m%d_%H%M%S"),
@@ -3833,6 +6101,10 @@ impl View for Reader {
                 self.search_direction = dir;
                 true
             },
+            Event::Select(EntryId::SearchScope(scope)) => {
+                context.settings.reader.search_scope = scope;
+                true
+            },
             Event::Select(EntryId::SetFontFamily(ref font_family)) => {
                 self.set_font_family(font_family, hub, rq, context);
                 true
@@ -3853,6 +6125,10 @@ impl View for Reader {
                 self.set_margin_width(width, hub, rq, context);
                 true
             },
+            Event::Select(EntryId::SetMarginBias(bias)) => {
+                self.set_margin_bias(bias, hub, rq, context);
+                true
+            },
             Event::Select(EntryId::SetLineHeight(v)) => {
                 let line_height = 1.0 + v as f32 / 10.0;
                 self.set_line_height(line_height, hub, rq, context);
@@ -3894,8 +6170,15 @@ impl View for Reader {
                 true
             },
             Event::Device(DeviceEvent::Button { code: ButtonCode::Home, status: ButtonStatus::Pressed, .. }) => {
-                self.quit(context);
-                hub.send(Event::Back).ok();
+                match context.settings.reader.home_button {
+                    HomeButtonAction::Quit => {
+                        self.quit(context);
+                        hub.send(Event::Back).ok();
+                    },
+                    HomeButtonAction::ToggleBars => self.toggle_bars(None, hub, rq, context),
+                    HomeButtonAction::Bookmark => self.toggle_bookmark(rq),
+                    HomeButtonAction::None => (),
+                }
                 true
             },
             Event::Select(EntryId::Quit) |
@@ -3926,17 +6209,25 @@ impl View for Reader {
         }
     }
 
-    fn render(&self, fb: &mut dyn Framebuffer, rect: Rectangle, _fonts: &mut Fonts) {
-        fb.draw_rectangle(&rect, WHITE);
+    fn render(&self, fb: &mut dyn Framebuffer, rect: Rectangle, fonts: &mut Fonts) {
+        let paper_tint = self.info.reader.as_ref().and_then(|r| r.paper_tint)
+                             .or(self.warm_paper);
+
+        fb.draw_rectangle(&rect, paper_tint.map_or(WHITE, Color::Gray));
 
         for chunk in &self.chunks {
-            let Resource { ref pixmap, scale, .. } = self.cache[&chunk.location];
+            let Resource { ref pixmap, scale, contrast, .. } = self.cache[&chunk.location];
+            let contrast = contrast.unwrap_or(self.contrast);
             let chunk_rect = chunk.frame - chunk.frame.min + chunk.position;
 
             if let Some(region_rect) = rect.intersection(&chunk_rect) {
                 let chunk_frame = region_rect - chunk.position + chunk.frame.min;
                 let chunk_position = region_rect.min;
-                fb.draw_framed_pixmap_contrast(pixmap, &chunk_frame, chunk_position, self.contrast.exponent, self.contrast.gray);
+                if let Some(level) = paper_tint {
+                    fb.draw_framed_pixmap_tinted(pixmap, &chunk_frame, chunk_position, contrast.exponent, contrast.gray, level);
+                } else {
+                    fb.draw_framed_pixmap_contrast(pixmap, &chunk_frame, chunk_position, contrast.exponent, contrast.gray);
+                }
 
                 if let Some(rects) = self.noninverted_regions.get(&chunk.location) {
                     for r in rects {
@@ -3976,16 +6267,33 @@ impl View for Reader {
                 }
 
                 if let Some(annotations) = self.annotations.get(&chunk.location) {
+                    let thickness = scale_by_dpi(THICKNESS_MEDIUM, CURRENT_DEVICE.dpi) as i32;
                     for annot in annotations {
                         let drift = if annot.note.is_empty() { HIGHLIGHT_DRIFT } else { ANNOTATION_DRIFT };
+                        let underline_color = if annot.note.is_empty() { GRAY08 } else { BLACK };
+                        // Shade the given rectangle, or draw a line along its bottom edge.
+                        let mark = |fb: &mut dyn Framebuffer, rect: &Rectangle| {
+                            match self.annotation_style {
+                                AnnotationStyle::Shade => {
+                                    if let Some(ref sel_rect) = rect.intersection(&region_rect) {
+                                        fb.shift_region(sel_rect, drift);
+                                    }
+                                },
+                                AnnotationStyle::Underline => {
+                                    let line_rect = rect![rect.min.x, rect.max.y - thickness,
+                                                          rect.max.x, rect.max.y];
+                                    if let Some(ref line_rect) = line_rect.intersection(&region_rect) {
+                                        fb.draw_rectangle(line_rect, underline_color);
+                                    }
+                                },
+                            }
+                        };
                         let [start, end] = annot.selection;
                         if let Some(text) = self.text.get(&chunk.location) {
                             let mut last_rect: Option<Rectangle> = None;
                             for word in text.iter().filter(|w| w.location >= start && w.location <= end) {
                                 let rect = (word.rect * scale).to_rect() - chunk.frame.min + chunk.position;
-                                if let Some(ref sel_rect) = rect.intersection(&region_rect) {
-                                    fb.shift_region(sel_rect, drift);
-                                }
+                                mark(fb, &rect);
                                 if let Some(last) = last_rect {
                                     // Are `rect` and `last` on the same line?
                                     if rect.max.y.min(last.max.y) - rect.min.y.max(last.min.y) > rect.height().min(last.height()) as i32 / 2 &&
@@ -3997,9 +6305,7 @@ impl View for Reader {
                                             rect![rect.max.x, (last.min.y + rect.min.y) / 2,
                                                   last.min.x, (last.max.y + rect.max.y) / 2]
                                         };
-                                        if let Some(ref sel_rect) = space.intersection(&region_rect) {
-                                            fb.shift_region(sel_rect, drift);
-                                        }
+                                        mark(fb, &space);
                                     }
                                 }
                                 last_rect = Some(rect);
@@ -4008,6 +6314,17 @@ impl View for Reader {
                     }
                 }
 
+                if let Some([start, end]) = self.read_aloud.as_ref().and_then(|r| r.range) {
+                    if let Some(text) = self.text.get(&chunk.location) {
+                        for word in text.iter().filter(|w| w.location >= start && w.location <= end) {
+                            let rect = (word.rect * scale).to_rect() - chunk.frame.min + chunk.position;
+                            if let Some(ref speech_rect) = rect.intersection(&region_rect) {
+                                fb.invert_region(speech_rect);
+                            }
+                        }
+                    }
+                }
+
                 if let Some(sel) = self.selection.as_ref() {
                     if let Some(text) = self.text.get(&chunk.location) {
                         let mut last_rect: Option<Rectangle> = None;
@@ -4038,7 +6355,31 @@ impl View for Reader {
             }
         }
 
-        if self.info.reader.as_ref().map_or(false, |r| r.bookmarks.contains(&self.current_page)) {
+        if self.page_separator.line {
+            let dpi = CURRENT_DEVICE.dpi;
+            let thickness = scale_by_dpi(THICKNESS_SMALL, dpi) as i32;
+            for pair in self.chunks.windows(2) {
+                let top = pair[0].position.y + pair[0].frame.height() as i32;
+                let bottom = pair[1].position.y;
+                if bottom - top < thickness {
+                    continue;
+                }
+                let y_min = (top + bottom - thickness) / 2;
+                let line_rect = rect![pair[0].position.x.min(pair[1].position.x), y_min,
+                                      (pair[0].position.x + pair[0].frame.width() as i32)
+                                      .max(pair[1].position.x + pair[1].frame.width() as i32),
+                                      y_min + thickness];
+                if let Some(line_rect) = line_rect.intersection(&rect) {
+                    fb.draw_rectangle(&line_rect, SEPARATOR_NORMAL);
+                }
+            }
+        }
+
+        if let Some(ruler_rect) = self.reading_ruler_rect().and_then(|r| r.intersection(&rect)) {
+            fb.draw_blended_rectangle(&ruler_rect, BLACK, READING_RULER_ALPHA);
+        }
+
+        if !self.immersive && self.info.reader.as_ref().is_some_and(|r| r.bookmarks.contains(&self.current_page)) {
             let dpi = CURRENT_DEVICE.dpi;
             let thickness = scale_by_dpi(3.0, dpi) as u16;
             let radius = mm_to_px(0.4, dpi) as i32 + thickness as i32;
@@ -4049,6 +6390,24 @@ impl View for Reader {
                                                   &BorderSpec { thickness, color: WHITE },
                                                   &BLACK);
         }
+
+        if let Some(corner) = self.page_indicator.filter(|_| !self.immersive) {
+            if locate::<TopBar>(self).is_none() {
+                let font = font_from_style(fonts, &PAGE_INDICATOR_STYLE, CURRENT_DEVICE.dpi);
+                let text = page_text(self.current_page, self.pages_count, self.synthetic, 2);
+                let plan = font.plan(&text, None, None);
+                let padding = font.em() as i32;
+                let x = match corner {
+                    PageIndicatorCorner::NorthWest | PageIndicatorCorner::SouthWest => self.rect.min.x + padding,
+                    PageIndicatorCorner::NorthEast | PageIndicatorCorner::SouthEast => self.rect.max.x - padding - plan.width,
+                };
+                let y = match corner {
+                    PageIndicatorCorner::NorthWest | PageIndicatorCorner::NorthEast => self.rect.min.y + padding + font.ascender(),
+                    PageIndicatorCorner::SouthWest | PageIndicatorCorner::SouthEast => self.rect.max.y - padding,
+                };
+                font.render(fb, PAGE_INDICATOR_COLOR, &plan, pt!(x, y));
+            }
+        }
     }
 
     fn render_rect(&self, rect: &Rectangle) -> Rectangle {
@@ -4076,6 +6435,7 @@ impl View for Reader {
                                            rect.max.x,
                                            small_height + big_thickness];
                 self.children[1].resize(separator_rect, hub, rq, context);
+                floating_layer_start = 2;
             } else if self.children[0].is::<Filler>() {
                 let mut index = 1;
                 if self.children[index].is::<SearchBar>() {
@@ -0,0 +1,132 @@
+use crate::device::CURRENT_DEVICE;
+use crate::framebuffer::Framebuffer;
+use crate::font::{Fonts, font_from_style, NORMAL_STYLE};
+use crate::geom::{Rectangle, CornerSpec, BorderSpec};
+use crate::gesture::GestureEvent;
+use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, ViewId, Align};
+use crate::view::{THICKNESS_LARGE, BORDER_RADIUS_MEDIUM};
+use crate::view::label::Label;
+use crate::view::text_area::TextArea;
+use crate::unit::scale_by_dpi;
+use crate::color::{BLACK, WHITE};
+use crate::context::Context;
+
+// The number of lines of the note that are visible at once.
+const VISIBLE_LINES_COUNT: i32 = 6;
+
+// Edits the note of an annotation in a multi-line text area.
+pub struct NoteEditor {
+    id: Id,
+    rect: Rectangle,
+    children: Vec<Box<dyn View>>,
+}
+
+// The rectangles of the editor, of its label and of its text area.
+fn layout(context: &mut Context) -> [Rectangle; 3] {
+    let dpi = CURRENT_DEVICE.dpi;
+    let (width, height) = context.display.dims;
+    let font = font_from_style(&mut context.fonts, &NORMAL_STYLE, dpi);
+    let x_height = font.x_heights.0 as i32;
+    let padding = font.em() as i32;
+    let area_height = VISIBLE_LINES_COUNT * font.line_height() + 2 * x_height;
+    let total_height = 6 * x_height + area_height + padding;
+
+    let x_min = 2 * padding;
+    let x_max = width as i32 - 2 * padding;
+    let y_min = (height as i32 / 3 - total_height / 2).max(padding);
+    let y_max = y_min + total_height;
+
+    [rect![x_min, y_min, x_max, y_max],
+     rect![x_min + padding, y_min + x_height, x_max - padding, y_min + 5 * x_height],
+     rect![x_min + padding, y_min + 6 * x_height, x_max - padding, y_min + 6 * x_height + area_height]]
+}
+
+impl NoteEditor {
+    pub fn new(text: &str, context: &mut Context) -> NoteEditor {
+        let id = ID_FEEDER.next();
+        let [rect, label_rect, area_rect] = layout(context);
+        let mut children = Vec::new();
+
+        let label = Label::new(label_rect, "Note".to_string(), Align::Center);
+        children.push(Box::new(label) as Box<dyn View>);
+
+        let mut text_area = TextArea::new(area_rect, ViewId::EditNoteInput);
+        text_area.set_text(text, true, &mut RenderQueue::new(), context);
+        children.push(Box::new(text_area) as Box<dyn View>);
+
+        NoteEditor {
+            id,
+            rect,
+            children,
+        }
+    }
+}
+
+impl View for NoteEditor {
+    fn handle_event(&mut self, evt: &Event, _hub: &Hub, bus: &mut Bus, _rq: &mut RenderQueue, context: &mut Context) -> bool {
+        match *evt {
+            Event::Submit(..) => {
+                bus.push_back(Event::Close(ViewId::EditNote));
+                false
+            },
+            Event::Gesture(GestureEvent::Tap(center)) | Event::Gesture(GestureEvent::HoldFingerShort(center, _)) => {
+                if !self.rect.includes(center) && !context.kb_rect.includes(center) {
+                    if !context.kb_rect.is_empty() {
+                        bus.push_back(Event::Close(ViewId::EditNote));
+                    }
+                    true
+                } else {
+                    self.rect.includes(center)
+                }
+            },
+            Event::Gesture(..) => true,
+            _ => false,
+        }
+    }
+
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, _fonts: &mut Fonts) {
+        let dpi = CURRENT_DEVICE.dpi;
+        let border_radius = scale_by_dpi(BORDER_RADIUS_MEDIUM, dpi) as i32;
+        let border_thickness = scale_by_dpi(THICKNESS_LARGE, dpi) as u16;
+        fb.draw_rounded_rectangle_with_border(&self.rect,
+                                              &CornerSpec::Uniform(border_radius),
+                                              &BorderSpec { thickness: border_thickness,
+                                                            color: BLACK },
+                                              &WHITE);
+    }
+
+    fn resize(&mut self, _rect: Rectangle, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
+        let [rect, label_rect, area_rect] = layout(context);
+        self.children[0].resize(label_rect, hub, rq, context);
+        self.children[1].resize(area_rect, hub, rq, context);
+        self.rect = rect;
+    }
+
+    fn is_background(&self) -> bool {
+        true
+    }
+
+    fn view_id(&self) -> Option<ViewId> {
+        Some(ViewId::EditNote)
+    }
+
+    fn rect(&self) -> &Rectangle {
+        &self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut Rectangle {
+        &mut self.rect
+    }
+
+    fn children(&self) -> &Vec<Box<dyn View>> {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Box<dyn View>> {
+        &mut self.children
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+}
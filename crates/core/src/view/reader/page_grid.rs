@@ -0,0 +1,270 @@
+use std::mem;
+use std::thread;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use fxhash::FxHashMap;
+use crate::device::CURRENT_DEVICE;
+use crate::framebuffer::{Framebuffer, UpdateMode, Pixmap};
+use crate::document::{Document, Location};
+use crate::geom::{Rectangle, BorderSpec, Dir};
+use crate::font::{Fonts, font_from_style, NORMAL_STYLE};
+use crate::color::{BLACK, WHITE, TEXT_NORMAL};
+use crate::input::{DeviceEvent, ButtonCode, ButtonStatus};
+use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData, ViewId};
+use crate::view::{SMALL_BAR_HEIGHT, THICKNESS_SMALL, THICKNESS_LARGE};
+use crate::gesture::GestureEvent;
+use crate::unit::scale_by_dpi;
+use crate::context::Context;
+use super::page_label;
+
+const COLUMNS_COUNT: usize = 3;
+const ROWS_COUNT: usize = 3;
+const THUMBNAILS_COUNT: usize = COLUMNS_COUNT * ROWS_COUNT;
+// The maximum number of thumbnails kept in the cache.
+const CACHE_SIZE: usize = 8 * THUMBNAILS_COUNT;
+
+// Shows the thumbnails of the pages surrounding the current one, in a grid.
+// Tapping a thumbnail goes to its page, swiping shows the next or previous pages,
+// and tapping outside of the thumbnails closes the grid.
+// The missing thumbnails are rendered in the background, and drawn as they arrive.
+pub struct PageGrid {
+    id: Id,
+    rect: Rectangle,
+    children: Vec<Box<dyn View>>,
+    doc: Arc<Mutex<Box<dyn Document>>>,
+    pages_count: usize,
+    current_page: usize,
+    // The page of the first thumbnail.
+    first_page: usize,
+    // The thumbnails, by page.
+    cache: FxHashMap<usize, Pixmap>,
+    page_names: BTreeMap<usize, String>,
+    right_to_left: bool,
+    // Cleared to stop the rendering of the thumbnails of the pages that aren't shown anymore.
+    loading: Arc<AtomicBool>,
+}
+
+impl PageGrid {
+    pub fn new(rect: Rectangle, doc: Arc<Mutex<Box<dyn Document>>>, current_page: usize, cache: FxHashMap<usize, Pixmap>,
+               page_names: BTreeMap<usize, String>, right_to_left: bool, hub: &Hub) -> PageGrid {
+        let pages_count = doc.lock().unwrap().pages_count();
+        let first_page = current_page.saturating_sub(THUMBNAILS_COUNT / 2)
+                                     .min(pages_count.saturating_sub(THUMBNAILS_COUNT));
+        let mut page_grid = PageGrid {
+            id: ID_FEEDER.next(),
+            rect,
+            children: Vec::new(),
+            doc,
+            pages_count,
+            current_page,
+            first_page,
+            cache,
+            page_names,
+            right_to_left,
+            loading: Arc::new(AtomicBool::new(false)),
+        };
+        // The thumbnails rendered for another orientation are too large.
+        let frame = page_grid.thumbnail_frame(0);
+        page_grid.cache.retain(|_, pixmap| pixmap.width <= frame.width() && pixmap.height <= frame.height());
+        page_grid.load_thumbnails(hub);
+        page_grid
+    }
+
+    // Gives the cache back, so that it can be reused when the grid is shown again.
+    pub fn take_cache(&mut self) -> FxHashMap<usize, Pixmap> {
+        mem::take(&mut self.cache)
+    }
+
+    fn pages(&self) -> impl Iterator<Item=usize> {
+        self.first_page..(self.first_page + THUMBNAILS_COUNT).min(self.pages_count)
+    }
+
+    fn cell_rect(&self, index: usize) -> Rectangle {
+        let cell_width = self.rect.width() as i32 / COLUMNS_COUNT as i32;
+        let cell_height = self.rect.height() as i32 / ROWS_COUNT as i32;
+        let x_min = self.rect.min.x + (index % COLUMNS_COUNT) as i32 * cell_width;
+        let y_min = self.rect.min.y + (index / COLUMNS_COUNT) as i32 * cell_height;
+        rect![x_min, y_min, x_min + cell_width, y_min + cell_height]
+    }
+
+    // The area of a cell where the thumbnail is drawn: the page number is written below it.
+    fn thumbnail_frame(&self, index: usize) -> Rectangle {
+        let label_height = scale_by_dpi(SMALL_BAR_HEIGHT, CURRENT_DEVICE.dpi) as i32;
+        let padding = label_height / 4;
+        let cell_rect = self.cell_rect(index);
+        rect![cell_rect.min.x + padding, cell_rect.min.y + padding,
+              cell_rect.max.x - padding, cell_rect.max.y - label_height]
+    }
+
+    // Renders the missing thumbnails of the shown pages in the background.
+    fn load_thumbnails(&mut self, hub: &Hub) {
+        self.loading.store(false, Ordering::Relaxed);
+
+        let pages = self.pages().filter(|page| !self.cache.contains_key(page))
+                        .collect::<Vec<usize>>();
+        if pages.is_empty() {
+            return;
+        }
+
+        let frame = self.thumbnail_frame(0);
+        let loading = Arc::new(AtomicBool::new(true));
+        let loading2 = Arc::clone(&loading);
+        let doc2 = Arc::clone(&self.doc);
+        let hub2 = hub.clone();
+        self.loading = loading;
+
+        thread::spawn(move || {
+            for page in pages {
+                if !loading2.load(Ordering::Relaxed) {
+                    break;
+                }
+                let mut doc = doc2.lock().unwrap();
+                let pixmap = doc.dims(page).and_then(|(width, height)| {
+                    let scale = (frame.width() as f32 / width).min(frame.height() as f32 / height);
+                    doc.pixmap(Location::Exact(page), scale, CURRENT_DEVICE.color_samples())
+                }).map(|(pixmap, _)| pixmap);
+                drop(doc);
+                if let Some(pixmap) = pixmap {
+                    hub2.send(Event::PageThumbnail(page, pixmap)).ok();
+                }
+            }
+        });
+    }
+
+    fn insert_thumbnail(&mut self, page: usize, pixmap: Pixmap) {
+        self.cache.insert(page, pixmap);
+
+        if self.cache.len() > CACHE_SIZE {
+            let center = self.first_page + THUMBNAILS_COUNT / 2;
+            let mut pages = self.cache.keys().cloned().collect::<Vec<usize>>();
+            pages.sort_by_key(|page| (*page as isize - center as isize).abs());
+            for page in pages.into_iter().skip(CACHE_SIZE) {
+                self.cache.remove(&page);
+            }
+        }
+    }
+
+    // The swipes follow the reading direction: the next pages are east of the current ones
+    // when reading from right to left.
+    fn go_to_neighbor(&mut self, dir: Dir, hub: &Hub, rq: &mut RenderQueue) {
+        let forward = match dir {
+            Dir::West => !self.right_to_left,
+            Dir::East => self.right_to_left,
+            _ => return,
+        };
+        let first_page = if forward {
+            if self.first_page + THUMBNAILS_COUNT >= self.pages_count {
+                return;
+            }
+            self.first_page + THUMBNAILS_COUNT
+        } else {
+            if self.first_page == 0 {
+                return;
+            }
+            self.first_page.saturating_sub(THUMBNAILS_COUNT)
+        };
+        self.first_page = first_page;
+        self.load_thumbnails(hub);
+        rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+    }
+}
+
+impl Drop for PageGrid {
+    fn drop(&mut self) {
+        self.loading.store(false, Ordering::Relaxed);
+    }
+}
+
+impl View for PageGrid {
+    fn handle_event(&mut self, evt: &Event, hub: &Hub, bus: &mut Bus, rq: &mut RenderQueue, _context: &mut Context) -> bool {
+        match *evt {
+            Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) => {
+                let page = self.pages().enumerate()
+                               .find(|(index, _)| self.cell_rect(*index).includes(center))
+                               .map(|(_, page)| page);
+                if let Some(page) = page {
+                    hub.send(Event::GoTo(page)).ok();
+                }
+                bus.push_back(Event::Close(ViewId::PageGrid));
+                true
+            },
+            Event::Gesture(GestureEvent::Swipe { dir, start, .. }) if self.rect.includes(start) => {
+                self.go_to_neighbor(dir, hub, rq);
+                true
+            },
+            Event::PageThumbnail(page, ref pixmap) => {
+                let index = self.pages().position(|p| p == page);
+                self.insert_thumbnail(page, pixmap.clone());
+                if let Some(index) = index {
+                    rq.add(RenderData::new(self.id, self.cell_rect(index), UpdateMode::Gui));
+                }
+                true
+            },
+            Event::Device(DeviceEvent::Button { code: ButtonCode::Home, status: ButtonStatus::Pressed, .. }) => {
+                bus.push_back(Event::Close(ViewId::PageGrid));
+                true
+            },
+            Event::Gesture(GestureEvent::HoldFingerShort(center, ..)) if self.rect.includes(center) => true,
+            _ => false,
+        }
+    }
+
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, fonts: &mut Fonts) {
+        let dpi = CURRENT_DEVICE.dpi;
+        let thin_thickness = scale_by_dpi(THICKNESS_SMALL, dpi) as u16;
+        let thick_thickness = scale_by_dpi(THICKNESS_LARGE, dpi) as u16;
+        let font = font_from_style(fonts, &NORMAL_STYLE, dpi);
+        let x_height = font.x_heights.0 as i32;
+
+        fb.draw_rectangle(&self.rect, WHITE);
+
+        for (index, page) in self.pages().enumerate() {
+            let frame = self.thumbnail_frame(index);
+
+            if let Some(pixmap) = self.cache.get(&page) {
+                let (width, height) = (pixmap.width as i32, pixmap.height as i32);
+                let pt = pt!(frame.min.x + (frame.width() as i32 - width) / 2,
+                             frame.min.y + (frame.height() as i32 - height) / 2);
+                fb.draw_pixmap(pixmap, pt);
+                let thickness = if page == self.current_page { thick_thickness } else { thin_thickness };
+                let border = thickness as i32;
+                fb.draw_rectangle_outline(&rect![pt.x - border, pt.y - border,
+                                                 pt.x + width + border, pt.y + height + border],
+                                          &BorderSpec { thickness, color: BLACK });
+            }
+
+            let label = page_label(page, &self.page_names, false);
+            let plan = font.plan(&label, Some(frame.width() as i32), None);
+            let cell_rect = self.cell_rect(index);
+            let dy = (cell_rect.max.y - frame.max.y - x_height) / 2;
+            let pt = pt!(frame.min.x + (frame.width() as i32 - plan.width) / 2,
+                         cell_rect.max.y - dy);
+            font.render(fb, TEXT_NORMAL[1], &plan, pt);
+        }
+    }
+
+    fn rect(&self) -> &Rectangle {
+        &self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut Rectangle {
+        &mut self.rect
+    }
+
+    fn children(&self) -> &Vec<Box<dyn View>> {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Box<dyn View>> {
+        &mut self.children
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn view_id(&self) -> Option<ViewId> {
+        Some(ViewId::PageGrid)
+    }
+}
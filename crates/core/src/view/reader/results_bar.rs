@@ -21,7 +21,7 @@ pub struct ResultsBar {
 }
 
 impl ResultsBar {
-    pub fn new(rect: Rectangle, current_page: usize, pages_count: usize, count: usize, completed: bool) -> ResultsBar {
+    pub fn new(rect: Rectangle, current_page: usize, pages_count: usize, current_result: usize, count: usize, completed: bool) -> ResultsBar {
         let id = ID_FEEDER.next();
         let mut children = Vec::new();
         let side = rect.height() as i32;
@@ -43,6 +43,7 @@ impl ResultsBar {
         let (small_half_width, big_half_width) = halves(rect.width() as i32 - 2 * side);
         let results_label = ResultsLabel::new(rect![pt!(rect.min.x + side, rect.min.y),
                                                     pt!(rect.min.x + side + small_half_width, rect.max.y)],
+                                              current_result,
                                               count,
                                               completed);
         children.push(Box::new(results_label) as Box<dyn View>);
@@ -75,9 +76,9 @@ impl ResultsBar {
         }
     }
 
-    pub fn update_results_label(&mut self, count: usize, rq: &mut RenderQueue) {
+    pub fn update_results_label(&mut self, current_result: usize, count: usize, rq: &mut RenderQueue) {
         let results_label = self.children[1].as_mut().downcast_mut::<ResultsLabel>().unwrap();
-        results_label.update(count, rq);
+        results_label.update(current_result, count, rq);
     }
 
     pub fn update_page_label(&mut self, current_page: usize, pages_count: usize, rq: &mut RenderQueue) {
@@ -131,7 +132,6 @@ impl View for ResultsBar {
                 bus.push_back(Event::Toggle(ViewId::GoToResultsPage));
                 true
             },
-            Event::ToggleNear(ViewId::PageMenu, _) => true,
             Event::Gesture(GestureEvent::Tap(center)) |
             Event::Gesture(GestureEvent::HoldFingerShort(center, ..)) if self.rect.includes(center) => true,
             Event::Gesture(GestureEvent::Swipe { start, .. }) if self.rect.includes(start) => true,
@@ -3,29 +3,33 @@ use crate::font::{Fonts, font_from_style, NORMAL_STYLE};
 use crate::framebuffer::{Framebuffer, UpdateMode};
 use crate::color::TEXT_NORMAL;
 use crate::geom::{Rectangle};
-use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData};
+use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData, ViewId};
+use crate::gesture::GestureEvent;
 use crate::context::Context;
 
 pub struct ResultsLabel {
     id: Id,
     rect: Rectangle,
     children: Vec<Box<dyn View>>,
+    current: usize,
     count: usize,
     completed: bool,
 }
 
 impl ResultsLabel {
-    pub fn new(rect: Rectangle, count: usize, completed: bool) -> ResultsLabel {
+    pub fn new(rect: Rectangle, current: usize, count: usize, completed: bool) -> ResultsLabel {
         ResultsLabel {
             id: ID_FEEDER.next(),
             rect,
             children: Vec::new(),
+            current,
             count,
             completed,
         }
     }
 
-    pub fn update(&mut self, count: usize, rq: &mut RenderQueue) {
+    pub fn update(&mut self, current: usize, count: usize, rq: &mut RenderQueue) {
+        self.current = current;
         self.count = count;
         rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
     }
@@ -39,16 +43,22 @@ impl ResultsLabel {
 
         if self.count == 0 {
             format!("No {}", qualifier)
-        } else {
+        } else if self.current == 0 {
             format!("{} {}", self.count, qualifier)
+        } else {
+            format!("{} of {} {}", self.current, self.count, qualifier)
         }
     }
 }
 
 
 impl View for ResultsLabel {
-    fn handle_event(&mut self, evt: &Event, _hub: &Hub, _bus: &mut Bus, rq: &mut RenderQueue, _context: &mut Context) -> bool {
+    fn handle_event(&mut self, evt: &Event, _hub: &Hub, bus: &mut Bus, rq: &mut RenderQueue, _context: &mut Context) -> bool {
         match *evt {
+            Event::Gesture(GestureEvent::HoldFingerShort(center, ..)) if self.rect.includes(center) => {
+                bus.push_back(Event::ToggleNear(ViewId::ResultsMenu, self.rect));
+                true
+            },
             Event::EndOfSearch => {
                 self.completed = true;
                 rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
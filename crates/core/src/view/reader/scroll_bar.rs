@@ -0,0 +1,111 @@
+use std::thread;
+use std::time::Duration;
+use crate::device::CURRENT_DEVICE;
+use crate::framebuffer::Framebuffer;
+use crate::geom::Rectangle;
+use crate::font::Fonts;
+use crate::color::{BLACK, WHITE, SEPARATOR_NORMAL};
+use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, ViewId, THICKNESS_SMALL};
+use crate::gesture::GestureEvent;
+use crate::unit::scale_by_dpi;
+use crate::context::Context;
+
+const SCROLL_BAR_CLOSE_DELAY: Duration = Duration::from_secs(3);
+pub const SCROLL_BAR_WIDTH: f32 = 12.0;
+
+// Shows the visible part of the current chapter on the right edge of the screen.
+// Tapping the bar goes to the corresponding location of the chapter.
+pub struct ScrollBar {
+    id: Id,
+    rect: Rectangle,
+    children: Vec<Box<dyn View>>,
+    view_id: ViewId,
+    // The locations of the start of the chapter and of the start of the next one.
+    bounds: [usize; 2],
+    // The visible range, in fractional locations.
+    range: [f32; 2],
+}
+
+impl ScrollBar {
+    pub fn new(rect: Rectangle, bounds: [usize; 2], range: [f32; 2], hub: &Hub) -> ScrollBar {
+        let id = ID_FEEDER.next();
+        let view_id = ViewId::ScrollBar(id);
+        let hub2 = hub.clone();
+
+        thread::spawn(move || {
+            thread::sleep(SCROLL_BAR_CLOSE_DELAY);
+            hub2.send(Event::Close(view_id)).ok();
+        });
+
+        ScrollBar {
+            id,
+            rect,
+            children: Vec::new(),
+            view_id,
+            bounds,
+            range,
+        }
+    }
+
+    fn length(&self) -> f32 {
+        (self.bounds[1].saturating_sub(self.bounds[0])).max(1) as f32
+    }
+}
+
+impl View for ScrollBar {
+    fn handle_event(&mut self, evt: &Event, hub: &Hub, _bus: &mut Bus, _rq: &mut RenderQueue, _context: &mut Context) -> bool {
+        match *evt {
+            Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) => {
+                let progress = (center.y - self.rect.min.y) as f32 / self.rect.height() as f32;
+                let location = self.bounds[0] + (progress * self.length()) as usize;
+                hub.send(Event::GoTo(location.min(self.bounds[1].saturating_sub(1)))).ok();
+                true
+            },
+            Event::Gesture(GestureEvent::HoldFingerShort(center, ..)) if self.rect.includes(center) => true,
+            _ => false,
+        }
+    }
+
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, _fonts: &mut Fonts) {
+        let thickness = scale_by_dpi(THICKNESS_SMALL, CURRENT_DEVICE.dpi) as i32;
+        let height = self.rect.height() as f32;
+        let x_center = (self.rect.min.x + self.rect.max.x) / 2;
+
+        fb.draw_rectangle(&self.rect, WHITE);
+        fb.draw_rectangle(&rect![x_center - thickness, self.rect.min.y,
+                                 x_center + thickness, self.rect.max.y],
+                          SEPARATOR_NORMAL);
+
+        let [top, bottom] = self.range.map(|position| {
+            let progress = ((position - self.bounds[0] as f32) / self.length()).clamp(0.0, 1.0);
+            self.rect.min.y + (progress * height) as i32
+        });
+        fb.draw_rectangle(&rect![self.rect.min.x, top,
+                                 self.rect.max.x, bottom.max(top + 2 * thickness)],
+                          BLACK);
+    }
+
+    fn rect(&self) -> &Rectangle {
+        &self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut Rectangle {
+        &mut self.rect
+    }
+
+    fn children(&self) -> &Vec<Box<dyn View>> {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Box<dyn View>> {
+        &mut self.children
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn view_id(&self) -> Option<ViewId> {
+        Some(self.view_id)
+    }
+}
@@ -0,0 +1,73 @@
+use std::thread;
+use std::time::Duration;
+use crate::framebuffer::Framebuffer;
+use crate::geom::Rectangle;
+use crate::font::Fonts;
+use crate::color::GRAY08;
+use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, ViewId};
+use crate::context::Context;
+
+const SCROLL_EDGE_CLOSE_DELAY: Duration = Duration::from_millis(250);
+pub const SCROLL_EDGE_HEIGHT: f32 = 8.0;
+
+// Briefly darkens the edge of the screen when scrolling stops at the start or the end of the document.
+pub struct ScrollEdge {
+    id: Id,
+    rect: Rectangle,
+    children: Vec<Box<dyn View>>,
+    view_id: ViewId,
+}
+
+impl ScrollEdge {
+    pub fn new(rect: Rectangle, hub: &Hub) -> ScrollEdge {
+        let id = ID_FEEDER.next();
+        let view_id = ViewId::ScrollEdge(id);
+        let hub2 = hub.clone();
+
+        thread::spawn(move || {
+            thread::sleep(SCROLL_EDGE_CLOSE_DELAY);
+            hub2.send(Event::Close(view_id)).ok();
+        });
+
+        ScrollEdge {
+            id,
+            rect,
+            children: Vec::new(),
+            view_id,
+        }
+    }
+}
+
+impl View for ScrollEdge {
+    fn handle_event(&mut self, _evt: &Event, _hub: &Hub, _bus: &mut Bus, _rq: &mut RenderQueue, _context: &mut Context) -> bool {
+        false
+    }
+
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, _fonts: &mut Fonts) {
+        fb.draw_rectangle(&self.rect, GRAY08);
+    }
+
+    fn rect(&self) -> &Rectangle {
+        &self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut Rectangle {
+        &mut self.rect
+    }
+
+    fn children(&self) -> &Vec<Box<dyn View>> {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Box<dyn View>> {
+        &mut self.children
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+
+    fn view_id(&self) -> Option<ViewId> {
+        Some(self.view_id)
+    }
+}
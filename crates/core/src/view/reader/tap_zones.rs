@@ -0,0 +1,162 @@
+use crate::device::CURRENT_DEVICE;
+use crate::framebuffer::Framebuffer;
+use crate::geom::{Point, Rectangle, Region, Dir, DiagDir, CornerSpec, BorderSpec, halves};
+use crate::font::{Fonts, font_from_style, NORMAL_STYLE};
+use crate::color::{BLACK, WHITE, TEXT_NORMAL};
+use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, ViewId};
+use crate::view::{THICKNESS_MEDIUM, BORDER_RADIUS_SMALL};
+use crate::gesture::GestureEvent;
+use crate::unit::scale_by_dpi;
+use crate::context::Context;
+
+pub struct TapZones {
+    id: Id,
+    rect: Rectangle,
+    children: Vec<Box<dyn View>>,
+    boundaries: Vec<Point>,
+    labels: Vec<(Point, String)>,
+}
+
+// The points of the segment from *start* to *end*.
+// The segment is either horizontal, vertical or diagonal.
+fn segment_points(start: Point, end: Point) -> impl Iterator<Item=Point> {
+    let step = pt!((end.x - start.x).signum(), (end.y - start.y).signum());
+    let count = (end.x - start.x).abs().max((end.y - start.y).abs());
+    (0..=count).map(move |i| start + step * i)
+}
+
+impl TapZones {
+    // The boundaries and the label anchors are derived from the dimensions
+    // of the regions returned by `Region::from_point`.
+    pub fn new(rect: Rectangle, labels: Vec<(Region, String)>, context: &Context) -> TapZones {
+        let strip_width = context.settings.reader.strip_width;
+        let corner_width = context.settings.reader.corner_width;
+        let m = rect.width().min(rect.height()) as f32 / 2.0;
+        let dc = (m * corner_width).max(1.0) as i32;
+        let ds = (m * strip_width).max(1.0) as i32;
+        let (x0, y0, x1, y1) = (rect.min.x, rect.min.y, rect.max.x - 1, rect.max.y - 1);
+
+        // The outline of the center region, and the diagonals between the strips.
+        let segments = [(pt!(x0 + ds, y0 + ds), pt!(x1 - ds, y0 + ds)),
+                        (pt!(x1 - ds, y0 + ds), pt!(x1 - ds, y1 - ds)),
+                        (pt!(x1 - ds, y1 - ds), pt!(x0 + ds, y1 - ds)),
+                        (pt!(x0 + ds, y1 - ds), pt!(x0 + ds, y0 + ds)),
+                        (pt!(x0, y0), pt!(x0 + ds, y0 + ds)),
+                        (pt!(x1, y0), pt!(x1 - ds, y0 + ds)),
+                        (pt!(x1, y1), pt!(x1 - ds, y1 - ds)),
+                        (pt!(x0, y1), pt!(x0 + ds, y1 - ds))];
+
+        // The corners are on top of the other regions.
+        let mut boundaries: Vec<Point> = segments.iter().flat_map(|&(start, end)| segment_points(start, end))
+                                                 .filter(|&pt| !matches!(Region::from_point(pt, rect, strip_width, corner_width),
+                                                                          Region::Corner(..)))
+                                                 .collect();
+
+        let hypotenuses = [(pt!(x0 + dc, y0), pt!(x0, y0 + dc)),
+                           (pt!(x1 - dc, y0), pt!(x1, y0 + dc)),
+                           (pt!(x1 - dc, y1), pt!(x1, y1 - dc)),
+                           (pt!(x0 + dc, y1), pt!(x0, y1 - dc))];
+        boundaries.extend(hypotenuses.iter().flat_map(|&(start, end)| segment_points(start, end)));
+
+        let center = rect.center();
+        let labels = labels.into_iter().map(|(region, text)| {
+            let anchor = match region {
+                Region::Corner(DiagDir::NorthWest) => pt!(x0 + dc / 3, y0 + dc / 3),
+                Region::Corner(DiagDir::NorthEast) => pt!(x1 - dc / 3, y0 + dc / 3),
+                Region::Corner(DiagDir::SouthEast) => pt!(x1 - dc / 3, y1 - dc / 3),
+                Region::Corner(DiagDir::SouthWest) => pt!(x0 + dc / 3, y1 - dc / 3),
+                Region::Strip(Dir::North) => pt!(center.x, y0 + ds / 2),
+                Region::Strip(Dir::East) => pt!(x1 - ds / 2, center.y),
+                Region::Strip(Dir::South) => pt!(center.x, y1 - ds / 2),
+                Region::Strip(Dir::West) => pt!(x0 + ds / 2, center.y),
+                Region::Center => center,
+            };
+            (anchor, text)
+        }).collect();
+
+        TapZones {
+            id: ID_FEEDER.next(),
+            rect,
+            children: Vec::new(),
+            boundaries,
+            labels,
+        }
+    }
+}
+
+impl View for TapZones {
+    fn handle_event(&mut self, evt: &Event, _hub: &Hub, bus: &mut Bus, _rq: &mut RenderQueue, _context: &mut Context) -> bool {
+        match *evt {
+            Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) => {
+                bus.push_back(Event::Close(ViewId::TapZones));
+                true
+            },
+            Event::Gesture(..) => true,
+            _ => false,
+        }
+    }
+
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, fonts: &mut Fonts) {
+        let dpi = CURRENT_DEVICE.dpi;
+        let thickness = scale_by_dpi(THICKNESS_MEDIUM, dpi) as i32;
+        let (small_thickness, big_thickness) = halves(thickness);
+
+        for pt in &self.boundaries {
+            let rect = rect![*pt - pt!(small_thickness), *pt + pt!(big_thickness)];
+            if let Some(rect) = rect.intersection(&self.rect) {
+                fb.draw_rectangle(&rect, BLACK);
+            }
+        }
+
+        let font = font_from_style(fonts, &NORMAL_STYLE, dpi);
+        let x_height = font.x_heights.0 as i32;
+        let padding = font.em() as i32 / 2;
+        let border_radius = scale_by_dpi(BORDER_RADIUS_SMALL, dpi) as i32;
+        let border_thickness = scale_by_dpi(THICKNESS_MEDIUM, dpi) as u16;
+        let max_width = self.rect.width() as i32 / 3 - 2 * padding;
+
+        for (center, text) in &self.labels {
+            let plan = font.plan(text, Some(max_width), None);
+            let width = plan.width + 2 * padding;
+            let height = 3 * x_height;
+            let mut label_rect = rect![center.x - width / 2, center.y - height / 2,
+                                       center.x - width / 2 + width, center.y - height / 2 + height];
+            let dx = (self.rect.min.x - label_rect.min.x).max(0) +
+                     (self.rect.max.x - label_rect.max.x).min(0);
+            let dy = (self.rect.min.y - label_rect.min.y).max(0) +
+                     (self.rect.max.y - label_rect.max.y).min(0);
+            label_rect += pt!(dx, dy);
+            fb.draw_rounded_rectangle_with_border(&label_rect,
+                                                  &CornerSpec::Uniform(border_radius),
+                                                  &BorderSpec { thickness: border_thickness,
+                                                                color: BLACK },
+                                                  &WHITE);
+            let pt = pt!(label_rect.min.x + padding, label_rect.max.y - x_height);
+            font.render(fb, TEXT_NORMAL[1], &plan, pt);
+        }
+    }
+
+    fn view_id(&self) -> Option<ViewId> {
+        Some(ViewId::TapZones)
+    }
+
+    fn rect(&self) -> &Rectangle {
+        &self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut Rectangle {
+        &mut self.rect
+    }
+
+    fn children(&self) -> &Vec<Box<dyn View>> {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Box<dyn View>> {
+        &mut self.children
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+}
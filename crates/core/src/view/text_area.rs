@@ -0,0 +1,372 @@
+use std::iter;
+use crate::device::CURRENT_DEVICE;
+use crate::framebuffer::{Framebuffer, UpdateMode};
+use super::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData, KeyboardEvent, ViewId, TextKind};
+use super::THICKNESS_MEDIUM;
+use super::input_field::{closest_char_boundary, word_boundary};
+use crate::gesture::GestureEvent;
+use crate::font::{Font, Fonts, font_from_style, NORMAL_STYLE, FONT_SIZES};
+use crate::geom::{Rectangle, Point, Dir, LinearDir, BorderSpec};
+use crate::color::{TEXT_NORMAL, BLACK};
+use crate::context::Context;
+use crate::unit::scale_by_dpi;
+
+// A line of the wrapped text, as a range of bytes.
+// The line breaks of the text start new lines.
+#[derive(Debug, Copy, Clone)]
+struct Line {
+    start: usize,
+    end: usize,
+}
+
+// A multi-line input field: the text is wrapped to the field's width,
+// and swiping up or down scrolls the lines that don't fit.
+pub struct TextArea {
+    id: Id,
+    pub rect: Rectangle,
+    children: Vec<Box<dyn View>>,
+    view_id: ViewId,
+    text: String,
+    partial: String,
+    cursor: usize,
+    // The index of the first visible line.
+    scroll: usize,
+    focused: bool,
+}
+
+impl TextArea {
+    pub fn new(rect: Rectangle, view_id: ViewId) -> TextArea {
+        TextArea {
+            id: ID_FEEDER.next(),
+            rect,
+            children: Vec::new(),
+            view_id,
+            text: "".to_string(),
+            partial: "".to_string(),
+            cursor: 0,
+            scroll: 0,
+            focused: false,
+        }
+    }
+
+    pub fn set_text(&mut self, text: &str, move_cursor: bool, rq: &mut RenderQueue, context: &mut Context) {
+        if self.text != text {
+            self.text = text.to_string();
+            if move_cursor {
+                self.cursor = self.text.len();
+            }
+            self.scroll_to_cursor(&mut context.fonts);
+            rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+        }
+    }
+
+    // Splits the text into lines that are at most *max_width* wide, at the spaces when possible.
+    // The spaces where a line is cut stay at its end.
+    fn lines(&self, font: &mut Font, max_width: i32) -> Vec<Line> {
+        let mut lines = Vec::new();
+        let mut offset = 0;
+
+        for paragraph in self.text.split('\n') {
+            let chars = paragraph.chars().collect::<Vec<char>>();
+            let indices = paragraph.char_indices().map(|(i, _)| offset + i)
+                                   .chain(iter::once(offset + paragraph.len()))
+                                   .collect::<Vec<usize>>();
+            let mut plan = font.plan(paragraph, None, Some(&["-liga".to_string()]));
+            let mut start = 0;
+
+            while plan.width > max_width && start < chars.len() {
+                let (mut index, mut width) = font.cut_point(&plan, max_width);
+                if index == 0 {
+                    index = 1;
+                    width = plan.glyph_advance(0);
+                } else if chars.get(start + index) == Some(&' ') {
+                    width += plan.glyph_advance(index);
+                    index += 1;
+                }
+                let next_plan = plan.split_off(index, width);
+                let end = (start + index).min(chars.len());
+                lines.push(Line { start: indices[start], end: indices[end] });
+                start = end;
+                plan = next_plan;
+            }
+
+            lines.push(Line { start: indices[start], end: indices[chars.len()] });
+            offset += paragraph.len() + 1;
+        }
+
+        lines
+    }
+
+    // The index of the line that contains the given byte offset.
+    fn line_index(lines: &[Line], offset: usize) -> usize {
+        lines.iter().rposition(|line| line.start <= offset).unwrap_or(0)
+    }
+
+    fn metrics(&self, font: &mut Font) -> (i32, i32, i32) {
+        let padding = font.em() as i32;
+        let x_height = font.x_heights.0 as i32;
+        let line_height = font.line_height();
+        (padding, x_height, line_height)
+    }
+
+    fn visible_lines_count(&self, x_height: i32, line_height: i32) -> usize {
+        ((self.rect.height() as i32 - 2 * x_height) / line_height).max(1) as usize
+    }
+
+    fn scroll_to_cursor(&mut self, fonts: &mut Fonts) {
+        let font = font_from_style(fonts, &NORMAL_STYLE, CURRENT_DEVICE.dpi);
+        let (padding, x_height, line_height) = self.metrics(font);
+        let max_width = self.rect.width() as i32 - 2 * padding;
+        let lines = self.lines(font, max_width);
+        let count = self.visible_lines_count(x_height, line_height);
+        let index = TextArea::line_index(&lines, self.cursor);
+        if index < self.scroll {
+            self.scroll = index;
+        } else if index >= self.scroll + count {
+            self.scroll = index + 1 - count;
+        }
+        self.scroll = self.scroll.min(lines.len().saturating_sub(count));
+    }
+
+    fn scroll_by(&mut self, dir: LinearDir, fonts: &mut Fonts) -> bool {
+        let font = font_from_style(fonts, &NORMAL_STYLE, CURRENT_DEVICE.dpi);
+        let (padding, x_height, line_height) = self.metrics(font);
+        let max_width = self.rect.width() as i32 - 2 * padding;
+        let lines_count = self.lines(font, max_width).len();
+        let count = self.visible_lines_count(x_height, line_height);
+        let step = count.saturating_sub(1).max(1);
+        let scroll = match dir {
+            LinearDir::Forward => (self.scroll + step).min(lines_count.saturating_sub(count)),
+            LinearDir::Backward => self.scroll.saturating_sub(step),
+        };
+        let changed = scroll != self.scroll;
+        self.scroll = scroll;
+        changed
+    }
+
+    fn index_from_position(&self, position: Point, fonts: &mut Fonts) -> usize {
+        let font = font_from_style(fonts, &NORMAL_STYLE, CURRENT_DEVICE.dpi);
+        let (padding, x_height, line_height) = self.metrics(font);
+        let max_width = self.rect.width() as i32 - 2 * padding;
+        let lines = self.lines(font, max_width);
+        let row = ((position.y - self.rect.min.y - x_height).max(0) / line_height) as usize;
+        let line = lines[(self.scroll + row).min(lines.len() - 1)];
+        let text = &self.text[line.start..line.end];
+        let plan = font.plan(text, None, Some(&["-liga".to_string()]));
+        let index = plan.index_from_advance(position.x - self.rect.min.x - padding);
+        text.char_indices().nth(index)
+            .map(|(i, _)| line.start + i).unwrap_or(line.end)
+    }
+
+    fn char_move(&mut self, dir: LinearDir) {
+        if let Some(index) = closest_char_boundary(&self.text, self.cursor, dir) {
+            self.cursor = index;
+        }
+    }
+
+    fn char_delete(&mut self, dir: LinearDir) {
+        match dir {
+            LinearDir::Backward if self.cursor > 0 => {
+                if let Some(index) = closest_char_boundary(&self.text, self.cursor, dir) {
+                    self.cursor = index;
+                    self.text.remove(index);
+                }
+            },
+            LinearDir::Forward if self.cursor < self.text.len() => {
+                self.text.remove(self.cursor);
+            },
+            _ => (),
+        }
+    }
+
+    fn word_move(&mut self, dir: LinearDir) {
+        self.cursor = word_boundary(&self.text, self.cursor, dir);
+    }
+
+    fn word_delete(&mut self, dir: LinearDir) {
+        let next_cursor = word_boundary(&self.text, self.cursor, dir);
+        match dir {
+            LinearDir::Backward => {
+                self.text.drain(next_cursor..self.cursor);
+                self.cursor = next_cursor;
+            },
+            LinearDir::Forward => {
+                self.text.drain(self.cursor..next_cursor);
+            }
+        }
+    }
+
+    // The extremums are those of the current line.
+    fn extremum_move(&mut self, dir: LinearDir) {
+        match dir {
+            LinearDir::Backward => self.cursor = self.text[..self.cursor].rfind('\n').map_or(0, |i| i + 1),
+            LinearDir::Forward => self.cursor = self.text[self.cursor..].find('\n')
+                                                    .map_or(self.text.len(), |i| self.cursor + i),
+        }
+    }
+
+    fn extremum_delete(&mut self, dir: LinearDir) {
+        let cursor = self.cursor;
+        self.extremum_move(dir);
+        let (start, end) = if self.cursor < cursor { (self.cursor, cursor) } else { (cursor, self.cursor) };
+        self.text.drain(start..end);
+        self.cursor = start;
+    }
+}
+
+impl View for TextArea {
+    fn handle_event(&mut self, evt: &Event, hub: &Hub, bus: &mut Bus, rq: &mut RenderQueue, context: &mut Context) -> bool {
+        match *evt {
+            Event::Gesture(GestureEvent::Tap(center)) if self.rect.includes(center) => {
+                if !self.focused {
+                    hub.send(Event::Focus(Some(self.view_id))).ok();
+                } else {
+                    self.cursor = self.index_from_position(center, &mut context.fonts);
+                    rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+                }
+                true
+            },
+            Event::Gesture(GestureEvent::Swipe { dir, start, .. }) if self.rect.includes(start) => {
+                let dir = match dir {
+                    Dir::North => LinearDir::Forward,
+                    Dir::South => LinearDir::Backward,
+                    _ => return true,
+                };
+                if self.scroll_by(dir, &mut context.fonts) {
+                    rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+                }
+                true
+            },
+            Event::Focus(id_opt) => {
+                let focused = id_opt.is_some() && id_opt.unwrap() == self.view_id;
+                if self.focused != focused {
+                    self.focused = focused;
+                    rq.add(RenderData::new(self.id, self.rect, UpdateMode::Gui));
+                }
+                false
+            },
+            Event::Keyboard(kbd_evt) if self.focused => {
+                let length = self.text.len();
+                match kbd_evt {
+                    KeyboardEvent::Append(c) => {
+                        self.text.insert(self.cursor, c);
+                        self.partial.clear();
+                        if let Some(index) = closest_char_boundary(&self.text, self.cursor, LinearDir::Forward) {
+                            self.cursor = index;
+                        }
+                    },
+                    KeyboardEvent::Partial(c) => {
+                        self.partial.push(c);
+                    },
+                    KeyboardEvent::Move { target, dir } => {
+                        match target {
+                            TextKind::Char => self.char_move(dir),
+                            TextKind::Word => self.word_move(dir),
+                            TextKind::Extremum => self.extremum_move(dir),
+                        }
+                    },
+                    KeyboardEvent::Delete { target, dir } => {
+                        match target {
+                            TextKind::Char => self.char_delete(dir),
+                            TextKind::Word => self.word_delete(dir),
+                            TextKind::Extremum => self.extremum_delete(dir),
+                        }
+                    },
+                    KeyboardEvent::Submit => {
+                        bus.push_back(Event::Submit(self.view_id, self.text.clone()));
+                    },
+                };
+                if self.text.len() != length {
+                    bus.push_back(Event::TextChanged(self.view_id, self.text.clone()));
+                }
+                self.scroll_to_cursor(&mut context.fonts);
+                rq.add(RenderData::no_wait(self.id, self.rect, UpdateMode::Gui));
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn render(&self, fb: &mut dyn Framebuffer, _rect: Rectangle, fonts: &mut Fonts) {
+        let dpi = CURRENT_DEVICE.dpi;
+        let font = font_from_style(fonts, &NORMAL_STYLE, dpi);
+        let (padding, x_height, line_height) = self.metrics(font);
+        let max_width = self.rect.width() as i32 - 2 * padding;
+        let thickness = scale_by_dpi(THICKNESS_MEDIUM, dpi) as i32;
+
+        fb.draw_rectangle(&self.rect, TEXT_NORMAL[0]);
+        fb.draw_rectangle_outline(&self.rect,
+                                  &BorderSpec { thickness: thickness as u16, color: BLACK });
+
+        let lines = self.lines(font, max_width);
+        let count = self.visible_lines_count(x_height, line_height);
+        let scroll = self.scroll.min(lines.len().saturating_sub(count));
+        let ascender = font.ascender();
+        let features = ["-liga".to_string()];
+
+        for (row, line) in lines.iter().skip(scroll).take(count).enumerate() {
+            let plan = font.plan(&self.text[line.start..line.end], None, Some(&features));
+            let pt = pt!(self.rect.min.x + padding,
+                         self.rect.min.y + x_height + row as i32 * line_height + ascender);
+            font.render(fb, TEXT_NORMAL[1], &plan, pt);
+        }
+
+        // Show the position of the visible lines when some aren't visible.
+        if lines.len() > count {
+            let height = self.rect.height() as i32 - 2 * thickness;
+            let top = self.rect.min.y + thickness + height * scroll as i32 / lines.len() as i32;
+            let bottom = self.rect.min.y + thickness + height * (scroll + count) as i32 / lines.len() as i32;
+            fb.draw_rectangle(&rect![self.rect.max.x - 3 * thickness, top,
+                                     self.rect.max.x - thickness, bottom],
+                              BLACK);
+        }
+
+        if !self.focused {
+            return;
+        }
+
+        let index = TextArea::line_index(&lines, self.cursor);
+
+        if index < scroll || index >= scroll + count {
+            return;
+        }
+
+        let line = lines[index];
+        let dx = font.plan(&self.text[line.start..self.cursor.min(line.end)], None, Some(&features)).width;
+        let baseline = self.rect.min.y + x_height + (index - scroll) as i32 * line_height + ascender;
+
+        fb.draw_rectangle(&rect![self.rect.min.x + padding + dx,
+                                 baseline - 2 * x_height,
+                                 self.rect.min.x + padding + dx + thickness,
+                                 baseline + x_height / 2],
+                          BLACK);
+
+        if !self.partial.is_empty() {
+            font.set_size(FONT_SIZES[0], dpi);
+            let plan = font.plan(&self.partial, None, None);
+            let pt = pt!(self.rect.min.x + padding + dx + 3 * thickness,
+                         baseline + font.x_heights.0 as i32);
+            font.render(fb, TEXT_NORMAL[1], &plan, pt);
+        }
+    }
+
+    fn rect(&self) -> &Rectangle {
+        &self.rect
+    }
+
+    fn rect_mut(&mut self) -> &mut Rectangle {
+        &mut self.rect
+    }
+
+    fn children(&self) -> &Vec<Box<dyn View>> {
+        &self.children
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Box<dyn View>> {
+        &mut self.children
+    }
+
+    fn id(&self) -> Id {
+        self.id
+    }
+}
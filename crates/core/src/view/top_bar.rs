@@ -2,12 +2,15 @@ use crate::framebuffer::{Framebuffer, UpdateMode};
 use crate::gesture::GestureEvent;
 use crate::input::DeviceEvent;
 use crate::view::{View, Event, Hub, Bus, Id, ID_FEEDER, RenderQueue, RenderData, ViewId, Align};
+use crate::view::common::locate;
 use crate::view::icon::Icon;
 use crate::view::clock::Clock;
 use crate::view::battery::Battery;
 use crate::view::label::Label;
+use crate::view::page_label::PageLabel;
 use crate::geom::{Rectangle};
 use crate::font::Fonts;
+use crate::settings::TopBarSettings;
 use crate::context::Context;
 
 #[derive(Debug)]
@@ -15,10 +18,11 @@ pub struct TopBar {
     id: Id,
     rect: Rectangle,
     children: Vec<Box<dyn View>>,
+    settings: TopBarSettings,
 }
 
 impl TopBar {
-    pub fn new(rect: Rectangle, root_event: Event, title: String, context: &mut Context) -> TopBar {
+    pub fn new(rect: Rectangle, root_event: Event, title: String, settings: &TopBarSettings, context: &mut Context) -> TopBar {
         let id = ID_FEEDER.next();
         let mut children = Vec::new();
 
@@ -33,32 +37,55 @@ impl TopBar {
                                   root_event);
         children.push(Box::new(root_icon) as Box<dyn View>);
 
-        let mut clock_rect = rect![rect.max - pt!(4*side, side),
-                                   rect.max - pt!(3*side, 0)];
-        let clock_label = Clock::new(&mut clock_rect, context);
-        let title_rect = rect![rect.min.x + side, rect.min.y,
-                               clock_rect.min.x, rect.max.y];
+        let clock_label = if settings.clock {
+            let mut clock_rect = rect![rect.max - pt!(4*side, side),
+                                       rect.max - pt!(3*side, 0)];
+            Some(Clock::new(&mut clock_rect, context))
+        } else {
+            None
+        };
+
+        let clock_width = clock_label.as_ref().map_or(0, |c| c.rect().width() as i32);
+        let battery_width = if settings.battery_percentage {
+            side + Battery::percentage_width(&mut context.fonts)
+        } else {
+            side
+        };
+
+        let rects = TopBar::layout(rect, settings, clock_width, battery_width);
+
+        let title_rect = rects[1];
         let title_label = Label::new(title_rect, title, Align::Center)
                                 .event(Some(Event::ToggleNear(ViewId::TitleMenu, title_rect)));
         children.push(Box::new(title_label) as Box<dyn View>);
-        children.push(Box::new(clock_label) as Box<dyn View>);
 
-        let capacity = context.battery.capacity().map_or(0.0, |v| v[0]);
-        let status = context.battery.status().map_or(crate::battery::Status::Discharging, |v| v[0]);
-        let battery_widget = Battery::new(rect![rect.max - pt!(3*side, side),
-                                                rect.max - pt!(2*side, 0)],
-                                          capacity,
-                                          status);
-        children.push(Box::new(battery_widget) as Box<dyn View>);
+        if settings.page {
+            let page_label = PageLabel::new(rects[children.len()], 0, 0, false);
+            children.push(Box::new(page_label) as Box<dyn View>);
+        }
+
+        if let Some(mut clock_label) = clock_label {
+            *clock_label.rect_mut() = rects[children.len()];
+            children.push(Box::new(clock_label) as Box<dyn View>);
+        }
+
+        if settings.battery {
+            let capacity = context.battery.capacity().map_or(0.0, |v| v[0]);
+            let status = context.battery.status().map_or(crate::battery::Status::Discharging, |v| v[0]);
+            let battery_widget = Battery::new(rects[children.len()],
+                                              capacity,
+                                              status)
+                                         .percentage(settings.battery_percentage);
+            children.push(Box::new(battery_widget) as Box<dyn View>);
+        }
 
         let name = if context.settings.frontlight { "frontlight" } else { "frontlight-disabled" };
         let frontlight_icon = Icon::new(name,
-                                        rect![rect.max - pt!(2*side, side),
-                                              rect.max - pt!(side, 0)],
+                                        rects[children.len()],
                                         Event::Show(ViewId::Frontlight));
         children.push(Box::new(frontlight_icon) as Box<dyn View>);
 
-        let menu_rect = rect![rect.max-side, rect.max];
+        let menu_rect = rects[children.len()];
         let menu_icon = Icon::new("menu",
                                   menu_rect,
                                   Event::ToggleNear(ViewId::MainMenu, menu_rect));
@@ -68,9 +95,56 @@ impl TopBar {
             id,
             rect,
             children,
+            settings: *settings,
         }
     }
 
+    // Returns the rectangles of the children, from left to right.
+    fn layout(rect: Rectangle, settings: &TopBarSettings, clock_width: i32, battery_width: i32) -> Vec<Rectangle> {
+        let side = rect.height() as i32;
+        let mut rects = Vec::new();
+        let mut x = rect.max.x;
+
+        rects.push(rect![x - side, rect.min.y, x, rect.max.y]);
+        x -= side;
+        rects.push(rect![x - side, rect.min.y, x, rect.max.y]);
+        x -= side;
+
+        if settings.battery {
+            rects.push(rect![x - battery_width, rect.min.y, x, rect.max.y]);
+            x -= battery_width;
+        }
+
+        if settings.clock {
+            rects.push(rect![x - clock_width, rect.min.y, x, rect.max.y]);
+            x -= clock_width;
+        }
+
+        if settings.page {
+            let page_width = (3 * side).min(x - rect.min.x - side);
+            rects.push(rect![x - page_width, rect.min.y, x, rect.max.y]);
+            x -= page_width;
+        }
+
+        rects.push(rect![rect.min.x + side, rect.min.y, x, rect.max.y]);
+        rects.push(rect![rect.min, rect.min + side]);
+
+        rects.reverse();
+        rects
+    }
+
+    pub fn settings(&self) -> &TopBarSettings {
+        &self.settings
+    }
+
+    pub fn page_label(mut self, current_page: usize, pages_count: usize, synthetic: bool) -> TopBar {
+        if let Some(index) = locate::<PageLabel>(&self) {
+            let rect = *self.children[index].rect();
+            self.children[index] = Box::new(PageLabel::new(rect, current_page, pages_count, synthetic)) as Box<dyn View>;
+        }
+        self
+    }
+
     pub fn update_root_icon(&mut self, name: &str, rq: &mut RenderQueue) {
         let icon = self.child_mut(0).downcast_mut::<Icon>().unwrap();
         if icon.name != name {
@@ -84,22 +158,34 @@ impl TopBar {
         title_label.update(title, rq);
     }
 
+    pub fn update_page_label(&mut self, current_page: usize, pages_count: usize, rq: &mut RenderQueue) {
+        if let Some(index) = locate::<PageLabel>(self) {
+            let page_label = self.children[index].downcast_mut::<PageLabel>().unwrap();
+            page_label.update(current_page, pages_count, rq);
+        }
+    }
+
     pub fn update_frontlight_icon(&mut self, rq: &mut RenderQueue, context: &mut Context) {
         let name = if context.settings.frontlight { "frontlight" } else { "frontlight-disabled" };
-        let icon = self.child_mut(4).downcast_mut::<Icon>().unwrap();
+        let index = self.len() - 2;
+        let icon = self.child_mut(index).downcast_mut::<Icon>().unwrap();
         icon.name = name.to_string();
         rq.add(RenderData::new(icon.id(), *icon.rect(), UpdateMode::Gui));
     }
 
     pub fn update_clock_label(&mut self, rq: &mut RenderQueue) {
-        if let Some(clock_label) = self.children[2].downcast_mut::<Clock>() {
-            clock_label.update(rq);
+        if let Some(index) = locate::<Clock>(self) {
+            if let Some(clock_label) = self.children[index].downcast_mut::<Clock>() {
+                clock_label.update(rq);
+            }
         }
     }
 
     pub fn update_battery_widget(&mut self, rq: &mut RenderQueue, context: &mut Context) {
-        if let Some(battery_widget) = self.children[3].downcast_mut::<Battery>() {
-            battery_widget.update(rq, context);
+        if let Some(index) = locate::<Battery>(self) {
+            if let Some(battery_widget) = self.children[index].downcast_mut::<Battery>() {
+                battery_widget.update(rq, context);
+            }
         }
     }
 
@@ -126,24 +212,15 @@ impl View for TopBar {
 
     fn resize(&mut self, rect: Rectangle, hub: &Hub, rq: &mut RenderQueue, context: &mut Context) {
         let side = rect.height() as i32;
-        self.children[0].resize(rect![rect.min, rect.min+side], hub, rq, context);
-        let clock_width = self.children[2].rect().width() as i32;
-        let clock_rect = rect![rect.max - pt!(3*side + clock_width, side),
-                               rect.max - pt!(3*side, 0)];
-        self.children[1].resize(rect![rect.min.x + side,
-                                      rect.min.y,
-                                      clock_rect.min.x,
-                                      rect.max.y],
-                                hub, rq, context);
-        self.children[2].resize(clock_rect, hub, rq, context);
-        self.children[3].resize(rect![rect.max - pt!(3*side, side),
-                                      rect.max - pt!(2*side, 0)],
-                                hub, rq, context);
-        self.children[4].resize(rect![rect.max - pt!(2*side, side),
-                                      rect.max - pt!(side, 0)],
-                                hub, rq, context);
-        self.children[5].resize(rect![rect.max-side, rect.max],
-                                hub, rq, context);
+        let clock_width = locate::<Clock>(self)
+                                .map_or(0, |index| self.children[index].rect().width() as i32);
+        let battery_width = locate::<Battery>(self)
+                                  .map_or(side, |index| self.children[index].rect().width() as i32 -
+                                                        self.rect.height() as i32 + side);
+        let rects = TopBar::layout(rect, &self.settings, clock_width, battery_width);
+        for (child, child_rect) in self.children.iter_mut().zip(rects) {
+            child.resize(child_rect, hub, rq, context);
+        }
         self.rect = rect;
     }
 
@@ -16,7 +16,7 @@ use sdl2::rect::Point as SdlPoint;
 use sdl2::rect::Rect as SdlRect;
 use plato_core::framebuffer::{Framebuffer, UpdateMode};
 use plato_core::input::{DeviceEvent, FingerStatus, ButtonCode, ButtonStatus};
-use plato_core::document::sys_info_as_html;
+use plato_core::document::{DrmProtected, sys_info_as_html};
 use plato_core::view::{View, Event, ViewId, EntryId, AppCmd, EntryKind};
 use plato_core::view::{process_render_queue, wait_for_all, handle_event, RenderQueue, RenderData};
 use plato_core::view::home::Home;
@@ -44,7 +44,7 @@ use plato_core::frontlight::{Frontlight, LightLevels};
 use plato_core::lightsensor::LightSensor;
 use plato_core::library::Library;
 use plato_core::font::Fonts;
-use plato_core::context::Context;
+use plato_core::context::{Context, SETTINGS_BUNDLE_FILENAME};
 use plato_core::pt;
 use plato_core::png;
 
@@ -397,7 +397,7 @@ fn main() -> Result<(), Error> {
 
         while let Ok(evt) = rx.recv_timeout(Duration::from_millis(20)) {
             match evt {
-                Event::Open(info) => {
+                Event::Open(info, anchor) => {
                     let rotation = context.display.rotation;
                     if let Some(n) = info.reader.as_ref()
                                          .and_then(|r| r.rotation.map(|n| CURRENT_DEVICE.from_canonical(n))) {
@@ -409,19 +409,31 @@ fn main() -> Result<(), Error> {
                         }
                     }
                     let path = info.file.path.clone();
-                    if let Some(r) = Reader::new(context.fb.rect(), *info, &tx, &mut context) {
-                        let mut next_view = Box::new(r) as Box<dyn View>;
-                        transfer_notifications(view.as_mut(), next_view.as_mut(), &mut rq, &mut context);
-                        history.push(view as Box<dyn View>);
-                        view = next_view;
-                    } else {
-                        if context.display.rotation != rotation {
-                            if let Ok(dims) = context.fb.set_rotation(rotation) {
-                                context.display.rotation = rotation;
-                                context.display.dims = dims;
+                    match Reader::new(context.fb.rect(), *info, anchor.as_deref(), &tx, &mut context) {
+                        Ok(r) => {
+                            let mut next_view = Box::new(r) as Box<dyn View>;
+                            transfer_notifications(view.as_mut(), next_view.as_mut(), &mut rq, &mut context);
+                            history.push(view as Box<dyn View>);
+                            view = next_view;
+                        },
+                        Err(e) => {
+                            if context.display.rotation != rotation {
+                                if let Ok(dims) = context.fb.set_rotation(rotation) {
+                                    context.display.rotation = rotation;
+                                    context.display.dims = dims;
+                                }
                             }
-                        }
-                        handle_event(view.as_mut(), &Event::Invalid(path), &tx, &mut bus, &mut rq, &mut context);
+                            let msg = if e.is::<DrmProtected>() {
+                                e.to_string()
+                            } else {
+                                let name = path.file_name().map_or_else(|| path.to_string_lossy(),
+                                                                        |n| n.to_string_lossy());
+                                format!("Can't open {}: {:#}.", name, e)
+                            };
+                            let notif = Notification::new(msg, &tx, &mut rq, &mut context);
+                            view.children_mut().push(Box::new(notif) as Box<dyn View>);
+                            handle_event(view.as_mut(), &Event::Invalid(path), &tx, &mut bus, &mut rq, &mut context);
+                        },
                     }
                 },
                 Event::OpenHtml(ref html, ref link_uri) => {
@@ -561,6 +573,33 @@ fn main() -> Result<(), Error> {
                     view.children_mut().push(Box::new(notif) as Box<dyn View>);
                 },
                 Event::Notify(msg) => {
+                    if let Some(reader) = view.downcast_mut::<Reader>().filter(|r| r.is_immersive()) {
+                        reader.notify(msg, &tx, &mut rq, &mut context);
+                    } else {
+                        let notif = Notification::new(msg, &tx, &mut rq, &mut context);
+                        view.children_mut().push(Box::new(notif) as Box<dyn View>);
+                    }
+                },
+                Event::Select(EntryId::ExportSettings) => {
+                    let path = context.library.home.join(SETTINGS_BUNDLE_FILENAME);
+                    let msg = match context.export_settings(&path) {
+                        Err(e) => format!("Can't export settings: {:#}.", e),
+                        Ok(()) => format!("Exported settings to {}.", SETTINGS_BUNDLE_FILENAME),
+                    };
+                    let notif = Notification::new(msg, &tx, &mut rq, &mut context);
+                    view.children_mut().push(Box::new(notif) as Box<dyn View>);
+                },
+                Event::Select(EntryId::ImportSettings(mode)) => {
+                    let path = context.library.home.join(SETTINGS_BUNDLE_FILENAME);
+                    let msg = match context.import_settings(&path, mode) {
+                        Err(e) => format!("Can't import settings: {:#}.", e),
+                        Ok(()) => {
+                            context.fb.set_inverted(context.settings.inverted);
+                            view.handle_event(&Event::Reseed, &tx, &mut bus, &mut rq, &mut context);
+                            rq.add(RenderData::new(view.id(), context.fb.rect(), UpdateMode::Full));
+                            "Imported settings.".to_string()
+                        },
+                    };
                     let notif = Notification::new(msg, &tx, &mut rq, &mut context);
                     view.children_mut().push(Box::new(notif) as Box<dyn View>);
                 },
@@ -568,12 +607,25 @@ fn main() -> Result<(), Error> {
                 Event::CheckFetcher(..) |
                 Event::FetcherAddDocument(..) |
                 Event::FetcherRemoveDocument(..) |
+                Event::FetcherStatus(..) |
+                Event::PageShared(..) |
+                Event::QuoteShared(..) |
+                Event::ReadAloud(..) |
+                Event::StopReadAloud |
+                Event::PageTurned(..) |
+                Event::HookLink(..) |
                 Event::FetcherSearch { .. } if !view.is::<Home>() => {
                     if let Some(home) = history.get_mut(0).filter(|view| view.is::<Home>()) {
                         let (tx, _rx) = mpsc::channel();
                         home.handle_event(&evt, &tx, &mut VecDeque::new(), &mut RenderQueue::new(), &mut context);
                     }
                 },
+                // Home reports the absence of hooks through the real hub.
+                Event::Translate(..) if !view.is::<Home>() => {
+                    if let Some(home) = history.get_mut(0).filter(|view| view.is::<Home>()) {
+                        home.handle_event(&evt, &tx, &mut VecDeque::new(), &mut RenderQueue::new(), &mut context);
+                    }
+                },
                 Event::SetWifi(enable) => {
                     if context.settings.wifi != enable {
                         context.settings.wifi = enable;
@@ -1,4 +1,4 @@
-use std::io;
+use std::io::{self, Write};
 use std::env;
 use std::fs::{self, File};
 use std::path::PathBuf;
@@ -7,6 +7,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use plato_core::chrono::{Duration, Utc, Local, DateTime};
 use plato_core::serde::{Serialize, Deserialize};
 use plato_core::serde_json::{self, json, Value as JsonValue};
+use plato_core::regex::Regex;
 use reqwest::blocking::Client;
 use plato_core::anyhow::{Error, Context, format_err};
 use plato_core::helpers::{load_toml, load_json, save_json, decode_entities};
@@ -16,6 +17,8 @@ const SESSION_PATH: &str = ".session.json";
 const URLS_PATH: &str = "urls.txt";
 // Nearly RFC 3339
 const DATE_FORMAT: &str = "%FT%T%z";
+// The elements that don't belong to the text of an article downloaded as HTML.
+const DISCARDED_ELEMENTS: [&str; 6] = ["script", "style", "nav", "aside", "iframe", "form"];
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 #[serde(crate = "plato_core::serde")]
@@ -29,6 +32,26 @@ struct Settings {
     sync_finished: bool,
     remove_finished: bool,
     balance_limit: usize,
+    max_per_run: usize,
+    format: Format,
+}
+
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "plato_core::serde")]
+#[serde(rename_all = "kebab-case")]
+enum Format {
+    #[default]
+    Epub,
+    Html,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Epub => "epub",
+            Format::Html => "html",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,6 +132,24 @@ fn update_token(client: &Client, session: &mut Session, settings: &Settings) ->
     }
 }
 
+// Removes the scripts, the style sheets, the navigation and the ads.
+fn clean_html(html: &str) -> String {
+    let mut html = html.to_string();
+    for name in DISCARDED_ELEMENTS {
+        let re = Regex::new(&format!(r"(?is)<{0}\b.*?</{0}\s*>", name)).unwrap();
+        html = re.replace_all(&html, "").into_owned();
+    }
+    let re = Regex::new(r#"(?is)<link\b[^>]*>|\sstyle\s*=\s*("[^"]*"|'[^']*')"#).unwrap();
+    re.replace_all(&html, "").into_owned()
+}
+
+// Wraps the content of an article in a minimal HTML document.
+fn article_as_html(title: &str, content: &str) -> String {
+    let title = title.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    format!("<html>\n\t<head>\n\t\t<meta charset=\"utf-8\"/>\n\t\t<title>{0}</title>\n\t</head>\n\
+             \t<body>\n\t\t<h1>{0}</h1>\n{1}\n\t</body>\n</html>", title, clean_html(content))
+}
+
 // The *detail* parameter is only available in 2.4.0 and up.
 fn is_detail_available(client: &Client, settings: &Settings) -> bool {
     // /api/info is only available in 2.4.0 and up.
@@ -117,6 +158,21 @@ fn is_detail_available(client: &Client, settings: &Settings) -> bool {
           .map_or(false, |response| response.status().is_success())
 }
 
+// Reads the events sent by *Plato* until one of the given type arrives.
+fn read_event(kind: &str) -> Result<JsonValue, Error> {
+    loop {
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Err(format_err!("the standard input was closed"));
+        }
+        if let Ok(event) = serde_json::from_str::<JsonValue>(&line) {
+            if event.get("type").and_then(JsonValue::as_str) == Some(kind) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
 fn main() -> Result<(), Error> {
     let mut args = env::args().skip(1);
     let library_path = PathBuf::from(args.next()
@@ -153,8 +209,7 @@ fn main() -> Result<(), Error> {
             });
             println!("{}", event);
         }
-        let mut line = String::new();
-        io::stdin().read_line(&mut line)?;
+        read_event("network")?;
     }
 
     if !save_path.exists() {
@@ -170,6 +225,12 @@ fn main() -> Result<(), Error> {
     let sigterm = Arc::new(AtomicBool::new(false));
     signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&sigterm))?;
 
+    let event = json!({
+        "type": "status",
+        "state": "syncing",
+    });
+    println!("{}", event);
+
     if let Ok(contents) = fs::read_to_string(URLS_PATH) {
         for line in contents.lines() {
             let query = json!({"url": line});
@@ -203,101 +264,98 @@ fn main() -> Result<(), Error> {
         });
         println!("{}", event);
 
-        let mut line = String::new();
-        io::stdin().read_line(&mut line)?;
+        let event = read_event("search")?;
 
         let last_removals_count = session.removals_count;
         let mut archivals_count = 0;
 
-        if let Ok(event) = serde_json::from_str::<JsonValue>(&line) {
-            if let Some(results) = event.get("results").and_then(JsonValue::as_array) {
-                let message = if results.is_empty() {
-                    "No finished articles.".to_string()
-                } else {
-                    format!("Found {} finished article{}.", results.len(), if results.len() != 1 { "s" } else { "" })
-                };
-                let event = json!({
-                    "type": "notify",
-                    "message": &message,
-                });
-                println!("{}", event);
+        if let Some(results) = event.get("results").and_then(JsonValue::as_array) {
+            let message = if results.is_empty() {
+                "No finished articles.".to_string()
+            } else {
+                format!("Found {} finished article{}.", results.len(), if results.len() != 1 { "s" } else { "" })
+            };
+            let event = json!({
+                "type": "notify",
+                "message": &message,
+            });
+            println!("{}", event);
 
-                for entry in results {
-                    if sigterm.load(Ordering::Relaxed) {
-                        break;
-                    }
+            for entry in results {
+                if sigterm.load(Ordering::Relaxed) {
+                    break;
+                }
 
-                    if settings.sync_finished {
-                        if let Some(id) = entry.get("identifier")
+                if settings.sync_finished {
+                    if let Some(id) = entry.get("identifier")
+                                           .and_then(JsonValue::as_str)
+                                           .and_then(|v| v.parse::<u64>().ok()) {
+                        let url = format!("{}/api/entries/{}", &settings.base_url, id);
+                        let query = json!({"archive": 1});
+                        let response = client.patch(&url)
+                                             .header(reqwest::header::AUTHORIZATION,
+                                                     format!("Bearer {}", &session.access_token.data))
+                                             .json(&query)
+                                             .send();
+                        let response = response.unwrap();
+                        if response.status().is_success() {
+                            archivals_count += 1;
+                        } else {
+                            let status = response.status();
+                            let body: JsonValue = response.json()?;
+                            let err_desc = body.get("error_description")
                                                .and_then(JsonValue::as_str)
-                                               .and_then(|v| v.parse::<u64>().ok()) {
-                            let url = format!("{}/api/entries/{}", &settings.base_url, id);
-                            let query = json!({"archive": 1});
-                            let response = client.patch(&url)
-                                                 .header(reqwest::header::AUTHORIZATION,
-                                                         format!("Bearer {}", &session.access_token.data))
-                                                 .json(&query)
-                                                 .send();
-                            let response = response.unwrap();
-                            if response.status().is_success() {
-                                archivals_count += 1;
-                            } else {
-                                let status = response.status();
-                                let body: JsonValue = response.json()?;
-                                let err_desc = body.get("error_description")
-                                                   .and_then(JsonValue::as_str)
-                                                   .or_else(|| status.canonical_reason())
-                                                   .unwrap_or_else(|| status.as_str());
-                                eprintln!("Can't mark {} as read: {}.", id, err_desc);
-                            }
+                                               .or_else(|| status.canonical_reason())
+                                               .unwrap_or_else(|| status.as_str());
+                            eprintln!("Can't mark {} as read: {}.", id, err_desc);
                         }
                     }
-
-                    if settings.remove_finished {
-                        if let Some(path) = entry.pointer("/file/path")
-                                                 .and_then(JsonValue::as_str) {
-                            let event = json!({
-                                "type": "removeDocument",
-                                "path": path,
-                            });
-                            println!("{}", event);
-                            session.removals_count = session.removals_count.wrapping_add(1)
-                        }
-                    }
-
-                    if let Some(opened) = entry.pointer("/readerInfo/opened")
-                                               .and_then(JsonValue::as_str) {
-                        session.last_opened = opened.to_string();
-                    }
                 }
 
-                if !results.is_empty() {
-                    if settings.sync_finished {
-                        let message = if archivals_count > 0 {
-                            format!("Marked {} finished article{} as read.", archivals_count, if archivals_count != 1 { "s" } else { "" })
-                        } else {
-                            "No finished articles marked as read.".to_string()
-                        };
+                if settings.remove_finished {
+                    if let Some(path) = entry.pointer("/file/path")
+                                             .and_then(JsonValue::as_str) {
                         let event = json!({
-                            "type": "notify",
-                            "message": &message,
+                            "type": "removeDocument",
+                            "path": path,
                         });
                         println!("{}", event);
+                        session.removals_count = session.removals_count.wrapping_add(1)
                     }
+                }
 
-                    if settings.remove_finished {
-                        let removals_count = session.removals_count.saturating_sub(last_removals_count);
-                        let message = if removals_count > 0 {
-                            format!("Removed {} finished article{}.", removals_count, if removals_count != 1 { "s" } else { "" })
-                        } else {
-                            "No finished articles removed.".to_string()
-                        };
-                        let event = json!({
-                            "type": "notify",
-                            "message": &message,
-                        });
-                        println!("{}", event);
-                    }
+                if let Some(opened) = entry.pointer("/readerInfo/opened")
+                                           .and_then(JsonValue::as_str) {
+                    session.last_opened = opened.to_string();
+                }
+            }
+
+            if !results.is_empty() {
+                if settings.sync_finished {
+                    let message = if archivals_count > 0 {
+                        format!("Marked {} finished article{} as read.", archivals_count, if archivals_count != 1 { "s" } else { "" })
+                    } else {
+                        "No finished articles marked as read.".to_string()
+                    };
+                    let event = json!({
+                        "type": "notify",
+                        "message": &message,
+                    });
+                    println!("{}", event);
+                }
+
+                if settings.remove_finished {
+                    let removals_count = session.removals_count.saturating_sub(last_removals_count);
+                    let message = if removals_count > 0 {
+                        format!("Removed {} finished article{}.", removals_count, if removals_count != 1 { "s" } else { "" })
+                    } else {
+                        "No finished articles removed.".to_string()
+                    };
+                    let event = json!({
+                        "type": "notify",
+                        "message": &message,
+                    });
+                    println!("{}", event);
                 }
             }
         }
@@ -305,6 +363,8 @@ fn main() -> Result<(), Error> {
 
     let mut page = 1;
     let mut pages_count = 0;
+    let mut total_count = 0;
+    let mut processed_count = 0;
     let last_downloads_count = session.downloads_count;
     let url = format!("{}/api/entries", &settings.base_url);
     let mut query = json!({
@@ -336,6 +396,7 @@ fn main() -> Result<(), Error> {
                 let total = entries.get("total")
                                    .and_then(|v| v.as_u64())
                                    .unwrap();
+                total_count = total;
                 let message = if total == 0 {
                     "No new articles.".to_string()
                 } else {
@@ -358,10 +419,14 @@ fn main() -> Result<(), Error> {
             for element in items {
                 if sigterm.load(Ordering::Relaxed) ||
                     (settings.balance_limit > 0 &&
-                     session.downloads_count.saturating_sub(session.removals_count) >= settings.balance_limit) {
+                     session.downloads_count.saturating_sub(session.removals_count) >= settings.balance_limit) ||
+                    (settings.max_per_run > 0 &&
+                     session.downloads_count.saturating_sub(last_downloads_count) >= settings.max_per_run) {
                     break 'outer;
                 }
 
+                processed_count += 1;
+
                 let id = element.get("id")
                                 .and_then(JsonValue::as_u64)
                                 .ok_or_else(|| format_err!("missing id"))?;
@@ -399,6 +464,17 @@ fn main() -> Result<(), Error> {
                                   .map(|v| v.format("%Y").to_string())
                                   .unwrap_or_default();
 
+                let origin_url = element.get("origin_url")
+                                        .and_then(JsonValue::as_str)
+                                        .filter(|v| !v.is_empty())
+                                        .or_else(|| element.get("url").and_then(JsonValue::as_str))
+                                        .map(String::from)
+                                        .unwrap_or_default();
+
+                let reading_time = element.get("reading_time")
+                                          .and_then(JsonValue::as_u64)
+                                          .filter(|v| *v > 0);
+
                 let updated_at = element.get("updated_at")
                                         .and_then(JsonValue::as_str)
                                         .and_then(|v| DateTime::parse_from_str(v, DATE_FORMAT).ok())
@@ -406,32 +482,61 @@ fn main() -> Result<(), Error> {
 
                 session.since = updated_at.timestamp();
 
-                let epub_path = save_path.join(&format!("{}.epub", id));
-                if epub_path.exists() {
+                let doc_path = save_path.join(&format!("{}.{}", id, settings.format.extension()));
+                if doc_path.exists() {
                     continue;
                 }
 
-                let mut file = File::create(&epub_path)?;
-                let url = format!("{}/api/entries/{}/export.epub", settings.base_url, id);
+                let mut file = File::create(&doc_path)?;
+                // There's no HTML export: the HTML is built from the entry's content.
+                let url = if settings.format == Format::Html {
+                    format!("{}/api/entries/{}", settings.base_url, id)
+                } else {
+                    format!("{}/api/entries/{}/export.{}", settings.base_url, id, settings.format.extension())
+                };
 
                 let response = client.get(&url)
                                      .header(reqwest::header::AUTHORIZATION,
                                              format!("Bearer {}", &session.access_token.data))
                                      .send()
-                                     .and_then(|mut body| body.copy_to(&mut file));
+                                     .map_err(Error::from)
+                                     .and_then(|mut body| {
+                                         let status = body.status();
+                                         if !status.is_success() {
+                                             return Err(format_err!("{}", status.canonical_reason()
+                                                                                .unwrap_or_else(|| status.as_str())));
+                                         }
+                                         if settings.format == Format::Html {
+                                             let entry: JsonValue = body.json()?;
+                                             let content = entry.get("content")
+                                                                .and_then(JsonValue::as_str)
+                                                                .ok_or_else(|| format_err!("missing content"))?;
+                                             file.write_all(article_as_html(&title, content).as_bytes())?;
+                                         } else {
+                                             body.copy_to(&mut file)?;
+                                         }
+                                         Ok(())
+                                     });
 
                 if let Err(err) = response {
                     eprintln!("Can't download {}: {:#}.", id, err);
-                    fs::remove_file(epub_path).ok();
+                    fs::remove_file(doc_path).ok();
                     continue;
                 }
 
                 session.downloads_count = session.downloads_count.wrapping_add(1);
 
-                if let Ok(path) = epub_path.strip_prefix(&library_path) {
+                let event = json!({
+                    "type": "progress",
+                    "current": processed_count,
+                    "total": total_count,
+                });
+                println!("{}", event);
+
+                if let Ok(path) = doc_path.strip_prefix(&library_path) {
                     let file_info = json!({
                         "path": path,
-                        "kind": "epub",
+                        "kind": settings.format.extension(),
                         "size": file.metadata().ok()
                                     .map_or(0, |m| m.len()),
                     });
@@ -441,6 +546,8 @@ fn main() -> Result<(), Error> {
                         "author": author,
                         "year": year,
                         "identifier": id.to_string(),
+                        "url": origin_url,
+                        "readingTime": reading_time,
                         "added": updated_at.with_timezone(&Local)
                                            .format("%Y-%m-%d %H:%M:%S")
                                            .to_string(),
@@ -469,11 +576,15 @@ fn main() -> Result<(), Error> {
     if pages_count > 0 {
         let downloads_count = session.downloads_count
                                      .saturating_sub(last_downloads_count);
-        let message = if downloads_count > 0 {
+        let remaining_count = total_count.saturating_sub(processed_count);
+        let mut message = if downloads_count > 0 {
             format!("Downloaded {} article{}.", downloads_count, if downloads_count != 1 { "s" } else { "" })
         } else {
             "No articles downloaded.".to_string()
         };
+        if remaining_count > 0 {
+            message = format!("{} {} remaining.", message, remaining_count);
+        }
         let event = json!({
             "type": "notify",
             "message": &message,
@@ -481,6 +592,12 @@ fn main() -> Result<(), Error> {
         println!("{}", event);
     }
 
+    let event = json!({
+        "type": "status",
+        "state": "synced",
+    });
+    println!("{}", event);
+
     if !wifi {
         let event = json!({
             "type": "setWifi",
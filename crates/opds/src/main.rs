@@ -0,0 +1,472 @@
+use std::io::{self, BufRead};
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use plato_core::chrono::Local;
+use plato_core::fxhash::FxHashMap;
+use plato_core::serde::{Serialize, Deserialize};
+use plato_core::serde_json::{self, json, Value as JsonValue};
+use plato_core::anyhow::{Error, Context, format_err};
+use plato_core::helpers::{load_toml, decode_entities, escape_html};
+use plato_core::document::html::xml::XmlParser;
+use plato_core::document::html::dom::NodeRef;
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::Url;
+
+const SETTINGS_PATH: &str = "Settings.toml";
+// The prefix of the links handled by this hook.
+const LINK_PREFIX: &str = "hook:opds:";
+const ACQUISITION_REL: &str = "http://opds-spec.org/acquisition";
+// The supported formats, by order of preference.
+const FORMATS: [(&str, &str); 4] = [("application/epub+zip", "epub"),
+                                    ("application/pdf", "pdf"),
+                                    ("image/vnd.djvu", "djvu"),
+                                    ("application/x-cbz", "cbz")];
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "plato_core::serde")]
+#[serde(default, rename_all = "kebab-case")]
+struct Settings {
+    catalogs: Vec<Catalog>,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "plato_core::serde")]
+#[serde(default, rename_all = "kebab-case")]
+struct Catalog {
+    name: String,
+    url: String,
+    username: String,
+    password: String,
+}
+
+#[derive(Default, Debug, Clone)]
+struct Feed {
+    title: String,
+    entries: Vec<Entry>,
+    // The URL of the next page of the feed.
+    next: Option<String>,
+}
+
+#[derive(Default, Debug, Clone)]
+struct Entry {
+    title: String,
+    author: String,
+    year: String,
+    language: String,
+    publisher: String,
+    identifier: String,
+    description: String,
+    // The URL of the navigation feed this entry points to.
+    navigation: Option<String>,
+    // The URL and the kind of the preferred acquisition link.
+    acquisition: Option<(String, String)>,
+}
+
+fn request(client: &Client, catalog: &Catalog, url: &str) -> RequestBuilder {
+    let builder = client.get(url);
+    if catalog.username.is_empty() {
+        builder
+    } else {
+        builder.basic_auth(&catalog.username, Some(&catalog.password))
+    }
+}
+
+fn child_text(node: &NodeRef, tag_name: &str) -> String {
+    node.children()
+        .find(|child| child.tag_name() == Some(tag_name))
+        .map(|child| decode_entities(child.text().trim()).into_owned())
+        .unwrap_or_default()
+}
+
+fn parse_entry(node: &NodeRef, base: &Url) -> Entry {
+    let authors = node.children()
+                      .filter(|child| child.tag_name() == Some("author"))
+                      .map(|child| child_text(&child, "name"))
+                      .filter(|name| !name.is_empty())
+                      .collect::<Vec<String>>();
+    let description = Some(child_text(node, "summary"))
+                          .filter(|text| !text.is_empty())
+                          .unwrap_or_else(|| child_text(node, "content"));
+    let year = Some(child_text(node, "issued"))
+                   .filter(|text| !text.is_empty())
+                   .unwrap_or_else(|| child_text(node, "published"))
+                   .chars().take(4).collect();
+
+    let mut entry = Entry {
+        title: child_text(node, "title"),
+        author: authors.join(", "),
+        year,
+        language: child_text(node, "language"),
+        publisher: child_text(node, "publisher"),
+        identifier: child_text(node, "id"),
+        description,
+        .. Default::default()
+    };
+
+    let mut acquisitions = Vec::new();
+
+    for link in node.children().filter(|child| child.tag_name() == Some("link")) {
+        let href = link.attribute("href")
+                       .and_then(|href| base.join(&decode_entities(href)).ok());
+        let Some(href) = href else {
+            continue;
+        };
+        let rel = link.attribute("rel").unwrap_or_default();
+        let kind = link.attribute("type").unwrap_or_default();
+        if rel.starts_with(ACQUISITION_REL) {
+            if let Some(index) = FORMATS.iter().position(|(mime, _)| kind.starts_with(mime)) {
+                acquisitions.push((index, href.to_string()));
+            }
+        } else if kind.starts_with("application/atom+xml") && entry.navigation.is_none() {
+            entry.navigation = Some(href.to_string());
+        }
+    }
+
+    entry.acquisition = acquisitions.into_iter().min_by_key(|(index, _)| *index)
+                                    .map(|(index, href)| (href, FORMATS[index].1.to_string()));
+    entry
+}
+
+fn fetch_feed(client: &Client, catalog: &Catalog, url: &str) -> Result<Feed, Error> {
+    let response = request(client, catalog, url).send()?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format_err!("{}", status.canonical_reason().unwrap_or_else(|| status.as_str())));
+    }
+
+    let base = response.url().clone();
+    let text = response.text()?;
+    let xml = XmlParser::new(&text).parse();
+    let root = xml.root().find("feed")
+                  .ok_or_else(|| format_err!("not an Atom feed"))?;
+
+    let next = root.children()
+                   .filter(|child| child.tag_name() == Some("link"))
+                   .find(|link| link.attribute("rel") == Some("next"))
+                   .and_then(|link| link.attribute("href"))
+                   .and_then(|href| base.join(&decode_entities(href)).ok())
+                   .map(|url| url.to_string());
+
+    let entries = root.children()
+                      .filter(|child| child.tag_name() == Some("entry"))
+                      .map(|child| parse_entry(&child, &base))
+                      .collect();
+
+    Ok(Feed {
+        title: child_text(&root, "title"),
+        entries,
+        next,
+    })
+}
+
+// The URLs referenced by the links of our pages.
+// The links only carry the URLs' keys: the HTML engine would otherwise mangle the URLs.
+#[derive(Default, Debug)]
+struct Links {
+    urls: Vec<String>,
+    keys: FxHashMap<String, usize>,
+}
+
+impl Links {
+    fn key(&mut self, url: &str) -> usize {
+        if let Some(key) = self.keys.get(url) {
+            return *key;
+        }
+        let key = self.urls.len();
+        self.urls.push(url.to_string());
+        self.keys.insert(url.to_string(), key);
+        key
+    }
+
+    fn url(&self, key: usize) -> Option<&str> {
+        self.urls.get(key).map(String::as_str)
+    }
+}
+
+// The links of our pages have the form `hook:opds:ACTION:CATALOG_INDEX:URL_KEY`.
+fn link(action: &str, index: usize, url: &str, links: &mut Links) -> String {
+    format!("{}{}:{}:{}", LINK_PREFIX, action, index, links.key(url))
+}
+
+// Returns the action, the catalog index and the URL key of the given link.
+fn parse_link(uri: &str) -> Option<(&str, usize, usize)> {
+    let mut parts = uri.strip_prefix(LINK_PREFIX)?.split(':');
+    let action = parts.next().filter(|action| !action.is_empty())?;
+    let index = parts.next()?.parse::<usize>().ok()?;
+    let key = parts.next()?.parse::<usize>().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((action, index, key))
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!("<html>\n\t<head>\n\t\t<title>{0}</title>\n\t\t\
+             <link rel=\"stylesheet\" type=\"text/css\" href=\"css/html.css\"/>\n\t\
+             </head>\n\t<body>\n\t\t<h2>{0}</h2>\n{1}\t</body>\n</html>", escape_html(title), body)
+}
+
+fn catalogs_as_html(catalogs: &[Catalog], links: &mut Links) -> String {
+    let mut body = "\t\t<ul>\n".to_string();
+    for (index, catalog) in catalogs.iter().enumerate() {
+        let name = if catalog.name.is_empty() { &catalog.url } else { &catalog.name };
+        body.push_str(&format!("\t\t\t<li><a href=\"{}\">{}</a></li>\n",
+                               link("feed", index, &catalog.url, links), escape_html(name)));
+    }
+    body.push_str("\t\t</ul>\n");
+    page("Catalogs", &body)
+}
+
+fn feed_as_html(feed: &Feed, index: usize, links: &mut Links) -> String {
+    let mut body = "\t\t<ul>\n".to_string();
+    for entry in &feed.entries {
+        let title = escape_html(&entry.title);
+        if let Some((url, kind)) = entry.acquisition.as_ref() {
+            let mut text = format!("<a href=\"{}\">{}</a>", link("get", index, url, links), title);
+            if !entry.author.is_empty() {
+                text = format!("{} — <i>{}</i>", text, escape_html(&entry.author));
+            }
+            body.push_str(&format!("\t\t\t<li>{} ({})</li>\n", text, kind.to_uppercase()));
+        } else if let Some(url) = entry.navigation.as_ref() {
+            body.push_str(&format!("\t\t\t<li><a href=\"{}\">{}</a></li>\n",
+                                   link("feed", index, url, links), title));
+        }
+    }
+    body.push_str("\t\t</ul>\n");
+    if let Some(url) = feed.next.as_ref() {
+        body.push_str(&format!("\t\t<p><a href=\"{}\">Next page</a></p>\n", link("feed", index, url, links)));
+    }
+    page(&feed.title, &body)
+}
+
+fn open_html(html: &str) {
+    let event = json!({
+        "type": "openHtml",
+        "html": html,
+    });
+    println!("{}", event);
+}
+
+fn notify(message: &str) {
+    let event = json!({
+        "type": "notify",
+        "message": message,
+    });
+    println!("{}", event);
+}
+
+fn file_name(entry: &Entry, kind: &str) -> String {
+    let stem = entry.title.chars()
+                    .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                    .collect::<String>();
+    let stem = stem.trim_matches('_');
+    format!("{}.{}", if stem.is_empty() { "book" } else { stem }, kind)
+}
+
+fn download(client: &Client, catalog: &Catalog, entry: &Entry, library_path: &Path, save_path: &Path) -> Result<(), Error> {
+    let (url, kind) = entry.acquisition.as_ref()
+                           .ok_or_else(|| format_err!("nothing to download"))?;
+    let path = save_path.join(file_name(entry, kind));
+    if path.exists() {
+        notify(&format!("{} was already downloaded.", entry.title));
+        return Ok(());
+    }
+
+    // Download to a temporary file, so that an interrupted download doesn't leave a truncated document.
+    let partial_path = path.with_extension("part");
+    let mut file = File::create(&partial_path)?;
+    let result = request(client, catalog, url).send()
+                                              .and_then(|response| response.error_for_status())
+                                              .and_then(|mut response| response.copy_to(&mut file));
+    if let Err(e) = result {
+        fs::remove_file(&partial_path).ok();
+        return Err(e.into());
+    }
+    fs::rename(&partial_path, &path)?;
+
+    if let Ok(path) = path.strip_prefix(library_path) {
+        let file_info = json!({
+            "path": path,
+            "kind": kind,
+            "size": file.metadata().ok()
+                        .map_or(0, |m| m.len()),
+        });
+
+        let info = json!({
+            "title": entry.title,
+            "author": entry.author,
+            "year": entry.year,
+            "language": entry.language,
+            "publisher": entry.publisher,
+            "identifier": entry.identifier,
+            "description": entry.description,
+            "added": Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            "file": file_info,
+        });
+
+        let event = json!({
+            "type": "addDocument",
+            "info": &info,
+        });
+        println!("{}", event);
+    }
+
+    notify(&format!("Downloaded {}.", entry.title));
+    Ok(())
+}
+
+fn main() -> Result<(), Error> {
+    let mut args = env::args().skip(1);
+    let library_path = PathBuf::from(args.next()
+                                         .ok_or_else(|| format_err!("missing argument: library path"))?);
+    let save_path = PathBuf::from(args.next()
+                                      .ok_or_else(|| format_err!("missing argument: save path"))?);
+    let wifi = args.next()
+                   .ok_or_else(|| format_err!("missing argument: wifi status"))
+                   .and_then(|v| v.parse::<bool>().map_err(Into::into))?;
+    let online = args.next()
+                     .ok_or_else(|| format_err!("missing argument: online status"))
+                     .and_then(|v| v.parse::<bool>().map_err(Into::into))?;
+    let settings = load_toml::<Settings, _>(SETTINGS_PATH)
+                             .with_context(|| format!("can't load settings from {}", SETTINGS_PATH))?;
+
+    if settings.catalogs.is_empty() {
+        notify("No catalogs defined.");
+        return Ok(());
+    }
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    if !online {
+        if !wifi {
+            notify("Establishing a network connection.");
+            let event = json!({
+                "type": "setWifi",
+                "enable": true,
+            });
+            println!("{}", event);
+        } else {
+            notify("Waiting for the network to come up.");
+        }
+        // Skip the other events until the network is up.
+        for line in lines.by_ref() {
+            let event = serde_json::from_str::<JsonValue>(&line?).unwrap_or_default();
+            if event.get("type").and_then(JsonValue::as_str) == Some("network") {
+                break;
+            }
+        }
+    }
+
+    if !save_path.exists() {
+        fs::create_dir(&save_path)?;
+    }
+
+    let client = Client::new();
+    // The entries of the visited feeds, by acquisition URL.
+    let mut entries = FxHashMap::default();
+    let mut links = Links::default();
+
+    if settings.catalogs.len() == 1 {
+        let uri = link("feed", 0, &settings.catalogs[0].url, &mut links);
+        follow_link(&uri, &client, &settings, &mut entries, &mut links, &library_path, &save_path);
+    } else {
+        open_html(&catalogs_as_html(&settings.catalogs, &mut links));
+    }
+
+    // Handle the links followed in the opened pages, until *Plato* closes our standard input.
+    for line in lines {
+        let line = line?;
+        if let Ok(event) = serde_json::from_str::<JsonValue>(&line) {
+            if event.get("type").and_then(JsonValue::as_str) == Some("followLink") {
+                if let Some(uri) = event.get("uri").and_then(JsonValue::as_str) {
+                    follow_link(uri, &client, &settings, &mut entries, &mut links, &library_path, &save_path);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn follow_link(uri: &str, client: &Client, settings: &Settings, entries: &mut FxHashMap<String, Entry>,
+               links: &mut Links, library_path: &Path, save_path: &Path) {
+    let Some((action, index, key)) = parse_link(uri) else {
+        return;
+    };
+    let Some(catalog) = settings.catalogs.get(index) else {
+        return;
+    };
+    let Some(url) = links.url(key).map(String::from) else {
+        return;
+    };
+    let url = url.as_str();
+
+    match action {
+        "feed" => {
+            match fetch_feed(client, catalog, url) {
+                Ok(feed) => {
+                    if feed.entries.is_empty() {
+                        notify("The feed is empty.");
+                        return;
+                    }
+                    open_html(&feed_as_html(&feed, index, links));
+                    for entry in feed.entries {
+                        if let Some((url, _)) = entry.acquisition.clone() {
+                            entries.insert(url, entry);
+                        }
+                    }
+                },
+                Err(e) => {
+                    eprintln!("Can't load {}: {:#}.", url, e);
+                    notify(&format!("Can't load the feed: {}.", e));
+                },
+            }
+        },
+        "get" => {
+            let Some(entry) = entries.get(url) else {
+                notify("Unknown book: reopen its feed.");
+                return;
+            };
+            notify(&format!("Downloading {}.", entry.title));
+            if let Err(e) = download(client, catalog, entry, library_path, save_path) {
+                eprintln!("Can't download {}: {:#}.", url, e);
+                notify(&format!("Can't download {}: {}.", entry.title, e));
+            }
+        },
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_hides_url() {
+        let mut links = Links::default();
+        let url = "https://example.org/opds?q=a&amp;b=c:d#e";
+        let uri = link("feed", 2, url, &mut links);
+        assert_eq!(uri, "hook:opds:feed:2:0");
+        assert_eq!(link("get", 1, url, &mut links), "hook:opds:get:1:0");
+        assert_eq!(link("get", 1, "https://example.org/book.epub", &mut links), "hook:opds:get:1:1");
+        assert_eq!(links.url(0), Some(url));
+        assert_eq!(links.url(2), None);
+    }
+
+    #[test]
+    fn test_parse_link() {
+        let mut links = Links::default();
+        links.key("https://example.org/");
+        let uri = link("get", 3, "https://example.org/book.epub", &mut links);
+        assert_eq!(parse_link(&uri), Some(("get", 3, 1)));
+        assert_eq!(parse_link("hook:opds:feed:0:0"), Some(("feed", 0, 0)));
+        assert_eq!(parse_link("hook:other:feed:0:0"), None);
+        assert_eq!(parse_link("hook:opds:feed:0"), None);
+        assert_eq!(parse_link("hook:opds:feed:x:0"), None);
+        assert_eq!(parse_link("hook:opds:feed:0:https://example.org/"), None);
+        assert_eq!(parse_link("hook:opds::0:0"), None);
+        assert_eq!(parse_link("hook:opds:feed:0:0:1"), None);
+    }
+}
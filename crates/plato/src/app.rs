@@ -1,5 +1,6 @@
 use std::fs::File;
 use std::env;
+use std::mem;
 use std::thread;
 use std::process::Command;
 use std::path::Path;
@@ -20,7 +21,7 @@ use plato_core::view::calculator::Calculator;
 use plato_core::view::sketch::Sketch;
 use plato_core::view::touch_events::TouchEvents;
 use plato_core::view::rotation_values::RotationValues;
-use plato_core::document::sys_info_as_html;
+use plato_core::document::{DrmProtected, sys_info_as_html};
 use plato_core::input::{DeviceEvent, PowerSource, ButtonCode, ButtonStatus, VAL_RELEASE, VAL_PRESS};
 use plato_core::input::{raw_events, device_events, usb_events, display_rotate_event, button_scheme_event};
 use plato_core::gesture::{GestureEvent, gesture_events};
@@ -39,7 +40,7 @@ use plato_core::device::{CURRENT_DEVICE, Orientation, FrontlightKind};
 use plato_core::library::Library;
 use plato_core::font::Fonts;
 use plato_core::rtc::Rtc;
-use plato_core::context::Context;
+use plato_core::context::{Context, SETTINGS_BUNDLE_FILENAME};
 
 pub const APP_NAME: &str = "Plato";
 const FB_DEVICE: &str = "/dev/fb0";
@@ -82,6 +83,7 @@ struct HistoryItem {
     rotation: i8,
     monochrome: bool,
     dithered: bool,
+    inverted: bool,
 }
 
 fn build_context(fb: Box<dyn Framebuffer>) -> Result<Context, Error> {
@@ -714,9 +716,10 @@ pub fn run() -> Result<(), Error> {
                 context.set_frontlight(!context.settings.frontlight);
                 view.handle_event(&Event::ToggleFrontlight, &tx, &mut bus, &mut rq, &mut context);
             },
-            Event::Open(info) => {
+            Event::Open(info, anchor) => {
                 let rotation = context.display.rotation;
                 let dithered = context.fb.dithered();
+                let inverted = context.fb.inverted();
                 if let Some(reader_info) = info.reader.as_ref() {
                     if let Some(n) = reader_info.rotation.map(|n| CURRENT_DEVICE.from_canonical(n)) {
                         if CURRENT_DEVICE.orientation(n) != CURRENT_DEVICE.orientation(rotation) {
@@ -729,30 +732,47 @@ pub fn run() -> Result<(), Error> {
                         }
                     }
                     context.fb.set_dithered(reader_info.dithered);
+                    if let Some(enable) = reader_info.inverted {
+                        context.fb.set_inverted(enable);
+                    }
                 } else {
                     context.fb.set_dithered(context.settings.reader.dithered_kinds.contains(&info.file.kind));
                 }
                 let path = info.file.path.clone();
-                if let Some(r) = Reader::new(context.fb.rect(), *info, &tx, &mut context) {
-                    let mut next_view = Box::new(r) as Box<dyn View>;
-                    transfer_notifications(view.as_mut(), next_view.as_mut(), &mut rq, &mut context);
-                    history.push(HistoryItem {
-                        view,
-                        rotation,
-                        monochrome: context.fb.monochrome(),
-                        dithered,
-                    });
-                    view = next_view;
-                } else {
-                    if context.display.rotation != rotation {
-                        if let Ok(dims) = context.fb.set_rotation(rotation) {
-                            raw_sender.send(display_rotate_event(rotation)).ok();
-                            context.display.rotation = rotation;
-                            context.display.dims = dims;
+                match Reader::new(context.fb.rect(), *info, anchor.as_deref(), &tx, &mut context) {
+                    Ok(r) => {
+                        let mut next_view = Box::new(r) as Box<dyn View>;
+                        transfer_notifications(view.as_mut(), next_view.as_mut(), &mut rq, &mut context);
+                        history.push(HistoryItem {
+                            view,
+                            rotation,
+                            monochrome: context.fb.monochrome(),
+                            dithered,
+                            inverted,
+                        });
+                        view = next_view;
+                    },
+                    Err(e) => {
+                        if context.display.rotation != rotation {
+                            if let Ok(dims) = context.fb.set_rotation(rotation) {
+                                raw_sender.send(display_rotate_event(rotation)).ok();
+                                context.display.rotation = rotation;
+                                context.display.dims = dims;
+                            }
                         }
-                    }
-                    context.fb.set_dithered(dithered);
-                    handle_event(view.as_mut(), &Event::Invalid(path), &tx, &mut bus, &mut rq, &mut context);
+                        context.fb.set_dithered(dithered);
+                        context.fb.set_inverted(inverted);
+                        let msg = if e.is::<DrmProtected>() {
+                            e.to_string()
+                        } else {
+                            let name = path.file_name().map_or_else(|| path.to_string_lossy(),
+                                                                    |n| n.to_string_lossy());
+                            format!("Can't open {}: {:#}.", name, e)
+                        };
+                        let notif = Notification::new(msg, &tx, &mut rq, &mut context);
+                        view.children_mut().push(Box::new(notif) as Box<dyn View>);
+                        handle_event(view.as_mut(), &Event::Invalid(path), &tx, &mut bus, &mut rq, &mut context);
+                    },
                 }
             },
             Event::Select(EntryId::About) => {
@@ -774,6 +794,7 @@ pub fn run() -> Result<(), Error> {
                     rotation: context.display.rotation,
                     monochrome: context.fb.monochrome(),
                     dithered: context.fb.dithered(),
+                    inverted: context.fb.inverted(),
                 });
                 view = next_view;
             },
@@ -787,6 +808,7 @@ pub fn run() -> Result<(), Error> {
                     rotation: context.display.rotation,
                     monochrome: context.fb.monochrome(),
                     dithered: context.fb.dithered(),
+                    inverted: context.fb.inverted(),
                 });
                 view = next_view;
             },
@@ -814,6 +836,7 @@ pub fn run() -> Result<(), Error> {
                     rotation: context.display.rotation,
                     monochrome,
                     dithered: context.fb.dithered(),
+                    inverted: context.fb.inverted(),
                 });
                 view = next_view;
             },
@@ -826,6 +849,9 @@ pub fn run() -> Result<(), Error> {
                     if item.dithered != context.fb.dithered() {
                         context.fb.set_dithered(item.dithered);
                     }
+                    if item.inverted != context.fb.inverted() {
+                        context.fb.set_inverted(item.inverted);
+                    }
                     if CURRENT_DEVICE.orientation(item.rotation) != CURRENT_DEVICE.orientation(context.display.rotation) {
                         wait_for_all(&mut updating, &mut context);
                         if let Ok(dims) = context.fb.set_rotation(item.rotation) {
@@ -884,7 +910,10 @@ pub fn run() -> Result<(), Error> {
             },
             Event::Select(EntryId::ToggleInverted) => {
                 context.fb.toggle_inverted();
-                context.settings.inverted = context.fb.inverted();
+                // Within a book, the inversion is stored in the book's reading state.
+                if !view.is::<Reader>() {
+                    context.settings.inverted = context.fb.inverted();
+                }
                 rq.add(RenderData::new(view.id(), context.fb.rect(), UpdateMode::Full));
             },
             Event::Select(EntryId::ToggleDithered) => {
@@ -937,18 +966,69 @@ pub fn run() -> Result<(), Error> {
                 let notif = Notification::new(msg, &tx, &mut rq, &mut context);
                 view.children_mut().push(Box::new(notif) as Box<dyn View>);
             },
+            Event::Select(EntryId::ExportSettings) => {
+                let path = context.library.home.join(SETTINGS_BUNDLE_FILENAME);
+                let msg = match context.export_settings(&path) {
+                    Err(e) => format!("Can't export settings: {:#}.", e),
+                    Ok(()) => format!("Exported settings to {}.", SETTINGS_BUNDLE_FILENAME),
+                };
+                let notif = Notification::new(msg, &tx, &mut rq, &mut context);
+                view.children_mut().push(Box::new(notif) as Box<dyn View>);
+            },
+            Event::Select(EntryId::ImportSettings(mode)) => {
+                let path = context.library.home.join(SETTINGS_BUNDLE_FILENAME);
+                let wifi = context.settings.wifi;
+                let msg = match context.import_settings(&path, mode) {
+                    Err(e) => format!("Can't import settings: {:#}.", e),
+                    Ok(()) => {
+                        let enable = mem::replace(&mut context.settings.wifi, wifi);
+                        set_wifi(enable, &mut context);
+                        if context.settings.frontlight {
+                            let levels = context.settings.frontlight_levels;
+                            context.frontlight.set_warmth(levels.warmth);
+                            context.frontlight.set_intensity(levels.intensity);
+                        } else {
+                            context.frontlight.set_intensity(0.0);
+                            context.frontlight.set_warmth(0.0);
+                        }
+                        context.fb.set_inverted(context.settings.inverted);
+                        view.handle_event(&Event::Reseed, &tx, &mut bus, &mut rq, &mut context);
+                        rq.add(RenderData::new(view.id(), context.fb.rect(), UpdateMode::Full));
+                        "Imported settings.".to_string()
+                    },
+                };
+                let notif = Notification::new(msg, &tx, &mut rq, &mut context);
+                view.children_mut().push(Box::new(notif) as Box<dyn View>);
+            },
             Event::CheckFetcher(..) |
             Event::FetcherAddDocument(..) |
             Event::FetcherRemoveDocument(..) |
+            Event::FetcherStatus(..) |
+            Event::PageShared(..) |
+            Event::QuoteShared(..) |
+            Event::ReadAloud(..) |
+            Event::StopReadAloud |
+            Event::PageTurned(..) |
+            Event::HookLink(..) |
             Event::FetcherSearch { .. } if !view.is::<Home>() => {
                 if let Some(entry) = history.get_mut(0).filter(|entry| entry.view.is::<Home>()) {
                     let (tx, _rx) = mpsc::channel();
                     entry.view.handle_event(&evt, &tx, &mut VecDeque::new(), &mut RenderQueue::new(), &mut context);
                 }
             },
+            // Home reports the absence of hooks through the real hub.
+            Event::Translate(..) if !view.is::<Home>() => {
+                if let Some(entry) = history.get_mut(0).filter(|entry| entry.view.is::<Home>()) {
+                    entry.view.handle_event(&evt, &tx, &mut VecDeque::new(), &mut RenderQueue::new(), &mut context);
+                }
+            },
             Event::Notify(msg) => {
-                let notif = Notification::new(msg, &tx, &mut rq, &mut context);
-                view.children_mut().push(Box::new(notif) as Box<dyn View>);
+                if let Some(reader) = view.downcast_mut::<Reader>().filter(|r| r.is_immersive()) {
+                    reader.notify(msg, &tx, &mut rq, &mut context);
+                } else {
+                    let notif = Notification::new(msg, &tx, &mut rq, &mut context);
+                    view.children_mut().push(Box::new(notif) as Box<dyn View>);
+                }
             },
             Event::Select(EntryId::Reboot) => {
                 exit_status = ExitStatus::Reboot;